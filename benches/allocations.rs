@@ -0,0 +1,89 @@
+//! Counts heap allocations per delivery, to keep the simulation loop lean. Run with
+//! `cargo bench --bench allocations`.
+use jiminy::{
+    error::Result,
+    form::Form,
+    game::{DeliveryOutcome, Extra, GameState, Runs},
+    generate::PlayerGenerator,
+    model::NaiveStatsModel,
+    player::PlayerDb,
+    seed,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// The allocations made by `f`
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+const OUTCOMES: usize = 1_000_000;
+const MATCHES: u64 = 200;
+
+fn main() -> Result<()> {
+    // The extras of an outcome are stored inline. A Vec, as they were once kept in,
+    // allocates for every delivery with an extra.
+    let inline = allocations(|| {
+        for i in 0..OUTCOMES {
+            black_box(DeliveryOutcome::byes((i % 4) as u8));
+        }
+    });
+    let boxed = allocations(|| {
+        for i in 0..OUTCOMES {
+            black_box(vec![Extra::Bye(Runs::Running((i % 4) as u8))]);
+        }
+    });
+    println!(
+        "outcomes with extras: {:.2} allocations each (vs {:.2} in a Vec)",
+        inline as f64 / OUTCOMES as f64,
+        boxed as f64 / OUTCOMES as f64
+    );
+
+    let generator = PlayerGenerator::default();
+    let mut rng = seed::rng(0);
+    let mut db = PlayerDb::new();
+    let home = generator.squad(&mut rng, &mut db, 0, "Home")?;
+    let away = generator.squad(&mut rng, &mut db, 1, "Away")?;
+    let mut deliveries = 0;
+    let start = Instant::now();
+    let total = allocations(|| {
+        for i in 0..MATCHES {
+            let mut state =
+                GameState::new(Form::t20(), home.clone(), away.clone()).expect("valid teams");
+            state
+                .simulate_seeded(&NaiveStatsModel {}, &db, i)
+                .expect("simulated");
+            deliveries += state.events().len();
+        }
+    });
+    println!(
+        "T20 simulation: {:.2} allocations per delivery, {:.2?} per match",
+        total as f64 / deliveries as f64,
+        start.elapsed() / MATCHES as u32
+    );
+    Ok(())
+}
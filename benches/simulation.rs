@@ -0,0 +1,128 @@
+//! Simulation throughput, in matches simulated per second for each model and format, and
+//! the cost of applying a single delivery to the state of a match. Run with
+//! `cargo bench --bench simulation`.
+//!
+//! Target: at least 10,000 T20 matches per second on a single core with the NullModel.
+//! This is not yet met; a T20 match takes around 130µs.
+//! A change that slows any of these noticeably should say why in its description.
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use jiminy::{
+    form::Form,
+    game::{GameState, MatchEvent},
+    generate::PlayerGenerator,
+    model::{NaiveStatsModel, NullModel, PlayerRatingNull},
+    player::PlayerDb,
+    seed,
+    team::Team,
+};
+
+/// The formats to simulate, by name
+fn formats() -> Vec<(&'static str, Form)> {
+    vec![
+        ("t10", Form::t10()),
+        ("t20", Form::t20()),
+        ("odi", Form::odi()),
+        ("test", Form::test()),
+    ]
+}
+
+/// A side of average players for the null model
+fn null_team(db: &mut PlayerDb<PlayerRatingNull>, id: u16) -> Team {
+    let players: Vec<_> = (0..11)
+        .map(|i| {
+            let player = db
+                .add(format!("{}_{}", id, i), PlayerRatingNull::default())
+                .expect("unique names");
+            (player.id, player.name.clone())
+        })
+        .collect();
+    Team {
+        id,
+        name: format!("team_{}", id),
+        keeper: Some(players[4].0),
+        players,
+        captain: None,
+        home: None,
+        roles: Default::default(),
+    }
+}
+
+fn null_model(c: &mut Criterion) {
+    let mut db = PlayerDb::new();
+    let team_a = null_team(&mut db, 0);
+    let team_b = null_team(&mut db, 1);
+    let mut group = c.benchmark_group("null_model");
+    group.throughput(Throughput::Elements(1));
+    for (name, form) in formats() {
+        let state = GameState::new(form, team_a.clone(), team_b.clone()).expect("valid teams");
+        let mut rng = seed::rng(0);
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || state.clone(),
+                |mut state| state.simulate_to_completion(&NullModel {}, &db, &mut rng),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn naive_stats_model(c: &mut Criterion) {
+    let generator = PlayerGenerator::default();
+    let mut rng = seed::rng(0);
+    let mut db = PlayerDb::new();
+    let home = generator
+        .squad(&mut rng, &mut db, 0, "Home")
+        .expect("generated");
+    let away = generator
+        .squad(&mut rng, &mut db, 1, "Away")
+        .expect("generated");
+    let mut group = c.benchmark_group("naive_stats_model");
+    group.throughput(Throughput::Elements(1));
+    for (name, form) in formats() {
+        let state = GameState::new(form, home.clone(), away.clone()).expect("valid teams");
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || state.clone(),
+                |mut state| state.simulate_to_completion(&NaiveStatsModel {}, &db, &mut rng),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Applying the recorded events of a T20 match, without generating them
+fn update(c: &mut Criterion) {
+    let mut db = PlayerDb::new();
+    let team_a = null_team(&mut db, 0);
+    let team_b = null_team(&mut db, 1);
+    let fresh = GameState::new(Form::t20(), team_a, team_b).expect("valid teams");
+    let mut played = fresh.clone();
+    played
+        .simulate_seeded(&NullModel {}, &db, 1)
+        .expect("simulated");
+    let events = played.events().to_vec();
+    let deliveries = events
+        .iter()
+        .filter(|event| matches!(event, MatchEvent::Delivery(_)))
+        .count();
+    let mut group = c.benchmark_group("update");
+    group.throughput(Throughput::Elements(deliveries as u64));
+    group.bench_function("t20_deliveries", |b| {
+        b.iter_batched(
+            || fresh.clone(),
+            |mut state| {
+                for event in &events {
+                    state.apply(event).expect("replayable");
+                }
+                state
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, null_model, naive_stats_model, update);
+criterion_main!(benches);
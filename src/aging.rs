@@ -0,0 +1,147 @@
+//! Development and decline of players' ratings with age, applied between seasons
+use crate::{
+    form::Form,
+    model::{PlayerRating, PlayerRatingNaiveStats},
+    player::{Date, PlayerDb},
+};
+use serde::{Deserialize, Serialize};
+
+/// Ratings that can improve or decline
+pub trait Develop {
+    /// Improve the rating by the given fraction, or worsen it if the change is negative
+    fn develop(&mut self, change: f32);
+}
+
+impl Develop for PlayerRatingNaiveStats {
+    fn develop(&mut self, change: f32) {
+        let factor = (1. + change).max(0.1);
+        self.batting.avg *= factor;
+        // Scoring rate changes less than the ability to stay in
+        self.batting.sr *= factor.sqrt();
+        self.batting.r4 *= factor.sqrt();
+        self.batting.r6 *= factor.sqrt();
+        self.bowling.sr /= factor;
+        self.bowling.avg /= factor * factor.sqrt();
+        self.bowling.sr = self.bowling.sr.max(1.);
+    }
+}
+
+/// How ratings change with age over a season. Players improve until their peak, hold
+/// steady through it, and then decline at an accelerating rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgingCurve {
+    /// The age at which players stop improving
+    pub peak_start: f32,
+    /// The age at which players start to decline
+    pub peak_end: f32,
+    /// The fractional improvement per season before the peak
+    pub growth: f32,
+    /// The fractional decline in the first season after the peak, which grows by a tenth
+    /// with each further year
+    pub decline: f32,
+}
+
+impl Default for AgingCurve {
+    fn default() -> Self {
+        Self {
+            peak_start: 26.,
+            peak_end: 32.,
+            growth: 0.04,
+            decline: 0.05,
+        }
+    }
+}
+
+impl AgingCurve {
+    /// The usual curve for a format. The shortest formats reward athleticism, so players
+    /// peak earlier and decline faster, while craft lasts longer in multi-day cricket.
+    pub fn for_form(form: &Form) -> Self {
+        match form.overs_per_innings {
+            Some(overs) if overs <= 20 => Self {
+                peak_start: 25.,
+                peak_end: 30.,
+                growth: 0.05,
+                decline: 0.06,
+            },
+            Some(_) => Self::default(),
+            None => Self {
+                peak_start: 27.,
+                peak_end: 33.,
+                growth: 0.04,
+                decline: 0.04,
+            },
+        }
+    }
+
+    /// The fractional change in rating over a season begun at the given age
+    pub fn change(&self, age: f32) -> f32 {
+        if age < self.peak_start {
+            // The youngest players improve the fastest
+            self.growth * (1. + 0.1 * (self.peak_start - age))
+        } else if age <= self.peak_end {
+            0.
+        } else {
+            -self.decline * (1. + 0.1 * (age - self.peak_end))
+        }
+    }
+}
+
+/// Age every player with a known date of birth by a season starting on the given date.
+/// Players whose birth date is unknown are unchanged.
+pub fn age_players<R>(db: &mut PlayerDb<R>, curve: &AgingCurve, season_start: &Date)
+where
+    R: PlayerRating + Develop,
+{
+    for player in db.players_mut() {
+        if let Some(born) = &player.born {
+            let age = season_start.years_since(born);
+            player.rating.develop(curve.change(age));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::naive_stats::{BatRatingNaiveStats, BowlRatingNaiveStats};
+
+    #[test]
+    fn young_improve_and_old_decline() -> crate::error::Result<()> {
+        let rating = || PlayerRatingNaiveStats {
+            batting: BatRatingNaiveStats {
+                avg: 40.,
+                sr: 80.,
+                r4: 0.1,
+                r6: 0.01,
+            },
+            bowling: BowlRatingNaiveStats {
+                sr: 40.,
+                avg: 30.,
+                wide_rate: 0.02,
+                no_ball_rate: 0.01,
+            },
+            fielding: Default::default(),
+            running: Default::default(),
+        };
+        let mut db = PlayerDb::new();
+        let young = db.add("young".to_string(), rating())?.id;
+        let peak = db.add("peak".to_string(), rating())?.id;
+        let old = db.add("old".to_string(), rating())?.id;
+        let unknown = db.add("unknown".to_string(), rating())?.id;
+        db.get_mut(young).unwrap().born = Some("2004-06-01".parse()?);
+        db.get_mut(peak).unwrap().born = Some("1996-01-15".parse()?);
+        db.get_mut(old).unwrap().born = Some(Date::new(1988, 11, 30)?);
+        let season = Date::new(2024, 4, 1)?;
+        age_players(&mut db, &AgingCurve::for_form(&Form::odi()), &season);
+        let avg = |id| db.get(id).unwrap().rating.batting.avg;
+        assert!(avg(young) > 40.);
+        assert_eq!(avg(peak), 40.);
+        assert!(avg(old) < 40.);
+        assert_eq!(avg(unknown), 40.);
+        assert!(db.get(old).unwrap().rating.bowling.sr > 40.);
+        // T20 players decline sooner
+        let t20 = AgingCurve::for_form(&Form::t20());
+        assert!(t20.change(31.) < 0. && AgingCurve::default().change(31.) == 0.);
+        Ok(())
+    }
+}
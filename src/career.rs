@@ -0,0 +1,440 @@
+//! Career records of players, accumulated across completed matches
+use crate::{
+    error::{Error, Result},
+    game::{stats::Matchup, GameState},
+    player::PlayerId,
+};
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    io::{Read, Write},
+};
+
+pub mod leaderboard;
+pub use leaderboard::{Leader, Leaderboards};
+
+/// A batter's highest score
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub runs: u16,
+    pub not_out: bool,
+}
+
+impl HighScore {
+    fn better_than(&self, other: &Self) -> bool {
+        (self.runs, self.not_out) > (other.runs, other.not_out)
+    }
+
+    /// Order scores by most runs, with a not out score above an out one
+    pub fn compare(&self, other: &Self) -> Ordering {
+        (self.runs, self.not_out).cmp(&(other.runs, other.not_out))
+    }
+}
+
+impl Display for HighScore {
+    /// Formats in the usual style, e.g. "112*" for a not out score
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let not_out = if self.not_out { "*" } else { "" };
+        write!(f, "{}{}", self.runs, not_out)
+    }
+}
+
+/// A bowler's figures in an innings, e.g. 5/42
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Figures {
+    pub wickets: u8,
+    pub runs: u16,
+}
+
+impl Figures {
+    /// Order figures by most wickets, then fewest runs
+    pub fn compare(&self, other: &Self) -> Ordering {
+        self.wickets
+            .cmp(&other.wickets)
+            .then(other.runs.cmp(&self.runs))
+    }
+}
+
+impl Display for Figures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.wickets, self.runs)
+    }
+}
+
+/// Career batting aggregates
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BattingRecord {
+    pub innings: u32,
+    pub not_outs: u32,
+    pub runs: u32,
+    pub balls: u32,
+    pub fours: u32,
+    pub sixes: u32,
+    pub fifties: u32,
+    pub hundreds: u32,
+    pub highest: Option<HighScore>,
+}
+
+impl BattingRecord {
+    /// Runs per dismissal, or 0 if the batter has never been out
+    pub fn average(&self) -> f32 {
+        let dismissals = self.innings.saturating_sub(self.not_outs);
+        if dismissals == 0 {
+            return 0.;
+        }
+        self.runs as f32 / dismissals as f32
+    }
+
+    /// Runs per 100 balls, or 0 if the batter has yet to face a ball
+    pub fn strike_rate(&self) -> f32 {
+        if self.balls == 0 {
+            return 0.;
+        }
+        self.runs as f32 * 100. / self.balls as f32
+    }
+
+    /// Add another record of the same batter
+    pub fn combine(&mut self, other: &Self) {
+        self.innings += other.innings;
+        self.not_outs += other.not_outs;
+        self.runs += other.runs;
+        self.balls += other.balls;
+        self.fours += other.fours;
+        self.sixes += other.sixes;
+        self.fifties += other.fifties;
+        self.hundreds += other.hundreds;
+        if let Some(score) = other.highest {
+            if self.highest.is_none_or(|h| score.better_than(&h)) {
+                self.highest = Some(score);
+            }
+        }
+    }
+}
+
+/// Career bowling aggregates
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BowlingRecord {
+    pub innings: u32,
+    pub balls: u32,
+    pub maidens: u32,
+    pub runs: u32,
+    pub wickets: u32,
+    /// Innings in which five or more wickets were taken
+    pub five_wicket_innings: u32,
+    pub best: Option<Figures>,
+}
+
+impl BowlingRecord {
+    /// Runs conceded per wicket, or 0 if the bowler has yet to take one
+    pub fn average(&self) -> f32 {
+        if self.wickets == 0 {
+            return 0.;
+        }
+        self.runs as f32 / self.wickets as f32
+    }
+
+    /// Balls bowled per wicket, or 0 if the bowler has yet to take one
+    pub fn strike_rate(&self) -> f32 {
+        if self.wickets == 0 {
+            return 0.;
+        }
+        self.balls as f32 / self.wickets as f32
+    }
+
+    /// Runs conceded per over, or 0 if the bowler has yet to bowl a legal ball
+    pub fn economy(&self, balls_per_over: u8) -> f32 {
+        if self.balls == 0 {
+            return 0.;
+        }
+        self.runs as f32 * balls_per_over as f32 / self.balls as f32
+    }
+
+    /// Add another record of the same bowler
+    pub fn combine(&mut self, other: &Self) {
+        self.innings += other.innings;
+        self.balls += other.balls;
+        self.maidens += other.maidens;
+        self.runs += other.runs;
+        self.wickets += other.wickets;
+        self.five_wicket_innings += other.five_wicket_innings;
+        if let Some(figures) = other.best {
+            if self
+                .best
+                .is_none_or(|b| figures.compare(&b) == Ordering::Greater)
+            {
+                self.best = Some(figures);
+            }
+        }
+    }
+}
+
+/// Career fielding aggregates
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FieldingRecord {
+    /// Catches taken, including those as keeper
+    pub catches: u32,
+    pub run_outs: u32,
+    pub stumpings: u32,
+    /// Catches dropped
+    #[serde(default)]
+    pub drops: u32,
+}
+
+impl FieldingRecord {
+    /// Add another record of the same fielder
+    pub fn combine(&mut self, other: &Self) {
+        self.catches += other.catches;
+        self.run_outs += other.run_outs;
+        self.stumpings += other.stumpings;
+        self.drops += other.drops;
+    }
+}
+
+/// The full career record of a single player
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CareerRecord {
+    pub matches: u32,
+    pub batting: BattingRecord,
+    pub bowling: BowlingRecord,
+    #[serde(default)]
+    pub fielding: FieldingRecord,
+}
+
+impl CareerRecord {
+    /// Add another record of the same player, e.g. from another competition
+    pub fn combine(&mut self, other: &Self) {
+        self.matches += other.matches;
+        self.batting.combine(&other.batting);
+        self.bowling.combine(&other.bowling);
+        self.fielding.combine(&other.fielding);
+    }
+}
+
+/// Career records of all players who have appeared in a recorded match
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CareerStats {
+    records: FnvHashMap<PlayerId, CareerRecord>,
+    /// The record of each batter against each bowler, by batter and then bowler
+    #[serde(default)]
+    matchups: FnvHashMap<PlayerId, FnvHashMap<PlayerId, Matchup>>,
+}
+
+impl CareerStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the career record of a player, if they have played a recorded match
+    pub fn get(&self, id: PlayerId) -> Option<&CareerRecord> {
+        self.records.get(&id)
+    }
+
+    /// The batter's career record against the bowler, if they have met in a recorded
+    /// match
+    pub fn matchup(&self, batter: PlayerId, bowler: PlayerId) -> Option<&Matchup> {
+        self.matchups.get(&batter)?.get(&bowler)
+    }
+
+    /// Iterate over the records of all players
+    pub fn iter(&self) -> impl Iterator<Item = (PlayerId, &CareerRecord)> {
+        self.records.iter().map(|(id, rec)| (*id, rec))
+    }
+
+    /// Add the performances in a completed match to the career records
+    pub fn add_match(&mut self, game: &GameState) -> Result<()> {
+        if !game.complete() {
+            return Err(Error::MatchInProgress);
+        }
+        let (team_a, team_b) = game.teams();
+        for (id, _) in team_a.players.iter().chain(team_b.players.iter()) {
+            self.records.entry(*id).or_default().matches += 1;
+        }
+        for innings in game.completed_innings().iter().filter(|st| !st.forfeited) {
+            for (batter, bowler, matchup) in &innings.matchups {
+                self.matchups
+                    .entry(*batter)
+                    .or_default()
+                    .entry(*bowler)
+                    .or_default()
+                    .combine(matchup);
+            }
+            for (id, st) in innings.batting_stats.batters() {
+                let batting = &mut self.records.entry(*id).or_default().batting;
+                let score = HighScore {
+                    runs: st.runs,
+                    not_out: st.out.is_none(),
+                };
+                batting.innings += 1;
+                batting.not_outs += score.not_out as u32;
+                batting.runs += st.runs as u32;
+                batting.balls += st.balls as u32;
+                batting.fours += st.fours as u32;
+                batting.sixes += st.sixes as u32;
+                match st.runs {
+                    50..=99 => batting.fifties += 1,
+                    100.. => batting.hundreds += 1,
+                    _ => (),
+                }
+                if batting.highest.is_none_or(|h| score.better_than(&h)) {
+                    batting.highest = Some(score);
+                }
+            }
+            for (id, st) in innings.bowling_stats.bowlers() {
+                let bowling = &mut self.records.entry(*id).or_default().bowling;
+                let figures = Figures {
+                    wickets: st.wickets,
+                    runs: st.runs,
+                };
+                bowling.innings += 1;
+                bowling.balls += st.balls as u32;
+                bowling.maidens += st.maiden_overs as u32;
+                bowling.runs += st.runs as u32;
+                bowling.wickets += st.wickets as u32;
+                if st.wickets >= 5 {
+                    bowling.five_wicket_innings += 1;
+                }
+                if bowling
+                    .best
+                    .is_none_or(|b| figures.compare(&b) == Ordering::Greater)
+                {
+                    bowling.best = Some(figures);
+                }
+            }
+            for (id, st) in innings.fielding_stats.fielders() {
+                let fielding = &mut self.records.entry(id).or_default().fielding;
+                fielding.catches += st.catches as u32;
+                fielding.run_outs += st.run_outs as u32;
+                fielding.stumpings += st.stumpings as u32;
+                fielding.drops += st.drops as u32;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the records of another set of matches, e.g. another competition
+    pub fn merge(&mut self, other: &CareerStats) {
+        for (id, record) in &other.records {
+            self.records.entry(*id).or_default().combine(record);
+        }
+        for (batter, bowlers) in &other.matchups {
+            let matchups = self.matchups.entry(*batter).or_default();
+            for (bowler, matchup) in bowlers {
+                matchups.entry(*bowler).or_default().combine(matchup);
+            }
+        }
+    }
+
+    /// Write the career records as JSON
+    pub fn save_json<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    /// Load career records previously saved as JSON
+    pub fn load_json<R: Read>(reader: R) -> Result<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{form, game, model, player::PlayerDb, tests::test_team};
+    use rand::thread_rng;
+
+    #[test]
+    fn empty_records() {
+        let batting = BattingRecord {
+            innings: 2,
+            not_outs: 2,
+            runs: 30,
+            ..Default::default()
+        };
+        assert_eq!((batting.average(), batting.strike_rate()), (0., 0.));
+        let bowling = BowlingRecord::default();
+        assert_eq!(bowling.average(), 0.);
+        assert_eq!(bowling.strike_rate(), 0.);
+        assert_eq!(bowling.economy(6), 0.);
+    }
+
+    #[test]
+    fn career_round_trip() -> Result<()> {
+        use model::NullModel;
+        use CareerStats;
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "PAK")?;
+        let team_b = test_team(&mut db, 2, "BAN")?;
+        let db = db;
+        let mut career = CareerStats::new();
+        let mut rng = thread_rng();
+        let model = NullModel {};
+        for _ in 0..2 {
+            let mut state =
+                game::GameState::new(form::Form::odi(), team_a.clone(), team_b.clone())?;
+            assert!(career.add_match(&state).is_err());
+            state.simulate_to_completion(&model, &db, &mut rng)?;
+            career.add_match(&state)?;
+        }
+        let opener = team_a.players[0].0;
+        assert_eq!(career.get(opener).unwrap().matches, 2);
+        assert_eq!(career.get(opener).unwrap().batting.innings, 2);
+
+        let mut buffer = Vec::new();
+        career.save_json(&mut buffer)?;
+        let loaded = CareerStats::load_json(buffer.as_slice())?;
+        assert_eq!(
+            loaded.get(opener).unwrap().batting.runs,
+            career.get(opener).unwrap().batting.runs
+        );
+
+        // Every run the opener scored came off one of the other side's bowlers
+        let matchup_runs: u32 = team_b
+            .players
+            .iter()
+            .filter_map(|(bowler, _)| loaded.matchup(opener, *bowler))
+            .map(|matchup| matchup.runs)
+            .sum();
+        assert_eq!(matchup_runs, career.get(opener).unwrap().batting.runs);
+        let mut state = game::GameState::new(form::Form::odi(), team_a.clone(), team_b.clone())?;
+        state.set_prior_matchups(&loaded);
+        let snapshot = state.snapshot(&db)?;
+        assert_eq!(
+            Some(&snapshot.prior_matchup),
+            loaded.matchup(snapshot.striker.id, snapshot.bowler.id)
+        );
+        assert_eq!(snapshot.matchup, game::stats::Matchup::default());
+        Ok(())
+    }
+
+    #[test]
+    fn drops_recorded() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "WI")?;
+        let team_b = test_team(&mut db, 2, "ZIM")?;
+        let model = model::CompositeModel {
+            bowling: model::BasicBowling {},
+            batting: model::BasicBatting {},
+            fielding: model::RatedFielding {},
+        };
+        let mut state = game::GameState::new(form::Form::odi(), team_a.clone(), team_b.clone())?;
+        state.simulate_to_completion(&model, &db, &mut crate::seed::rng(3))?;
+        let mut career = CareerStats::new();
+        career.add_match(&state)?;
+        let dropped = (0..2)
+            .filter_map(|innings| state.innings_dropped_catches(innings))
+            .map(|drops| drops.len() as u32)
+            .sum::<u32>();
+        let recorded: u32 = team_a
+            .players
+            .iter()
+            .chain(&team_b.players)
+            .filter_map(|(id, _)| career.get(*id))
+            .map(|record| record.fielding.drops)
+            .sum();
+        assert!(dropped > 0);
+        assert_eq!(recorded, dropped);
+        Ok(())
+    }
+}
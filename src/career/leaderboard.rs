@@ -0,0 +1,251 @@
+//! Leaderboards of the best performers across a series or tournament. A `Tournament` or
+//! `Cup` keeps its own; for a series, add each match as it is played.
+use super::{CareerRecord, CareerStats, Figures, HighScore};
+use crate::{error::Result, game::GameState, player::PlayerId};
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// A player's place on a leaderboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Leader<T> {
+    pub player: PlayerId,
+    pub name: String,
+    pub value: T,
+}
+
+/// The records of every player in a set of matches, from which leaderboards are drawn.
+/// Each leaderboard lists the top players, best first, with ties in order of name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Leaderboards {
+    stats: CareerStats,
+    names: FnvHashMap<PlayerId, String>,
+    balls_per_over: u8,
+}
+
+impl Leaderboards {
+    pub fn new(balls_per_over: u8) -> Self {
+        Self {
+            balls_per_over,
+            ..Default::default()
+        }
+    }
+
+    /// Add the performances in a completed match
+    pub fn add_match(&mut self, game: &GameState) -> Result<()> {
+        self.stats.add_match(game)?;
+        let (team_a, team_b) = game.teams();
+        for (id, name) in team_a.players.iter().chain(&team_b.players) {
+            self.names.insert(*id, name.clone());
+        }
+        Ok(())
+    }
+
+    /// Add the performances in another set of matches, e.g. another group of a cup
+    pub fn merge(&mut self, other: &Leaderboards) {
+        self.stats.merge(&other.stats);
+        for (id, name) in &other.names {
+            self.names.insert(*id, name.clone());
+        }
+    }
+
+    /// The records of every player in the matches added
+    pub fn stats(&self) -> &CareerStats {
+        &self.stats
+    }
+
+    /// The top `n` players by a value of their records, where `value` returns None for
+    /// players who don't qualify
+    fn top<T>(
+        &self,
+        n: usize,
+        value: impl Fn(&CareerRecord) -> Option<T>,
+        compare: impl Fn(&T, &T) -> Ordering,
+    ) -> Vec<Leader<T>> {
+        let mut leaders: Vec<Leader<T>> = self
+            .stats
+            .iter()
+            .filter_map(|(player, record)| {
+                Some(Leader {
+                    player,
+                    name: self.names.get(&player).cloned().unwrap_or_default(),
+                    value: value(record)?,
+                })
+            })
+            .collect();
+        leaders.sort_by(|a, b| compare(&b.value, &a.value).then_with(|| a.name.cmp(&b.name)));
+        leaders.truncate(n);
+        leaders
+    }
+
+    pub fn most_runs(&self, n: usize) -> Vec<Leader<u32>> {
+        let runs = |r: &CareerRecord| (r.batting.innings > 0).then_some(r.batting.runs);
+        self.top(n, runs, Ord::cmp)
+    }
+
+    pub fn most_wickets(&self, n: usize) -> Vec<Leader<u32>> {
+        let wickets = |r: &CareerRecord| (r.bowling.balls > 0).then_some(r.bowling.wickets);
+        self.top(n, wickets, Ord::cmp)
+    }
+
+    pub fn most_catches(&self, n: usize) -> Vec<Leader<u32>> {
+        let catches = |r: &CareerRecord| (r.fielding.catches > 0).then_some(r.fielding.catches);
+        self.top(n, catches, Ord::cmp)
+    }
+
+    /// The highest score of each batter
+    pub fn highest_scores(&self, n: usize) -> Vec<Leader<HighScore>> {
+        self.top(n, |r| r.batting.highest, HighScore::compare)
+    }
+
+    /// The best figures of each bowler
+    pub fn best_figures(&self, n: usize) -> Vec<Leader<Figures>> {
+        self.top(n, |r| r.bowling.best, Figures::compare)
+    }
+
+    /// The highest batting averages among batters out at least once in `min_innings`
+    /// or more innings
+    pub fn batting_average(&self, n: usize, min_innings: u32) -> Vec<Leader<f32>> {
+        let average = |r: &CareerRecord| {
+            let batting = &r.batting;
+            let qualified = batting.innings >= min_innings && batting.innings > batting.not_outs;
+            qualified.then(|| batting.average())
+        };
+        self.top(n, average, compare_f32)
+    }
+
+    /// The highest batting strike rates among batters who faced `min_balls` or more
+    pub fn batting_strike_rate(&self, n: usize, min_balls: u32) -> Vec<Leader<f32>> {
+        let strike_rate = |r: &CareerRecord| {
+            (r.batting.balls > 0 && r.batting.balls >= min_balls).then(|| r.batting.strike_rate())
+        };
+        self.top(n, strike_rate, compare_f32)
+    }
+
+    /// The lowest bowling averages among bowlers with `min_wickets` or more
+    pub fn bowling_average(&self, n: usize, min_wickets: u32) -> Vec<Leader<f32>> {
+        let average = |r: &CareerRecord| {
+            let wickets = r.bowling.wickets;
+            (wickets > 0 && wickets >= min_wickets).then(|| r.bowling.average())
+        };
+        self.top(n, average, |a, b| compare_f32(b, a))
+    }
+
+    /// The lowest economy rates among bowlers who bowled `min_balls` or more
+    pub fn economy(&self, n: usize, min_balls: u32) -> Vec<Leader<f32>> {
+        let bpo = self.balls_per_over;
+        let economy = |r: &CareerRecord| {
+            let balls = r.bowling.balls;
+            (balls > 0 && balls >= min_balls).then(|| r.bowling.economy(bpo))
+        };
+        self.top(n, economy, |a, b| compare_f32(b, a))
+    }
+
+    /// Write the top five in each leaderboard. Averages need two innings or three
+    /// wickets, and rates five overs.
+    #[cfg(feature = "std-display")]
+    pub fn write_summary<W: std::io::Write + ?Sized>(&self, w: &mut W) -> Result<()> {
+        use prettytable::{format::consts::*, Table};
+        const N: usize = 5;
+        let min_balls = 5 * self.balls_per_over as u32;
+        let boards: Vec<(&str, Vec<(String, String)>)> = vec![
+            ("Most runs", show(self.most_runs(N))),
+            ("Most wickets", show(self.most_wickets(N))),
+            ("Highest score", show(self.highest_scores(N))),
+            ("Best bowling", show(self.best_figures(N))),
+            ("Most catches", show(self.most_catches(N))),
+            ("Batting average", show_f32(self.batting_average(N, 2))),
+            (
+                "Strike rate",
+                show_f32(self.batting_strike_rate(N, min_balls)),
+            ),
+            ("Bowling average", show_f32(self.bowling_average(N, 3))),
+            ("Economy", show_f32(self.economy(N, min_balls))),
+        ];
+        for (title, leaders) in boards {
+            let mut table = Table::new();
+            table.set_format(*FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row![title, ""]);
+            for (name, value) in leaders {
+                table.add_row(row![name, value]);
+            }
+            table.print(w)?;
+        }
+        Ok(())
+    }
+}
+
+fn compare_f32(a: &f32, b: &f32) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+#[cfg(feature = "std-display")]
+fn show<T: std::fmt::Display>(leaders: Vec<Leader<T>>) -> Vec<(String, String)> {
+    leaders
+        .into_iter()
+        .map(|l| (l.name, l.value.to_string()))
+        .collect()
+}
+
+#[cfg(feature = "std-display")]
+fn show_f32(leaders: Vec<Leader<f32>>) -> Vec<(String, String)> {
+    leaders
+        .into_iter()
+        .map(|l| (l.name, format!("{:.2}", l.value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{form::Form, model::NullModel, player::PlayerDb, seed, tests::test_team};
+
+    #[test]
+    fn series_leaderboards() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "WI")?;
+        let team_b = test_team(&mut db, 2, "SL")?;
+        let mut series = Leaderboards::new(6);
+        let mut each = Vec::new();
+        for i in 0..3 {
+            let mut game = GameState::new(Form::t20(), team_a.clone(), team_b.clone())?;
+            game.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(i))?;
+            series.add_match(&game)?;
+            let mut single = Leaderboards::new(6);
+            single.add_match(&game)?;
+            each.push(single);
+        }
+        let runs = series.most_runs(22);
+        assert!(runs.windows(2).all(|w| w[0].value >= w[1].value));
+        for leader in &runs {
+            let record = series.stats().get(leader.player).unwrap();
+            assert_eq!(leader.value, record.batting.runs);
+            assert!(leader.name.starts_with("WI_") || leader.name.starts_with("SL_"));
+        }
+        let figures = series.best_figures(5);
+        assert!(figures
+            .windows(2)
+            .all(|w| w[0].value.compare(&w[1].value) != Ordering::Less));
+        let economy = series.economy(22, 12);
+        assert!(!economy.is_empty());
+        assert!(economy.windows(2).all(|w| w[0].value <= w[1].value));
+        // Only those who were out qualify for an average
+        for leader in series.batting_average(22, 1) {
+            let batting = &series.stats().get(leader.player).unwrap().batting;
+            assert!(batting.innings > batting.not_outs);
+        }
+        assert!(series.most_runs(0).is_empty());
+
+        // The same boards come from merging those of each match
+        let mut merged = Leaderboards::new(6);
+        for single in &each {
+            merged.merge(single);
+        }
+        assert_eq!(merged.most_runs(22), runs);
+        assert_eq!(merged.most_wickets(22), series.most_wickets(22));
+        assert_eq!(merged.highest_scores(22), series.highest_scores(22));
+        assert_eq!(merged.best_figures(22), series.best_figures(22));
+        assert_eq!(merged.most_catches(22), series.most_catches(22));
+        Ok(())
+    }
+}
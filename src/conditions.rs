@@ -1,8 +1,10 @@
 //! Conditions of a match such as weather and ball state
-use crate::game::DeliveryOutcome;
+use crate::{game::DeliveryOutcome, team::Team};
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
 
 /// The style and manufacturer of the cricket ball
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum BallType {
     /// Used in test matches.
     /// TODO: Split into manufacturer, i.e.
@@ -18,7 +20,7 @@ pub enum BallType {
 }
 
 /// Style and conditions of a ball
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ball {
     /// The style of ball
     pub ball_type: BallType,
@@ -26,6 +28,54 @@ pub struct Ball {
     pub deliveries: u16,
     /// A proxy for wear-and-tear due to batting
     pub runs: u16,
+    /// How quickly the ball roughens
+    #[serde(default)]
+    pub wear: BallWear,
+    /// The balls in an over of the match, by which the age of the ball is measured
+    #[serde(default = "default_balls_per_over")]
+    pub balls_per_over: u8,
+}
+
+fn default_balls_per_over() -> u8 {
+    6
+}
+
+/// How the wear on the ball builds up. The roughness is 0 for a new ball, and the ball
+/// starts to reverse swing once one side is rough enough.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BallWear {
+    /// Roughness added by each delivery scuffing the pitch
+    pub per_delivery: f32,
+    /// Roughness added by each run, from the ball being hit into the outfield and boards
+    pub per_run: f32,
+    /// The roughness at which reverse swing begins
+    pub reverse_threshold: f32,
+}
+
+impl Default for BallWear {
+    /// Reverse swing begins after about 40 overs at a typical scoring rate
+    fn default() -> Self {
+        Self {
+            per_delivery: 1. / 300.,
+            per_run: 1. / 600.,
+            reverse_threshold: 1.,
+        }
+    }
+}
+
+/// How the state of the ball assists the bowlers. Each factor scales the movement a
+/// bowler gets from that source.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConditionFactors {
+    /// Conventional swing, strongest with the new ball and fading as the shine wears off
+    pub swing: f32,
+    /// Reverse swing, which is zero until the ball is rough enough
+    pub reverse_swing: f32,
+    /// Movement off the seam, which fades as the seam flattens
+    pub seam: f32,
+    /// Grip for the spinners, which grows as the ball gets older and rougher
+    pub spin: f32,
 }
 
 impl Ball {
@@ -33,15 +83,315 @@ impl Ball {
         self.deliveries += 1;
         self.runs += ball.runs.runs() as u16;
     }
+
+    /// The wear on the ball, 0 when new
+    pub fn roughness(&self) -> f32 {
+        self.deliveries as f32 * self.wear.per_delivery + self.runs as f32 * self.wear.per_run
+    }
+
+    /// The assistance the ball gives to swing, seam, and spin in its current state. The
+    /// middle overs, between the new ball losing its shine and the old ball reversing,
+    /// give the least help to the seamers.
+    pub fn condition_factors(&self) -> ConditionFactors {
+        // The shine lasts longer on a red ball
+        let shine_overs = match self.ball_type {
+            BallType::RedLeather => 15.,
+            BallType::WhiteLeather => 10.,
+        };
+        let overs = self.deliveries as f32 / self.balls_per_over as f32;
+        let roughness = self.roughness();
+        let reverse_swing =
+            ((roughness - self.wear.reverse_threshold) / self.wear.reverse_threshold).clamp(0., 1.);
+        ConditionFactors {
+            swing: 0.2 + 0.8 * (-overs / shine_overs).exp(),
+            reverse_swing,
+            seam: 0.5 + 0.5 * (-overs / (2. * shine_overs)).exp(),
+            spin: 1. + 0.5 * roughness.min(1.),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Weather {
+    /// The fraction of the sky covered by cloud, which helps the ball swing
+    #[serde(default)]
+    pub cloud_cover: f32,
+}
+
+/// The character of the pitch, which the sides consider when picking their XIs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pitch {
+    /// How much the pitch helps the spinners, from 0 (none) to 1 (a raging turner)
+    pub turn: f32,
+    /// How much the pitch helps the seamers, from 0 (flat) to 1 (green)
+    pub seam: f32,
 }
 
-#[derive(Debug, Clone)]
-pub struct Weather {}
+/// Tracks the time of play elapsed in the match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Clock {
+    /// Seconds of play elapsed
+    pub elapsed: u32,
+}
+
+impl Clock {
+    /// Typical time taken to bowl a delivery, including the bowler walking back to their
+    /// mark
+    pub const SECONDS_PER_DELIVERY: u32 = 30;
+    /// Typical time taken to change ends and bowlers between overs
+    pub const SECONDS_PER_CHANGEOVER: u32 = 45;
+    /// Typical time taken for a dismissed batter to leave and the next to come in
+    pub const SECONDS_PER_WICKET: u32 = 120;
+
+    /// The time taken by a delivery, including any change of batters or of ends after it.
+    /// An over without a wicket takes 225 seconds at six balls an over.
+    pub fn delivery_seconds(ball: &DeliveryOutcome, over_complete: bool) -> u32 {
+        let mut seconds = Self::SECONDS_PER_DELIVERY;
+        if ball.wicket.is_some() {
+            seconds += Self::SECONDS_PER_WICKET;
+        }
+        if over_complete {
+            seconds += Self::SECONDS_PER_CHANGEOVER;
+        }
+        seconds
+    }
+
+    /// Advance the clock by the given number of seconds
+    pub fn advance(&mut self, seconds: u32) {
+        self.elapsed += seconds;
+    }
+}
 
 /// Tracks other conditions not related to the players or sides
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conditions {
     pub ball: Ball,
     pub weather: Weather,
-    // TODO: Pitch characteristics
+    pub clock: Clock,
+    #[serde(default)]
+    pub pitch: Pitch,
+}
+
+/// A ground that matches are played at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Venue {
+    pub name: String,
+    /// The country the ground is in. Sides are at home anywhere in their home country.
+    pub country: String,
+}
+
+/// Where a side is playing, relative to its home
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Ground {
+    Home,
+    Away,
+    /// Neither side is at home, or both are
+    Neutral,
+}
+
+impl Venue {
+    /// Whether the venue is in the home country of the given side
+    pub fn is_home_of(&self, team: &Team) -> bool {
+        team.home
+            .as_ref()
+            .is_some_and(|home| home.country == self.country)
+    }
+
+    /// Where the given side is playing against the opponent at this venue
+    pub fn ground(&self, team: &Team, opponent: &Team) -> Ground {
+        match (self.is_home_of(team), self.is_home_of(opponent)) {
+            (true, false) => Ground::Home,
+            (false, true) => Ground::Away,
+            _ => Ground::Neutral,
+        }
+    }
+}
+
+/// How well the batters from a country play each kind of bowling, from -1 (badly) to 1
+/// (well), having grown up on pitches that suit it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Familiarity {
+    pub spin: f32,
+    pub pace: f32,
+}
+
+/// How much the venue favours one side over the other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HomeAdvantage {
+    /// The relative boost to the home side, and penalty to the away side, in both
+    /// batting and bowling
+    pub effect: f32,
+    /// The relative boost to a batter for each unit of familiarity with the kind of
+    /// bowling they face
+    pub familiarity_effect: f32,
+    /// The familiarity of the batters from each country. Batters from countries that
+    /// aren't listed are neither familiar nor unfamiliar with either kind.
+    pub familiarity: FnvHashMap<String, Familiarity>,
+}
+
+impl Default for HomeAdvantage {
+    fn default() -> Self {
+        Self {
+            effect: 0.05,
+            familiarity_effect: 0.05,
+            familiarity: FnvHashMap::default(),
+        }
+    }
+}
+
+impl HomeAdvantage {
+    /// No advantage to either side
+    pub fn none() -> Self {
+        Self {
+            effect: 0.,
+            familiarity_effect: 0.,
+            familiarity: FnvHashMap::default(),
+        }
+    }
+
+    /// Set the familiarity of the batters from the given country
+    pub fn with_familiarity(
+        mut self,
+        country: impl Into<String>,
+        familiarity: Familiarity,
+    ) -> Self {
+        self.familiarity.insert(country.into(), familiarity);
+        self
+    }
+
+    /// How much the conditions favour a batter from the side batting on the given ground
+    /// over the bowler, as a relative change in the batter's average. This is negative if
+    /// the bowler is favoured. The home country of the batting side gives its familiarity
+    /// with spin, or with pace if the bowler doesn't spin, when the bowler's style is
+    /// known.
+    pub fn batting_advantage(
+        &self,
+        batting: Ground,
+        country: Option<&str>,
+        spin: Option<bool>,
+    ) -> f32 {
+        let home = match batting {
+            Ground::Home => self.effect,
+            Ground::Away => -self.effect,
+            Ground::Neutral => 0.,
+        };
+        let familiarity = country
+            .and_then(|country| self.familiarity.get(country))
+            .zip(spin)
+            .map_or(0., |(f, spin)| if spin { f.spin } else { f.pace });
+        home + self.familiarity_effect * familiarity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Result, form, game, model, player::PlayerDb, tests::test_team, tournament};
+    use rand::thread_rng;
+
+    #[test]
+    fn ball_ages() {
+        let mut ball = Ball {
+            ball_type: BallType::RedLeather,
+            deliveries: 0,
+            runs: 0,
+            wear: BallWear::default(),
+            balls_per_over: 6,
+        };
+        let new = ball.condition_factors();
+        assert_eq!(new.reverse_swing, 0.);
+        ball.deliveries = 120;
+        ball.runs = 60;
+        let middle = ball.condition_factors();
+        assert!(middle.swing < new.swing && middle.seam < new.seam);
+        assert_eq!(middle.reverse_swing, 0.);
+        ball.deliveries = 360;
+        ball.runs = 200;
+        let old = ball.condition_factors();
+        assert!(old.reverse_swing > 0.);
+        assert!(old.spin > new.spin);
+        // The shine lasts for overs rather than balls, so it lasts longer in balls with
+        // longer overs
+        let shine = |balls_per_over| {
+            Ball {
+                deliveries: 60,
+                runs: 0,
+                balls_per_over,
+                ..ball.clone()
+            }
+            .condition_factors()
+            .swing
+        };
+        assert!(shine(8) > shine(6));
+        assert_eq!(
+            shine(5),
+            Ball {
+                deliveries: 72,
+                ..ball
+            }
+            .condition_factors()
+            .swing
+        );
+    }
+
+    #[test]
+    fn home_advantage() {
+        let advantage = HomeAdvantage::default().with_familiarity(
+            "India",
+            Familiarity {
+                spin: 1.,
+                pace: -0.5,
+            },
+        );
+        let home = advantage.batting_advantage(Ground::Home, None, None);
+        assert!(home > 0.);
+        assert_eq!(advantage.batting_advantage(Ground::Away, None, None), -home);
+        let india = Some("India");
+        let against_spin = advantage.batting_advantage(Ground::Neutral, india, Some(true));
+        let against_pace = advantage.batting_advantage(Ground::Neutral, india, Some(false));
+        assert!(against_spin > 0. && against_pace < 0.);
+        assert_eq!(
+            HomeAdvantage::none().batting_advantage(Ground::Home, india, Some(true)),
+            0.
+        );
+    }
+
+    #[test]
+    fn home_venues_in_a_tournament() -> Result<()> {
+        use model::NullModel;
+        use std::sync::Arc;
+        let venue = |name: &str, country: &str| Venue {
+            name: name.into(),
+            country: country.into(),
+        };
+        let mut db = PlayerDb::new();
+        let hosts = test_team(&mut db, 0, "IND")?.with_home(venue("Wankhede", "India"));
+        let visitors = test_team(&mut db, 1, "SA")?.with_home(venue("Newlands", "South Africa"));
+        let teams = vec![
+            Arc::new(visitors.clone()),
+            Arc::new(test_team(&mut db, 2, "SL")?),
+            Arc::new(hosts.clone()),
+        ];
+        let mut tournament = tournament::Tournament::round_robin(form::Form::t20(), teams)
+            .hosted_at(venue("Eden Gardens", "India"));
+        for fixture in tournament.fixtures() {
+            let host_playing = fixture.home == 2 || fixture.away == 2;
+            assert_eq!(fixture.home == 2, host_playing);
+            assert_eq!(fixture.neutral, !host_playing);
+        }
+        tournament.play(&NullModel {}, &db, &mut thread_rng())?;
+
+        let mut state = game::GameState::new(form::Form::t20(), hosts.clone(), visitors.clone())?;
+        assert_eq!(state.snapshot(&db)?.home_advantage, 0.);
+        state.set_venue(venue("Chepauk", "India"), HomeAdvantage::default());
+        assert_eq!(state.ground(hosts.id), Ground::Home);
+        assert_eq!(state.ground(visitors.id), Ground::Away);
+        assert!(state.snapshot(&db)?.home_advantage > 0.);
+        let replayed = game::GameState::replay(&state.log(), None)?;
+        assert_eq!(replayed.venue(), state.venue());
+        Ok(())
+    }
 }
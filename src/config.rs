@@ -0,0 +1,102 @@
+//! Formats and competitions defined declaratively, e.g. in a TOML file
+use crate::{
+    conditions::{HomeAdvantage, Venue},
+    error::Result,
+    form::Form,
+    tournament::Points,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::Read;
+
+/// Read a configuration from TOML
+pub fn load_toml<T: DeserializeOwned, R: Read>(mut reader: R) -> Result<T> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    Ok(toml::from_str(&text)?)
+}
+
+/// Read a configuration from JSON
+pub fn load_json<T: DeserializeOwned, R: Read>(reader: R) -> Result<T> {
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// A format given either by the name of a preset, e.g. `form = "t20"`, or in full
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FormConfig {
+    Preset(String),
+    Custom(Box<Form>),
+}
+
+impl Default for FormConfig {
+    fn default() -> Self {
+        Self::Preset("test".into())
+    }
+}
+
+impl FormConfig {
+    /// The format described, checked that a match can be played in it
+    pub fn resolve(&self) -> Result<Form> {
+        let form = match self {
+            Self::Preset(name) => Form::preset(name)?,
+            Self::Custom(form) => form.as_ref().clone(),
+        };
+        form.validate()?;
+        Ok(form)
+    }
+}
+
+impl From<Form> for FormConfig {
+    fn from(form: Form) -> Self {
+        Self::Custom(Box::new(form))
+    }
+}
+
+/// The rules of a round-robin tournament. The teams are given separately.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TournamentConfig {
+    pub form: FormConfig,
+    pub points: Points,
+    /// The ground every match is played at, if the tournament has a single host
+    pub host: Option<Venue>,
+    pub home_advantage: HomeAdvantage,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::form::TieBreaker;
+
+    #[test]
+    fn formats_from_toml() -> Result<()> {
+        let config: TournamentConfig = load_toml(
+            r#"
+            form = "T20"
+            points = { win = 4, shared = 2 }
+            host = { name = "Lord's", country = "England" }
+            "#
+            .as_bytes(),
+        )?;
+        assert_eq!(config.form.resolve()?.overs_per_innings, Some(20));
+        assert_eq!(config.points.win, 4);
+        assert_eq!(config.host.map(|v| v.country).as_deref(), Some("England"));
+
+        // A custom format in full, round-tripped through TOML
+        let indoor = Form::builder()
+            .innings(1)
+            .overs(8)
+            .players(8)
+            .overs_per_bowler(2)
+            .tie_breaker(TieBreaker::FewerWickets)
+            .build()?;
+        let text = toml::to_string(&FormConfig::from(indoor)).expect("serializable");
+        let loaded: FormConfig = load_toml(text.as_bytes())?;
+        let loaded = loaded.resolve()?;
+        assert_eq!(loaded.batsmen_per_side, 8);
+        assert_eq!(loaded.tie_breaker, TieBreaker::FewerWickets);
+
+        assert!(FormConfig::Preset("hundred".into()).resolve().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! Elo-style ratings of teams and players, updated from match results
+use crate::{
+    error::{Error, Result},
+    game::{GameState, MatchResult},
+    player::PlayerId,
+    team::Team,
+};
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
+
+/// The rating of a team or player with no results yet
+pub const INITIAL_RATING: f32 = 1500.;
+
+/// Ratings of teams and their players. Every player in a side moves by the same amount
+/// as the side, so player ratings follow the results of the teams they play for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EloRatings {
+    /// The largest change in rating from a single match
+    pub k: f32,
+    teams: FnvHashMap<u16, f32>,
+    players: FnvHashMap<PlayerId, f32>,
+}
+
+impl Default for EloRatings {
+    fn default() -> Self {
+        Self::new(20.)
+    }
+}
+
+impl EloRatings {
+    pub fn new(k: f32) -> Self {
+        Self {
+            k,
+            teams: FnvHashMap::default(),
+            players: FnvHashMap::default(),
+        }
+    }
+
+    pub fn team(&self, id: u16) -> f32 {
+        self.teams.get(&id).copied().unwrap_or(INITIAL_RATING)
+    }
+
+    pub fn player(&self, id: PlayerId) -> f32 {
+        self.players.get(&id).copied().unwrap_or(INITIAL_RATING)
+    }
+
+    /// Seed a team's rating, e.g. from an official ranking
+    pub fn set_team(&mut self, id: u16, rating: f32) {
+        self.teams.insert(id, rating);
+    }
+
+    pub fn set_player(&mut self, id: PlayerId, rating: f32) {
+        self.players.insert(id, rating);
+    }
+
+    /// The expected score of a side rated `rating` against one rated `opponent`, where
+    /// a win scores 1 and a tie or draw scores 1/2
+    pub fn expected(rating: f32, opponent: f32) -> f32 {
+        1. / (1. + 10_f32.powf((opponent - rating) / 400.))
+    }
+
+    /// Update the ratings from a result between two teams
+    pub fn record(&mut self, team_a: &Team, team_b: &Team, result: &MatchResult) {
+        let score_a = match result {
+            MatchResult::Win { winner, .. } if *winner == team_a.id => 1.,
+            MatchResult::Win { .. } => 0.,
+            MatchResult::Tie | MatchResult::Draw => 0.5,
+            // Too little was played to say anything about the sides
+            MatchResult::NoResult => return,
+        };
+        let expected_a = Self::expected(self.team(team_a.id), self.team(team_b.id));
+        let delta = self.k * (score_a - expected_a);
+        for (team, change) in [(team_a, delta), (team_b, -delta)] {
+            *self.teams.entry(team.id).or_insert(INITIAL_RATING) += change;
+            for (id, _) in &team.players {
+                *self.players.entry(*id).or_insert(INITIAL_RATING) += change;
+            }
+        }
+    }
+
+    /// Update the ratings from a completed match, whether simulated or ingested
+    pub fn record_match(&mut self, game: &GameState) -> Result<()> {
+        let result = game.result().ok_or(Error::MatchInProgress)?;
+        let (team_a, team_b) = game.teams();
+        self.record(team_a, team_b, &result);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{form, game, model, player::PlayerDb, tests::test_team};
+    use rand::thread_rng;
+
+    #[test]
+    fn elo_league() -> Result<()> {
+        use model::EloModel;
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "AFG")?;
+        let team_b = test_team(&mut db, 2, "IRE")?;
+        let mut ratings = EloRatings::default();
+        ratings.set_team(team_a.id, 1700.);
+        for player in &team_a.players {
+            ratings.set_player(player.0, 1700.);
+        }
+        let model = EloModel { ratings };
+        let mut state = game::GameState::new(form::Form::t20(), team_a.clone(), team_b)?;
+        state.simulate_to_completion(&model, &db, &mut thread_rng())?;
+
+        let mut ratings = model.ratings;
+        ratings.record_match(&state)?;
+        let total = ratings.team(team_a.id) + ratings.team(2);
+        assert!((total - 1700. - INITIAL_RATING).abs() < 1e-3);
+        if let Some(game::MatchResult::Win { winner, .. }) = state.result() {
+            let opener = team_a.players[0].0;
+            assert_eq!(ratings.player(opener) > 1700., winner == team_a.id);
+        }
+        Ok(())
+    }
+}
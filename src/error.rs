@@ -7,10 +7,77 @@ pub enum Error {
     PlayerNotFound(PlayerId),
     #[error("Duplicate player ID: {0}")]
     DuplicatePlayerId(PlayerId),
+    #[error("No player IDs are left after {0}")]
+    PlayerIdsExhausted(PlayerId),
     #[error("Match is complete")]
     MatchComplete,
+    #[error("Team {0} is not batting")]
+    NotBatting(u16),
+    #[error("Player {0} cannot come in to bat")]
+    CannotBat(PlayerId),
+    #[error("Player {0} cannot come on as a substitute")]
+    InvalidSubstitute(PlayerId),
+    #[error("Innings is already in progress")]
+    InningsInProgress,
     #[error("Object not available: {0}")]
     MissingData(String),
+    #[error("Replay does not match the recorded data: {0}")]
+    ReplayMismatch(String),
+    #[error("Not supported: {0}")]
+    Unsupported(String),
+    #[error("Match is not yet complete")]
+    MatchInProgress,
+    #[error("Saved parameters are for {found}, not {expected}")]
+    ModelMismatch { expected: String, found: String },
+    #[error("Team {0} is not valid: {1}")]
+    InvalidTeam(String, String),
+    #[error("Team {team} has {players} players, not {expected}")]
+    TeamSize {
+        team: String,
+        players: usize,
+        expected: usize,
+    },
+    #[error("Player {1} is listed more than once in team {0}")]
+    DuplicateInTeam(String, PlayerId),
+    #[error("Player {player} is given as {position} of team {team} but is not in it")]
+    NotInTeam {
+        team: String,
+        player: PlayerId,
+        position: String,
+    },
+    #[error("Team {0} has no wicket-keeper")]
+    NoKeeper(String),
+    #[error("Team {0} has {1} bowlers, fewer than the minimum")]
+    TooFewBowlers(String, usize),
+    #[error("Invalid field: {0}")]
+    InvalidField(String),
+    #[error("Invalid format: {0}")]
+    InvalidForm(String),
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
+    #[error("Invalid player rating: {0}")]
+    InvalidRating(String),
+    #[error("Invalid compact encoding: {0}")]
+    InvalidEncoding(String),
+    #[error("Invalid delivery: {0}")]
+    InvalidDelivery(String),
+    #[error("Invalid weights: {0}")]
+    InvalidWeights(String),
+    #[error("Database has schema version {0}, newer than this version of the crate")]
+    SchemaVersion(usize),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[cfg(feature = "sqlite")]
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
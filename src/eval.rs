@@ -0,0 +1,350 @@
+//! Evaluating how well a model predicts recorded matches
+use crate::{
+    error::Result,
+    game::{DeliveryOutcome, DeliveryOutcomeKind, GameState, MatchEvent},
+    model::{expected_runs, Model, PlayerRating},
+    player::PlayerDb,
+};
+use serde::{Deserialize, Serialize};
+
+/// The number of outcome classes that per-ball predictions are scored on
+const CLASSES: usize = 8;
+/// The smallest probability credited to an outcome, so that a model that rules out
+/// something that happened has a large but finite log-loss
+const MIN_PROB: f64 = 1e-6;
+
+/// The dismissal kinds whose frequencies are compared
+const DISMISSALS: [DeliveryOutcomeKind; 6] = [
+    DeliveryOutcomeKind::Bowled,
+    DeliveryOutcomeKind::Caught,
+    DeliveryOutcomeKind::Lbw,
+    DeliveryOutcomeKind::RunOut,
+    DeliveryOutcomeKind::Stumped,
+    DeliveryOutcomeKind::HitWicket,
+];
+
+/// The coarse class of an outcome that predictions are scored on: dots, ones, twos, three
+/// or more run off the bat, fours, sixes, any extra, and any wicket. Models differ in how
+/// finely they distinguish outcomes, so this is the level at which they can be compared.
+fn class(kind: DeliveryOutcomeKind) -> usize {
+    use DeliveryOutcomeKind::*;
+    match kind {
+        Runs(runs) => (runs as usize).min(3),
+        Four => 4,
+        Six => 5,
+        Wide | NoBall | Byes(_) | LegByes(_) => 6,
+        Bowled | Caught | Lbw | RunOut | Stumped | HitWicket => 7,
+    }
+}
+
+/// The runs added to the total by a delivery
+fn total_runs(outcome: &DeliveryOutcome) -> u16 {
+    outcome.runs.runs() as u16
+        + outcome
+            .extras
+            .iter()
+            .map(|ex| ex.runs() as u16)
+            .sum::<u16>()
+}
+
+/// Observed and predicted scoring in one over of the innings, over all innings evaluated
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OverCalibration {
+    /// The number of overs completed before this one
+    pub over: u16,
+    pub deliveries: u32,
+    pub observed_runs: u32,
+    pub expected_runs: f32,
+}
+
+impl OverCalibration {
+    pub fn observed_run_rate(&self) -> f32 {
+        self.observed_runs as f32 / self.deliveries.max(1) as f32
+    }
+
+    pub fn expected_run_rate(&self) -> f32 {
+        self.expected_runs / self.deliveries.max(1) as f32
+    }
+}
+
+/// Observed and predicted counts of one kind of dismissal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DismissalCalibration {
+    pub kind: DeliveryOutcomeKind,
+    pub observed: u32,
+    pub expected: f32,
+}
+
+/// The recorded total of an innings and the total expected by the model along the same
+/// sequence of states
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InningsCalibration {
+    pub observed: u16,
+    pub expected: f32,
+}
+
+/// How well a model predicted the deliveries of a set of matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Evaluation {
+    /// The number of deliveries scored
+    pub deliveries: u32,
+    log_loss: f64,
+    brier: f64,
+    /// Scoring by over of the innings
+    pub by_over: Vec<OverCalibration>,
+    pub dismissals: Vec<DismissalCalibration>,
+    pub innings: Vec<InningsCalibration>,
+}
+
+impl Evaluation {
+    /// The mean negative log-probability the model gave to the class of each delivery
+    pub fn log_loss(&self) -> f64 {
+        self.log_loss / self.deliveries.max(1) as f64
+    }
+
+    /// The mean Brier score of the model's prediction of the class of each delivery
+    pub fn brier(&self) -> f64 {
+        self.brier / self.deliveries.max(1) as f64
+    }
+
+    /// The mean amount by which the expected innings totals exceed the recorded ones
+    pub fn innings_bias(&self) -> f32 {
+        let n = self.innings.len().max(1) as f32;
+        self.innings
+            .iter()
+            .map(|inn| inn.expected - inn.observed as f32)
+            .sum::<f32>()
+            / n
+    }
+}
+
+/// Replay each match and score the model's outcome distribution before every delivery
+/// against what actually happened. The matches are typically replayed from recorded data,
+/// e.g. by `CricsheetMatch::replay`, and every player in them must be in the database.
+pub fn evaluate<'a, M, R>(
+    model: &M,
+    db: &PlayerDb<R>,
+    games: impl IntoIterator<Item = &'a GameState>,
+) -> Result<Evaluation>
+where
+    M: Model<R> + ?Sized,
+    R: PlayerRating,
+{
+    let mut eval = Evaluation {
+        deliveries: 0,
+        log_loss: 0.,
+        brier: 0.,
+        by_over: Vec::new(),
+        dismissals: DISMISSALS
+            .iter()
+            .map(|&kind| DismissalCalibration {
+                kind,
+                observed: 0,
+                expected: 0.,
+            })
+            .collect(),
+        innings: Vec::new(),
+    };
+    for game in games {
+        let log = game.log();
+        // Start from the logged venue, conditions and model, before any event
+        let mut state = GameState::replay(&log, Some(0))?;
+        // Innings are numbered across all of the matches
+        let first_innings = eval.innings.len();
+        for event in &log.events {
+            if let MatchEvent::Delivery(outcome) = event {
+                let snapshot = state.snapshot(db)?;
+                let distribution = model.outcome_distribution(&snapshot);
+                let innings = first_innings + state.innings_started().saturating_sub(1);
+                eval.score(
+                    &distribution,
+                    outcome,
+                    innings,
+                    snapshot.context.overs.completed,
+                );
+            }
+            state.apply(event)?;
+        }
+    }
+    Ok(eval)
+}
+
+impl Evaluation {
+    fn score(
+        &mut self,
+        distribution: &[(f32, DeliveryOutcomeKind)],
+        outcome: &DeliveryOutcome,
+        innings: usize,
+        over: u16,
+    ) {
+        let total: f32 = distribution.iter().map(|(p, _)| p).sum();
+        let mut probs = [0.; CLASSES];
+        if total > 0. {
+            for (p, kind) in distribution {
+                probs[class(*kind)] += (p / total) as f64;
+            }
+        }
+        let kind = outcome.kind();
+        let actual = class(kind);
+        self.deliveries += 1;
+        self.log_loss -= probs[actual].max(MIN_PROB).ln();
+        self.brier += probs
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p - if i == actual { 1. } else { 0. }).powi(2))
+            .sum::<f64>();
+
+        let expected = if total > 0. {
+            expected_runs(distribution)
+        } else {
+            0.
+        };
+        let runs = total_runs(outcome);
+        let over = over as usize;
+        if self.by_over.len() <= over {
+            self.by_over.resize_with(over + 1, Default::default);
+        }
+        let by_over = &mut self.by_over[over];
+        by_over.over = over as u16;
+        by_over.deliveries += 1;
+        by_over.observed_runs += runs as u32;
+        by_over.expected_runs += expected;
+
+        for dismissal in self.dismissals.iter_mut() {
+            if dismissal.kind == kind {
+                dismissal.observed += 1;
+            }
+            if total > 0. {
+                dismissal.expected += distribution
+                    .iter()
+                    .filter(|(_, k)| *k == dismissal.kind)
+                    .map(|(p, _)| p / total)
+                    .sum::<f32>();
+            }
+        }
+
+        if self.innings.len() <= innings {
+            self.innings.resize(
+                innings + 1,
+                InningsCalibration {
+                    observed: 0,
+                    expected: 0.,
+                },
+            );
+        }
+        let inn = &mut self.innings[innings];
+        inn.observed += runs;
+        inn.expected += expected;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        conditions::{HomeAdvantage, Venue},
+        form::Form,
+        game::{DeliveryOutcome, GameSnapshot},
+        model::{ModelInfo, Named, NullModel, PlayerRatingNull},
+        player::PlayerDb,
+        seed,
+        tests::test_team,
+    };
+    use rand::RngCore;
+
+    fn empty() -> Result<Evaluation> {
+        evaluate(&NullModel {}, &PlayerDb::<PlayerRatingNull>::new(), [])
+    }
+
+    #[test]
+    fn scored_by_hand() -> Result<()> {
+        let mut eval = empty()?;
+        let distribution = [
+            (2., DeliveryOutcomeKind::Runs(0)),
+            (1., DeliveryOutcomeKind::Runs(1)),
+            (1., DeliveryOutcomeKind::Caught),
+        ];
+        eval.score(&distribution, &DeliveryOutcome::running(0), 0, 0);
+        // A dot ball given a half chance
+        assert!((eval.log_loss() - 2f64.ln()).abs() < 1e-9);
+        assert!((eval.brier() - 0.375).abs() < 1e-9);
+        // A four the model ruled out is credited the smallest probability
+        eval.score(&distribution, &DeliveryOutcome::four(), 0, 1);
+        assert_eq!(eval.deliveries, 2);
+        let log_loss = (2f64.ln() - MIN_PROB.ln()) / 2.;
+        assert!((eval.log_loss() - log_loss).abs() < 1e-9);
+        assert!((eval.brier() - 0.875).abs() < 1e-9);
+
+        let overs: Vec<_> = eval
+            .by_over
+            .iter()
+            .map(|o| (o.over, o.deliveries, o.observed_runs, o.expected_runs))
+            .collect();
+        assert_eq!(overs, [(0, 1, 0, 0.25), (1, 1, 4, 0.25)]);
+        assert_eq!(eval.innings.len(), 1);
+        assert_eq!(eval.innings[0].observed, 4);
+        assert_eq!(eval.innings[0].expected, 0.5);
+        assert!((eval.innings_bias() - -3.5).abs() < 1e-6);
+        let caught = eval
+            .dismissals
+            .iter()
+            .find(|d| d.kind == DeliveryOutcomeKind::Caught)
+            .unwrap();
+        assert_eq!((caught.observed, caught.expected), (0, 0.5));
+        Ok(())
+    }
+
+    /// Expects a four from every ball when the batting side is at home, and a dot ball
+    /// otherwise
+    struct HomeScoring;
+
+    impl Named for HomeScoring {
+        const NAME: &'static str = "HomeScoring";
+    }
+
+    impl<R: PlayerRating> Model<R> for HomeScoring {
+        fn generate_delivery(
+            &self,
+            _rng: &mut dyn RngCore,
+            _state: GameSnapshot<R>,
+        ) -> DeliveryOutcome {
+            DeliveryOutcome::running(0)
+        }
+
+        fn outcome_distribution(&self, state: &GameSnapshot<R>) -> Vec<(f32, DeliveryOutcomeKind)> {
+            if state.home_advantage > 0. {
+                vec![(1., DeliveryOutcomeKind::Four)]
+            } else {
+                vec![(1., DeliveryOutcomeKind::Runs(0))]
+            }
+        }
+
+        fn info(&self) -> ModelInfo {
+            ModelInfo::of::<Self>()
+        }
+    }
+
+    #[test]
+    fn venue_kept() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let venue = Venue {
+            name: "Eden Park".into(),
+            country: "New Zealand".into(),
+        };
+        let mut team_a = test_team(&mut db, 1, "NZ")?;
+        team_a.home = Some(venue.clone());
+        let team_b = test_team(&mut db, 2, "AUS")?;
+        let mut game = GameState::new(Form::t20(), team_a, team_b)?;
+        let advantage = HomeAdvantage {
+            effect: 0.1,
+            ..HomeAdvantage::default()
+        };
+        game.set_venue(venue, advantage);
+        game.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(1))?;
+        let eval = evaluate(&HomeScoring, &db, [&game])?;
+        let home_balls = game.innings_deliveries(0).unwrap().len();
+        assert_eq!(eval.innings[0].expected, 4. * home_balls as f32);
+        assert_eq!(eval.innings[1].expected, 0.);
+        Ok(())
+    }
+}
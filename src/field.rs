@@ -0,0 +1,229 @@
+//! Where the fielding side places its fielders
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// The number of fielders placed by the captain, i.e. all but the bowler and the keeper
+pub const FIELDERS: u8 = 9;
+
+/// The positions of the fielders other than the bowler and the keeper, by area of the
+/// ground. Close catchers and the ring are inside the 30-yard circle; the boundary riders
+/// are outside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Field {
+    /// Slips, gully and close catchers around the bat
+    pub catchers: u8,
+    /// Fielders in the ring saving the single
+    pub ring: u8,
+    /// Fielders on the boundary
+    pub boundary: u8,
+    /// The fielders on the off side, out of all nine
+    pub off_side: u8,
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Self::balanced()
+    }
+}
+
+impl Field {
+    /// Slips and close catchers to take the edges, at the cost of gaps for boundaries
+    pub fn attacking() -> Self {
+        Self {
+            catchers: 4,
+            ring: 4,
+            boundary: 1,
+            off_side: 6,
+        }
+    }
+
+    /// Two slips, a ring saving the single and three boundary riders
+    pub fn balanced() -> Self {
+        Self {
+            catchers: 2,
+            ring: 4,
+            boundary: 3,
+            off_side: 5,
+        }
+    }
+
+    /// No catchers and the boundary protected, conceding singles
+    pub fn defensive() -> Self {
+        Self {
+            catchers: 0,
+            ring: 4,
+            boundary: 5,
+            off_side: 5,
+        }
+    }
+
+    /// The fielders on the leg side
+    pub fn leg_side(&self) -> u8 {
+        FIELDERS.saturating_sub(self.off_side)
+    }
+
+    /// Check that the field places every fielder, with no more than `max_outside_ring`
+    /// outside the circle if restricted
+    pub fn validate(&self, max_outside_ring: Option<u8>) -> Result<()> {
+        let invalid = |reason: String| Err(Error::InvalidField(reason));
+        let placed = self.catchers as u16 + self.ring as u16 + self.boundary as u16;
+        if placed != FIELDERS as u16 {
+            return invalid(format!("{} fielders placed, not {}", placed, FIELDERS));
+        }
+        if self.off_side > FIELDERS {
+            return invalid(format!("{} fielders on the off side", self.off_side));
+        }
+        match max_outside_ring {
+            Some(max) if self.boundary > max => invalid(format!(
+                "{} fielders outside the circle, but only {} allowed",
+                self.boundary, max
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The field brought in to meet the restriction on fielders outside the circle, if
+    /// any. Boundary riders over the limit come into the ring.
+    pub fn restricted(mut self, max_outside_ring: Option<u8>) -> Self {
+        if let Some(max) = max_outside_ring {
+            let excess = self.boundary.saturating_sub(max);
+            self.boundary -= excess;
+            self.ring += excess;
+        }
+        self
+    }
+
+    /// How much more likely an edge or mistimed shot is to go to hand than with the
+    /// balanced field
+    pub fn catch_factor(&self) -> f32 {
+        1. + 0.2 * (self.catchers as f32 - 2.)
+    }
+
+    /// How much more likely a shot hit to beat the field is to reach the boundary than
+    /// with the balanced field
+    pub fn boundary_factor(&self) -> f32 {
+        1. - 0.1 * (self.boundary as f32 - 3.)
+    }
+
+    /// How much more likely a ball worked into the field is to bring a second run than
+    /// with the balanced field. Fewer fielders in the ring leave bigger gaps.
+    pub fn gap_factor(&self) -> f32 {
+        1. + 0.15 * (4. - self.ring as f32)
+    }
+}
+
+/// The area of the ground a scoring shot went to, as one of the eight sectors of a wagon
+/// wheel. The sides are relative to the batter, so a cover drive is always to Cover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Zone {
+    ThirdMan,
+    Point,
+    Cover,
+    LongOff,
+    LongOn,
+    MidWicket,
+    SquareLeg,
+    FineLeg,
+}
+
+impl Zone {
+    /// Every zone, round the ground from third man to fine leg
+    pub const ALL: [Zone; 8] = [
+        Zone::ThirdMan,
+        Zone::Point,
+        Zone::Cover,
+        Zone::LongOff,
+        Zone::LongOn,
+        Zone::MidWicket,
+        Zone::SquareLeg,
+        Zone::FineLeg,
+    ];
+
+    /// The position of the zone in `Zone::ALL`
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn off_side(self) -> bool {
+        self.index() < 4
+    }
+
+    /// The centre of the zone in degrees round from straight past the bowler, positive
+    /// on the off side and negative on the leg side
+    pub fn angle(self) -> f32 {
+        let offset = 22.5 + 45. * (self.index() as f32 - 3.5).abs().floor();
+        if self.off_side() {
+            offset
+        } else {
+            -offset
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{form, game, model, player::PlayerDb, strategy, tests::test_team};
+    use rand::thread_rng;
+
+    #[test]
+    fn presets_and_restrictions() {
+        for field in &[Field::attacking(), Field::balanced(), Field::defensive()] {
+            assert!(field.validate(None).is_ok());
+        }
+        let balanced = Field::balanced();
+        assert_eq!(
+            (balanced.catch_factor(), balanced.boundary_factor()),
+            (1., 1.)
+        );
+        let defensive = Field::defensive();
+        assert!(defensive.validate(Some(2)).is_err());
+        let powerplay = defensive.restricted(Some(2));
+        assert!(powerplay.validate(Some(2)).is_ok());
+        assert_eq!((powerplay.ring, powerplay.boundary), (7, 2));
+        assert!(Field::attacking().catch_factor() > 1.);
+        assert!(defensive.boundary_factor() < 1.);
+        let overfull = Field {
+            catchers: u8::MAX,
+            ring: u8::MAX,
+            boundary: 1,
+            off_side: 0,
+        };
+        assert!(matches!(
+            overfull.validate(None),
+            Err(Error::InvalidField(_))
+        ));
+    }
+
+    #[test]
+    fn field_settings() -> Result<()> {
+        use model::{BasicBatting, BasicBowling, CompositeModel, RatedFielding};
+        use strategy::SituationalField;
+        use Field;
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "SA")?;
+        let team_b = test_team(&mut db, 2, "USA")?;
+        let model = CompositeModel {
+            bowling: BasicBowling {},
+            batting: BasicBatting {},
+            fielding: RatedFielding {},
+        };
+        let mut state = game::GameState::new(form::Form::t20(), team_a.clone(), team_b.clone())?;
+        assert_eq!(state.field(), Some(Field::balanced().restricted(Some(2))));
+        assert!(state.set_field(Field::defensive()).is_err());
+        state.set_fielding_strategy(&team_b, SituationalField::default())?;
+        state.set_fielding_strategy(&team_a, SituationalField::default())?;
+        assert_eq!(state.field(), Some(Field::attacking()));
+        state.simulate_to_completion(&model, &db, &mut thread_rng())?;
+        let log = state.log();
+        let fields = log.events.iter().filter_map(|event| match event {
+            game::MatchEvent::SetField(field) => Some(field),
+            _ => None,
+        });
+        assert!(fields.clone().count() >= 2);
+        assert!(fields.clone().all(|field| field.validate(None).is_ok()));
+        let replayed = game::GameState::replay(&log, None)?;
+        assert_eq!(replayed.result(), state.result());
+        Ok(())
+    }
+}
@@ -1,25 +1,162 @@
 //! Struct to define the format of a match
 
-use crate::conditions::{Ball, BallType};
+use crate::{
+    conditions::{Ball, BallType, BallWear},
+    error::{Error, Result},
+    field::FIELDERS,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+
+/// A count of overs in the usual style: the overs completed, and the legal balls bowled
+/// in the over in progress. Ordered by the number of balls.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct Overs {
+    #[serde(rename = "overs", alias = "over")]
+    pub completed: u16,
+    #[serde(alias = "ball")]
+    pub balls: u8,
+}
+
+impl Overs {
+    pub fn new(completed: u16, balls: u8) -> Self {
+        Self { completed, balls }
+    }
+
+    /// The overs taken up by a number of legal balls
+    pub fn from_balls(balls: u16, balls_per_over: u8) -> Self {
+        let per_over = balls_per_over.max(1) as u16;
+        Self {
+            completed: balls / per_over,
+            balls: (balls % per_over) as u8,
+        }
+    }
+
+    /// The total number of legal balls
+    pub fn total_balls(&self, balls_per_over: u8) -> u16 {
+        self.completed * balls_per_over as u16 + self.balls as u16
+    }
+
+    /// The overs after a further number of legal balls
+    pub fn add_balls(&self, balls: u16, balls_per_over: u8) -> Self {
+        Self::from_balls(self.total_balls(balls_per_over) + balls, balls_per_over)
+    }
+
+    /// The number of overs as a decimal, e.g. 45.5 for 45.3 overs of 6 balls, for rates
+    pub fn as_f32(&self, balls_per_over: u8) -> f32 {
+        self.completed as f32 + self.balls as f32 / balls_per_over as f32
+    }
+}
+
+impl Display for Overs {
+    /// Formats in the usual style, e.g. "45.3", or "45" once an over is complete
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.balls == 0 {
+            write!(f, "{}", self.completed)
+        } else {
+            write!(f, "{}.{}", self.completed, self.balls)
+        }
+    }
+}
+
+/// The penalty applied to a fielding side that falls behind the required over rate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum OverRatePenalty {
+    /// One fewer fielder is allowed outside the inner ring for the remainder of the
+    /// innings once the scheduled end of the innings has passed.
+    ExtraFielderInRing,
+    /// The given number of penalty runs is awarded to the batting side for each over the
+    /// fielding side is behind at the end of the innings.
+    PenaltyRuns(u8),
+}
+
+/// Rules governing the rate at which overs must be bowled
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OverRateRules {
+    /// The time allowed to bowl each over, in seconds
+    pub seconds_per_over: u32,
+    pub penalty: OverRatePenalty,
+}
+
+/// A limit on the fielders allowed outside the 30-yard circle for a stretch of an innings
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FieldRestriction {
+    /// The over, counted from 0, before which the restriction applies
+    pub until_over: u16,
+    pub max_outside_ring: u8,
+}
+
+/// How a tied match is decided when it must have a winner, e.g. in a knockout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TieBreaker {
+    /// The tie stands
+    #[default]
+    None,
+    /// The side that hit more fours and sixes wins
+    BoundaryCount,
+    /// The side that lost fewer wickets wins
+    FewerWickets,
+}
+
+/// The lead on first innings needed to enforce the follow-on in a match of the given
+/// number of days, as set by the Laws
+pub fn follow_on_margin(days: u8) -> u16 {
+    match days {
+        0 | 1 => 75,
+        2 => 100,
+        3 | 4 => 150,
+        _ => 200,
+    }
+}
 
 /// Defines the format of a match
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Form {
     /// The type and style of ball used.
     pub ball_type: BallType,
+    /// How quickly the ball wears
+    #[serde(default)]
+    pub ball_wear: BallWear,
     /// The number of turns each side has to bat.
     pub innings: u8,
     /// The number of overs in an innings, if limited
     pub overs_per_innings: Option<u16>,
     /// The number of balls per over.
     pub balls_per_over: u8,
-    // TODO: time limit, days/hours?
+    /// The number of days scheduled for the match, if it is time-limited
+    pub days: Option<u8>,
+    /// The number of overs scheduled to be bowled in each day of play
+    pub overs_per_day: u16,
+    /// Required over rate and the penalty for falling behind it, if enforced in-match
+    pub over_rate: Option<OverRateRules>,
     // TODO: ball type/color
     // TODO: overs for new ball (80 in test)?
-    // TODO: fielding restrictions/powerplays
     // TODO: players per side (almost always 11)?
     pub batsmen_per_side: u8,
-    // TODO: Maximum overs per bowler (10 in ODI?)
+    /// The most overs any one bowler may bowl in an innings, if limited
+    #[serde(default)]
+    pub overs_per_bowler: Option<u16>,
+    /// The restrictions on fielders outside the circle through the innings, in order. The
+    /// field is unrestricted after the last.
+    #[serde(default)]
+    pub field_restrictions: Vec<FieldRestriction>,
+    /// How a tied match is decided when it must have a winner
+    #[serde(default)]
+    pub tie_breaker: TieBreaker,
+    /// The lead on first innings at which the side batting first makes the other side
+    /// follow on, i.e. bat again straight away, if the follow-on is played
+    #[serde(default)]
+    pub follow_on: Option<u16>,
+    /// The unsuccessful reviews of umpires' decisions each side may make in an innings,
+    /// if the Decision Review System is used
+    #[serde(default)]
+    pub reviews_per_innings: Option<u8>,
+    /// The overs each side must face, unless bowled out or the target is reached sooner,
+    /// for a limited-overs match to have a result
+    #[serde(default)]
+    pub min_overs_for_result: Option<u16>,
 }
 
 impl Default for Form {
@@ -28,14 +165,25 @@ impl Default for Form {
             innings: 2,
             overs_per_innings: None,
             balls_per_over: 6,
+            days: Some(5),
+            overs_per_day: 90,
+            over_rate: None,
             batsmen_per_side: 11,
+            overs_per_bowler: None,
+            field_restrictions: Vec::new(),
+            tie_breaker: TieBreaker::None,
+            follow_on: Some(follow_on_margin(5)),
+            reviews_per_innings: Some(3),
+            min_overs_for_result: None,
             ball_type: BallType::RedLeather,
+            ball_wear: BallWear::default(),
         }
     }
 }
 
 impl Form {
-    /// Standard test format. Could also be called first_class.
+    /// Standard test format. Could also be called first_class. Scheduled for five days of
+    /// 90 overs, so a match still unfinished after 450 overs is drawn.
     pub fn test() -> Self {
         Self::default()
     }
@@ -45,7 +193,31 @@ impl Form {
         Self {
             innings: 1,
             overs_per_innings: Some(50),
+            overs_per_bowler: Some(10),
+            field_restrictions: vec![
+                FieldRestriction {
+                    until_over: 10,
+                    max_outside_ring: 2,
+                },
+                FieldRestriction {
+                    until_over: 40,
+                    max_outside_ring: 4,
+                },
+                FieldRestriction {
+                    until_over: 50,
+                    max_outside_ring: 5,
+                },
+            ],
+            days: None,
+            // 3.5 hours for 50 overs
+            over_rate: Some(OverRateRules {
+                seconds_per_over: 252,
+                penalty: OverRatePenalty::ExtraFielderInRing,
+            }),
             ball_type: BallType::WhiteLeather,
+            follow_on: None,
+            reviews_per_innings: Some(2),
+            min_overs_for_result: Some(20),
             ..Default::default()
         }
     }
@@ -55,17 +227,416 @@ impl Form {
         Self {
             innings: 1,
             overs_per_innings: Some(20),
+            overs_per_bowler: Some(4),
+            field_restrictions: vec![
+                FieldRestriction {
+                    until_over: 6,
+                    max_outside_ring: 2,
+                },
+                FieldRestriction {
+                    until_over: 20,
+                    max_outside_ring: 5,
+                },
+            ],
+            days: None,
+            // 85 minutes for 20 overs
+            over_rate: Some(OverRateRules {
+                seconds_per_over: 255,
+                penalty: OverRatePenalty::ExtraFielderInRing,
+            }),
+            ball_type: BallType::WhiteLeather,
+            follow_on: None,
+            reviews_per_innings: Some(2),
+            min_overs_for_result: Some(5),
+            ..Default::default()
+        }
+    }
+
+    /// Ten10, with two overs per bowler and a powerplay for the first three
+    pub fn t10() -> Self {
+        Self {
+            innings: 1,
+            overs_per_innings: Some(10),
+            overs_per_bowler: Some(2),
+            field_restrictions: vec![
+                FieldRestriction {
+                    until_over: 3,
+                    max_outside_ring: 2,
+                },
+                FieldRestriction {
+                    until_over: 10,
+                    max_outside_ring: 5,
+                },
+            ],
+            days: None,
+            // 45 minutes for 10 overs
+            over_rate: Some(OverRateRules {
+                seconds_per_over: 270,
+                penalty: OverRatePenalty::ExtraFielderInRing,
+            }),
             ball_type: BallType::WhiteLeather,
+            follow_on: None,
+            reviews_per_innings: None,
             ..Default::default()
         }
     }
 
+    /// The preset format with the given name: "test", "odi", "t20", or "t10", in any case
+    pub fn preset(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "test" => Ok(Self::test()),
+            "odi" => Ok(Self::odi()),
+            "t20" => Ok(Self::t20()),
+            "t10" => Ok(Self::t10()),
+            _ => Err(Error::InvalidForm(format!("no preset called \"{}\"", name))),
+        }
+    }
+
+    /// Build a custom format, starting from the test format
+    pub fn builder() -> FormBuilder {
+        FormBuilder::from(Self::default())
+    }
+
+    /// Check that a match can be played in the format
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(Error::InvalidForm(reason));
+        if self.innings == 0 {
+            return invalid("no innings".into());
+        }
+        if self.balls_per_over == 0 {
+            return invalid("no balls per over".into());
+        }
+        if self.batsmen_per_side < 2 {
+            return invalid(format!("{} batters per side", self.batsmen_per_side));
+        }
+        if self.overs_per_innings == Some(0) {
+            return invalid("no overs per innings".into());
+        }
+        if self.days == Some(0) || (self.days.is_some() && self.overs_per_day == 0) {
+            return invalid("no time to play".into());
+        }
+        match (self.overs_per_bowler, self.overs_per_innings) {
+            (Some(0), _) => return invalid("no overs per bowler".into()),
+            // Every player may bowl, so the limit must allow the innings to be completed
+            (Some(per_bowler), Some(overs))
+                if (per_bowler as u32) * (self.batsmen_per_side as u32) < overs as u32 =>
+            {
+                return invalid(format!(
+                    "{} bowlers of {} overs each can't bowl {} overs",
+                    self.batsmen_per_side, per_bowler, overs
+                ));
+            }
+            _ => (),
+        }
+        let mut previous = 0;
+        for restriction in &self.field_restrictions {
+            if restriction.until_over <= previous {
+                return invalid("field restrictions are not in order".into());
+            }
+            if restriction.max_outside_ring > FIELDERS {
+                return invalid(format!(
+                    "{} fielders allowed outside the circle",
+                    restriction.max_outside_ring
+                ));
+            }
+            previous = restriction.until_over;
+        }
+        match (self.min_overs_for_result, self.overs_per_innings) {
+            (Some(_), None) => {
+                return invalid("minimum overs for a result without limited overs".into())
+            }
+            (Some(min), Some(overs)) if min > overs => {
+                return invalid(format!(
+                    "{} overs needed for a result in a {} over innings",
+                    min, overs
+                ));
+            }
+            _ => (),
+        }
+        if let Some(overs) = self.overs_per_innings {
+            if previous > overs {
+                return invalid(format!(
+                    "field restrictions last {} overs of a {} over innings",
+                    previous, overs
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The total number of overs available in the match, if it is time-limited
+    pub fn match_overs(&self) -> Option<u16> {
+        self.days.map(|d| d as u16 * self.overs_per_day)
+    }
+
+    /// The overs taken up by a number of legal balls in this format
+    pub fn overs(&self, balls: u16) -> Overs {
+        Overs::from_balls(balls, self.balls_per_over)
+    }
+
+    /// The number of legal balls in an innings, if it is limited by overs
+    pub fn innings_balls(&self) -> Option<u16> {
+        self.overs_per_innings
+            .map(|overs| Overs::new(overs, 0).total_balls(self.balls_per_over))
+    }
+
+    /// The most fielders allowed outside the circle after the given overs of an innings,
+    /// if restricted
+    pub fn max_outside_ring(&self, overs: Overs) -> Option<u8> {
+        self.field_restrictions
+            .iter()
+            .find(|r| overs.completed < r.until_over)
+            .map(|r| r.max_outside_ring)
+    }
+
+    /// The format revised for innings of `overs` overs, e.g. after rain. Following the
+    /// standard playing conditions, the limit on each bowler and the field restrictions
+    /// are scaled down in proportion and rounded up.
+    pub fn reduced(&self, overs: u16) -> Result<Self> {
+        let scheduled = match self.overs_per_innings {
+            Some(scheduled) if overs <= scheduled => scheduled,
+            Some(scheduled) => {
+                return Err(Error::InvalidForm(format!(
+                    "can't extend {} over innings to {} overs",
+                    scheduled, overs
+                )))
+            }
+            None => {
+                return Err(Error::InvalidForm(
+                    "overs can only be reduced in a limited-overs format".into(),
+                ))
+            }
+        };
+        let scale = |n: u16| ((n as u32 * overs as u32).div_ceil(scheduled as u32)) as u16;
+        let mut field_restrictions: Vec<FieldRestriction> = Vec::new();
+        for restriction in &self.field_restrictions {
+            let until_over = scale(restriction.until_over);
+            // Keep the stricter restriction where two now end in the same over
+            if field_restrictions
+                .last()
+                .is_none_or(|previous| previous.until_over < until_over)
+            {
+                field_restrictions.push(FieldRestriction {
+                    until_over,
+                    ..*restriction
+                });
+            }
+        }
+        let form = Self {
+            overs_per_innings: Some(overs),
+            overs_per_bowler: self.overs_per_bowler.map(scale),
+            field_restrictions,
+            ..self.clone()
+        };
+        form.validate()?;
+        Ok(form)
+    }
+
+    /// Whether the side that batted second, trailing the other side by `deficit` runs
+    /// after the first `innings_played` innings, follows on
+    pub fn follows_on(&self, innings_played: usize, deficit: i32) -> bool {
+        // Only after each side's first innings, and only if there are innings to spare
+        innings_played == 2
+            && self.innings >= 2
+            && self.follow_on.is_some_and(|lead| deficit >= lead as i32)
+    }
+
+    /// Whether the batting side may declare their innings closed. This is only allowed
+    /// when innings are not limited by overs.
+    pub fn declarations_allowed(&self) -> bool {
+        self.overs_per_innings.is_none()
+    }
+
     /// Generate a fresh ball
     pub(crate) fn new_ball(&self) -> Ball {
         Ball {
             ball_type: self.ball_type,
             deliveries: 0,
             runs: 0,
+            wear: self.ball_wear,
+            balls_per_over: self.balls_per_over,
+        }
+    }
+}
+
+/// Defines a custom format, e.g. for an exhibition or indoor match. The format is
+/// checked when it is built.
+#[derive(Debug, Clone)]
+pub struct FormBuilder {
+    form: Form,
+}
+
+impl From<Form> for FormBuilder {
+    /// Start from an existing format
+    fn from(form: Form) -> Self {
+        Self { form }
+    }
+}
+
+impl FormBuilder {
+    /// The number of turns each side has to bat
+    pub fn innings(mut self, innings: u8) -> Self {
+        self.form.innings = innings;
+        self
+    }
+
+    /// Limit each innings to the given number of overs. Limited-overs matches are not
+    /// time-limited, and declarations aren't allowed.
+    pub fn overs(mut self, overs: u16) -> Self {
+        self.form.overs_per_innings = Some(overs);
+        self.form.days = None;
+        self
+    }
+
+    pub fn balls_per_over(mut self, balls: u8) -> Self {
+        self.form.balls_per_over = balls;
+        self
+    }
+
+    /// Schedule the match over the given number of days, with the given overs in each.
+    /// The follow-on, if played, is enforced at the lead the Laws set for that many days.
+    pub fn days(mut self, days: u8, overs_per_day: u16) -> Self {
+        self.form.days = Some(days);
+        self.form.overs_per_day = overs_per_day;
+        if self.form.follow_on.is_some() {
+            self.form.follow_on = Some(follow_on_margin(days));
+        }
+        self
+    }
+
+    /// The lead on first innings at which the follow-on is enforced, or None to always
+    /// alternate innings
+    pub fn follow_on(mut self, lead: Option<u16>) -> Self {
+        self.form.follow_on = lead;
+        self
+    }
+
+    /// The number of players in each side
+    pub fn players(mut self, players: u8) -> Self {
+        self.form.batsmen_per_side = players;
+        self
+    }
+
+    /// Limit the overs any one bowler may bowl in an innings
+    pub fn overs_per_bowler(mut self, overs: u16) -> Self {
+        self.form.overs_per_bowler = Some(overs);
+        self
+    }
+
+    /// Restrict the fielders outside the circle until the given over, counted from 0.
+    /// Restrictions must be added in order.
+    pub fn powerplay(mut self, until_over: u16, max_outside_ring: u8) -> Self {
+        self.form.field_restrictions.push(FieldRestriction {
+            until_over,
+            max_outside_ring,
+        });
+        self
+    }
+
+    pub fn over_rate(mut self, rules: OverRateRules) -> Self {
+        self.form.over_rate = Some(rules);
+        self
+    }
+
+    pub fn ball(mut self, ball_type: BallType, wear: BallWear) -> Self {
+        self.form.ball_type = ball_type;
+        self.form.ball_wear = wear;
+        self
+    }
+
+    /// The overs each side must face for the match to have a result, if any
+    pub fn min_overs(mut self, overs: Option<u16>) -> Self {
+        self.form.min_overs_for_result = overs;
+        self
+    }
+
+    /// The unsuccessful reviews each side may make in an innings, or None for no reviews
+    pub fn reviews(mut self, per_innings: Option<u8>) -> Self {
+        self.form.reviews_per_innings = per_innings;
+        self
+    }
+
+    pub fn tie_breaker(mut self, tie_breaker: TieBreaker) -> Self {
+        self.form.tie_breaker = tie_breaker;
+        self
+    }
+
+    /// The format, if it is valid
+    pub fn build(self) -> Result<Form> {
+        self.form.validate()?;
+        Ok(self.form)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_validates() -> Result<()> {
+        for form in &[Form::test(), Form::odi(), Form::t20(), Form::t10()] {
+            form.validate()?;
         }
+        let indoor = Form::builder()
+            .innings(1)
+            .overs(8)
+            .players(8)
+            .overs_per_bowler(2)
+            .powerplay(2, 1)
+            .tie_breaker(TieBreaker::BoundaryCount)
+            .build()?;
+        assert!(!indoor.declarations_allowed());
+        assert_eq!(indoor.max_outside_ring(Overs::new(1, 0)), Some(1));
+        assert_eq!(indoor.max_outside_ring(Overs::new(2, 0)), None);
+        let too_few_overs = Form::builder().overs(20).players(4).overs_per_bowler(4);
+        assert!(matches!(too_few_overs.build(), Err(Error::InvalidForm(_))));
+        // Limits too large to multiply in 16 bits are fine
+        Form::builder()
+            .overs(u16::MAX)
+            .players(11)
+            .overs_per_bowler(u16::MAX)
+            .build()?;
+        let out_of_order = FormBuilder::from(Form::t20()).powerplay(4, 2);
+        assert!(out_of_order.build().is_err());
+        let four_day = Form::builder().days(4, 96).build()?;
+        assert!(four_day.follows_on(2, 150) && !four_day.follows_on(2, 149));
+        assert!(!four_day.follows_on(3, 200));
+        assert!(!Form::t20().follows_on(2, 300));
+        Ok(())
+    }
+
+    #[test]
+    fn reduced_overs() -> Result<()> {
+        let odi = Form::odi().reduced(35)?;
+        assert_eq!(odi.overs_per_innings, Some(35));
+        assert_eq!(odi.overs_per_bowler, Some(7));
+        assert_eq!(odi.max_outside_ring(Overs::new(6, 0)), Some(2));
+        assert_eq!(odi.max_outside_ring(Overs::new(7, 0)), Some(4));
+        assert_eq!(odi.max_outside_ring(Overs::new(34, 0)), Some(5));
+        let t20 = Form::t20().reduced(7)?;
+        assert_eq!(t20.overs_per_bowler, Some(2));
+        assert_eq!(t20.max_outside_ring(Overs::new(2, 0)), Some(2));
+        assert_eq!(t20.max_outside_ring(Overs::new(3, 0)), Some(5));
+        assert!(Form::t20().reduced(21).is_err());
+        assert!(Form::test().reduced(60).is_err());
+        assert!(Form::t20().reduced(4).is_err());
+        let no_minimum = Form::builder().overs(4).min_overs(Some(5)).build();
+        assert!(matches!(no_minimum, Err(Error::InvalidForm(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn overs_arithmetic() {
+        let overs = Overs::new(45, 3);
+        assert_eq!(overs.to_string(), "45.3");
+        assert_eq!(overs.total_balls(6), 273);
+        assert_eq!(overs.add_balls(3, 6), Overs::new(46, 0));
+        assert_eq!(overs.add_balls(3, 6).to_string(), "46");
+        assert_eq!(Form::test().overs(273), overs);
+        assert!(overs < Overs::new(45, 4) && overs > Overs::new(44, 5));
+        assert_eq!(Form::t20().innings_balls(), Some(120));
+        assert_eq!(Form::test().innings_balls(), None);
+        assert_eq!(Form::test().match_overs(), Some(450));
+        assert_eq!(Form::t20().match_overs(), None);
     }
 }
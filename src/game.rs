@@ -1,31 +1,119 @@
 //! Description of the state and events of a match.
 use crate::{
-    conditions::{Conditions, Weather},
+    career::CareerStats,
+    conditions::{Clock, Conditions, Ground, HomeAdvantage, Pitch, Venue, Weather},
     error::{Error, Result},
-    form,
-    model::PlayerRating,
+    field::{Field, Zone},
+    form::{self, Overs},
+    model::{pipeline::Delivery, ModelInfo, PlayerRating},
+    morale::Morale,
     player::{Player, PlayerDb, PlayerId},
+    strategy::{
+        BattingOrderContext, BattingOrderStrategy, BowlerOption, BowlingContext, BowlingStrategy,
+        DeclarationContext, DeclarationStrategy, FieldContext, FieldingStrategy, Phase,
+        ReviewStrategy,
+    },
     team::Team,
 };
+use smallvec::{smallvec, SmallVec};
+pub mod compact;
+pub mod driver;
+pub mod log;
+pub mod observer;
+pub mod result;
+pub mod review;
+pub mod scorecard;
+pub mod session;
 pub mod stats;
-use stats::InningsStats;
+pub mod stream;
+pub use compact::{CompactLog, DeliveryCodec};
+pub use driver::{BallNumber, Deliveries, ScoreSnapshot};
+pub use log::{MatchEvent, MatchLog};
+pub use observer::MatchObserver;
+pub use result::{series_summary, AbandonReason, Margin, MatchResult};
+pub use review::{Appeal, Review, ReviewOutcome, Umpiring};
+pub use scorecard::Scorecard;
+pub use session::{CloseOfPlay, CloseScore, SessionSummary};
+use stats::{
+    BatterInningsStats, BowlerInningsStats, DeliveryRecord, DroppedCatch, EntryPoint, InningsStats,
+    KeeperInningsStats, Matchup, Milestone, OverSummary, PitchMap, TeamFieldingInningsStats,
+    WagonWheel,
+};
+pub use stream::{JsonLinesObserver, StreamEvent};
 
-use std::fmt::{self, Display};
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display},
+    io::{Read, Write},
+    sync::Arc,
+};
 
 /// Tracks the state of an ongoing match
-pub struct GameState<'a> {
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameState {
     /// The rules of the match
     form: form::Form,
+    /// The rules as the match was scheduled, if the overs have since been reduced
+    #[serde(default)]
+    scheduled_form: Option<form::Form>,
     /// The home team
-    team_a: &'a Team,
+    team_a: Arc<Team>,
     /// The visiting team
-    team_b: &'a Team,
+    team_b: Arc<Team>,
     /// Current innings in-progress. Is None when the game is complete.
-    current_innings_stats: Option<InningsStats<'a>>,
-    /// Previous innings stats
-    previous_innings: Vec<InningsStats<'a>>,
+    current_innings_stats: Option<InningsStats>,
+    /// Previous innings stats. These are shared between clones of the state, since they
+    /// no longer change.
+    previous_innings: Arc<Vec<InningsStats>>,
     /// Other conditions
     conditions: Conditions,
+    /// The ground the match is played at, if known
+    #[serde(default)]
+    venue: Option<Venue>,
+    /// How much the venue favours the side at home
+    #[serde(default)]
+    home_advantage: HomeAdvantage,
+    /// Declaration strategies of each side, by team ID. A side without a strategy
+    /// never declares automatically.
+    #[serde(skip)]
+    declaration_strategies: FnvHashMap<u16, Arc<dyn DeclarationStrategy>>,
+    /// Bowling strategies of each side, by team ID. A side without a strategy bowls its
+    /// usual rotation.
+    #[serde(skip)]
+    bowling_strategies: FnvHashMap<u16, Arc<dyn BowlingStrategy>>,
+    /// Fielding strategies of each side, by team ID. A side without a strategy sets the
+    /// balanced field.
+    #[serde(skip)]
+    fielding_strategies: FnvHashMap<u16, Arc<dyn FieldingStrategy>>,
+    /// Batting order strategies of each side, by team ID. A side without a strategy bats
+    /// in its usual order.
+    #[serde(skip)]
+    batting_order_strategies: FnvHashMap<u16, Arc<dyn BattingOrderStrategy>>,
+    /// Review strategies of each side, by team ID
+    #[serde(skip)]
+    review_strategies: FnvHashMap<u16, Arc<dyn ReviewStrategy>>,
+    /// How reliably the umpires decide appeals in simulated deliveries
+    #[serde(default)]
+    umpiring: Umpiring,
+    /// The field set by the fielding side for the current over, if any
+    #[serde(default)]
+    field: Option<Field>,
+    /// Every event applied to the match, in order
+    events: Vec<MatchEvent>,
+    /// The model that simulated the match, if any deliveries were simulated
+    #[serde(default)]
+    model: Option<ModelInfo>,
+    /// Why the match was abandoned, if it was
+    #[serde(default)]
+    abandoned: Option<AbandonReason>,
+    /// The ID of the team that forfeited the match, if one did
+    #[serde(default)]
+    forfeited: Option<u16>,
+    /// Records of batters against bowlers from before the match, by batter and then
+    /// bowler
+    #[serde(default)]
+    prior_matchups: Arc<Vec<(PlayerId, PlayerId, Matchup)>>,
 }
 
 /// The snapshot at a moment (e.g. striker, bowler, non-striker, fielders...)
@@ -36,26 +124,218 @@ where
     pub bowler: &'a Player<R>,
     pub striker: &'a Player<R>,
     pub non_striker: &'a Player<R>,
+    /// The whole fielding side, including the bowler and the keeper
+    pub fielders: Vec<&'a Player<R>>,
+    /// The fielding side's wicket-keeper, if one is designated
+    pub keeper: Option<&'a Player<R>>,
     pub conditions: Conditions,
+    /// Whether the fielding side must keep an extra fielder inside the ring as an over
+    /// rate penalty
+    pub extra_fielder_in_ring: bool,
+    /// Where the fielding side has placed its fielders
+    pub field: Field,
+    /// How tired the bowler is, from 0 (fresh) to 1 (exhausted)
+    pub bowler_fatigue: f32,
+    /// How much the venue favours the striker over the bowler, as a relative change in
+    /// the striker's average. This is negative if the bowler is favoured.
+    pub home_advantage: f32,
+    /// The form of the striker coming into the match
+    pub striker_morale: Morale,
+    /// The form of the bowler coming into the match
+    pub bowler_morale: Morale,
+    /// The striker's record against the bowler in this match
+    pub matchup: Matchup,
+    /// The striker's record against the bowler before this match, if known
+    pub prior_matchup: Matchup,
+    /// The state of the match before the delivery
+    pub context: MatchContext,
+}
+
+impl<R: PlayerRating> GameSnapshot<'_, R> {
+    /// The striker caught by the given fielder off the current bowler. A catch by the
+    /// designated keeper is taken behind the stumps, and one by the bowler is caught and
+    /// bowled.
+    pub fn caught_by(&self, fielder: &Player<R>) -> DeliveryOutcome {
+        let striker = self.striker.id;
+        let bowler = &self.bowler.name;
+        if fielder.id == self.bowler.id {
+            DeliveryOutcome::caught_and_bowled(striker, self.bowler.id, bowler)
+        } else if self.keeper.is_some_and(|keeper| keeper.id == fielder.id) {
+            DeliveryOutcome::caught_behind(striker, bowler, fielder.id, &fielder.name)
+        } else {
+            DeliveryOutcome::caught(striker, bowler, fielder.id, &fielder.name)
+        }
+    }
+}
+
+/// The state of the match as seen from the current innings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MatchContext {
+    /// The index of the innings in the match, starting at 0
+    pub innings: usize,
+    /// The batting side's score in this innings
+    pub runs: u16,
+    pub wickets: u8,
+    /// The overs bowled in the innings
+    #[serde(flatten)]
+    pub overs: Overs,
+    /// The number of legal balls left in the innings, if limited by overs or time
+    pub balls_remaining: Option<u16>,
+    /// The number of wickets the batting side has in hand
+    pub wickets_remaining: u8,
+    /// The score the batting side must reach in this innings to win, if chasing
+    pub target: Option<u16>,
+    /// The run rate needed to reach the target, if chasing in a limited innings
+    pub required_run_rate: Option<f32>,
+    /// Whether the delivery is a free hit following a no-ball
+    pub free_hit: bool,
+    /// The runs scored by the batters at the crease since the last wicket
+    pub partnership_runs: u16,
+    /// The legal balls faced since the last wicket
+    pub partnership_balls: u16,
+    /// The legal balls in a row off which no runs were scored
+    pub dots_in_a_row: u16,
+    /// The legal balls the striker has faced in their innings
+    pub striker_balls: u16,
+    /// The striker's position in the batting order, from 1 for the first opener
+    #[serde(default)]
+    pub striker_position: u8,
+    /// The score when the striker came in
+    #[serde(default)]
+    pub striker_came_in: EntryPoint,
 }
 
-impl<'a> GameState<'a> {
-    pub fn new(rules: form::Form, team_a: &'a Team, team_b: &'a Team) -> Result<Self> {
-        let current_innings_stats = Some(InningsStats::new(team_a, team_b, rules.balls_per_over)?);
+impl GameState {
+    /// Set up a match between two sides, whose line-ups are checked against the format.
+    /// The players are checked against the database, along with the keeper and the
+    /// bowlers, once the match is first simulated, since a match set up to be replayed
+    /// from a record doesn't need them.
+    pub fn new(
+        rules: form::Form,
+        team_a: impl Into<Arc<Team>>,
+        team_b: impl Into<Arc<Team>>,
+    ) -> Result<Self> {
+        let team_a = team_a.into();
+        let team_b = team_b.into();
+        rules.validate()?;
+        team_a.validate_lineup(rules.batsmen_per_side as usize)?;
+        team_b.validate_lineup(rules.batsmen_per_side as usize)?;
+        let mut current_innings_stats = InningsStats::new(&team_a, &team_b, rules.balls_per_over)?;
+        current_innings_stats
+            .bowling_stats
+            .set_max_overs(rules.overs_per_bowler);
+        current_innings_stats.max_overs = rules.overs_per_innings;
         let ball = rules.new_ball();
         Ok(Self {
             form: rules,
+            scheduled_form: None,
             team_a,
             team_b,
-            current_innings_stats,
-            previous_innings: Vec::new(),
+            current_innings_stats: Some(current_innings_stats),
+            previous_innings: Arc::default(),
             conditions: Conditions {
                 ball,
-                weather: Weather {},
+                weather: Weather::default(),
+                clock: Clock::default(),
+                pitch: Pitch::default(),
             },
+            venue: None,
+            home_advantage: HomeAdvantage::default(),
+            declaration_strategies: FnvHashMap::default(),
+            bowling_strategies: FnvHashMap::default(),
+            fielding_strategies: FnvHashMap::default(),
+            batting_order_strategies: FnvHashMap::default(),
+            review_strategies: FnvHashMap::default(),
+            umpiring: Umpiring::default(),
+            field: None,
+            events: Vec::new(),
+            model: None,
+            abandoned: None,
+            forfeited: None,
+            prior_matchups: Arc::default(),
         })
     }
 
+    /// Set the strategy the given team will use to decide when to declare
+    pub fn set_declaration_strategy(
+        &mut self,
+        team: &Team,
+        strategy: impl DeclarationStrategy + 'static,
+    ) {
+        self.declaration_strategies
+            .insert(team.id, Arc::new(strategy));
+    }
+
+    /// Set the strategy the given team will use to choose its bowlers. If the team is
+    /// about to bowl the first over of an innings, the strategy chooses who opens.
+    pub fn set_bowling_strategy(
+        &mut self,
+        team: &Team,
+        strategy: impl BowlingStrategy + 'static,
+    ) -> Result<()> {
+        self.bowling_strategies.insert(team.id, Arc::new(strategy));
+        match &self.current_innings_stats {
+            Some(st) if !st.started() => self.consult_bowling(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Set the strategy the given team will use to set its field. If the team is about
+    /// to bowl the first over of an innings, the strategy sets the field for it.
+    pub fn set_fielding_strategy(
+        &mut self,
+        team: &Team,
+        strategy: impl FieldingStrategy + 'static,
+    ) -> Result<()> {
+        self.fielding_strategies.insert(team.id, Arc::new(strategy));
+        match &self.current_innings_stats {
+            Some(st) if !st.started() => self.consult_field(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Set the strategy the given team will use to change its batting order when a
+    /// wicket falls
+    pub fn set_batting_order_strategy(
+        &mut self,
+        team: &Team,
+        strategy: impl BattingOrderStrategy + 'static,
+    ) {
+        self.batting_order_strategies
+            .insert(team.id, Arc::new(strategy));
+    }
+
+    /// Set the pitch and the weather the match is played in
+    pub fn set_conditions(&mut self, pitch: Pitch, weather: Weather) {
+        self.conditions.pitch = pitch;
+        self.conditions.weather = weather;
+    }
+
+    /// Play the match at the given ground, which favours a side at home by the given
+    /// advantage
+    pub fn set_venue(&mut self, venue: Venue, advantage: HomeAdvantage) {
+        self.venue = Some(venue);
+        self.home_advantage = advantage;
+    }
+
+    /// The ground the match is played at, if known
+    pub fn venue(&self) -> Option<&Venue> {
+        self.venue.as_ref()
+    }
+
+    /// Where the team with the given ID is playing. The ground is neutral if the venue
+    /// isn't known.
+    pub fn ground(&self, team: u16) -> Ground {
+        let (team, opponent) = if team == self.team_a.id {
+            (&self.team_a, &self.team_b)
+        } else {
+            (&self.team_b, &self.team_a)
+        };
+        self.venue
+            .as_ref()
+            .map_or(Ground::Neutral, |venue| venue.ground(team, opponent))
+    }
+
     // TODO: might need to constrain the db and snapshot references to distinguish them from the
     // lifetime of this GameState
     pub fn snapshot<'b, R>(&self, db: &'b PlayerDb<R>) -> Result<GameSnapshot<'b, R>>
@@ -65,9 +345,7 @@ impl<'a> GameState<'a> {
         let bowler_id = self.bowler().ok_or(Error::MatchComplete)?;
         let striker_id = self.striker().ok_or(Error::MatchComplete)?;
         let non_striker_id = self.non_striker().ok_or(Error::MatchComplete)?;
-        let bowler = db
-            .get(bowler_id)
-            .ok_or(Error::PlayerNotFound(bowler_id))?;
+        let bowler = db.get(bowler_id).ok_or(Error::PlayerNotFound(bowler_id))?;
         let striker = db
             .get(striker_id)
             .ok_or(Error::PlayerNotFound(striker_id))?;
@@ -75,26 +353,106 @@ impl<'a> GameState<'a> {
             .get(non_striker_id)
             .ok_or(Error::PlayerNotFound(non_striker_id))?;
         let conditions = self.conditions.clone();
+        let extra_fielder_in_ring = self
+            .current_innings_stats
+            .as_ref()
+            .is_some_and(|st| st.over_rate_penalty);
+        let bowler_fatigue = self.current_innings_stats.as_ref().map_or(0., |st| {
+            st.bowling_stats
+                .current_bowler_stats()
+                .fatigue(self.form.balls_per_over)
+        });
+        let context = self.context().ok_or(Error::MatchComplete)?;
+        let st = self
+            .current_innings_stats
+            .as_ref()
+            .ok_or(Error::MatchComplete)?;
+        let fielding_team = self.team(st.bowling_team);
+        let home_advantage = self.home_advantage.batting_advantage(
+            self.ground(st.batting_team),
+            self.team(st.batting_team)
+                .home
+                .as_ref()
+                .map(|home| home.country.as_str()),
+            bowler.bowls.map(|style| style.spin()),
+        );
+        let fielders = fielding_team
+            .players
+            .iter()
+            .map(|(id, _)| db.get(*id).ok_or(Error::PlayerNotFound(*id)))
+            .collect::<Result<_>>()?;
+        let keeper = fielding_team
+            .keeper
+            .map(|id| db.get(id).ok_or(Error::PlayerNotFound(id)))
+            .transpose()?;
         Ok(GameSnapshot {
             bowler,
             striker,
             non_striker,
+            fielders,
+            keeper,
             conditions,
+            extra_fielder_in_ring,
+            field: self.field().ok_or(Error::MatchComplete)?,
+            bowler_fatigue,
+            home_advantage,
+            striker_morale: striker.morale,
+            bowler_morale: bowler.morale,
+            matchup: self.matchup(striker_id, bowler_id),
+            prior_matchup: self.prior_matchup(striker_id, bowler_id),
+            context,
+        })
+    }
+
+    /// The state of the current innings, or None if the match is complete
+    pub fn context(&self) -> Option<MatchContext> {
+        let st = self.current_innings_stats.as_ref()?;
+        let runs = st.runs();
+        let (partnership_runs, partnership_balls) = st.partnership();
+        let striker = st.batting_stats.striker();
+        let striker_stats = st
+            .batting_stats
+            .batters()
+            .iter()
+            .find(|(id, _)| *id == striker)
+            .map(|(_, bat)| bat);
+        Some(MatchContext {
+            innings: self.previous_innings.len(),
+            runs,
+            wickets: st.wickets(),
+            overs: st.overs,
+            balls_remaining: self.balls_remaining(),
+            wickets_remaining: self.form.batsmen_per_side - 1 - st.wickets(),
+            target: self.runs_required().map(|required| runs + required),
+            required_run_rate: self.required_run_rate(),
+            free_hit: self.form.overs_per_innings.is_some() && st.free_hit(),
+            partnership_runs,
+            partnership_balls,
+            dots_in_a_row: st.dots_in_a_row(),
+            striker_balls: striker_stats.map_or(0, |bat| bat.balls),
+            striker_position: striker_stats.map_or(0, |bat| bat.position),
+            striker_came_in: striker_stats
+                .and_then(|bat| bat.came_in)
+                .unwrap_or_default(),
         })
     }
 
-    /// Get the current bowler
-    fn bowler(&self) -> Option<PlayerId> {
+    /// The bowler of the current over, or None if the match is complete
+    pub fn bowler(&self) -> Option<PlayerId> {
         self.current_innings_stats
             .as_ref()
             .map(|st| st.bowling_stats.current_bowler())
     }
-    fn striker(&self) -> Option<PlayerId> {
+
+    /// The batter on strike, or None if the match is complete
+    pub fn striker(&self) -> Option<PlayerId> {
         self.current_innings_stats
             .as_ref()
             .map(|st| st.batting_stats.striker())
     }
-    fn non_striker(&self) -> Option<PlayerId> {
+
+    /// The batter at the bowler's end, or None if the match is complete
+    pub fn non_striker(&self) -> Option<PlayerId> {
         self.current_innings_stats
             .as_ref()
             .map(|st| st.batting_stats.non_striker())
@@ -105,34 +463,582 @@ impl<'a> GameState<'a> {
         // NOTE: There are other ways for a game to be finished than completion of all
         // innings.
         // e.g.: last batting team overtakes, time limit, team with no more
-        // opportunities still has lower score, forfeture/abandonment, ...
-        // Some of these are accounted for, but not all.
+        // opportunities still has lower score, forfeiture, abandonment, ...
+        // Each of these closes the current innings.
         self.current_innings_stats.is_none()
     }
 
     /// Batting team declares to complete their innings
     pub fn declare(&mut self) -> Result<()> {
+        self.current_innings_stats
+            .as_mut()
+            .ok_or(Error::MatchComplete)?
+            .declared = true;
+        self.events.push(MatchEvent::Declare);
         self.new_innings()
     }
 
+    /// Override the current bowler, e.g. to follow a recorded match
+    pub fn set_bowler(&mut self, bowler: PlayerId) -> Result<()> {
+        let bowling_team = self
+            .current_innings_stats
+            .as_ref()
+            .map(|st| self.team(st.bowling_team))
+            .ok_or(Error::MatchComplete)?;
+        bowling_team
+            .get_name(bowler)
+            .ok_or(Error::PlayerNotFound(bowler))?;
+        let innings_stats = self
+            .current_innings_stats
+            .as_mut()
+            .ok_or(Error::MatchComplete)?;
+        if innings_stats.overs.balls == 0 {
+            innings_stats.bowling_stats.bring_on(bowler);
+        } else {
+            innings_stats.bowling_stats.set_current_bowler(bowler);
+        }
+        self.events.push(MatchEvent::SetBowler(bowler));
+        Ok(())
+    }
+
+    /// Override the batters at the crease, e.g. to follow a recorded match. A batter
+    /// other than the next in the order can only replace one who has not faced a ball.
+    pub fn set_batters(&mut self, striker: PlayerId, non_striker: PlayerId) -> Result<()> {
+        self.current_innings_stats
+            .as_mut()
+            .ok_or(Error::MatchComplete)?
+            .batting_stats
+            .set_batters(striker, non_striker)?;
+        self.events.push(MatchEvent::SetBatters {
+            striker,
+            non_striker,
+        });
+        Ok(())
+    }
+
+    /// Send in a batter who has still to bat next, e.g. at the fall of a wicket. If the
+    /// batter who has just come in has yet to face a ball, the promoted batter takes their
+    /// place and they come in next instead.
+    pub fn promote(&mut self, batter: PlayerId) -> Result<()> {
+        self.current_innings_stats
+            .as_mut()
+            .ok_or(Error::MatchComplete)?
+            .promote(batter)?;
+        self.events.push(MatchEvent::Promote(batter));
+        Ok(())
+    }
+
+    /// Reduce the innings of a limited-overs match to the given overs, e.g. after a
+    /// delay for rain. This applies from the current innings, along with the reduced
+    /// limits on each bowler. The innings closes at once if it has reached the new limit.
+    /// If fewer overs remain than are needed for a result, play is abandoned with no result.
+    pub fn reduce_overs(&mut self, overs: u16) -> Result<()> {
+        let innings_stats = self
+            .current_innings_stats
+            .as_mut()
+            .ok_or(Error::MatchComplete)?;
+        let bowled = innings_stats.overs.completed + (innings_stats.overs.balls > 0) as u16;
+        if overs < bowled {
+            return Err(Error::InvalidForm(format!(
+                "{} overs have already been bowled in the innings",
+                innings_stats.overs
+            )));
+        }
+        if self
+            .form
+            .min_overs_for_result
+            .is_some_and(|min| overs < min)
+        {
+            // The innings in progress can't reach the minimum, so there is no result
+            self.stop()?;
+            self.abandoned = Some(AbandonReason::Weather);
+            self.events.push(MatchEvent::ReduceOvers(overs));
+            return Ok(());
+        }
+        let innings_stats = self
+            .current_innings_stats
+            .as_mut()
+            .ok_or(Error::MatchComplete)?;
+        let form = self
+            .scheduled_form
+            .as_ref()
+            .unwrap_or(&self.form)
+            .reduced(overs)?;
+        innings_stats
+            .bowling_stats
+            .set_max_overs(form.overs_per_bowler);
+        innings_stats.max_overs = form.overs_per_innings;
+        if self.scheduled_form.is_none() {
+            self.scheduled_form = Some(self.form.clone());
+        }
+        self.form = form;
+        self.events.push(MatchEvent::ReduceOvers(overs));
+        self.check_innings_end()
+    }
+
+    /// The overs per innings the match was scheduled for before any reduction, if limited
+    pub fn scheduled_overs(&self) -> Option<u16> {
+        self.scheduled_form
+            .as_ref()
+            .unwrap_or(&self.form)
+            .overs_per_innings
+    }
+
+    /// Replace a player who can take no further part in the match, e.g. through injury,
+    /// with a substitute who takes their place in the side for the rest of the match. A
+    /// batter at the crease retires not out and the substitute comes in at once, and a
+    /// bowler part way through an over has it finished by the substitute.
+    pub fn substitute(
+        &mut self,
+        team: u16,
+        player: PlayerId,
+        replacement: (PlayerId, String),
+    ) -> Result<()> {
+        if self.complete() {
+            return Err(Error::MatchComplete);
+        }
+        let playing = |t: &Team| t.get_name(replacement.0).is_some();
+        if playing(&self.team_a) || playing(&self.team_b) {
+            return Err(Error::InvalidSubstitute(replacement.0));
+        }
+        let side = if team == self.team_a.id {
+            &mut self.team_a
+        } else if team == self.team_b.id {
+            &mut self.team_b
+        } else {
+            return Err(Error::MissingData(format!("No team with ID {}", team)));
+        };
+        let name = side
+            .get_name(player)
+            .ok_or(Error::PlayerNotFound(player))?
+            .to_string();
+        Arc::make_mut(side).replace(player, replacement.clone())?;
+        if let Some(innings_stats) = self.current_innings_stats.as_mut() {
+            innings_stats.substitute(team, player, &replacement);
+        }
+        self.events.push(MatchEvent::Substitute(Substitution {
+            team,
+            player: (player, name),
+            replacement,
+        }));
+        Ok(())
+    }
+
+    /// The given team forfeits the innings they are due to bat (Law 15). The innings is
+    /// recorded as complete without a ball being bowled.
+    pub fn forfeit_innings(&mut self, team: &Team) -> Result<()> {
+        let innings_stats = self
+            .current_innings_stats
+            .as_mut()
+            .ok_or(Error::MatchComplete)?;
+        if innings_stats.batting_team != team.id {
+            return Err(Error::NotBatting(team.id));
+        }
+        if innings_stats.started() {
+            return Err(Error::InningsInProgress);
+        }
+        innings_stats.forfeited = true;
+        self.events.push(MatchEvent::Forfeit(team.id));
+        self.new_innings()
+    }
+
+    /// Abandon the match, e.g. when weather prevents any further play. A limited-overs
+    /// match with enough of the chase played is decided on the scores so far; otherwise
+    /// there is no result, or a draw if play started in a match without limited overs.
+    pub fn abandon(&mut self, reason: AbandonReason) -> Result<()> {
+        self.stop()?;
+        self.abandoned = Some(reason.clone());
+        self.events.push(MatchEvent::Abandon(reason));
+        Ok(())
+    }
+
+    /// The given team forfeits the match, e.g. by refusing to play on, which is awarded
+    /// to the other side
+    pub fn forfeit(&mut self, team: &Team) -> Result<()> {
+        if team.id != self.team_a.id && team.id != self.team_b.id {
+            return Err(Error::InvalidTeam(
+                team.name.clone(),
+                "not playing in the match".into(),
+            ));
+        }
+        self.stop()?;
+        self.forfeited = Some(team.id);
+        self.events.push(MatchEvent::ForfeitMatch(team.id));
+        Ok(())
+    }
+
+    /// End the match at once, keeping the innings in progress if it has started
+    fn stop(&mut self) -> Result<()> {
+        let innings_stats = self.close_innings()?;
+        if innings_stats.started() {
+            Arc::make_mut(&mut self.previous_innings).push(innings_stats);
+        }
+        Ok(())
+    }
+
     /// Update the game state based on the outcome of a delivery
     pub fn update(&mut self, ball: &DeliveryOutcome) -> Result<()> {
+        let scored = ball.runs.runs() > 0
+            || ball.wicket.is_some()
+            || ball
+                .extras
+                .iter()
+                .any(|ex| !matches!(ex, Extra::Penalty(_)));
+        if ball.is_dead_ball() && scored {
+            return Err(Error::InvalidDelivery(
+                "nothing but penalty runs can be scored from a dead ball".into(),
+            ));
+        }
         self.conditions.ball.update(ball);
 
+        let innings = self.previous_innings.len();
         let innings_stats = self
             .current_innings_stats
             .as_mut()
             .ok_or(Error::MatchComplete)?;
         innings_stats.update(ball)?;
+        self.events.push(MatchEvent::Delivery(ball.clone()));
+        let over_complete = ball.legal() && innings_stats.overs.balls == 0;
+        let seconds = Clock::delivery_seconds(ball, over_complete);
+        self.conditions.clock.advance(seconds);
+        innings_stats.advance_clock(
+            seconds,
+            self.form.over_rate.as_ref(),
+            self.form.overs_per_innings,
+        );
+        self.check_innings_end()?;
+        if ball.wicket.is_some() && self.previous_innings.len() == innings {
+            self.consult_batting_order()?;
+        }
+        // A new innings has already consulted the bowling strategy for its first over, and
+        // its batting side has yet to face a ball to declare on
+        if over_complete && self.previous_innings.len() == innings {
+            self.consult_declaration()?;
+            self.consult_bowling()?;
+            self.consult_field()?;
+        }
+        Ok(())
+    }
+
+    /// The number of overs bowled so far in the match
+    pub fn match_overs_bowled(&self) -> u16 {
+        self.previous_innings
+            .iter()
+            .chain(self.current_innings_stats.iter())
+            .map(|st| st.overs.completed)
+            .sum()
+    }
+
+    /// The number of overs remaining in the match, if it is time-limited
+    pub fn match_overs_remaining(&self) -> Option<u16> {
+        self.form
+            .match_overs()
+            .map(|overs| overs.saturating_sub(self.match_overs_bowled()))
+    }
 
-        // Check if we need to change to a new innings
+    /// The batting side's run rate in the current innings, in runs per over, or 0 before
+    /// a legal ball is bowled
+    pub fn run_rate(&self) -> Option<f32> {
+        self.current_innings_stats.as_ref().map(|st| st.run_rate())
+    }
+
+    /// The number of legal balls remaining in the current innings, if it is limited by
+    /// overs or by the time remaining in the match
+    pub fn balls_remaining(&self) -> Option<u16> {
+        let innings_stats = self.current_innings_stats.as_ref()?;
+        match self.form.innings_balls() {
+            Some(balls) => Some(balls.saturating_sub(innings_stats.balls_bowled())),
+            None => self.match_overs_remaining().map(|overs| {
+                let balls = Overs::new(overs, 0).total_balls(self.form.balls_per_over);
+                balls.saturating_sub(innings_stats.overs.balls as u16)
+            }),
+        }
+    }
+
+    /// The number of runs the batting side needs to win, if they are chasing in the
+    /// final innings
+    pub fn runs_required(&self) -> Option<u16> {
+        let innings_stats = self.current_innings_stats.as_ref()?;
+        if self.previous_innings.len() + 1 != 2 * self.form.innings as usize {
+            return None;
+        }
+        let target = self.score_of(innings_stats.bowling_team) + 1;
+        Some(target.saturating_sub(self.score_of(innings_stats.batting_team)))
+    }
+
+    /// The run rate, in runs per over, the batting side needs to win in the final innings,
+    /// or 0 once no balls remain
+    pub fn required_run_rate(&self) -> Option<f32> {
+        let runs = self.runs_required()?;
+        let balls = self.balls_remaining()?;
+        if balls == 0 {
+            return Some(0.);
+        }
+        Some(runs as f32 * self.form.balls_per_over as f32 / balls as f32)
+    }
+
+    /// The fielding side's over rate in the current innings, in overs per hour
+    pub fn over_rate(&self) -> Option<f32> {
+        self.current_innings_stats.as_ref().map(|st| st.over_rate())
+    }
+
+    /// The situation as seen by the batting side when deciding whether to declare.
+    /// Returns None if the match is complete.
+    pub fn declaration_context(&self) -> Option<DeclarationContext> {
+        let innings_stats = self.current_innings_stats.as_ref()?;
+        let lead = self.score_of(innings_stats.batting_team) as i32
+            - self.score_of(innings_stats.bowling_team) as i32;
+        let innings = self.previous_innings.len();
+        Some(DeclarationContext {
+            innings,
+            final_innings: innings + 1 == 2 * self.form.innings as usize,
+            lead,
+            overs_remaining: self.match_overs_remaining(),
+            wickets_in_hand: self.form.batsmen_per_side - 1 - innings_stats.wickets(),
+            captain: self.team(innings_stats.batting_team).captain,
+        })
+    }
+
+    /// Ask the batting side's declaration strategy whether to declare, and do so if it
+    /// decides to.
+    fn consult_declaration(&mut self) -> Result<()> {
+        if !self.form.declarations_allowed() {
+            return Ok(());
+        }
+        let context = match self.declaration_context() {
+            Some(context) => context,
+            None => return Ok(()),
+        };
+        let batting_team = self.batting_team().ok_or(Error::MatchComplete)?;
+        let declare = self
+            .declaration_strategies
+            .get(&batting_team.id)
+            .is_some_and(|strategy| strategy.declare(&context));
+        if declare {
+            self.declare()?;
+        }
+        Ok(())
+    }
+
+    /// The situation as seen by the fielding side when choosing the bowler of the next
+    /// over. Returns None if the match is complete.
+    pub fn bowling_context(&self) -> Option<BowlingContext> {
+        let innings_stats = self.current_innings_stats.as_ref()?;
+        let team = self.team(innings_stats.bowling_team);
+        let bowling = &innings_stats.bowling_stats;
+        let bpo = self.form.balls_per_over;
+        let previous = bowling.previous_bowler();
+        let striker = innings_stats.batting_stats.striker();
+        let options = bowling
+            .rotation()
+            .iter()
+            .filter(|&&id| Some(id) != previous)
+            .filter_map(|&id| {
+                let overs_left = bowling.overs_left(id, bpo);
+                if overs_left == Some(0) {
+                    return None;
+                }
+                let stats = bowling
+                    .bowlers()
+                    .iter()
+                    .find(|(b, _)| *b == id)
+                    .map(|(_, st)| st);
+                Some(BowlerOption {
+                    id,
+                    role: team.role(id),
+                    overs: stats.map_or(0, |st| st.overs(bpo).completed),
+                    runs: stats.map_or(0, |st| st.runs),
+                    wickets: stats.map_or(0, |st| st.wickets),
+                    fatigue: stats.map_or(0., |st| st.fatigue(bpo)),
+                    overs_left,
+                    against_striker: {
+                        let mut matchup = self.prior_matchup(striker, id);
+                        matchup.combine(&self.matchup(striker, id));
+                        matchup
+                    },
+                })
+            })
+            .collect();
+        Some(BowlingContext {
+            over: innings_stats.overs.completed,
+            phase: Phase::of(innings_stats.overs.completed, self.form.overs_per_innings),
+            overs_remaining: self
+                .balls_remaining()
+                .map(|balls| balls.div_ceil(bpo as u16)),
+            striker,
+            non_striker: innings_stats.batting_stats.non_striker(),
+            previous,
+            options,
+            captain: team.captain,
+        })
+    }
+
+    /// Ask the fielding side's bowling strategy who should bowl the next over, and bring
+    /// them on if they are not the bowler the rotation chose.
+    fn consult_bowling(&mut self) -> Result<()> {
+        let context = match self.bowling_context() {
+            Some(context) => context,
+            None => return Ok(()),
+        };
+        let bowling_team = self
+            .current_innings_stats
+            .as_ref()
+            .ok_or(Error::MatchComplete)?
+            .bowling_team;
+        let choice = self
+            .bowling_strategies
+            .get(&bowling_team)
+            .and_then(|strategy| strategy.choose(&context));
+        match choice {
+            Some(bowler)
+                if Some(bowler) != self.bowler()
+                    && context.options.iter().any(|b| b.id == bowler) =>
+            {
+                self.set_bowler(bowler)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The most fielders allowed outside the circle in the current over, if restricted.
+    /// A side penalised for its over rate must bring one more fielder in.
+    fn max_outside_ring(&self) -> Option<u8> {
+        let innings_stats = self.current_innings_stats.as_ref()?;
+        let max = self.form.max_outside_ring(innings_stats.overs)?;
+        Some(max.saturating_sub(innings_stats.over_rate_penalty as u8))
+    }
+
+    /// The field in place for the current over: the one the fielding side set, or the
+    /// balanced field, brought in as far as the restrictions require. Returns None if the
+    /// match is complete.
+    pub fn field(&self) -> Option<Field> {
+        self.current_innings_stats.as_ref()?;
+        Some(
+            self.field
+                .unwrap_or_default()
+                .restricted(self.max_outside_ring()),
+        )
+    }
+
+    /// Set the field for the rest of the over, e.g. to follow a recorded match
+    pub fn set_field(&mut self, field: Field) -> Result<()> {
+        if self.complete() {
+            return Err(Error::MatchComplete);
+        }
+        field.validate(self.max_outside_ring())?;
+        self.field = Some(field);
+        self.events.push(MatchEvent::SetField(field));
+        Ok(())
+    }
+
+    /// The situation as seen by the fielding side when setting the field. Returns None if
+    /// the match is complete.
+    pub fn field_context(&self) -> Option<FieldContext> {
+        let innings_stats = self.current_innings_stats.as_ref()?;
+        Some(FieldContext {
+            situation: self.context()?,
+            phase: Phase::of(innings_stats.overs.completed, self.form.overs_per_innings),
+            max_outside_ring: self.max_outside_ring(),
+            bowler: self.bowler()?,
+            captain: self.team(innings_stats.bowling_team).captain,
+        })
+    }
+
+    /// Ask the fielding side's fielding strategy to set the field for the next over
+    fn consult_field(&mut self) -> Result<()> {
+        let context = match self.field_context() {
+            Some(context) => context,
+            None => return Ok(()),
+        };
+        let bowling_team = self
+            .current_innings_stats
+            .as_ref()
+            .ok_or(Error::MatchComplete)?
+            .bowling_team;
+        let field = match self.fielding_strategies.get(&bowling_team) {
+            Some(strategy) => strategy.field(&context),
+            None => return Ok(()),
+        };
+        let field = field.restricted(context.max_outside_ring);
+        if self.field == Some(field) {
+            return Ok(());
+        }
+        self.set_field(field)
+    }
+
+    /// The situation as seen by the batting side when changing its order. Returns None if
+    /// the match is complete.
+    pub fn batting_order_context(&self) -> Option<BattingOrderContext> {
+        let innings_stats = self.current_innings_stats.as_ref()?;
+        Some(BattingOrderContext {
+            situation: self.context()?,
+            incoming: innings_stats.batting_stats.incoming(),
+            remaining: innings_stats.batting_stats.remaining(),
+            captain: self.team(innings_stats.batting_team).captain,
+        })
+    }
+
+    /// Ask the batting side's batting order strategy whether to send in someone other
+    /// than the next batter, and do so if it decides to
+    fn consult_batting_order(&mut self) -> Result<()> {
+        let context = match self.batting_order_context() {
+            Some(context) => context,
+            None => return Ok(()),
+        };
+        let batting_team = self.batting_team().ok_or(Error::MatchComplete)?;
+        let choice = self
+            .batting_order_strategies
+            .get(&batting_team.id)
+            .and_then(|strategy| strategy.next_batter(&context));
+        match choice {
+            Some(batter) if context.remaining.contains(&batter) => self.promote(batter),
+            _ => Ok(()),
+        }
+    }
+
+    /// The team currently batting, if the match is in progress
+    pub fn batting_team(&self) -> Option<&Team> {
+        self.current_innings_stats
+            .as_ref()
+            .map(|st| self.team(st.batting_team))
+    }
+
+    /// Update the game state with an event that occurs outside of a delivery. No ball is
+    /// counted.
+    pub fn non_delivery_event(&mut self, event: &NonDeliveryEvent) -> Result<()> {
+        let innings_stats = self
+            .current_innings_stats
+            .as_mut()
+            .ok_or(Error::MatchComplete)?;
+        innings_stats.non_delivery_event(event)?;
+        self.events.push(MatchEvent::NonDelivery(event.clone()));
+        let innings = self.previous_innings.len();
+        self.check_innings_end()?;
+        if self.previous_innings.len() == innings {
+            self.consult_batting_order()?;
+        }
+        Ok(())
+    }
+
+    /// Check if we need to change to a new innings, and do so if necessary
+    fn check_innings_end(&mut self) -> Result<()> {
+        let innings_stats = self
+            .current_innings_stats
+            .as_ref()
+            .ok_or(Error::MatchComplete)?;
+        // If time has run out the match is over, regardless of the state of the innings
+        if self.match_overs_remaining() == Some(0) {
+            let last_innings_stats = self.close_innings()?;
+            Arc::make_mut(&mut self.previous_innings).push(last_innings_stats);
+            return Ok(());
+        }
         let mut new_innings = false;
         if innings_stats.all_out() {
             assert_eq!(innings_stats.wickets() + 1, self.form.batsmen_per_side);
             new_innings = true;
         }
         if let Some(opi) = self.form.overs_per_innings {
-            if innings_stats.overs >= opi {
+            if innings_stats.overs.completed >= opi {
                 new_innings = true;
             }
         }
@@ -140,7 +1046,7 @@ impl<'a> GameState<'a> {
         let bowling_team = innings_stats.bowling_team;
         // If this is the last innings and the batting team caught up, end the match
         if self.previous_innings.len() + 1 == 2 * self.form.innings as usize
-            && self.team_score(batting_team) > self.team_score(bowling_team)
+            && self.score_of(batting_team) > self.score_of(bowling_team)
         {
             new_innings = true;
         }
@@ -150,23 +1056,32 @@ impl<'a> GameState<'a> {
         Ok(())
     }
 
-    /// Initiate a new innings
-    fn new_innings(&mut self) -> Result<()> {
-        let last_innings_stats = self
+    /// Take the current innings, applying any penalties due at its close
+    fn close_innings(&mut self) -> Result<InningsStats> {
+        let mut innings_stats = self
             .current_innings_stats
             .take()
             .ok_or(Error::MatchComplete)?;
+        innings_stats.close();
+        if let Some(rules) = &self.form.over_rate {
+            innings_stats.apply_over_rate_penalty(rules);
+        }
+        Ok(innings_stats)
+    }
+
+    /// Initiate a new innings
+    fn new_innings(&mut self) -> Result<()> {
+        let last_innings_stats = self.close_innings()?;
         let last_batting_team = last_innings_stats.batting_team;
         let last_bowling_team = last_innings_stats.bowling_team;
-        self.previous_innings.push(last_innings_stats);
+        let last_forfeited = last_innings_stats.forfeited;
+        Arc::make_mut(&mut self.previous_innings).push(last_innings_stats);
         // If all innings have been played (or if the game is over), exit
         if self.previous_innings.len() >= 2 * self.form.innings as usize {
             return Ok(());
         }
-        // Make the losing team go first regardless if they are losing by 150 or more
-        // and both teams have had equal opportunities so far.
-        let last_batting_runs = self.team_score(last_batting_team);
-        let last_bowling_runs = self.team_score(last_bowling_team);
+        let last_batting_runs = self.score_of(last_batting_team);
+        let last_bowling_runs = self.score_of(last_bowling_team);
 
         // If the team just batting has run out of opportunities to overtake, the match
         // is called.
@@ -176,24 +1091,131 @@ impl<'a> GameState<'a> {
             return Ok(());
         }
 
-        let (next_batting_team, next_bowling_team) = if self.previous_innings.len() % 2 == 0
-            && last_batting_runs + 150 <= last_bowling_runs
-        {
-            (last_batting_team, last_bowling_team)
-        } else {
-            (last_bowling_team, last_batting_team)
-        };
+        // The sides alternate, unless the side that just batted is made to follow on. A
+        // side that forfeited its innings didn't bat, so it isn't sent in again.
+        let deficit = last_bowling_runs as i32 - last_batting_runs as i32;
+        let (next_batting_team, next_bowling_team) =
+            if !last_forfeited && self.form.follows_on(self.previous_innings.len(), deficit) {
+                (last_batting_team, last_bowling_team)
+            } else {
+                (last_bowling_team, last_batting_team)
+            };
 
-        self.current_innings_stats = Some(InningsStats::new(
-            next_batting_team,
-            next_bowling_team,
+        let mut innings_stats = InningsStats::new(
+            self.team(next_batting_team),
+            self.team(next_bowling_team),
             self.form.balls_per_over,
-        )?);
+        )?;
+        innings_stats
+            .bowling_stats
+            .set_max_overs(self.form.overs_per_bowler);
+        innings_stats.max_overs = self.form.overs_per_innings;
+        self.current_innings_stats = Some(innings_stats);
+        self.conditions.ball = self.form.new_ball();
+        self.field = None;
+        self.consult_bowling()?;
+        self.consult_field()
+    }
+
+    /// The rules of the match
+    pub fn form(&self) -> &form::Form {
+        &self.form
+    }
+
+    /// The model that simulated the match. If several were used, this is the first.
+    pub fn model(&self) -> Option<&ModelInfo> {
+        self.model.as_ref()
+    }
+
+    /// The two teams playing the match, home side first
+    pub fn teams(&self) -> (&Team, &Team) {
+        (&self.team_a, &self.team_b)
+    }
+
+    /// The team with the given ID. Any ID other than that of the home side refers to the
+    /// visiting side.
+    pub(crate) fn team(&self, id: u16) -> &Team {
+        if id == self.team_a.id {
+            &self.team_a
+        } else {
+            &self.team_b
+        }
+    }
+
+    /// Save the match as JSON so that it can be resumed later. Declaration strategies are
+    /// not saved, and must be set again after loading.
+    pub fn save_json<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer(writer, self)?;
         Ok(())
     }
 
+    /// Load a match previously saved as JSON
+    pub fn load_json<R: Read>(reader: R) -> Result<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// The stats of each completed innings
+    pub(crate) fn completed_innings(&self) -> &[InningsStats] {
+        &self.previous_innings
+    }
+
+    /// The batter's record against the bowler over the match so far
+    pub fn matchup(&self, batter: PlayerId, bowler: PlayerId) -> Matchup {
+        let mut matchup = Matchup::default();
+        for st in self
+            .previous_innings
+            .iter()
+            .chain(self.current_innings_stats.iter())
+        {
+            matchup.combine(&st.matchup(batter, bowler));
+        }
+        matchup
+    }
+
+    /// The batter's record against the bowler before this match, if it was given
+    pub fn prior_matchup(&self, batter: PlayerId, bowler: PlayerId) -> Matchup {
+        self.prior_matchups
+            .iter()
+            .find(|(b, w, _)| *b == batter && *w == bowler)
+            .map_or_else(Matchup::default, |(_, _, matchup)| *matchup)
+    }
+
+    /// Take the records of the players in this match against each other from their
+    /// careers, so that models and strategies can see them
+    pub fn set_prior_matchups(&mut self, careers: &CareerStats) {
+        let mut matchups = Vec::new();
+        for (batting, bowling) in [(&self.team_a, &self.team_b), (&self.team_b, &self.team_a)] {
+            for (batter, _) in &batting.players {
+                for (bowler, _) in &bowling.players {
+                    if let Some(matchup) = careers.matchup(*batter, *bowler) {
+                        matchups.push((*batter, *bowler, *matchup));
+                    }
+                }
+            }
+        }
+        self.prior_matchups = Arc::new(matchups);
+    }
+
     /// Returns the given team's current score
     pub fn team_score(&self, team: &Team) -> u16 {
+        self.score_of(team.id)
+    }
+
+    /// The runs by which the given team leads the other over the match so far, or None if
+    /// the team isn't playing in the match. A negative lead is a deficit.
+    pub fn lead(&self, team: &Team) -> Option<i32> {
+        let other = if team.id == self.team_a.id {
+            self.team_b.id
+        } else if team.id == self.team_b.id {
+            self.team_a.id
+        } else {
+            return None;
+        };
+        Some(self.score_of(team.id) as i32 - self.score_of(other) as i32)
+    }
+
+    /// The score of the team with the given ID
+    fn score_of(&self, team: u16) -> u16 {
         let mut score = self
             .previous_innings
             .iter()
@@ -213,18 +1235,178 @@ impl<'a> GameState<'a> {
         score
     }
 
+    /// The number of innings that have been started, including the one in progress
+    pub fn innings_started(&self) -> usize {
+        self.previous_innings.len() + self.current_innings_stats.iter().count()
+    }
+
+    /// The ball-by-ball log of the given innings, indexed from 0 in the order they were
+    /// played. Returns None if that innings has not started.
+    pub fn innings_deliveries(&self, innings: usize) -> Option<&[DeliveryRecord]> {
+        self.innings(innings).map(|st| st.deliveries.as_slice())
+    }
+
+    /// Per-over summaries of the given innings, indexed from 0 in the order they were
+    /// played. Returns None if that innings has not started.
+    pub fn innings_over_summaries(&self, innings: usize) -> Option<&[OverSummary]> {
+        self.innings(innings).map(|st| st.over_summaries())
+    }
+
+    /// Milestones reached by individuals in the given innings, indexed from 0 in the order
+    /// they were played. Returns None if that innings has not started.
+    pub fn innings_milestones(&self, innings: usize) -> Option<&[Milestone]> {
+        self.innings(innings).map(|st| st.milestones.as_slice())
+    }
+
+    /// Catches dropped in the given innings, indexed from 0 in the order they were played.
+    /// Returns None if that innings has not started.
+    pub fn innings_dropped_catches(&self, innings: usize) -> Option<&[DroppedCatch]> {
+        self.innings(innings)
+            .map(|st| st.dropped_catches.as_slice())
+    }
+
+    /// The fielding side's wicket-keeping in the given innings, indexed from 0 in the order
+    /// they were played. Returns None if that innings has not started or the fielding side
+    /// has no designated keeper.
+    pub fn innings_keeper_stats(&self, innings: usize) -> Option<&KeeperInningsStats> {
+        self.innings(innings)?.keeper_stats.as_ref()
+    }
+
+    /// Where the batter's scoring shots went in the given innings, indexed from 0 in the
+    /// order they were played. Returns None if that innings has not started or the model
+    /// placed none of the batter's shots.
+    pub fn innings_wagon_wheel(&self, innings: usize, batter: PlayerId) -> Option<&WagonWheel> {
+        self.innings(innings)?.wagon_wheel(batter)
+    }
+
+    /// Where the bowler's deliveries pitched in the given innings, indexed from 0 in the
+    /// order they were played. Returns None if that innings has not started or the bowler
+    /// did not bowl in it.
+    pub fn innings_pitch_map(&self, innings: usize, bowler: PlayerId) -> Option<&PitchMap> {
+        self.innings(innings)?
+            .bowling_stats
+            .bowlers()
+            .iter()
+            .find(|(id, _)| *id == bowler)
+            .map(|(_, st)| &st.pitch_map)
+    }
+
+    /// The fielding of each member of the fielding side in the given innings, indexed
+    /// from 0 in the order they were played. Returns None if that innings has not started.
+    pub fn innings_fielding_stats(&self, innings: usize) -> Option<&TeamFieldingInningsStats> {
+        self.innings(innings).map(|st| &st.fielding_stats)
+    }
+
+    /// The runs and wickets of the given innings, indexed from 0 in the order they were
+    /// played. Returns None if that innings has not started.
+    pub fn innings_total(&self, innings: usize) -> Option<(u16, u8)> {
+        self.innings(innings).map(|st| (st.runs(), st.wickets()))
+    }
+
+    /// The index of the innings in progress, counted from 0, or None if the match is
+    /// complete
+    pub fn current_innings(&self) -> Option<usize> {
+        self.current_innings_stats
+            .as_ref()
+            .map(|_| self.previous_innings.len())
+    }
+
+    /// The team currently fielding, if the match is in progress
+    pub fn bowling_team(&self) -> Option<&Team> {
+        self.current_innings_stats
+            .as_ref()
+            .map(|st| self.team(st.bowling_team))
+    }
+
+    /// The runs and wickets of the innings in progress
+    pub fn score(&self) -> Option<(u16, u8)> {
+        let st = self.current_innings_stats.as_ref()?;
+        Some((st.runs(), st.wickets()))
+    }
+
+    /// The overs bowled in the innings in progress
+    pub fn overs_bowled(&self) -> Option<Overs> {
+        self.current_innings_stats.as_ref().map(|st| st.overs)
+    }
+
+    /// The striker and the non-striker, in that order, with their innings so far
+    pub fn current_batters(&self) -> Option<[(PlayerId, &BatterInningsStats); 2]> {
+        let batting = &self.current_innings_stats.as_ref()?.batting_stats;
+        let stats = |id| {
+            batting
+                .batters()
+                .iter()
+                .find(|(b, _)| *b == id)
+                .map(|(_, st)| (id, st))
+        };
+        Some([stats(batting.striker())?, stats(batting.non_striker())?])
+    }
+
+    /// The bowler of the current over with their figures in the innings so far
+    pub fn current_bowler(&self) -> Option<(PlayerId, &BowlerInningsStats)> {
+        let bowling = &self.current_innings_stats.as_ref()?.bowling_stats;
+        Some((bowling.current_bowler(), bowling.current_bowler_stats()))
+    }
+
+    /// The stats of the given innings, whether complete or in progress
+    fn innings(&self, innings: usize) -> Option<&InningsStats> {
+        self.previous_innings
+            .iter()
+            .chain(self.current_innings_stats.iter())
+            .nth(innings)
+    }
+
     /// Print a summary of each innings to stdout
+    #[cfg(feature = "std-display")]
     pub fn print_innings_summary(&self) -> Result<()> {
-        for innings in self.previous_innings.iter() {
-            println!("\n{} innings:", innings.batting_team.name);
-            innings.batting_stats.print_summary(innings.batting_team)?;
+        self.write_innings_summary(&mut std::io::stdout().lock())
+    }
+
+    /// Write a summary of each innings
+    #[cfg(feature = "std-display")]
+    pub fn write_innings_summary<W: Write + ?Sized>(&self, w: &mut W) -> Result<()> {
+        for (i, innings) in self.previous_innings.iter().enumerate() {
+            // The standing at each break in the match
+            let standing = self
+                .standing(i)
+                .filter(|_| i + 1 < self.previous_innings.len() || !self.complete());
+            if innings.forfeited {
+                let name = &self.team(innings.batting_team).name;
+                writeln!(w, "\n{} innings forfeited", name)?;
+                if let Some(standing) = standing {
+                    writeln!(w, "{}", standing)?;
+                }
+                continue;
+            }
+            let batting_team = self.team(innings.batting_team);
+            writeln!(w, "\n{} innings:", batting_team.name)?;
+            innings.batting_stats.write_summary(w, batting_team)?;
+            innings.bowling_stats.write_summary(
+                w,
+                self.team(innings.bowling_team),
+                self.form.balls_per_over,
+            )?;
             innings
-                .bowling_stats
-                .print_summary(innings.bowling_team, self.form.balls_per_over)?;
-            println!("Total: {}/{}", innings.runs(), innings.wickets());
+                .fielding_stats
+                .write_summary(w, self.team(innings.bowling_team))?;
+            writeln!(w, "Total: {}/{}", innings.runs(), innings.wickets())?;
+            if let Some(standing) = standing {
+                writeln!(w, "{}", standing)?;
+            }
+        }
+        if let Some(state) = self.state_of_play() {
+            writeln!(w, "\n{}", state)?;
+        }
+        writeln!(
+            w,
+            "\n{}: {}",
+            self.team_a.name,
+            self.team_score(&self.team_a)
+        )?;
+        writeln!(w, "{}: {}", self.team_b.name, self.team_score(&self.team_b))?;
+        if let Some(summary) = self.result_summary() {
+            writeln!(w, "\n{}", summary)?;
         }
-        println!("\n{}: {}", self.team_a.name, self.team_score(self.team_a));
-        println!("{}: {}", self.team_b.name, self.team_score(self.team_b));
         Ok(())
     }
 }
@@ -232,40 +1414,92 @@ impl<'a> GameState<'a> {
 /// Methods of dismissal
 /// TODO: Consider holding PlayerId instead of name. The means we need another struct created with
 /// a PlayerDb to implement Display.
-#[derive(Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Dismissal {
     /// Legitimate delivery hits wicket and puts it down.
     Bowled { bowler: String },
     /// Ball is hit in the air and caught in-bounds
     Caught { caught: String, bowler: String },
+    /// Caught by the bowler off their own bowling
+    CaughtAndBowled { bowler: String },
+    /// Caught by the wicket-keeper off an edge. Told apart from other catches so that
+    /// the keeper's dismissals can be counted.
+    CaughtBehind { keeper: String, bowler: String },
     /// Leg before wicket: A delivery that would have hit the wickets instead first
     /// makes contact with the striker (not the bat). (bowler)
     Lbw { bowler: String },
-    /// The striker is put out while running (fielder)
+    /// The striker is put out while running (fielder). The outcome's runs are those
+    /// completed before the run out, and its `run_out_end` the end at which the wicket
+    /// was put down, which the incoming batter goes to.
     // TODO: Consider not distinguishing these, but letting the simulation access both
     RunOutStriker(String),
     /// The only method by which the non-striker can be dismissed.
     RunOutNonStriker(String),
     /// The wicket-keeper puts down the wicket while the striker is out of the crease.
     /// Takes precedence over run-out.
-    Stumped { keeper: String },
+    Stumped { keeper: String, bowler: String },
+    /// The striker puts down their own wicket while playing at the ball or setting off
+    /// for a run.
+    HitWicket { bowler: String },
     // TODO: rare dismissals
 }
 
+impl Dismissal {
+    /// Whether the dismissal is credited to the bowler's figures. Run outs are
+    /// attributed only to the fielders involved.
+    pub fn credited_to_bowler(&self) -> bool {
+        use Dismissal::*;
+        match self {
+            Bowled { .. }
+            | Caught { .. }
+            | CaughtAndBowled { .. }
+            | CaughtBehind { .. }
+            | Lbw { .. }
+            | Stumped { .. }
+            | HitWicket { .. } => true,
+            RunOutStriker(_) | RunOutNonStriker(_) => false,
+        }
+    }
+}
+
 impl Display for Dismissal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Dismissal::*;
         match &self {
             Bowled { bowler } => write!(f, "b {}", bowler),
             Caught { caught, bowler } => write!(f, "c {} b {}", caught, bowler),
+            CaughtAndBowled { bowler } => write!(f, "c & b {}", bowler),
+            CaughtBehind { keeper, bowler } => write!(f, "c †{} b {}", keeper, bowler),
             Lbw { bowler } => write!(f, "lbw b {}", bowler),
             RunOutStriker(fielder) | RunOutNonStriker(fielder) => write!(f, "runout ({})", fielder),
-            Stumped { keeper } => write!(f, "st {}", keeper),
+            Stumped { keeper, bowler } => write!(f, "st {} b {}", keeper, bowler),
+            HitWicket { bowler } => write!(f, "hit wicket b {}", bowler),
         }
     }
 }
 
+/// Events that can end a batter's innings without a delivery being completed. These do
+/// not count as a ball.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NonDeliveryEvent {
+    /// The bowler runs out the non-striker for leaving their ground before the ball is
+    /// released (sometimes called a "Mankad"). Not credited to the bowler's figures.
+    RunOutNonStriker { bowler: String },
+}
+
+/// A player replaced by a substitute during a match
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Substitution {
+    /// The ID of the side making the change
+    pub team: u16,
+    /// The ID and name of the player leaving the match
+    pub player: (PlayerId, String),
+    /// The ID and name of the substitute
+    pub replacement: (PlayerId, String),
+}
+
 /// Normal runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Runs {
     /// Runs acquired by running. Batsmen change ends if this is odd.
     /// This includes dots (value of 0)
@@ -287,18 +1521,25 @@ impl Runs {
     }
 }
 
+/// The extras on a single delivery. There are rarely more than two, e.g. byes off a
+/// no-ball, so they are kept inline rather than allocated.
+pub type Extras = SmallVec<[Extra; 2]>;
+
 /// Extra runs scored for a team that are not credited to an individual batter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Extra {
     /// One penalty run. Additional runs can still be scored off a no-ball. These are
     /// counted against the bowler.
     NoBall,
     /// One penalty run is awarded if the ball is judged to not be hittable with a
     /// normal cricket swing. A wide that is also a no-ball is counted as a no-ball.
-    /// Wides are counted against the bowler's stats.
-    Wide,
+    /// Wides are counted against the bowler's stats. Any runs taken off a wide, or a
+    /// wide that runs away to the boundary, are also scored as wides on top of the
+    /// penalty.
+    Wide(Runs),
     /// A bye in which the batsman does not make contact and the wicket is not made. Runs
     /// can be scored but this is often zero. Balls that make it to the boundary are scored as
-    /// fours. Byes and leg byes can be scored from no-balls and wides. Neither are counted against
+    /// fours. Byes and leg byes can be scored from no-balls. Neither are counted against
     /// the bowler's stats, although Byes are counted against the wicket-keeper.
     Bye(Runs),
     /// Similar to a bye, but with contact off the batter (not the bat) that is not LBW.
@@ -306,21 +1547,92 @@ pub enum Extra {
     LegBye(Runs),
     /// Penalty runs can also be awarded for various breaches of conduct.
     Penalty(u8),
+    /// Runs from a throw that goes astray, on top of those already run. They are scored
+    /// as whatever the batters were running for: to the striker if the ball was hit, and
+    /// otherwise as byes, leg byes or wides.
+    Overthrows(Runs),
 }
 
 impl Extra {
     pub fn runs(&self) -> u8 {
         use Extra::*;
         match &self {
-            NoBall | Wide => 1,
-            Bye(runs) | LegBye(runs) => runs.runs(),
+            NoBall => 1,
+            Wide(runs) => 1 + runs.runs(),
+            Bye(runs) | LegBye(runs) | Overthrows(runs) => runs.runs(),
             Penalty(n) => *n,
         }
     }
 }
 
+/// The kind of outcome of a delivery, without the players involved. This is the level at
+/// which models can state probabilities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeliveryOutcomeKind {
+    /// Runs taken off the bat, including none
+    Runs(u8),
+    Four,
+    Six,
+    Wide,
+    NoBall,
+    Byes(u8),
+    LegByes(u8),
+    Bowled,
+    Caught,
+    Lbw,
+    /// The striker is run out without completing a run
+    RunOut,
+    Stumped,
+    HitWicket,
+}
+
+impl DeliveryOutcomeKind {
+    /// The runs added to the batting side's total
+    pub fn runs(&self) -> u8 {
+        use DeliveryOutcomeKind::*;
+        match self {
+            Runs(runs) | Byes(runs) | LegByes(runs) => *runs,
+            Four => 4,
+            Six => 6,
+            Wide | NoBall => 1,
+            Bowled | Caught | Lbw | RunOut | Stumped | HitWicket => 0,
+        }
+    }
+
+    pub fn wicket(&self) -> bool {
+        use DeliveryOutcomeKind::*;
+        matches!(self, Bowled | Caught | Lbw | RunOut | Stumped | HitWicket)
+    }
+
+    pub fn legal(&self) -> bool {
+        !matches!(
+            self,
+            DeliveryOutcomeKind::Wide | DeliveryOutcomeKind::NoBall
+        )
+    }
+}
+
+/// One of the two ends of the pitch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum End {
+    /// Where the striker stands to face, in front of the wicket-keeper
+    Striker,
+    /// Where the bowler delivers from
+    Bowler,
+}
+
+/// A mistake by the fielding side on a delivery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldingError {
+    /// A catch went down, reprieving the striker
+    DroppedCatch { fielder: PlayerId },
+    /// A fumble that let the batters take an extra run, which is included in the outcome
+    Misfield { fielder: PlayerId },
+}
+
 /// The outcome of a single delivery. Also known as a "ball", although a delivery can
 /// result in a no-ball.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeliveryOutcome {
     /// Whether a batsman is dismissed along with the method. In standard cricket the
     /// ball is dead upon a dismissal so there are no double-plays.
@@ -328,14 +1640,139 @@ pub struct DeliveryOutcome {
     /// Runs scored by batting the ball into play
     pub runs: Runs,
     /// Any extra runs accrued on the play
-    pub extras: Vec<Extra>,
+    pub extras: Extras,
+    /// How the ball was bowled, if the model describes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery: Option<Delivery>,
+    /// Any dropped catch or misfield on the delivery
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fielding_error: Option<FieldingError>,
+    /// Any call by the umpire that changes how the delivery is scored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<UmpireSignal>,
+    /// Where a scoring shot went, if the model describes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<Zone>,
+    /// The fielders credited with any dismissal, in the order it names them. Empty if
+    /// they aren't known, e.g. in logs from before they were recorded, in which case
+    /// no fielder is credited.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fielders: Vec<PlayerId>,
+    /// For a run out, the end at which the wicket was put down. If it isn't known, the
+    /// batters are taken to have crossed on the failed run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_out_end: Option<End>,
+}
+
+/// A call by the umpire that changes how a delivery is scored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UmpireSignal {
+    /// Runs that weren't completed by grounding the bat beyond the popping crease. They
+    /// are left out of the runs scored, but the batters still finish at the ends they
+    /// ran to.
+    ShortRuns(u8),
+    /// The ball was called dead before it could be played, e.g. because the striker
+    /// wasn't ready. It isn't one of the over and nothing but penalty runs is scored.
+    DeadBall,
 }
 
 impl DeliveryOutcome {
     /// Whether the delivery should count as a legal ball
     pub fn legal(&self) -> bool {
         use Extra::*;
-        !self.extras.iter().any(|ex| matches!(ex, NoBall | Wide))
+        !self.is_dead_ball() && !self.extras.iter().any(|ex| matches!(ex, NoBall | Wide(_)))
+    }
+
+    /// Whether the umpire called the ball dead before it could be played
+    pub fn is_dead_ball(&self) -> bool {
+        self.signal == Some(UmpireSignal::DeadBall)
+    }
+
+    /// The runs the umpire disallowed as short
+    pub fn short_runs(&self) -> u8 {
+        match self.signal {
+            Some(UmpireSignal::ShortRuns(runs)) => runs,
+            _ => 0,
+        }
+    }
+
+    /// The extra that any overthrows on the delivery are scored as: byes, leg byes or
+    /// wides. None if the ball was hit, in which case they are the striker's.
+    pub fn overthrows_scored_as(&self) -> Option<&Extra> {
+        use Extra::*;
+        self.extras
+            .iter()
+            .find(|ex| matches!(ex, Wide(_) | Bye(_) | LegBye(_)))
+    }
+
+    /// The runs credited to the striker, off the bat and from any overthrows of them
+    pub fn batter_runs(&self) -> u8 {
+        let overthrows = match self.overthrows_scored_as() {
+            Some(_) => 0,
+            None => self.overthrows(),
+        };
+        self.runs.runs() + overthrows
+    }
+
+    /// The runs charged to the bowler: those credited to the striker, wides and no-balls
+    pub fn bowler_runs(&self) -> u8 {
+        let extras: u8 = self
+            .extras
+            .iter()
+            .filter(|ex| matches!(ex, Extra::NoBall | Extra::Wide(_)))
+            .map(Extra::runs)
+            .sum();
+        let overthrows = match self.overthrows_scored_as() {
+            Some(Extra::Wide(_)) => self.overthrows(),
+            _ => 0,
+        };
+        self.batter_runs() + extras + overthrows
+    }
+
+    /// The runs from overthrows on the delivery
+    fn overthrows(&self) -> u8 {
+        self.extras
+            .iter()
+            .filter(|ex| matches!(ex, Extra::Overthrows(_)))
+            .map(Extra::runs)
+            .sum()
+    }
+
+    /// The kind of the outcome. A dismissal takes precedence over any runs, and a wide or
+    /// no-ball over byes.
+    pub fn kind(&self) -> DeliveryOutcomeKind {
+        use DeliveryOutcomeKind as Kind;
+        if let Some((_, dismissal)) = &self.wicket {
+            return match dismissal {
+                Dismissal::Bowled { .. } => Kind::Bowled,
+                Dismissal::Caught { .. }
+                | Dismissal::CaughtAndBowled { .. }
+                | Dismissal::CaughtBehind { .. } => Kind::Caught,
+                Dismissal::Lbw { .. } => Kind::Lbw,
+                Dismissal::RunOutStriker(_) | Dismissal::RunOutNonStriker(_) => Kind::RunOut,
+                Dismissal::Stumped { .. } => Kind::Stumped,
+                Dismissal::HitWicket { .. } => Kind::HitWicket,
+            };
+        }
+        for extra in &self.extras {
+            match extra {
+                Extra::Wide(_) => return Kind::Wide,
+                Extra::NoBall => return Kind::NoBall,
+                _ => {}
+            }
+        }
+        for extra in &self.extras {
+            match extra {
+                Extra::Bye(runs) => return Kind::Byes(runs.runs()),
+                Extra::LegBye(runs) => return Kind::LegByes(runs.runs()),
+                _ => {}
+            }
+        }
+        match self.runs {
+            Runs::Four => Kind::Four,
+            Runs::Six => Kind::Six,
+            Runs::Running(runs) => Kind::Runs(runs),
+        }
     }
 
     // TODO: These should take the bowler ID and not just the name. This will require hooking up to
@@ -352,7 +1789,12 @@ impl DeliveryOutcome {
         }
     }
 
-    pub fn caught(striker_id: PlayerId, bowler_name: &str, catcher_name: &str) -> Self {
+    pub fn caught(
+        striker_id: PlayerId,
+        bowler_name: &str,
+        catcher_id: PlayerId,
+        catcher_name: &str,
+    ) -> Self {
         Self {
             wicket: Some((
                 striker_id,
@@ -361,9 +1803,41 @@ impl DeliveryOutcome {
                     bowler: bowler_name.to_string(),
                 },
             )),
+            fielders: vec![catcher_id],
             ..Default::default()
         }
+    }
 
+    pub fn caught_and_bowled(striker_id: PlayerId, bowler_id: PlayerId, bowler_name: &str) -> Self {
+        Self {
+            wicket: Some((
+                striker_id,
+                Dismissal::CaughtAndBowled {
+                    bowler: bowler_name.to_string(),
+                },
+            )),
+            fielders: vec![bowler_id],
+            ..Default::default()
+        }
+    }
+
+    pub fn caught_behind(
+        striker_id: PlayerId,
+        bowler_name: &str,
+        keeper_id: PlayerId,
+        keeper_name: &str,
+    ) -> Self {
+        Self {
+            wicket: Some((
+                striker_id,
+                Dismissal::CaughtBehind {
+                    keeper: keeper_name.to_string(),
+                    bowler: bowler_name.to_string(),
+                },
+            )),
+            fielders: vec![keeper_id],
+            ..Default::default()
+        }
     }
 
     pub fn lbw(striker_id: PlayerId, bowler_name: &str) -> Self {
@@ -378,10 +1852,53 @@ impl DeliveryOutcome {
         }
     }
 
+    pub fn stumped(
+        striker_id: PlayerId,
+        bowler_name: &str,
+        keeper_id: PlayerId,
+        keeper_name: &str,
+    ) -> Self {
+        Self {
+            wicket: Some((
+                striker_id,
+                Dismissal::Stumped {
+                    keeper: keeper_name.to_string(),
+                    bowler: bowler_name.to_string(),
+                },
+            )),
+            fielders: vec![keeper_id],
+            ..Default::default()
+        }
+    }
+
     pub fn dot() -> Self {
         Self::default()
     }
 
+    /// The batters run `ran` runs, of which `short` are called short and not scored
+    pub fn short_run(ran: u8, short: u8) -> Self {
+        Self {
+            signal: Some(UmpireSignal::ShortRuns(short)),
+            ..Self::running(ran.saturating_sub(short))
+        }
+    }
+
+    /// A ball called dead by the umpire, which doesn't count
+    pub fn dead_ball() -> Self {
+        Self {
+            signal: Some(UmpireSignal::DeadBall),
+            ..Default::default()
+        }
+    }
+
+    /// Runs taken after the ball beats both the bat and the keeper
+    pub fn byes(runs: u8) -> Self {
+        Self {
+            extras: smallvec![Extra::Bye(Runs::Running(runs))],
+            ..Default::default()
+        }
+    }
+
     pub fn four() -> Self {
         Self {
             runs: Runs::Four,
@@ -409,7 +1926,517 @@ impl Default for DeliveryOutcome {
         Self {
             wicket: None,
             runs: Runs::Running(0),
-            extras: Vec::new(),
+            extras: Extras::new(),
+            delivery: None,
+            fielding_error: None,
+            signal: None,
+            zone: None,
+            fielders: Vec::new(),
+            run_out_end: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        form::Form,
+        interop,
+        model::{self, NullModel, PlayerRatingNull},
+        player::PlayerDb,
+        seed,
+        strategy::{self, DeclarationContext, DeclarationStrategy},
+        tests::test_team,
+        tournament,
+    };
+    use rand::thread_rng;
+
+    /// Declares at the first chance in the first innings, and otherwise records when it
+    /// was asked
+    #[derive(Default)]
+    struct FirstInningsDeclaration {
+        asked: Arc<std::sync::Mutex<Vec<DeclarationContext>>>,
+    }
+
+    impl DeclarationStrategy for FirstInningsDeclaration {
+        fn declare(&self, context: &DeclarationContext) -> bool {
+            self.asked.lock().unwrap().push(context.clone());
+            context.innings == 0
+        }
+    }
+
+    #[test]
+    fn declare_after_facing_an_over() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "ENG")?;
+        let team_b = test_team(&mut db, 2, "NZ")?;
+        let mut state = GameState::new(form::Form::test(), team_a.clone(), team_b.clone())?;
+        let strategy = FirstInningsDeclaration::default();
+        let asked = strategy.asked.clone();
+        state.set_declaration_strategy(&team_a, strategy);
+        state.set_declaration_strategy(
+            &team_b,
+            FirstInningsDeclaration {
+                asked: asked.clone(),
+            },
+        );
+        let dot = DeliveryOutcome::default();
+        for _ in 0..6 {
+            state.update(&dot)?;
+        }
+        assert!(state.completed_innings()[0].declared);
+        // Bowled out with the last ball of the second over
+        for ball in 0..12 {
+            let wicket = match ball {
+                0 | 1 => None,
+                _ => {
+                    let bowler = state.bowler().unwrap();
+                    let bowler = state.bowling_team().unwrap().get_name(bowler).unwrap();
+                    let bowled = Dismissal::Bowled {
+                        bowler: bowler.to_string(),
+                    };
+                    Some((state.striker().unwrap(), bowled))
+                }
+            };
+            state.update(&DeliveryOutcome {
+                wicket,
+                ..Default::default()
+            })?;
+        }
+        assert_eq!(state.current_innings(), Some(2));
+        // Neither side is asked at the start of an innings, before facing a ball
+        let asked: Vec<usize> = asked.lock().unwrap().iter().map(|c| c.innings).collect();
+        assert_eq!(asked, vec![0, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn drawn_when_time_runs_out() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "ENG")?;
+        let team_b = test_team(&mut db, 2, "NZ")?;
+        let form = form::Form::builder().days(1, 20).build()?;
+        let mut state = GameState::new(form, team_a, team_b)?;
+        state.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(3))?;
+        assert_eq!(state.match_overs_bowled(), 20);
+        assert_eq!(state.match_overs_remaining(), Some(0));
+        assert_eq!(state.result(), Some(MatchResult::Draw));
+        Ok(())
+    }
+
+    #[test]
+    fn ring_penalty_when_behind_the_rate() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "IND")?;
+        let team_b = test_team(&mut db, 2, "PAK")?;
+        let mut state = GameState::new(form::Form::t20(), team_a, team_b)?;
+        let penalized = |state: &GameState| {
+            let innings = state.current_innings_stats.as_ref().unwrap();
+            innings.over_rate_penalty
+        };
+        // Seven wickets hold up play for long enough that the 19th over ends after the
+        // scheduled end of the innings, so the last over is bowled with the penalty
+        for over in 0..19 {
+            assert!(!penalized(&state));
+            for ball in 0..6 {
+                let wicket = (over < 7 && ball == 0).then(|| {
+                    let bowler = state.bowler().unwrap();
+                    let bowler = state.bowling_team().unwrap().get_name(bowler).unwrap();
+                    let bowled = Dismissal::Bowled {
+                        bowler: bowler.to_string(),
+                    };
+                    (state.striker().unwrap(), bowled)
+                });
+                state.update(&DeliveryOutcome {
+                    wicket,
+                    ..Default::default()
+                })?;
+            }
+        }
+        assert_eq!(state.current_innings(), Some(0));
+        assert!(penalized(&state));
+        assert!((state.over_rate().unwrap() - 19. * 3600. / 5115.).abs() < 1e-3);
+        Ok(())
+    }
+
+    #[test]
+    fn sides_validated_when_simulated() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "ENG")?;
+        let mut no_keeper = test_team(&mut db, 2, "NZ")?;
+        no_keeper.keeper = None;
+        let mut state = GameState::new(form::Form::t20(), team_a.clone(), no_keeper)?;
+        let simulated = state.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(1));
+        assert!(matches!(simulated, Err(Error::NoKeeper(_))));
+        assert_eq!(state.overs_bowled(), Some(Overs::default()));
+
+        let mut batters = test_team(&mut db, 2, "NZ")?;
+        for (id, _) in &batters.players {
+            batters.roles.insert(*id, crate::player::Role::MiddleOrder);
+        }
+        let mut state = GameState::new(form::Form::t20(), batters, team_a)?;
+        let simulated = state.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(1));
+        assert!(matches!(simulated, Err(Error::TooFewBowlers(_, 0))));
+        Ok(())
+    }
+
+    #[test]
+    fn sim_with_declarations() -> Result<()> {
+        use model::NullModel;
+        use strategy::LeadDeclaration;
+        let rules = form::Form::test();
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "ENG")?;
+        let team_b = test_team(&mut db, 2, "IND")?;
+        let db = db;
+        let mut state = GameState::new(rules, team_a.clone(), team_b.clone())?;
+        state.set_declaration_strategy(&team_a, LeadDeclaration::default());
+        state.set_declaration_strategy(&team_b, LeadDeclaration::default());
+        let mut rng = thread_rng();
+        let model = NullModel {};
+
+        let opening_stand: Vec<_> = state
+            .deliveries(&model, &db, &mut rng)
+            .take_while(|d| d.as_ref().is_ok_and(|(_, _, score)| score.wickets == 0))
+            .collect::<Result<_>>()?;
+        if let Some((number, _, _)) = opening_stand.first() {
+            assert_eq!(number.to_string(), "0.1");
         }
+        state.simulate_to_completion(&model, &db, &mut rng)?;
+        assert!(state.match_overs_remaining().is_some());
+        let lead = state.lead(&team_a).unwrap();
+        assert_eq!(state.lead(&team_b), Some(-lead));
+        let mut stranger = team_a.clone();
+        stranger.id = 99;
+        assert_eq!(state.lead(&stranger), None);
+        assert_eq!(state.state_of_play(), None);
+
+        let json = serde_json::to_string(&state.log())?;
+        let log: MatchLog = serde_json::from_str(&json)?;
+        let replayed = GameState::replay(&log, None)?;
+        assert!(replayed.complete());
+        assert_eq!(replayed.result(), state.result());
+        for i in 0..state.innings_started() {
+            assert_eq!(replayed.innings_total(i), state.innings_total(i));
+        }
+        let partial = GameState::replay(&log, Some(10))?;
+        assert_eq!(partial.innings_deliveries(0).unwrap().len(), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn caught_and_bowled() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "NZ")?;
+        let team_b = test_team(&mut db, 2, "BAN")?;
+        let mut state = GameState::new(form::Form::t20(), team_a, team_b)?;
+        let snapshot = state.snapshot(&db)?;
+        let bowler = snapshot.bowler;
+        let ball = snapshot.caught_by(bowler);
+        let (_, dismissal) = ball.wicket.as_ref().unwrap();
+        assert_eq!(dismissal.to_string(), format!("c & b {}", bowler.name));
+        assert_eq!(ball.fielders, vec![bowler.id]);
+        // Another fielder's catch is an ordinary one
+        let fielder = snapshot
+            .fielders
+            .iter()
+            .find(|f| f.id != bowler.id && snapshot.keeper.is_none_or(|k| k.id != f.id))
+            .unwrap();
+        let catch = snapshot.caught_by(fielder);
+        assert!(matches!(catch.wicket, Some((_, Dismissal::Caught { .. }))));
+        let bowler = bowler.id;
+        state.update(&ball)?;
+        assert_eq!(state.current_bowler().unwrap().1.wickets, 1);
+        let fielding = state.innings_fielding_stats(0).unwrap();
+        assert_eq!(fielding.get(bowler).unwrap().catches, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn t20_chase_rates() -> Result<()> {
+        use model::{Model, NullModel};
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "WI")?;
+        let team_b = test_team(&mut db, 2, "SL")?;
+        let db = db;
+        let mut state = GameState::new(form::Form::t20(), team_a.clone(), team_b.clone())?;
+        let mut rng = thread_rng();
+        let model = NullModel {};
+
+        assert!(state.required_run_rate().is_none());
+        assert_eq!(state.balls_remaining(), Some(120));
+        while state.innings_started() < 2 {
+            let ball = model.generate_delivery(&mut rng, state.snapshot(&db)?);
+            state.update(&ball)?;
+        }
+        // Save and resume at the innings break
+        let first_innings = state.innings_total(0);
+        let mut saved = Vec::new();
+        state.save_json(&mut saved)?;
+        let mut state = GameState::load_json(saved.as_slice())?;
+        assert_eq!(state.innings_total(0), first_innings);
+        while !state.complete() {
+            let ball = model.generate_delivery(&mut rng, state.snapshot(&db)?);
+            state.update(&ball)?;
+            if state.innings_started() == 2 && !state.complete() {
+                assert!(state.required_run_rate().is_some());
+                let context = state.context().unwrap();
+                assert_eq!(context.target, Some(state.innings_total(0).unwrap().0 + 1));
+                // The scoreboard of the chase
+                let (runs, wickets) = state.score().unwrap();
+                assert_eq!(state.current_innings(), Some(1));
+                assert_eq!(state.batting_team().unwrap().id, team_b.id);
+                assert_eq!(state.bowling_team().unwrap().id, team_a.id);
+                let deficit = first_innings.unwrap().0 as i32 - runs as i32;
+                assert_eq!(state.lead(&team_b), Some(-deficit));
+                let needed = format!("{} need {} run", team_b.name, deficit + 1);
+                assert!(state.state_of_play().unwrap().starts_with(&needed));
+                let [(striker, _), (non_striker, _)] = state.current_batters().unwrap();
+                assert_eq!(Some(striker), state.striker());
+                assert_ne!(striker, non_striker);
+                let (_, figures) = state.current_bowler().unwrap();
+                assert!(figures.wickets <= wickets);
+                assert_eq!(state.overs_bowled(), Some(context.overs));
+            }
+        }
+        #[cfg(feature = "std-display")]
+        {
+            let mut summary = Vec::new();
+            state.write_innings_summary(&mut summary)?;
+            assert!(String::from_utf8(summary)
+                .unwrap()
+                .contains("team_WI innings:"));
+        }
+
+        let cricsheet = interop::cricsheet::CricsheetMatch::from_game(&state)?;
+        assert_eq!(cricsheet.info.match_type, "T20");
+        assert_eq!(cricsheet.innings.len(), 2);
+        let mut json = Vec::new();
+        cricsheet.write_json(&mut json)?;
+
+        let imported = interop::cricsheet::CricsheetMatch::from_json(json.as_slice())?;
+        let mut replay_db = PlayerDb::<PlayerRatingNull>::new();
+        let (replay_a, replay_b) = imported.build_teams(&mut replay_db)?;
+        let replayed = imported.replay(replay_a, replay_b)?;
+        assert_eq!(replayed.innings_total(0), state.innings_total(0));
+        assert_eq!(replayed.innings_total(1), state.innings_total(1));
+        Ok(())
+    }
+
+    #[test]
+    fn rain_reduced_match() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "AFG")?;
+        let team_b = test_team(&mut db, 2, "IRE")?;
+        let mut state = GameState::new(Form::odi(), team_a, team_b)?;
+        let mut rng = seed::rng(5);
+        for delivery in state.deliveries(&NullModel {}, &db, &mut rng).take(150) {
+            delivery?;
+        }
+        // Still in the first innings, so there is something to reduce
+        assert_eq!(state.innings_started(), 1);
+        assert!(state.reduce_overs(20).is_err());
+        state.reduce_overs(35)?;
+        assert_eq!(state.scheduled_overs(), Some(50));
+        state.simulate_to_completion(&NullModel {}, &db, &mut rng)?;
+        let scorecard = state.scorecard()?;
+        for innings in &scorecard.innings {
+            assert_eq!(innings.max_overs, Some(35));
+            assert!(innings.overs.parse::<f32>().unwrap() <= 35.);
+        }
+        // Bowlers may have bowled more before the overs were reduced
+        for bowler in &scorecard.innings[1].bowling {
+            assert!(bowler.overs.parse::<f32>().unwrap() <= 7.);
+        }
+        let log = state.log();
+        assert_eq!(log.form.overs_per_innings, Some(50));
+        let replayed = GameState::replay(&log, None)?;
+        assert_eq!(replayed.form().overs_per_innings, Some(35));
+        assert_eq!(replayed.result(), state.result());
+        Ok(())
+    }
+
+    #[test]
+    fn no_result_below_minimum_overs() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = Arc::new(test_team(&mut db, 1, "NED")?);
+        let team_b = Arc::new(test_team(&mut db, 2, "SCO")?);
+        let form = Form::t20();
+        let mut state = GameState::new(form.clone(), team_a.clone(), team_b.clone())?;
+        let mut rng = seed::rng(6);
+        for delivery in state.deliveries(&NullModel {}, &db, &mut rng) {
+            let (number, _, score) = delivery?;
+            if number.innings == 1 || score.match_complete {
+                break;
+            }
+        }
+        // The chase has started, so it can be cut short
+        assert!(!state.complete());
+        assert_eq!(state.innings_started(), 2);
+        state.reduce_overs(4)?;
+        assert!(state.complete());
+        assert_eq!(state.result(), Some(MatchResult::NoResult));
+        assert_eq!(
+            state.result_summary().as_deref(),
+            Some("No result (match abandoned due to weather)")
+        );
+        let replayed = GameState::replay(&state.log(), None)?;
+        assert_eq!(replayed.result(), state.result());
+
+        let mut table = tournament::PointsTable::new(&[team_a.clone(), team_b], 6);
+        table.add_match(&state)?;
+        let standing = table.get(team_a.id).unwrap();
+        assert_eq!(standing.shared, 1);
+        assert_eq!(standing.points, u32::from(table.points().shared));
+        // A match without a result doesn't count towards net run rate
+        assert!(tournament::nrr::innings_run_rates(&state)?.is_empty());
+        assert_eq!((standing.runs_for, standing.balls_faced), (0, 0));
+        assert_eq!((standing.runs_against, standing.balls_bowled), (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn overs_reduced_mid_chase() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = Arc::new(test_team(&mut db, 1, "NED")?);
+        let team_b = Arc::new(test_team(&mut db, 2, "SCO")?);
+        let chase_to = |overs: u16| -> Result<GameState> {
+            let mut state = GameState::new(Form::t20(), team_a.clone(), team_b.clone())?;
+            let mut rng = seed::rng(6);
+            for delivery in state.deliveries(&NullModel {}, &db, &mut rng) {
+                let (number, _, score) = delivery?;
+                assert!(!score.match_complete);
+                if number.innings == 1 && number.overs.completed == overs {
+                    break;
+                }
+            }
+            Ok(state)
+        };
+
+        // The overs already bowled in the chase can't be taken back
+        let mut state = chase_to(10)?;
+        assert!(matches!(state.reduce_overs(4), Err(Error::InvalidForm(_))));
+        assert!(!state.complete());
+        assert_eq!(state.result(), None);
+
+        // A chase cut short of the minimum for a result can't be decided
+        let mut state = chase_to(3)?;
+        state.reduce_overs(4)?;
+        assert!(state.complete());
+        assert_eq!(state.result(), Some(MatchResult::NoResult));
+        let replayed = GameState::replay(&state.log(), None)?;
+        assert_eq!(replayed.result(), Some(MatchResult::NoResult));
+        Ok(())
+    }
+
+    #[test]
+    fn abandoned_and_forfeited() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "UAE")?;
+        let team_b = test_team(&mut db, 2, "NEP")?;
+        let mut rng = seed::rng(7);
+
+        let mut washout = GameState::new(Form::t20(), team_a.clone(), team_b.clone())?;
+        washout.abandon(AbandonReason::Weather)?;
+        assert_eq!(washout.result(), Some(MatchResult::NoResult));
+        assert_eq!(
+            washout.result_summary().as_deref(),
+            Some("No result (match abandoned due to weather)")
+        );
+        assert!(washout.abandon(AbandonReason::BadLight).is_err());
+
+        // Deep enough into the chase for a result on the scores so far
+        let mut state = GameState::new(Form::t20(), team_a.clone(), team_b.clone())?;
+        for delivery in state.deliveries(&NullModel {}, &db, &mut rng) {
+            let (number, _, score) = delivery?;
+            if (number.innings == 1 && score.balls >= 60) || score.match_complete {
+                break;
+            }
+        }
+        assert!(!state.complete());
+        state.abandon(AbandonReason::Weather)?;
+        assert_ne!(state.result(), Some(MatchResult::NoResult));
+        let summary = state.result_summary().unwrap();
+        assert!(!summary.contains("remaining"));
+        assert!(summary.ends_with("(on the par score, match abandoned due to weather)"));
+        // The side batting first is credited with par off the overs of the chase
+        let rates = tournament::nrr::innings_run_rates(&state)?;
+        let chase = state.completed_innings()[1].balls_bowled();
+        assert_eq!(rates[0].runs, state.par_score().unwrap());
+        assert_eq!((rates[0].balls, rates[1].balls), (chase, chase));
+        let replayed = GameState::replay(&state.log(), None)?;
+        assert_eq!(replayed.result(), state.result());
+
+        // A chase well ahead of par when play stops is won without balls to spare
+        let four = DeliveryOutcome {
+            runs: Runs::Four,
+            ..Default::default()
+        };
+        let dot = DeliveryOutcome::default();
+        let mut state = GameState::new(Form::t20(), team_a.clone(), team_b.clone())?;
+        for i in 0..150 {
+            state.update(if i >= 120 || i % 4 == 1 { &four } else { &dot })?;
+        }
+        state.abandon(AbandonReason::Weather)?;
+        assert_eq!(state.par_score(), Some(30));
+        assert_eq!(
+            state.result_summary(),
+            Some(format!(
+                "{} won by 10 wickets (on the par score, match abandoned due to weather)",
+                team_b.name
+            ))
+        );
+
+        let mut test = GameState::new(Form::test(), team_a.clone(), team_b.clone())?;
+        for delivery in test.deliveries(&NullModel {}, &db, &mut rng).take(30) {
+            delivery?;
+        }
+        test.abandon(AbandonReason::UnfitGround)?;
+        assert_eq!(test.result(), Some(MatchResult::Draw));
+
+        let mut conceded = GameState::new(Form::odi(), team_a.clone(), team_b.clone())?;
+        conceded.forfeit(&team_b)?;
+        let won = MatchResult::Win {
+            winner: team_a.id,
+            margin: Margin::Forfeit,
+        };
+        assert_eq!(conceded.result(), Some(won));
+        assert_eq!(
+            conceded.result_summary(),
+            Some(format!("{} won by forfeit", team_a.name))
+        );
+        let replayed = GameState::replay(&conceded.log(), None)?;
+        assert_eq!(replayed.result(), Some(won));
+        Ok(())
+    }
+
+    #[test]
+    fn forfeit_does_not_follow_on() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "ENG")?;
+        let team_b = test_team(&mut db, 2, "NZ")?;
+        let four = DeliveryOutcome {
+            runs: Runs::Four,
+            ..Default::default()
+        };
+        let lead_by = |runs: u16| -> Result<GameState> {
+            let mut state = GameState::new(Form::test(), team_a.clone(), team_b.clone())?;
+            for _ in 0..runs / 4 {
+                state.update(&four)?;
+            }
+            state.declare()?;
+            Ok(state)
+        };
+        // A side that forfeits its first innings doesn't bat again straight away, however
+        // far behind it is
+        let mut state = lead_by(240)?;
+        state.forfeit_innings(&team_b)?;
+        assert_eq!(state.batting_team().map(|t| t.id), Some(team_a.id));
+        // The same deficit after batting enforces the follow-on
+        let mut state = lead_by(240)?;
+        state.declare()?;
+        assert_eq!(state.batting_team().map(|t| t.id), Some(team_b.id));
+        Ok(())
     }
 }
@@ -0,0 +1,694 @@
+//! A compact binary encoding of deliveries, a few bytes each, for storing large numbers of
+//! simulated matches
+//!
+//! Each delivery starts with a header byte. Its low four bits hold the runs off the bat,
+//! and its high four bits flag which of the optional parts follow, in this order:
+//!
+//! - extras: their number, then a byte for each, followed by any umpire's signal in the
+//!   same form
+//! - a wicket: the kind of dismissal, the batter out, and the players named in it,
+//!   followed by any fielders credited with it: their number, then each player. The high
+//!   bits of the kind flag the end a run out was made at, if known, and whether fielders
+//!   follow
+//! - a fielding error: its kind and the fielder
+//! - how the ball was bowled and where it went: line, length and pace in one byte, then
+//!   speed and movement, then any scoring zone. The high bit of the first byte flags a
+//!   zone, and its other bits are all set when only the zone is known.
+//!
+//! Players are written as their index among the two line-ups, so a dot ball or a single
+//! takes one byte. A player in neither line-up, such as a substitute fielder, is written in
+//! full, so the encoding loses nothing.
+use super::{
+    log::{MatchEvent, MatchLog},
+    DeliveryOutcome, Dismissal, End, Extra, Extras, FieldingError, Runs, UmpireSignal,
+};
+use crate::{
+    conditions::{HomeAdvantage, Pitch, Venue, Weather},
+    error::{Error, Result},
+    field::Zone,
+    form::Form,
+    model::{
+        pipeline::{Delivery, Length, Line, Pace},
+        ModelInfo,
+    },
+    player::PlayerId,
+    team::Team,
+};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, sync::Arc};
+
+/// Flags in the header of a delivery
+const EXTRAS: u8 = 1 << 4;
+const WICKET: u8 = 1 << 5;
+const FIELDING_ERROR: u8 = 1 << 6;
+const DELIVERY: u8 = 1 << 7;
+
+/// The bits of the header, and of each extra, that hold a number of runs
+const HEADER_RUNS_BITS: u8 = 4;
+const EXTRA_VALUE_BITS: u8 = 5;
+
+/// In the kind of dismissal, flags that a run out was made at the given end
+const RUN_OUT_AT_STRIKER_END: u8 = 1 << 7;
+const RUN_OUT_AT_BOWLER_END: u8 = 1 << 6;
+/// In the kind of dismissal, flags that the fielders credited with it follow
+const FIELDERS: u8 = 1 << 5;
+
+/// In place of a player's index, marks a player written in full
+const IN_FULL: u8 = u8::MAX;
+
+/// In the byte describing the delivery, flags that a scoring zone follows
+const ZONE: u8 = 1 << 7;
+/// The rest of the byte describing a delivery when only the zone is known
+const NO_DELIVERY: u8 = ZONE - 1;
+
+/// Encodes and decodes the deliveries of a match between two sides
+pub struct DeliveryCodec {
+    /// The players of both sides, indexed by their position
+    players: Vec<(PlayerId, String)>,
+}
+
+impl DeliveryCodec {
+    pub fn new(team_a: &Team, team_b: &Team) -> Self {
+        let players = team_a.players.iter().chain(&team_b.players).cloned();
+        Self {
+            players: players.collect(),
+        }
+    }
+
+    /// Append the encoding of a delivery, or an error if it has more extras or fielders
+    /// than fit in a byte, or a name too long to write in full. Nothing is appended on an
+    /// error.
+    pub fn encode(&self, ball: &DeliveryOutcome, out: &mut Vec<u8>) -> Result<()> {
+        let mut encoded = Vec::new();
+        self.write(ball, &mut encoded)?;
+        out.extend(encoded);
+        Ok(())
+    }
+
+    fn write(&self, ball: &DeliveryOutcome, out: &mut Vec<u8>) -> Result<()> {
+        let (runs, runs_in_full) = runs_code(&ball.runs, HEADER_RUNS_BITS);
+        let mut header = runs;
+        if !ball.extras.is_empty() || ball.signal.is_some() {
+            header |= EXTRAS;
+        }
+        if ball.wicket.is_some() {
+            header |= WICKET;
+        }
+        if ball.fielding_error.is_some() {
+            header |= FIELDING_ERROR;
+        }
+        if ball.delivery.is_some() || ball.zone.is_some() {
+            header |= DELIVERY;
+        }
+        out.push(header);
+        out.extend(runs_in_full);
+
+        if header & EXTRAS != 0 {
+            let count = ball.extras.len() + ball.signal.is_some() as usize;
+            out.push(u8::try_from(count).map_err(|_| invalid(format!("{} extras", count)))?);
+            for extra in &ball.extras {
+                let (kind, (value, in_full)) = match extra {
+                    Extra::NoBall => (0, (0, None)),
+                    Extra::Wide(runs) => (1, runs_code(runs, EXTRA_VALUE_BITS)),
+                    Extra::Bye(runs) => (2, runs_code(runs, EXTRA_VALUE_BITS)),
+                    Extra::LegBye(runs) => (3, runs_code(runs, EXTRA_VALUE_BITS)),
+                    Extra::Penalty(runs) => (4, value_code(*runs, EXTRA_VALUE_BITS)),
+                    Extra::Overthrows(runs) => (5, runs_code(runs, EXTRA_VALUE_BITS)),
+                };
+                out.push(kind << EXTRA_VALUE_BITS | value);
+                out.extend(in_full);
+            }
+            if let Some(signal) = ball.signal {
+                let (kind, (value, in_full)) = match signal {
+                    UmpireSignal::ShortRuns(runs) => (6, value_code(runs, EXTRA_VALUE_BITS)),
+                    UmpireSignal::DeadBall => (7, (0, None)),
+                };
+                out.push(kind << EXTRA_VALUE_BITS | value);
+                out.extend(in_full);
+            }
+        }
+
+        if let Some((batter, dismissal)) = &ball.wicket {
+            let kind = match dismissal {
+                Dismissal::Bowled { .. } => 0,
+                Dismissal::Caught { .. } => 1,
+                Dismissal::Lbw { .. } => 2,
+                Dismissal::RunOutStriker(_) => 3,
+                Dismissal::RunOutNonStriker(_) => 4,
+                Dismissal::Stumped { .. } => 5,
+                Dismissal::HitWicket { .. } => 6,
+                Dismissal::CaughtBehind { .. } => 7,
+                Dismissal::CaughtAndBowled { .. } => 8,
+            };
+            let end = match ball.run_out_end {
+                Some(End::Striker) => RUN_OUT_AT_STRIKER_END,
+                Some(End::Bowler) => RUN_OUT_AT_BOWLER_END,
+                None => 0,
+            };
+            let fielders = if ball.fielders.is_empty() {
+                0
+            } else {
+                FIELDERS
+            };
+            out.push(kind | end | fielders);
+            self.push_id(*batter, out);
+            match dismissal {
+                Dismissal::Bowled { bowler }
+                | Dismissal::CaughtAndBowled { bowler }
+                | Dismissal::Lbw { bowler }
+                | Dismissal::HitWicket { bowler } => self.push_name(bowler, out)?,
+                Dismissal::Caught { caught, bowler } => {
+                    self.push_name(caught, out)?;
+                    self.push_name(bowler, out)?;
+                }
+                Dismissal::RunOutStriker(fielder) | Dismissal::RunOutNonStriker(fielder) => {
+                    self.push_name(fielder, out)?
+                }
+                Dismissal::Stumped { keeper, bowler }
+                | Dismissal::CaughtBehind { keeper, bowler } => {
+                    self.push_name(keeper, out)?;
+                    self.push_name(bowler, out)?;
+                }
+            }
+            if !ball.fielders.is_empty() {
+                let count = ball.fielders.len();
+                out.push(u8::try_from(count).map_err(|_| invalid(format!("{} fielders", count)))?);
+                for fielder in &ball.fielders {
+                    self.push_id(*fielder, out);
+                }
+            }
+        }
+
+        if let Some(error) = &ball.fielding_error {
+            let (kind, fielder) = match error {
+                FieldingError::DroppedCatch { fielder } => (0, fielder),
+                FieldingError::Misfield { fielder } => (1, fielder),
+            };
+            out.push(kind);
+            self.push_id(*fielder, out);
+        }
+
+        if header & DELIVERY != 0 {
+            let zone = if ball.zone.is_some() { ZONE } else { 0 };
+            match &ball.delivery {
+                Some(delivery) => {
+                    let index = |found: Option<usize>| found.unwrap_or_default() as u8;
+                    let line = index(Line::ALL.iter().position(|l| *l == delivery.line));
+                    let length = index(Length::ALL.iter().position(|l| *l == delivery.length));
+                    let pace = index(Pace::ALL.iter().position(|p| *p == delivery.pace));
+                    out.push(zone | line | length << 2 | pace << 5);
+                    out.extend(delivery.speed.to_le_bytes());
+                    out.extend(delivery.movement.to_le_bytes());
+                }
+                None => out.push(zone | NO_DELIVERY),
+            }
+            if let Some(zone) = ball.zone {
+                out.push(zone.index() as u8);
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode the delivery at the start of `input`, advancing past it
+    pub fn decode(&self, input: &mut &[u8]) -> Result<DeliveryOutcome> {
+        let header = read_byte(input)?;
+        let runs = read_runs(header & 0xf, HEADER_RUNS_BITS, input)?;
+
+        let mut extras = Extras::new();
+        let mut signal = None;
+        if header & EXTRAS != 0 {
+            for _ in 0..read_byte(input)? {
+                let byte = read_byte(input)?;
+                let value = byte & ((1 << EXTRA_VALUE_BITS) - 1);
+                let extra = match byte >> EXTRA_VALUE_BITS {
+                    0 => Extra::NoBall,
+                    1 => Extra::Wide(read_runs(value, EXTRA_VALUE_BITS, input)?),
+                    2 => Extra::Bye(read_runs(value, EXTRA_VALUE_BITS, input)?),
+                    3 => Extra::LegBye(read_runs(value, EXTRA_VALUE_BITS, input)?),
+                    4 => Extra::Penalty(read_value(value, EXTRA_VALUE_BITS, input)?),
+                    5 => Extra::Overthrows(read_runs(value, EXTRA_VALUE_BITS, input)?),
+                    6 => {
+                        let runs = read_value(value, EXTRA_VALUE_BITS, input)?;
+                        signal = Some(UmpireSignal::ShortRuns(runs));
+                        continue;
+                    }
+                    7 => {
+                        signal = Some(UmpireSignal::DeadBall);
+                        continue;
+                    }
+                    kind => return Err(invalid(format!("kind of extra {}", kind))),
+                };
+                extras.push(extra);
+            }
+        }
+
+        let (mut run_out_end, mut fielders) = (None, Vec::new());
+        let wicket = if header & WICKET != 0 {
+            let byte = read_byte(input)?;
+            run_out_end = match byte & (RUN_OUT_AT_STRIKER_END | RUN_OUT_AT_BOWLER_END) {
+                0 => None,
+                RUN_OUT_AT_STRIKER_END => Some(End::Striker),
+                RUN_OUT_AT_BOWLER_END => Some(End::Bowler),
+                _ => return Err(invalid(format!("kind of dismissal {}", byte))),
+            };
+            let kind = byte & !(RUN_OUT_AT_STRIKER_END | RUN_OUT_AT_BOWLER_END | FIELDERS);
+            let batter = self.read_id(input)?;
+            let dismissal = match kind {
+                0 => Dismissal::Bowled {
+                    bowler: self.read_name(input)?,
+                },
+                1 => Dismissal::Caught {
+                    caught: self.read_name(input)?,
+                    bowler: self.read_name(input)?,
+                },
+                2 => Dismissal::Lbw {
+                    bowler: self.read_name(input)?,
+                },
+                3 => Dismissal::RunOutStriker(self.read_name(input)?),
+                4 => Dismissal::RunOutNonStriker(self.read_name(input)?),
+                5 => Dismissal::Stumped {
+                    keeper: self.read_name(input)?,
+                    bowler: self.read_name(input)?,
+                },
+                6 => Dismissal::HitWicket {
+                    bowler: self.read_name(input)?,
+                },
+                7 => Dismissal::CaughtBehind {
+                    keeper: self.read_name(input)?,
+                    bowler: self.read_name(input)?,
+                },
+                8 => Dismissal::CaughtAndBowled {
+                    bowler: self.read_name(input)?,
+                },
+                kind => return Err(invalid(format!("kind of dismissal {}", kind))),
+            };
+            if byte & FIELDERS != 0 {
+                for _ in 0..read_byte(input)? {
+                    fielders.push(self.read_id(input)?);
+                }
+            }
+            Some((batter, dismissal))
+        } else {
+            None
+        };
+
+        let fielding_error = if header & FIELDING_ERROR != 0 {
+            let kind = read_byte(input)?;
+            let fielder = self.read_id(input)?;
+            Some(match kind {
+                0 => FieldingError::DroppedCatch { fielder },
+                1 => FieldingError::Misfield { fielder },
+                kind => return Err(invalid(format!("kind of fielding error {}", kind))),
+            })
+        } else {
+            None
+        };
+
+        let (mut delivery, mut zone) = (None, None);
+        if header & DELIVERY != 0 {
+            let byte = read_byte(input)?;
+            if byte & NO_DELIVERY != NO_DELIVERY {
+                delivery = Some(read_delivery(byte, input)?);
+            }
+            if byte & ZONE != 0 {
+                let index = read_byte(input)?;
+                let found = Zone::ALL.get(index as usize);
+                zone = Some(*found.ok_or_else(|| invalid(format!("zone {}", index)))?);
+            }
+        }
+
+        Ok(DeliveryOutcome {
+            wicket,
+            runs,
+            extras,
+            delivery,
+            fielding_error,
+            signal,
+            zone,
+            fielders,
+            run_out_end,
+        })
+    }
+
+    /// The position of a player among both sides, if it fits in a byte
+    fn index(&self, found: impl Fn(&(PlayerId, String)) -> bool) -> Option<u8> {
+        self.players
+            .iter()
+            .position(found)
+            .filter(|&i| i < IN_FULL as usize)
+            .map(|i| i as u8)
+    }
+
+    fn push_id(&self, id: PlayerId, out: &mut Vec<u8>) {
+        match self.index(|(p, _)| *p == id) {
+            Some(i) => out.push(i),
+            None => {
+                out.push(IN_FULL);
+                out.extend(id.0.to_le_bytes());
+            }
+        }
+    }
+
+    fn push_name(&self, name: &str, out: &mut Vec<u8>) -> Result<()> {
+        match self.index(|(_, n)| n == name) {
+            Some(i) => out.push(i),
+            None => {
+                let len = u16::try_from(name.len())
+                    .map_err(|_| invalid(format!("name of {} bytes", name.len())))?;
+                out.push(IN_FULL);
+                out.extend(len.to_le_bytes());
+                out.extend(name.as_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    fn read_id(&self, input: &mut &[u8]) -> Result<PlayerId> {
+        match read_byte(input)? {
+            IN_FULL => {
+                let bytes = read_bytes(input, 4)?;
+                Ok(PlayerId(u32::from_le_bytes([
+                    bytes[0], bytes[1], bytes[2], bytes[3],
+                ])))
+            }
+            i => self.player(i).map(|(id, _)| *id),
+        }
+    }
+
+    fn read_name(&self, input: &mut &[u8]) -> Result<String> {
+        match read_byte(input)? {
+            IN_FULL => {
+                let len = read_bytes(input, 2)?;
+                let len = u16::from_le_bytes([len[0], len[1]]);
+                let name = read_bytes(input, len as usize)?;
+                String::from_utf8(name.to_vec()).map_err(|err| invalid(err.to_string()))
+            }
+            i => self.player(i).map(|(_, name)| name.clone()),
+        }
+    }
+
+    fn player(&self, index: u8) -> Result<&(PlayerId, String)> {
+        self.players
+            .get(index as usize)
+            .ok_or_else(|| invalid(format!("player index {}", index)))
+    }
+}
+
+fn invalid(what: String) -> Error {
+    Error::InvalidEncoding(what)
+}
+
+/// A value in a field of the given width, and the whole value if it doesn't fit. The
+/// largest value of the field marks that the value follows.
+fn value_code(value: u8, bits: u8) -> (u8, Option<u8>) {
+    let in_full = (1 << bits) - 1;
+    if value < in_full {
+        (value, None)
+    } else {
+        (in_full, Some(value))
+    }
+}
+
+fn read_value(code: u8, bits: u8, input: &mut &[u8]) -> Result<u8> {
+    if code == (1 << bits) - 1 {
+        read_byte(input)
+    } else {
+        Ok(code)
+    }
+}
+
+/// Runs in a field of the given width, the two values below the largest standing for a
+/// four and a six
+fn runs_code(runs: &Runs, bits: u8) -> (u8, Option<u8>) {
+    let in_full = (1 << bits) - 1;
+    match runs {
+        Runs::Four => (in_full - 2, None),
+        Runs::Six => (in_full - 1, None),
+        Runs::Running(n) if *n < in_full - 2 => (*n, None),
+        Runs::Running(n) => (in_full, Some(*n)),
+    }
+}
+
+fn read_runs(code: u8, bits: u8, input: &mut &[u8]) -> Result<Runs> {
+    let in_full = (1 << bits) - 1;
+    Ok(match code {
+        c if c == in_full => Runs::Running(read_byte(input)?),
+        c if c == in_full - 1 => Runs::Six,
+        c if c == in_full - 2 => Runs::Four,
+        n => Runs::Running(n),
+    })
+}
+
+fn read_bytes<'a>(input: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+    if input.len() < n {
+        return Err(invalid("unexpected end of data".into()));
+    }
+    let (bytes, rest) = input.split_at(n);
+    *input = rest;
+    Ok(bytes)
+}
+
+fn read_byte(input: &mut &[u8]) -> Result<u8> {
+    read_bytes(input, 1).map(|b| b[0])
+}
+
+/// Decode how the ball was bowled, given the first byte describing it
+fn read_delivery(byte: u8, input: &mut &[u8]) -> Result<Delivery> {
+    let line = Line::ALL.get((byte & 0x3) as usize);
+    let length = Length::ALL.get((byte >> 2 & 0x7) as usize);
+    let pace = Pace::ALL.get((byte >> 5 & 0x3) as usize);
+    let (line, length, pace) = match (line, length, pace) {
+        (Some(line), Some(length), Some(pace)) => (*line, *length, *pace),
+        _ => return Err(invalid(format!("delivery {:#x}", byte))),
+    };
+    Ok(Delivery {
+        line,
+        length,
+        pace,
+        speed: read_f32(input)?,
+        movement: read_f32(input)?,
+    })
+}
+
+fn read_f32(input: &mut &[u8]) -> Result<f32> {
+    let b = read_bytes(input, 4)?;
+    Ok(f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// A match log with its deliveries in the compact encoding. The other events are few, and
+/// are kept as they are.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactLog {
+    pub form: Form,
+    pub team_a: Arc<Team>,
+    pub team_b: Arc<Team>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub venue: Option<Venue>,
+    #[serde(default)]
+    pub home_advantage: HomeAdvantage,
+    #[serde(default)]
+    pub pitch: Pitch,
+    #[serde(default)]
+    pub weather: Weather,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelInfo>,
+    /// The number of deliveries encoded
+    pub deliveries: u32,
+    /// The encoded deliveries, in the order they were bowled
+    pub encoded: Vec<u8>,
+    /// The events other than deliveries, each with the number of deliveries before it
+    pub events: Vec<(u32, MatchEvent)>,
+}
+
+impl CompactLog {
+    /// The full log, from which the match can be replayed
+    pub fn expand(&self) -> Result<MatchLog> {
+        let codec = DeliveryCodec::new(&self.team_a, &self.team_b);
+        let mut input = self.encoded.as_slice();
+        let mut events = Vec::with_capacity(self.deliveries as usize + self.events.len());
+        let mut bowled = 0;
+        for (before, event) in &self.events {
+            while bowled < *before {
+                events.push(MatchEvent::Delivery(codec.decode(&mut input)?));
+                bowled += 1;
+            }
+            events.push(event.clone());
+        }
+        while bowled < self.deliveries {
+            events.push(MatchEvent::Delivery(codec.decode(&mut input)?));
+            bowled += 1;
+        }
+        if !input.is_empty() {
+            return Err(invalid(format!(
+                "{} bytes after the last delivery",
+                input.len()
+            )));
+        }
+        Ok(MatchLog {
+            form: self.form.clone(),
+            team_a: self.team_a.clone(),
+            team_b: self.team_b.clone(),
+            events,
+            venue: self.venue.clone(),
+            home_advantage: self.home_advantage.clone(),
+            pitch: self.pitch.clone(),
+            weather: self.weather.clone(),
+            model: self.model.clone(),
+        })
+    }
+}
+
+impl MatchLog {
+    /// The log with its deliveries in the compact encoding, or an error if a delivery
+    /// can't be encoded
+    pub fn compact(&self) -> Result<CompactLog> {
+        let codec = DeliveryCodec::new(&self.team_a, &self.team_b);
+        let mut encoded = Vec::new();
+        let mut events = Vec::new();
+        let mut deliveries = 0;
+        for event in &self.events {
+            match event {
+                MatchEvent::Delivery(ball) => {
+                    codec.encode(ball, &mut encoded)?;
+                    deliveries += 1;
+                }
+                other => events.push((deliveries, other.clone())),
+            }
+        }
+        Ok(CompactLog {
+            form: self.form.clone(),
+            team_a: self.team_a.clone(),
+            team_b: self.team_b.clone(),
+            venue: self.venue.clone(),
+            home_advantage: self.home_advantage.clone(),
+            pitch: self.pitch.clone(),
+            weather: self.weather.clone(),
+            model: self.model.clone(),
+            deliveries,
+            encoded,
+            events,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        game::GameState, generate::PlayerGenerator, model::NaiveStatsModel, player::PlayerDb, seed,
+    };
+
+    #[test]
+    fn compact_round_trip() -> Result<()> {
+        let generator = PlayerGenerator::default();
+        let mut rng = seed::rng(11);
+        let mut db = PlayerDb::new();
+        let home = generator.squad(&mut rng, &mut db, 0, "Home")?;
+        let away = generator.squad(&mut rng, &mut db, 1, "Away")?;
+        let mut state = GameState::new(Form::t20(), home.clone(), away.clone())?;
+        state.simulate_to_completion(&NaiveStatsModel {}, &db, &mut rng)?;
+        let log = state.log();
+        let compact = log.compact()?;
+        assert!(compact.encoded.len() < 2 * compact.deliveries as usize);
+        let expanded = compact.expand()?;
+        assert_eq!(
+            serde_json::to_string(&expanded.events)?,
+            serde_json::to_string(&log.events)?
+        );
+        let replayed = GameState::replay(&expanded, None)?;
+        assert_eq!(replayed.result(), state.result());
+
+        // Values and players that don't fit in their fields are written in full
+        let codec = DeliveryCodec::new(&home, &away);
+        let ball = DeliveryOutcome {
+            wicket: Some((
+                PlayerId(9999),
+                Dismissal::Caught {
+                    caught: "A. Substitute".into(),
+                    bowler: away.players[10].1.clone(),
+                },
+            )),
+            runs: Runs::Running(13),
+            extras: smallvec::smallvec![Extra::NoBall, Extra::Bye(Runs::Four), Extra::Penalty(40)],
+            delivery: Some(Delivery {
+                line: Line::Leg,
+                length: Length::Bouncer,
+                pace: Pace::Spin,
+                speed: 81.5,
+                movement: -3.25,
+            }),
+            fielding_error: Some(FieldingError::Misfield {
+                fielder: away.players[3].0,
+            }),
+            signal: Some(UmpireSignal::ShortRuns(1)),
+            zone: Some(Zone::SquareLeg),
+            fielders: vec![PlayerId(9998)],
+            run_out_end: None,
+        };
+        let mut encoded = Vec::new();
+        codec.encode(&ball, &mut encoded)?;
+        let mut input = encoded.as_slice();
+        let decoded = codec.decode(&mut input)?;
+        assert!(input.is_empty());
+        assert_eq!(
+            serde_json::to_string(&decoded)?,
+            serde_json::to_string(&ball)?
+        );
+        assert!(codec.decode(&mut &encoded[..encoded.len() - 1]).is_err());
+
+        // A zone can be known without the delivery
+        let ball = DeliveryOutcome {
+            zone: Some(Zone::ThirdMan),
+            ..DeliveryOutcome::running(2)
+        };
+        let mut encoded = Vec::new();
+        codec.encode(&ball, &mut encoded)?;
+        assert_eq!(encoded.len(), 3);
+        let decoded = codec.decode(&mut encoded.as_slice())?;
+        assert_eq!((decoded.delivery, decoded.zone), (None, ball.zone));
+
+        // As is the end a run out was made at
+        let ball = DeliveryOutcome {
+            wicket: Some((
+                home.players[1].0,
+                Dismissal::RunOutNonStriker(away.players[4].1.clone()),
+            )),
+            fielders: vec![away.players[4].0, away.players[0].0],
+            run_out_end: Some(End::Striker),
+            ..DeliveryOutcome::running(1)
+        };
+        let mut encoded = Vec::new();
+        codec.encode(&ball, &mut encoded)?;
+        let decoded = codec.decode(&mut encoded.as_slice())?;
+        assert_eq!(decoded.run_out_end, Some(End::Striker));
+        assert_eq!(decoded.fielders, ball.fielders);
+
+        // Anything too long for its length field is an error rather than truncated
+        let long_name = DeliveryOutcome {
+            wicket: Some((
+                home.players[0].0,
+                Dismissal::RunOutStriker("x".repeat(70_000)),
+            )),
+            ..Default::default()
+        };
+        let too_many_extras = DeliveryOutcome {
+            extras: smallvec::smallvec![Extra::NoBall; 256],
+            ..Default::default()
+        };
+        let too_many_fielders = DeliveryOutcome {
+            wicket: long_name
+                .wicket
+                .clone()
+                .map(|(id, _)| (id, Dismissal::RunOutStriker(away.players[0].1.clone()))),
+            fielders: vec![away.players[0].0; 256],
+            ..Default::default()
+        };
+        // and leaves what was already encoded as it was
+        for ball in [long_name, too_many_extras, too_many_fielders].iter() {
+            let mut out = encoded.clone();
+            assert!(codec.encode(ball, &mut out).is_err());
+            assert_eq!(out, encoded);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+//! Driving a match to completion with a model
+use super::{DeliveryOutcome, FieldingError, GameState, MatchObserver, MatchResult};
+use crate::{
+    error::{Error, Result},
+    form::Overs,
+    model::{Model, PlayerRating},
+    player::PlayerDb,
+    seed,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display},
+    marker::PhantomData,
+};
+
+/// The position of a delivery in a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BallNumber {
+    /// The index of the innings in the match, starting at 0
+    pub innings: usize,
+    /// The overs bowled before the delivery
+    #[serde(flatten)]
+    pub overs: Overs,
+}
+
+impl Display for BallNumber {
+    /// Formats in the conventional style, e.g. "12.3" for the third ball of the 13th over
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.overs.completed, self.overs.balls + 1)
+    }
+}
+
+/// The score of the innings in which a delivery was bowled, just after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreSnapshot {
+    pub runs: u16,
+    pub wickets: u8,
+    /// The number of legal balls bowled in the innings
+    pub balls: u16,
+    /// Whether the delivery completed the match
+    pub match_complete: bool,
+}
+
+/// An iterator that simulates a match one delivery at a time. Created by
+/// GameState::deliveries.
+pub struct Deliveries<'s, M: ?Sized, R, G>
+where
+    R: PlayerRating,
+{
+    state: &'s mut GameState,
+    model: &'s M,
+    db: &'s PlayerDb<R>,
+    rng: &'s mut G,
+    /// Set after an error is returned so that iteration stops
+    failed: bool,
+    _rating: PhantomData<R>,
+}
+
+impl<'s, M, R, G> Iterator for Deliveries<'s, M, R, G>
+where
+    M: Model<R> + ?Sized,
+    R: PlayerRating,
+    G: Rng,
+{
+    type Item = Result<(BallNumber, DeliveryOutcome, ScoreSnapshot)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.state.complete() {
+            return None;
+        }
+        let result = self.state.simulate_delivery(self.model, self.db, self.rng);
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+impl GameState {
+    /// Lazily simulate the rest of the match, yielding each delivery along with the score
+    /// of its innings until the match is complete
+    pub fn deliveries<'s, M, R, G>(
+        &'s mut self,
+        model: &'s M,
+        db: &'s PlayerDb<R>,
+        rng: &'s mut G,
+    ) -> Deliveries<'s, M, R, G>
+    where
+        M: Model<R> + ?Sized,
+        R: PlayerRating,
+        G: Rng,
+    {
+        Deliveries {
+            state: self,
+            model,
+            db,
+            rng,
+            failed: false,
+            _rating: PhantomData,
+        }
+    }
+
+    /// Simulate and apply a single delivery
+    pub(crate) fn simulate_delivery<R: PlayerRating>(
+        &mut self,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        rng: &mut impl Rng,
+    ) -> Result<(BallNumber, DeliveryOutcome, ScoreSnapshot)> {
+        // Check the sides against the database before the first simulated delivery
+        if self.model.is_none() {
+            let players = self.form.batsmen_per_side as usize;
+            self.team_a.validate_for(players, db)?;
+            self.team_b.validate_for(players, db)?;
+        }
+        let ball = model.generate_delivery(rng, self.snapshot(db)?);
+        let ball = self.umpire(ball, rng)?;
+        if self.model.is_none() {
+            self.model = Some(model.info());
+        }
+        let innings = self.previous_innings.len();
+        self.update(&ball)?;
+        let stats = self.innings(innings).ok_or(Error::MatchComplete)?;
+        let record = stats.deliveries.last().ok_or(Error::MatchComplete)?;
+        let number = BallNumber {
+            innings,
+            overs: record.overs,
+        };
+        let score = ScoreSnapshot {
+            runs: stats.runs(),
+            wickets: stats.wickets(),
+            balls: stats.balls_bowled(),
+            match_complete: self.complete(),
+        };
+        Ok((number, ball, score))
+    }
+
+    /// Simulate the rest of the match ball by ball, returning the result
+    pub fn simulate_to_completion<R: PlayerRating>(
+        &mut self,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        rng: &mut impl Rng,
+    ) -> Result<MatchResult> {
+        self.simulate_with(model, db, rng, &mut ())
+    }
+
+    /// Simulate the rest of the match with a generator seeded from `seed`, so that the
+    /// result can be reproduced exactly
+    pub fn simulate_seeded<R: PlayerRating>(
+        &mut self,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        seed: u64,
+    ) -> Result<MatchResult> {
+        self.simulate_to_completion(model, db, &mut seed::rng(seed))
+    }
+
+    /// Simulate the rest of the match, notifying the observer of each event
+    pub fn simulate_with<R: PlayerRating>(
+        &mut self,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        rng: &mut impl Rng,
+        observer: &mut impl MatchObserver,
+    ) -> Result<MatchResult> {
+        while !self.complete() {
+            let reviewed = self
+                .current_innings_stats
+                .as_ref()
+                .map_or(0, |st| st.reviews.len());
+            let (number, ball, _) = self.simulate_delivery(model, db, rng)?;
+            observer.on_delivery(self, &ball);
+            if let Some(reviews) = self.innings_reviews(number.innings) {
+                for review in &reviews[reviewed.min(reviews.len())..] {
+                    observer.on_review(self, review);
+                }
+            }
+            if let Some((batter, dismissal)) = &ball.wicket {
+                observer.on_wicket(self, *batter, dismissal);
+            }
+            if let Some(FieldingError::DroppedCatch { .. }) = ball.fielding_error {
+                let drops = self.innings_dropped_catches(number.innings);
+                if let Some(drop) = drops.and_then(|d| d.last()) {
+                    observer.on_dropped_catch(self, drop);
+                }
+            }
+            if ball.legal() && number.overs.balls + 1 == self.form.balls_per_over {
+                let summaries = self.innings_over_summaries(number.innings);
+                if let Some(over) = summaries.and_then(|o| o.last()) {
+                    observer.on_over_complete(self, over);
+                }
+            }
+            for i in number.innings..self.previous_innings.len() {
+                observer.on_innings_end(self, i);
+            }
+        }
+        let result = self.result().ok_or(Error::MatchInProgress)?;
+        observer.on_match_end(self, &result);
+        Ok(result)
+    }
+}
@@ -0,0 +1,248 @@
+//! A record of the events of a match, from which its state can be rebuilt
+use super::{AbandonReason, DeliveryOutcome, GameState, NonDeliveryEvent, Review, Substitution};
+use crate::{
+    conditions::{HomeAdvantage, Pitch, Venue, Weather},
+    error::Result,
+    field::Field,
+    form::Form,
+    model::ModelInfo,
+    player::PlayerId,
+    team::Team,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// An event that changes the state of a match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MatchEvent {
+    /// A delivery was bowled
+    Delivery(DeliveryOutcome),
+    /// An event outside of a delivery
+    NonDelivery(NonDeliveryEvent),
+    /// The batting side declared
+    Declare,
+    /// The team with the given ID forfeited its innings
+    Forfeit(u16),
+    /// The current bowler was chosen
+    SetBowler(PlayerId),
+    /// The batters at the crease were chosen
+    SetBatters {
+        striker: PlayerId,
+        non_striker: PlayerId,
+    },
+    /// A player was replaced by a substitute
+    Substitute(Substitution),
+    /// The fielding side set its field
+    SetField(Field),
+    /// The batting side sent in the given batter next
+    Promote(PlayerId),
+    /// A side reviewed the umpire's decision on the delivery that follows
+    Review(Review),
+    /// The innings were reduced to the given overs from this point on
+    ReduceOvers(u16),
+    /// The match was abandoned
+    Abandon(AbandonReason),
+    /// The team with the given ID forfeited the match
+    ForfeitMatch(u16),
+}
+
+/// Everything needed to reconstruct a match: the rules, the line-ups, and each event in
+/// the order it occurred.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchLog {
+    pub form: Form,
+    /// The side that batted first, as decided by the toss
+    pub team_a: Arc<Team>,
+    /// The side that fielded first
+    pub team_b: Arc<Team>,
+    pub events: Vec<MatchEvent>,
+    /// The ground the match was played at, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub venue: Option<Venue>,
+    /// How much the venue favoured the side at home. Older logs take the default.
+    #[serde(default)]
+    pub home_advantage: HomeAdvantage,
+    /// The pitch the match was played on. Older logs take the default.
+    #[serde(default)]
+    pub pitch: Pitch,
+    /// The weather the match was played in. Older logs take the default.
+    #[serde(default)]
+    pub weather: Weather,
+    /// The model that simulated the match, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelInfo>,
+}
+
+impl GameState {
+    /// The events applied to this match so far
+    pub fn events(&self) -> &[MatchEvent] {
+        &self.events
+    }
+
+    /// A log of the match so far, from which it can be replayed
+    pub fn log(&self) -> MatchLog {
+        // The log starts from the original line-ups, so undo any substitutions
+        let mut team_a = self.team_a.clone();
+        let mut team_b = self.team_b.clone();
+        for event in self.events.iter().rev() {
+            if let MatchEvent::Substitute(sub) = event {
+                let side = if sub.team == team_a.id {
+                    &mut team_a
+                } else {
+                    &mut team_b
+                };
+                // The substitute is in the side, so this can't fail
+                let _ = Arc::make_mut(side).replace(sub.replacement.0, sub.player.clone());
+            }
+        }
+        MatchLog {
+            form: self.scheduled_form.as_ref().unwrap_or(&self.form).clone(),
+            team_a,
+            team_b,
+            events: self.events.clone(),
+            venue: self.venue.clone(),
+            home_advantage: self.home_advantage.clone(),
+            pitch: self.conditions.pitch.clone(),
+            weather: self.conditions.weather.clone(),
+            model: self.model.clone(),
+        }
+    }
+
+    /// Rebuild the state of a logged match. If `deliveries` is given, the state is
+    /// returned immediately after that many deliveries have been bowled; otherwise every
+    /// event is applied.
+    ///
+    /// Declaration, bowling and fielding strategies are not consulted during a replay,
+    /// since their decisions are already in the log.
+    pub fn replay(log: &MatchLog, deliveries: Option<usize>) -> Result<Self> {
+        let mut state = Self::new(log.form.clone(), log.team_a.clone(), log.team_b.clone())?;
+        state.model = log.model.clone();
+        state.venue = log.venue.clone();
+        state.home_advantage = log.home_advantage.clone();
+        state.set_conditions(log.pitch.clone(), log.weather.clone());
+        let mut bowled = 0;
+        for event in &log.events {
+            if deliveries.is_some_and(|n| bowled >= n) {
+                break;
+            }
+            state.apply(event)?;
+            if let MatchEvent::Delivery(_) = event {
+                bowled += 1;
+            }
+        }
+        Ok(state)
+    }
+
+    /// Apply a single logged event
+    pub fn apply(&mut self, event: &MatchEvent) -> Result<()> {
+        match event {
+            MatchEvent::Delivery(ball) => self.update(ball),
+            MatchEvent::NonDelivery(event) => self.non_delivery_event(event),
+            MatchEvent::Declare => self.declare(),
+            MatchEvent::Forfeit(team_id) => {
+                let team = Arc::clone(if *team_id == self.team_a.id {
+                    &self.team_a
+                } else {
+                    &self.team_b
+                });
+                self.forfeit_innings(&team)
+            }
+            MatchEvent::SetBowler(bowler) => self.set_bowler(*bowler),
+            MatchEvent::SetBatters {
+                striker,
+                non_striker,
+            } => self.set_batters(*striker, *non_striker),
+            MatchEvent::Substitute(sub) => {
+                self.substitute(sub.team, sub.player.0, sub.replacement.clone())
+            }
+            MatchEvent::SetField(field) => self.set_field(*field),
+            MatchEvent::Promote(batter) => self.promote(*batter),
+            MatchEvent::Review(review) => self.review(review.clone()),
+            MatchEvent::ReduceOvers(overs) => self.reduce_overs(*overs),
+            MatchEvent::Abandon(reason) => self.abandon(reason.clone()),
+            MatchEvent::ForfeitMatch(team_id) => {
+                let team = self.team(*team_id).clone();
+                self.forfeit(&team)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{model::NullModel, player::PlayerDb, seed, tests::test_team};
+
+    #[test]
+    fn home_advantage_replayed() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "IND")?;
+        let team_b = test_team(&mut db, 2, "SA")?;
+        let mut state = GameState::new(Form::t20(), team_a, team_b)?;
+        let advantage = HomeAdvantage {
+            effect: 0.2,
+            ..HomeAdvantage::default()
+        };
+        let venue = Venue {
+            name: "Chepauk".into(),
+            country: "India".into(),
+        };
+        state.set_venue(venue, advantage);
+        state.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(4))?;
+        let log: MatchLog = serde_json::from_str(&serde_json::to_string(&state.log())?)?;
+        assert_eq!(log.home_advantage.effect, 0.2);
+        assert_eq!(log.compact()?.expand()?.home_advantage.effect, 0.2);
+        // A match resumed from the log is simulated with the same advantage
+        let replayed = GameState::replay(&log, Some(30))?;
+        assert_eq!(replayed.home_advantage.effect, 0.2);
+        Ok(())
+    }
+
+    #[test]
+    fn conditions_replayed() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "SL")?;
+        let team_b = test_team(&mut db, 2, "PAK")?;
+        let mut state = GameState::new(Form::t20(), team_a, team_b)?;
+        let pitch = Pitch {
+            turn: 0.8,
+            seam: 0.1,
+        };
+        state.set_conditions(pitch, Weather { cloud_cover: 0.6 });
+        state.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(5))?;
+        let log: MatchLog = serde_json::from_str(&serde_json::to_string(&state.log())?)?;
+        for log in [log.clone(), log.compact()?.expand()?].iter() {
+            assert_eq!((log.pitch.turn, log.pitch.seam), (0.8, 0.1));
+            assert_eq!(log.weather.cloud_cover, 0.6);
+        }
+        let replayed = GameState::replay(&log, Some(30))?;
+        assert_eq!(replayed.conditions.pitch.turn, 0.8);
+        assert_eq!(replayed.conditions.weather.cloud_cover, 0.6);
+        Ok(())
+    }
+
+    #[test]
+    fn replay_to_a_delivery() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "AUS")?;
+        let team_b = test_team(&mut db, 2, "NZ")?;
+        let mut state = GameState::new(Form::t20(), team_a, team_b)?;
+        let mut rng = seed::rng(9);
+        for delivery in state.deliveries(&NullModel {}, &db, &mut rng).take(150) {
+            delivery?;
+        }
+        let partway = serde_json::to_string(&state.scorecard()?)?;
+        state.simulate_to_completion(&NullModel {}, &db, &mut rng)?;
+        let log = state.log();
+        // Stopping after 150 deliveries gives the state of the match at that point
+        let replayed = GameState::replay(&log, Some(150))?;
+        assert_eq!(serde_json::to_string(&replayed.scorecard()?)?, partway);
+        assert_eq!(replayed.result(), None);
+        let replayed = GameState::replay(&log, None)?;
+        assert_eq!(
+            serde_json::to_string(&replayed.scorecard()?)?,
+            serde_json::to_string(&state.scorecard()?)?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+//! Hooks to follow the events of a match as it is simulated
+use super::{
+    stats::{DroppedCatch, OverSummary},
+    DeliveryOutcome, Dismissal, GameState, MatchResult, Review,
+};
+use crate::player::PlayerId;
+
+/// Receives the events of a match as the driver simulates it, e.g. for live scoreboards,
+/// commentary, or logging. Each callback is given the state after the event, and does
+/// nothing by default.
+pub trait MatchObserver {
+    /// Called after every delivery
+    fn on_delivery(&mut self, _state: &GameState, _ball: &DeliveryOutcome) {}
+
+    /// Called when a side reviews the umpire's decision, after on_delivery for the
+    /// delivery reviewed
+    fn on_review(&mut self, _state: &GameState, _review: &Review) {}
+
+    /// Called when a batter is dismissed, after on_delivery and any review
+    fn on_wicket(&mut self, _state: &GameState, _batter: PlayerId, _dismissal: &Dismissal) {}
+
+    /// Called when a catch is dropped, after on_delivery
+    fn on_dropped_catch(&mut self, _state: &GameState, _drop: &DroppedCatch) {}
+
+    /// Called when the last ball of an over has been bowled
+    fn on_over_complete(&mut self, _state: &GameState, _over: &OverSummary) {}
+
+    /// Called when an innings closes, with its index in the match
+    fn on_innings_end(&mut self, _state: &GameState, _innings: usize) {}
+
+    /// Called once the match is complete
+    fn on_match_end(&mut self, _state: &GameState, _result: &MatchResult) {}
+}
+
+/// An observer that ignores every event
+impl MatchObserver for () {}
@@ -0,0 +1,443 @@
+//! The outcome of a completed match
+use super::GameState;
+use crate::{form::TieBreaker, team::Team};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+};
+
+/// The margin by which a match was won
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Margin {
+    /// The side batting first won by this many runs
+    Runs(u16),
+    /// The side batting last reached the target with this many wickets in hand
+    Wickets(u8),
+    /// The winning side only needed to bat once, and led by this many runs
+    InningsAndRuns(u16),
+    /// The other side forfeited the match
+    Forfeit,
+}
+
+/// Why a match was abandoned before it could be completed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AbandonReason {
+    Weather,
+    BadLight,
+    UnfitGround,
+    Other(String),
+}
+
+impl Display for AbandonReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbandonReason::Weather => write!(f, "weather"),
+            AbandonReason::BadLight => write!(f, "bad light"),
+            AbandonReason::UnfitGround => write!(f, "an unfit ground"),
+            AbandonReason::Other(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// The result of a completed match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchResult {
+    /// The team with the given ID won
+    Win { winner: u16, margin: Margin },
+    /// The scores finished level with the side batting last unable to continue
+    Tie,
+    /// Time ran out before a result was reached
+    Draw,
+    /// Too little of a limited-overs match was played for it to have a result
+    NoResult,
+}
+
+/// `n` followed by the singular or plural of a word, e.g. "1 wicket" or "5 wickets"
+fn count(n: u16, word: &str) -> String {
+    if n == 1 {
+        format!("{} {}", n, word)
+    } else {
+        format!("{} {}s", n, word)
+    }
+}
+
+/// The ordinal of a small number, e.g. "1st" or "2nd"
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+impl MatchResult {
+    /// The result of the given match in standard phrasing, e.g. "New Zealand won by 5
+    /// wickets (with 11 balls remaining)", "Australia won by an innings and 32 runs" or
+    /// "Match drawn"
+    pub fn describe(&self, game: &GameState) -> String {
+        match *self {
+            MatchResult::Win { winner, margin } => {
+                let name = &game.team(winner).name;
+                let won = match margin {
+                    Margin::Runs(runs) => format!("{} won by {}", name, count(runs, "run")),
+                    Margin::InningsAndRuns(runs) => {
+                        format!("{} won by an innings and {}", name, count(runs, "run"))
+                    }
+                    Margin::Forfeit => return format!("{} won by forfeit", name),
+                    Margin::Wickets(wickets) => {
+                        format!("{} won by {}", name, count(wickets as u16, "wicket"))
+                    }
+                };
+                // A match abandoned in the chase is decided on the par score, with the
+                // balls left unplayed rather than to spare
+                if let Some(reason) = &game.abandoned {
+                    return format!(
+                        "{} (on the par score, match abandoned due to {})",
+                        won, reason
+                    );
+                }
+                match (margin, game.balls_to_spare()) {
+                    (Margin::Wickets(_), Some(balls)) if balls > 0 => {
+                        format!("{} (with {} remaining)", won, count(balls, "ball"))
+                    }
+                    _ => won,
+                }
+            }
+            MatchResult::Tie => match (game.tie_winner(), game.form.tie_breaker) {
+                (Some(winner), TieBreaker::BoundaryCount) => format!(
+                    "Match tied ({} won on boundary count)",
+                    game.team(winner).name
+                ),
+                (Some(winner), TieBreaker::FewerWickets) => format!(
+                    "Match tied ({} won by losing fewer wickets)",
+                    game.team(winner).name
+                ),
+                _ => "Match tied".to_string(),
+            },
+            MatchResult::Draw => "Match drawn".to_string(),
+            MatchResult::NoResult => match &game.abandoned {
+                Some(reason) => format!("No result (match abandoned due to {})", reason),
+                None => "No result".to_string(),
+            },
+        }
+    }
+}
+
+/// A one-line summary of a series between two sides, e.g. "India lead the series 2-1",
+/// "England won the series 3-0" or "Series drawn 1-1". The series is over once
+/// `scheduled` matches have results. Ties and draws count towards neither side.
+pub fn series_summary(
+    team_a: &Team,
+    team_b: &Team,
+    results: &[MatchResult],
+    scheduled: usize,
+) -> String {
+    let wins = |id: u16| {
+        results
+            .iter()
+            .filter(|r| matches!(r, MatchResult::Win { winner, .. } if *winner == id))
+            .count()
+    };
+    let (a, b) = (wins(team_a.id), wins(team_b.id));
+    let over = results.len() >= scheduled;
+    let (leader, most, fewest) = match a.cmp(&b) {
+        Ordering::Greater => (team_a, a, b),
+        Ordering::Less => (team_b, b, a),
+        Ordering::Equal if over => return format!("Series drawn {}-{}", a, b),
+        Ordering::Equal => return format!("Series level at {}-{}", a, b),
+    };
+    let verb = if over { "won" } else { "lead" };
+    format!("{} {} the series {}-{}", leader.name, verb, most, fewest)
+}
+
+impl GameState {
+    /// The result of the match in standard phrasing, or None if it is still in progress
+    pub fn result_summary(&self) -> Option<String> {
+        self.result().map(|result| result.describe(self))
+    }
+
+    /// The state of the innings in progress in standard phrasing, e.g. "England trail by
+    /// 182 runs with 7 wickets remaining in the 2nd innings" or "India need 40 runs to win
+    /// with 3 wickets remaining". None in the first innings of the match or once it is
+    /// over.
+    pub fn state_of_play(&self) -> Option<String> {
+        self.standing(self.current_innings()?)
+    }
+
+    /// The standing of the side batting in the given innings at its close, or now if it
+    /// is in progress. None for the first innings, and for the final innings once it is
+    /// over, as the result says all there is to say.
+    pub(crate) fn standing(&self, innings: usize) -> Option<String> {
+        if innings == 0 {
+            return None;
+        }
+        let st = self.innings(innings)?;
+        let in_progress = self.current_innings() == Some(innings);
+        let so_far: Vec<_> = self
+            .previous_innings
+            .iter()
+            .chain(self.current_innings_stats.iter())
+            .take(innings + 1)
+            .collect();
+        let runs_of = |team| {
+            so_far
+                .iter()
+                .filter(|s| s.batting_team == team)
+                .map(|s| s.runs())
+                .sum::<u16>() as i32
+        };
+        let lead = runs_of(st.batting_team) - runs_of(st.bowling_team);
+        let name = &self.team(st.batting_team).name;
+        let wickets = (self.form.batsmen_per_side - 1).saturating_sub(st.wickets());
+        let remaining = format!("with {} remaining", count(wickets as u16, "wicket"));
+        if innings + 1 == 2 * self.form.innings as usize {
+            return in_progress.then(|| {
+                let needed = count((1 - lead) as u16, "run");
+                format!("{} need {} to win {}", name, needed, remaining)
+            });
+        }
+        let standing = match lead.cmp(&0) {
+            Ordering::Greater => format!("{} lead by {}", name, count(lead as u16, "run")),
+            Ordering::Less => format!("{} trail by {}", name, count(-lead as u16, "run")),
+            Ordering::Equal => format!("{} are level", name),
+        };
+        // The side's own innings, e.g. its 2nd in the 3rd of the match
+        let side_innings = so_far
+            .iter()
+            .filter(|s| s.batting_team == st.batting_team)
+            .count();
+        Some(if in_progress {
+            format!(
+                "{} {} in the {} innings",
+                standing,
+                remaining,
+                ordinal(side_innings)
+            )
+        } else {
+            format!("{} after the {} innings", standing, ordinal(side_innings))
+        })
+    }
+
+    /// The legal balls left unused in the final innings of a completed limited-overs
+    /// match
+    fn balls_to_spare(&self) -> Option<u16> {
+        let quota = self.form.innings_balls()?;
+        let last = self.previous_innings.last()?;
+        Some(quota.saturating_sub(last.balls_bowled()))
+    }
+
+    /// Whether enough of a limited-overs match was played for a result. Each side must
+    /// have faced the minimum overs, unless it was bowled out or reached its target
+    /// sooner. `chased` is whether the side batting last overtook the other.
+    fn constituted(&self, chased: bool) -> bool {
+        let min = match self.form.min_overs_for_result {
+            Some(min) => min,
+            None => return true,
+        };
+        let played = self.previous_innings.len();
+        played >= 2
+            && self.previous_innings.iter().enumerate().all(|(i, st)| {
+                st.overs.completed >= min || st.all_out() || (chased && i + 1 == played)
+            })
+    }
+
+    /// The result of a match abandoned before it could be completed. A limited-overs
+    /// match is decided if enough of the chase was played, by comparing the score with
+    /// the target scaled down by the share of the overs faced. Otherwise a match that
+    /// started in a format without limited overs is drawn.
+    fn abandoned_result(&self) -> MatchResult {
+        let last = match self.previous_innings.last() {
+            Some(last) => last,
+            None => return MatchResult::NoResult,
+        };
+        if last.max_overs.or(self.form.overs_per_innings).is_none() {
+            return MatchResult::Draw;
+        }
+        let par = match self.par_score() {
+            Some(par) if self.constituted(false) => par,
+            _ => return MatchResult::NoResult,
+        };
+        let batting_score = self.score_of(last.batting_team);
+        match batting_score.cmp(&par) {
+            Ordering::Greater => MatchResult::Win {
+                winner: last.batting_team,
+                margin: Margin::Wickets(self.form.batsmen_per_side - 1 - last.wickets()),
+            },
+            Ordering::Equal => MatchResult::Tie,
+            Ordering::Less => MatchResult::Win {
+                winner: last.bowling_team,
+                margin: Margin::Runs(par - batting_score),
+            },
+        }
+    }
+
+    /// The par score of the side chasing when a limited-overs match was abandoned in the
+    /// final innings: the target scaled down by the share of their overs faced
+    pub fn par_score(&self) -> Option<u16> {
+        self.abandoned.as_ref()?;
+        let last = self.previous_innings.last()?;
+        let overs = last.max_overs.or(self.form.overs_per_innings)?;
+        if self.previous_innings.len() != 2 * self.form.innings as usize {
+            return None;
+        }
+        let quota = overs as u32 * self.form.balls_per_over as u32;
+        let target = self.score_of(last.bowling_team) as u32;
+        Some((target * last.balls_bowled() as u32 / quota) as u16)
+    }
+
+    /// The result of the match, or None if it is still in progress
+    pub fn result(&self) -> Option<MatchResult> {
+        if !self.complete() {
+            return None;
+        }
+        if let Some(team) = self.forfeited {
+            let winner = if team == self.team_a.id {
+                self.team_b.id
+            } else {
+                self.team_a.id
+            };
+            return Some(MatchResult::Win {
+                winner,
+                margin: Margin::Forfeit,
+            });
+        }
+        if self.abandoned.is_some() {
+            return Some(self.abandoned_result());
+        }
+        let last = match self.previous_innings.last() {
+            Some(innings) => innings,
+            // A match that never started can't have a winner
+            None => return Some(MatchResult::Draw),
+        };
+        let last_batting = last.batting_team;
+        let last_bowling = last.bowling_team;
+        let batting_score = self.score_of(last_batting);
+        let bowling_score = self.score_of(last_bowling);
+        if !self.constituted(batting_score > bowling_score) {
+            return Some(MatchResult::NoResult);
+        }
+
+        if batting_score > bowling_score {
+            // Either the chase succeeded, or the last side to bat still leads
+            let final_innings = self.previous_innings.len() == 2 * self.form.innings as usize;
+            if !final_innings && self.match_overs_remaining() == Some(0) {
+                return Some(MatchResult::Draw);
+            }
+            let wickets = self.form.batsmen_per_side - 1 - last.wickets();
+            return Some(MatchResult::Win {
+                winner: last_batting,
+                margin: Margin::Wickets(wickets),
+            });
+        }
+
+        // The side batting last did not overtake. If they were cut short by time rather
+        // than bowled out, the match is drawn.
+        if self.match_overs_remaining() == Some(0) && !last.all_out() {
+            return Some(MatchResult::Draw);
+        }
+        if batting_score == bowling_score {
+            return Some(MatchResult::Tie);
+        }
+        let count_innings = |id: u16| {
+            self.previous_innings
+                .iter()
+                .filter(|st| st.batting_team == id)
+                .count()
+        };
+        let runs = bowling_score - batting_score;
+        let margin = if count_innings(last_bowling) < count_innings(last_batting) {
+            Margin::InningsAndRuns(runs)
+        } else {
+            Margin::Runs(runs)
+        };
+        Some(MatchResult::Win {
+            winner: last_bowling,
+            margin,
+        })
+    }
+
+    /// The side that wins a tied match by the format's tie-breaker. This is None if the
+    /// match wasn't tied, the format has no tie-breaker, or the sides are still level.
+    pub fn tie_winner(&self) -> Option<u16> {
+        if self.result() != Some(MatchResult::Tie) {
+            return None;
+        }
+        let (team_a, team_b) = (self.team_a.id, self.team_b.id);
+        // The boundaries hit and wickets lost by each side
+        let totals = |team: u16| {
+            self.previous_innings
+                .iter()
+                .filter(|st| st.batting_team == team)
+                .fold((0, 0), |(boundaries, wickets), st| {
+                    (boundaries + st.boundaries(), wickets + st.wickets() as u16)
+                })
+        };
+        let ((boundaries_a, wickets_a), (boundaries_b, wickets_b)) =
+            (totals(team_a), totals(team_b));
+        // Compare so that the greater is better
+        let (a, b) = match self.form.tie_breaker {
+            TieBreaker::None => return None,
+            TieBreaker::BoundaryCount => (boundaries_a, boundaries_b),
+            TieBreaker::FewerWickets => (wickets_b, wickets_a),
+        };
+        match a.cmp(&b) {
+            Ordering::Greater => Some(team_a),
+            Ordering::Less => Some(team_b),
+            Ordering::Equal => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::PlayerId;
+
+    #[test]
+    fn series_summaries() {
+        let team = |id: u16, name: &str| Team {
+            id,
+            name: name.into(),
+            players: vec![(PlayerId(id as u32), "player".into())],
+            keeper: None,
+            captain: None,
+            home: None,
+            roles: Default::default(),
+        };
+        let (india, england) = (team(0, "India"), team(1, "England"));
+        let win = |winner| MatchResult::Win {
+            winner,
+            margin: Margin::Runs(10),
+        };
+        let results = [win(0), MatchResult::Draw, win(1), win(0)];
+        assert_eq!(
+            series_summary(&india, &england, &results[..3], 5),
+            "Series level at 1-1"
+        );
+        assert_eq!(
+            series_summary(&india, &england, &results, 5),
+            "India lead the series 2-1"
+        );
+        assert_eq!(
+            series_summary(&england, &india, &results, 4),
+            "India won the series 2-1"
+        );
+        assert_eq!(
+            series_summary(&india, &england, &results[..3], 3),
+            "Series drawn 1-1"
+        );
+    }
+
+    #[test]
+    fn ordinals() {
+        let words: Vec<_> = [1, 2, 3, 4, 11, 12, 13, 21, 22].map(ordinal).to_vec();
+        assert_eq!(
+            words,
+            ["1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd"]
+        );
+    }
+}
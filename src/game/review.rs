@@ -0,0 +1,324 @@
+//! The umpires' decisions on appeals for LBW and catches behind, which are sometimes
+//! wrong, and reviews of them under the Decision Review System (DRS)
+use super::{log::MatchEvent, DeliveryOutcome, Dismissal, GameState};
+use crate::{
+    error::{Error, Result},
+    player::PlayerId,
+    strategy::{ConfidentReview, ReviewContext, ReviewStrategy},
+    team::Team,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// How reliably the on-field umpires decide appeals. By default the umpires never make a
+/// mistake, since models fitted on real outcomes already include the umpires' errors.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Umpiring {
+    /// The chance that a batter who is out LBW or caught behind is given not out
+    pub missed_out: f32,
+    /// The chance that a batter is wrongly given out LBW off a dot ball
+    pub false_out: f32,
+    /// The chance that a reviewed LBW is too close to call, so that the decision on the
+    /// field stands
+    pub umpires_call: f32,
+}
+
+impl Default for Umpiring {
+    fn default() -> Self {
+        Self::perfect()
+    }
+}
+
+impl Umpiring {
+    /// Umpires who never make a mistake
+    pub fn perfect() -> Self {
+        Self {
+            missed_out: 0.,
+            false_out: 0.,
+            umpires_call: 0.,
+        }
+    }
+
+    /// Umpires who make mistakes at roughly the rate seen in international cricket, for
+    /// models of what actually happened rather than of what was given
+    pub fn typical() -> Self {
+        Self {
+            missed_out: 0.08,
+            false_out: 0.002,
+            umpires_call: 0.2,
+        }
+    }
+
+    /// Whether the umpires ever get a decision wrong
+    pub fn is_perfect(&self) -> bool {
+        self.missed_out <= 0. && self.false_out <= 0.
+    }
+}
+
+/// A kind of dismissal that the umpire must decide on an appeal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Appeal {
+    Lbw,
+    CaughtBehind,
+}
+
+/// How a review turned out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReviewOutcome {
+    /// The decision on the field was reversed
+    Overturned,
+    /// The decision on the field was right, and the side loses the review
+    Upheld,
+    /// Too close to call, so the decision on the field stands but the side keeps the
+    /// review
+    UmpiresCall,
+}
+
+/// A review of an umpire's decision by one side
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Review {
+    /// The ID of the side that asked for the review
+    pub team: u16,
+    /// The batter the appeal was against
+    pub batter: PlayerId,
+    pub appeal: Appeal,
+    /// Whether the batter was given out on the field
+    pub given_out: bool,
+    pub outcome: ReviewOutcome,
+}
+
+impl Review {
+    /// Whether the side used up one of its reviews
+    pub fn lost(&self) -> bool {
+        self.outcome == ReviewOutcome::Upheld
+    }
+}
+
+impl GameState {
+    /// Set how reliably the umpires decide appeals in simulated deliveries. Umpire error
+    /// is off unless set here.
+    pub fn set_umpiring(&mut self, umpiring: Umpiring) {
+        self.umpiring = umpiring;
+    }
+
+    /// Set the strategy the given team will use to decide when to review. A side without
+    /// a strategy reviews when fairly sure the decision is wrong.
+    pub fn set_review_strategy(&mut self, team: &Team, strategy: impl ReviewStrategy + 'static) {
+        self.review_strategies.insert(team.id, Arc::new(strategy));
+    }
+
+    /// The reviews the given team has left in the current innings. None if the format
+    /// has no reviews or the match is complete.
+    pub fn reviews_remaining(&self, team: u16) -> Option<u8> {
+        let allowed = self.form.reviews_per_innings?;
+        let st = self.current_innings_stats.as_ref()?;
+        let lost = st
+            .reviews
+            .iter()
+            .filter(|review| review.team == team && review.lost())
+            .count();
+        Some(allowed.saturating_sub(lost as u8))
+    }
+
+    /// The reviews taken in the given innings, indexed from 0 in the order they were
+    /// played. Returns None if that innings has not started.
+    pub fn innings_reviews(&self, innings: usize) -> Option<&[Review]> {
+        self.innings(innings).map(|st| st.reviews.as_slice())
+    }
+
+    /// Record a review of the decision on the next delivery
+    pub fn review(&mut self, review: Review) -> Result<()> {
+        self.current_innings_stats
+            .as_mut()
+            .ok_or(Error::MatchComplete)?
+            .reviews
+            .push(review.clone());
+        self.events.push(MatchEvent::Review(review));
+        Ok(())
+    }
+
+    /// The umpire's decision on a simulated delivery, which may be wrong, after any
+    /// review of it. `ball` is what actually happened. Perfect umpires draw nothing from
+    /// `rng`, so that seeded simulations are unchanged.
+    pub(crate) fn umpire(
+        &mut self,
+        ball: DeliveryOutcome,
+        rng: &mut impl Rng,
+    ) -> Result<DeliveryOutcome> {
+        if self.umpiring.is_perfect() {
+            return Ok(ball);
+        }
+        let (batter, appeal) = match &ball.wicket {
+            Some((batter, Dismissal::Lbw { .. })) => (*batter, Appeal::Lbw),
+            Some((batter, Dismissal::CaughtBehind { .. })) => (*batter, Appeal::CaughtBehind),
+            Some(_) => return Ok(ball),
+            None => return self.false_out(ball, rng),
+        };
+        let given_out = rng.gen::<f32>() >= self.umpiring.missed_out;
+        let review = self.consider_review(appeal, batter, given_out, true, rng)?;
+        // The decision on the field stands unless it is overturned
+        let out = given_out != (review == Some(ReviewOutcome::Overturned));
+        Ok(match out {
+            true => ball,
+            false => DeliveryOutcome {
+                wicket: None,
+                fielders: Vec::new(),
+                ..ball
+            },
+        })
+    }
+
+    /// Possibly give the striker out LBW off a dot ball they weren't out to
+    fn false_out(&mut self, ball: DeliveryOutcome, rng: &mut impl Rng) -> Result<DeliveryOutcome> {
+        let free_hit = self.context().is_some_and(|context| context.free_hit);
+        let dot = ball.legal() && ball.runs.runs() == 0 && ball.extras.is_empty();
+        if free_hit || !dot || rng.gen::<f32>() >= self.umpiring.false_out {
+            return Ok(ball);
+        }
+        let (striker, bowler) = match (self.striker(), self.bowler()) {
+            (Some(striker), Some(bowler)) => (striker, bowler),
+            _ => return Ok(ball),
+        };
+        let bowler = self
+            .bowling_team()
+            .and_then(|team| team.get_name(bowler))
+            .ok_or(Error::PlayerNotFound(bowler))?
+            .to_string();
+        let review = self.consider_review(Appeal::Lbw, striker, true, false, rng)?;
+        if review == Some(ReviewOutcome::Overturned) {
+            return Ok(ball);
+        }
+        Ok(DeliveryOutcome {
+            wicket: Some((striker, Dismissal::Lbw { bowler })),
+            ..ball
+        })
+    }
+
+    /// Ask the side the decision went against whether to review it, and record the
+    /// outcome of any review. `out` is whether the batter was really out.
+    fn consider_review(
+        &mut self,
+        appeal: Appeal,
+        batter: PlayerId,
+        given_out: bool,
+        out: bool,
+        rng: &mut impl Rng,
+    ) -> Result<Option<ReviewOutcome>> {
+        let st = self
+            .current_innings_stats
+            .as_ref()
+            .ok_or(Error::MatchComplete)?;
+        // The batting side may review being given out, the fielding side a not out
+        let team = if given_out {
+            st.batting_team
+        } else {
+            st.bowling_team
+        };
+        let reviews_remaining = match self.reviews_remaining(team) {
+            Some(remaining) if remaining > 0 => remaining,
+            _ => return Ok(None),
+        };
+        let correct = given_out == out;
+        let confidence = match correct {
+            true => 0.7 * rng.gen::<f32>(),
+            false => 0.4 + 0.6 * rng.gen::<f32>(),
+        };
+        let context = ReviewContext {
+            innings: self.previous_innings.len(),
+            appeal,
+            batting: given_out,
+            confidence,
+            reviews_remaining,
+            batter_runs: st
+                .batting_stats
+                .batter_score(batter)
+                .map_or(0, |(runs, _)| runs),
+            wickets_in_hand: self.form.batsmen_per_side - 1 - st.wickets(),
+            captain: self.team(team).captain,
+        };
+        let review = match self.review_strategies.get(&team) {
+            Some(strategy) => strategy.review(&context),
+            None => ConfidentReview::default().review(&context),
+        };
+        if !review {
+            return Ok(None);
+        }
+        let close = appeal == Appeal::Lbw && rng.gen::<f32>() < self.umpiring.umpires_call;
+        let outcome = match (correct, close) {
+            (_, true) => ReviewOutcome::UmpiresCall,
+            (true, false) => ReviewOutcome::Upheld,
+            (false, false) => ReviewOutcome::Overturned,
+        };
+        self.review(Review {
+            team,
+            batter,
+            appeal,
+            given_out,
+            outcome,
+        })?;
+        Ok(Some(outcome))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        form::{self, Form},
+        model::{self, NullModel},
+        player::PlayerDb,
+        seed, strategy,
+        tests::test_team,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn perfect_by_default() -> Result<()> {
+        assert_eq!(Umpiring::default(), Umpiring::perfect());
+        assert!(!Umpiring::typical().is_perfect());
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "WI")?;
+        let team_b = test_team(&mut db, 2, "ZIM")?;
+        let mut state = GameState::new(Form::t20(), team_a, team_b)?;
+        state.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(1))?;
+        for innings in 0..2 {
+            assert_eq!(state.innings_reviews(innings), Some(&[][..]));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn umpire_reviews() -> Result<()> {
+        use model::NullModel;
+        use {ReviewOutcome, Umpiring};
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "WI")?;
+        let team_b = test_team(&mut db, 2, "ZIM")?;
+        let form = form::Form::t20();
+        let allowed = form.reviews_per_innings.unwrap_or(0) as usize;
+        let mut state = GameState::new(form, team_a.clone(), team_b)?;
+        state.set_umpiring(Umpiring {
+            missed_out: 0.5,
+            false_out: 0.05,
+            umpires_call: 0.2,
+        });
+        state.set_review_strategy(&team_a, strategy::NeverReview);
+        state.simulate_to_completion(&NullModel {}, &db, &mut thread_rng())?;
+        for innings in 0..2 {
+            let reviews = state.innings_reviews(innings).unwrap_or_default();
+            assert!(reviews.iter().all(|r| r.team != team_a.id));
+            assert!(reviews.iter().filter(|r| r.lost()).count() <= allowed);
+            for review in reviews {
+                if review.outcome == ReviewOutcome::Upheld {
+                    assert!(review.lost());
+                }
+            }
+        }
+        let replayed = GameState::replay(&state.log(), None)?;
+        assert_eq!(replayed.result(), state.result());
+        assert_eq!(replayed.innings_reviews(1), state.innings_reviews(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+//! A structured, serializable scorecard of a match
+use super::{
+    result::MatchResult,
+    stats::{EntryPoint, ExtrasStats, FallOfWicket, InningsStats},
+    GameState, Review,
+};
+use crate::{
+    error::{Error, Result},
+    model::ModelInfo,
+    player::PlayerId,
+};
+use serde::{Deserialize, Serialize};
+
+/// A single batter's line in the scorecard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BattingLine {
+    pub player: PlayerId,
+    pub name: String,
+    /// Description of the dismissal, or None if not out
+    pub dismissal: Option<String>,
+    pub runs: u16,
+    pub balls: u16,
+    pub fours: u8,
+    pub sixes: u8,
+    /// Whether the batter captained the side
+    #[serde(default)]
+    pub captain: bool,
+    /// Whether the batter kept wicket
+    #[serde(default)]
+    pub keeper: bool,
+    /// The batter's position in the order, from 1 for the first opener
+    #[serde(default)]
+    pub position: u8,
+    /// The score when the batter came in
+    #[serde(default)]
+    pub came_in: Option<EntryPoint>,
+    /// Minutes at the crease
+    #[serde(default)]
+    pub minutes: u32,
+}
+
+/// A single bowler's line in the scorecard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BowlingLine {
+    pub player: PlayerId,
+    pub name: String,
+    /// Overs bowled in the usual style, e.g. "9.3"
+    pub overs: String,
+    pub maidens: u16,
+    pub runs: u16,
+    pub wickets: u8,
+    pub wides: u16,
+    pub no_balls: u16,
+}
+
+/// A single fielder's line in the scorecard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldingLine {
+    pub player: PlayerId,
+    pub name: String,
+    pub catches: u8,
+    pub run_outs: u8,
+    pub stumpings: u8,
+    /// Catches dropped
+    pub drops: u8,
+}
+
+/// The scorecard of a single innings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InningsScorecard {
+    pub batting_team: String,
+    pub bowling_team: String,
+    pub runs: u16,
+    pub wickets: u8,
+    /// Overs faced in the usual style, e.g. "49.2"
+    pub overs: String,
+    /// The overs the innings was limited to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_overs: Option<u16>,
+    pub declared: bool,
+    pub forfeited: bool,
+    pub batting: Vec<BattingLine>,
+    pub extras: ExtrasStats,
+    pub fall_of_wickets: Vec<FallOfWicket>,
+    pub bowling: Vec<BowlingLine>,
+    /// Reviews of umpires' decisions, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reviews: Vec<Review>,
+    /// The fielders who took part in a dismissal or dropped a catch
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fielding: Vec<FieldingLine>,
+}
+
+/// The full scorecard of a match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scorecard {
+    pub team_a: String,
+    pub team_b: String,
+    pub innings: Vec<InningsScorecard>,
+    /// The result, if the match is complete
+    pub result: Option<MatchResult>,
+    /// The result in standard phrasing, if the match is complete
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// The model that simulated the match, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelInfo>,
+}
+
+impl GameState {
+    /// Build a structured scorecard of all innings played so far
+    pub fn scorecard(&self) -> Result<Scorecard> {
+        let innings = self
+            .previous_innings
+            .iter()
+            .chain(self.current_innings_stats.iter())
+            .map(|st| self.innings_scorecard(st))
+            .collect::<Result<_>>()?;
+        Ok(Scorecard {
+            team_a: self.team_a.name.clone(),
+            team_b: self.team_b.name.clone(),
+            innings,
+            result: self.result(),
+            summary: self.result_summary(),
+            model: self.model.clone(),
+        })
+    }
+
+    fn innings_scorecard(&self, st: &InningsStats) -> Result<InningsScorecard> {
+        let bpo = self.form.balls_per_over;
+        let batting_team = self.team(st.batting_team);
+        let batting = st
+            .batting_stats
+            .batters()
+            .iter()
+            .map(|(id, bat)| {
+                Ok(BattingLine {
+                    player: *id,
+                    name: batting_team
+                        .get_name(*id)
+                        .ok_or(Error::PlayerNotFound(*id))?
+                        .to_string(),
+                    dismissal: bat.out.as_ref().map(|d| d.to_string()),
+                    runs: bat.runs,
+                    balls: bat.balls,
+                    fours: bat.fours,
+                    sixes: bat.sixes,
+                    captain: batting_team.captain == Some(*id),
+                    keeper: batting_team.keeper == Some(*id),
+                    position: bat.position,
+                    came_in: bat.came_in,
+                    minutes: bat.minutes(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        let bowling = st
+            .bowling_stats
+            .bowlers()
+            .iter()
+            .map(|(id, bowl)| {
+                Ok(BowlingLine {
+                    player: *id,
+                    name: self
+                        .team(st.bowling_team)
+                        .get_name(*id)
+                        .ok_or(Error::PlayerNotFound(*id))?
+                        .to_string(),
+                    overs: bowl.overs(bpo).to_string(),
+                    maidens: bowl.maiden_overs,
+                    runs: bowl.runs,
+                    wickets: bowl.wickets,
+                    wides: bowl.wides,
+                    no_balls: bowl.no_balls,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let fielding = st
+            .fielding_stats
+            .fielders()
+            .filter(|(_, f)| f.dismissals() + f.drops > 0)
+            .map(|(id, f)| {
+                Ok(FieldingLine {
+                    player: id,
+                    name: self
+                        .team(st.bowling_team)
+                        .get_name(id)
+                        .ok_or(Error::PlayerNotFound(id))?
+                        .to_string(),
+                    catches: f.catches,
+                    run_outs: f.run_outs,
+                    stumpings: f.stumpings,
+                    drops: f.drops,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(InningsScorecard {
+            batting_team: self.team(st.batting_team).name.clone(),
+            bowling_team: self.team(st.bowling_team).name.clone(),
+            runs: st.runs(),
+            wickets: st.wickets(),
+            overs: st.overs.to_string(),
+            max_overs: st.max_overs,
+            declared: st.declared,
+            forfeited: st.forfeited,
+            batting,
+            extras: st.batting_stats.extras().clone(),
+            fall_of_wickets: st.fall_of_wickets.clone(),
+            bowling,
+            reviews: st.reviews.clone(),
+            fielding,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{form::Form, model::NullModel, player::PlayerDb, seed, tests::test_team};
+
+    #[test]
+    fn scorecard_adds_up() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "NED")?;
+        let team_b = test_team(&mut db, 2, "SCO")?;
+        let mut state = GameState::new(Form::t20(), team_a, team_b)?;
+        state.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(8))?;
+        let scorecard = state.scorecard()?;
+        assert_eq!(
+            (scorecard.team_a.as_str(), scorecard.team_b.as_str()),
+            ("team_NED", "team_SCO")
+        );
+        assert_eq!(scorecard.result, state.result());
+        assert!(scorecard.summary.is_some());
+        assert_eq!(scorecard.innings.len(), 2);
+        for innings in &scorecard.innings {
+            let batted: u16 = innings.batting.iter().map(|line| line.runs).sum();
+            assert_eq!(batted + innings.extras.total(), innings.runs);
+            let taken: u8 = innings.bowling.iter().map(|line| line.wickets).sum();
+            let dismissed = innings
+                .batting
+                .iter()
+                .filter(|line| line.dismissal.is_some())
+                .count();
+            assert!(taken as usize <= dismissed);
+            assert_eq!(dismissed, innings.wickets as usize);
+            assert_eq!(innings.fall_of_wickets.len(), innings.wickets as usize);
+            let positions: Vec<u8> = innings.batting.iter().map(|line| line.position).collect();
+            assert_eq!(positions, (1..=positions.len() as u8).collect::<Vec<_>>());
+        }
+        // The scorecard survives a round trip through JSON
+        let json = serde_json::to_string(&scorecard)?;
+        let loaded: Scorecard = serde_json::from_str(&json)?;
+        assert_eq!(serde_json::to_string(&loaded)?, json);
+        Ok(())
+    }
+}
@@ -0,0 +1,365 @@
+//! Summaries of each session and day of play in a match scheduled over days. Overs are
+//! placed by the match clock: each day is scheduled for the time its overs should take, and
+//! is split evenly into three sessions, as between lunch and tea. An over belongs to the
+//! session in which it started, and play on the last day goes on until the match ends.
+use super::GameState;
+use crate::{conditions::Clock, form::Overs};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+
+/// The number of sessions in a day's play
+pub const SESSIONS_PER_DAY: u16 = 3;
+
+/// The time scheduled for each ball when the format sets no over rate, so that a day of
+/// 90 six-ball overs lasts six hours
+pub const SCHEDULED_SECONDS_PER_BALL: u32 = 40;
+
+/// The play in a single session, by both sides if the innings changed during it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    /// The day of the match, starting at 1
+    pub day: u8,
+    /// The session of the day, starting at 1
+    pub session: u8,
+    /// Runs scored, including extras
+    pub runs: u16,
+    /// Wickets fallen
+    pub wickets: u8,
+    /// Overs bowled
+    pub overs: Overs,
+    /// Runs per over
+    pub run_rate: f32,
+}
+
+impl Display for SessionSummary {
+    /// Formats e.g. "Day 1, session 2: 98/3 off 30 overs (RR 3.27)"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {}, session {}: {}/{} off {} overs (RR {:.2})",
+            self.day, self.session, self.runs, self.wickets, self.overs, self.run_rate
+        )
+    }
+}
+
+/// The score of an innings at the close of a day's play
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CloseScore {
+    pub batting_team: String,
+    pub runs: u16,
+    pub wickets: u8,
+    pub overs: Overs,
+    /// Whether the innings had finished by the close
+    pub complete: bool,
+}
+
+impl Display for CloseScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}/{} ({} ov)",
+            self.batting_team, self.runs, self.wickets, self.overs
+        )
+    }
+}
+
+/// The report of a day's play: each session, and where the match stood at the close
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CloseOfPlay {
+    /// The day of the match, starting at 1
+    pub day: u8,
+    pub sessions: Vec<SessionSummary>,
+    /// The score of each innings begun by the close, in order
+    pub scores: Vec<CloseScore>,
+    /// Whether play on the day is over, either at stumps or at the end of the match
+    pub complete: bool,
+}
+
+impl Display for CloseOfPlay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let close = if self.complete { "Close" } else { "Latest" };
+        writeln!(f, "Day {}", self.day)?;
+        for session in &self.sessions {
+            writeln!(f, "  {}", session)?;
+        }
+        let scores: Vec<String> = self.scores.iter().map(|s| s.to_string()).collect();
+        write!(f, "{} of play: {}", close, scores.join(", "))
+    }
+}
+
+/// A single over, or the part of one that was bowled, placed in the match
+struct OverPlayed {
+    innings: usize,
+    /// Seconds of play in the match before the over began
+    start: u32,
+    runs: u16,
+    wickets: u8,
+    /// Legal balls bowled in the over
+    balls: u8,
+    /// The score of the innings at the end of the over
+    total_runs: u16,
+    total_wickets: u8,
+}
+
+impl GameState {
+    /// The play in each session so far, in order. Empty unless the match is scheduled
+    /// over days.
+    pub fn session_summaries(&self) -> Vec<SessionSummary> {
+        if self.session_seconds().is_none() {
+            return Vec::new();
+        }
+        let mut sessions: Vec<(SessionSummary, u16)> = Vec::new();
+        for over in self.overs_played() {
+            let (day, session) = self.session_at(over.start);
+            let index = match sessions.last() {
+                Some((last, _)) if (last.day, last.session) == (day, session) => sessions.len() - 1,
+                _ => {
+                    let summary = SessionSummary {
+                        day,
+                        session,
+                        runs: 0,
+                        wickets: 0,
+                        overs: Overs::default(),
+                        run_rate: 0.,
+                    };
+                    sessions.push((summary, 0));
+                    sessions.len() - 1
+                }
+            };
+            let (summary, balls) = &mut sessions[index];
+            summary.runs += over.runs;
+            summary.wickets += over.wickets;
+            *balls += over.balls as u16;
+        }
+        let balls_per_over = self.form.balls_per_over;
+        sessions
+            .into_iter()
+            .map(|(summary, balls)| SessionSummary {
+                overs: Overs::from_balls(balls, balls_per_over),
+                run_rate: match balls {
+                    0 => 0.,
+                    b => summary.runs as f32 * balls_per_over as f32 / b as f32,
+                },
+                ..summary
+            })
+            .collect()
+    }
+
+    /// A report of each day on which there has been play, in order. Empty unless the
+    /// match is scheduled over days.
+    pub fn close_of_play_reports(&self) -> Vec<CloseOfPlay> {
+        let sessions = self.session_summaries();
+        let days = match sessions.last() {
+            Some(last) => last.day,
+            None => return Vec::new(),
+        };
+        let played = self.overs_played();
+        let innings: Vec<_> = self
+            .previous_innings
+            .iter()
+            .chain(self.current_innings_stats.iter())
+            .collect();
+        let last_day = self.form.days.unwrap_or(days);
+        let day_seconds = self.session_seconds().unwrap_or_default() * SESSIONS_PER_DAY as u32;
+        (1..=days)
+            .map(|day| {
+                let close = if day < last_day {
+                    day as u32 * day_seconds
+                } else {
+                    u32::MAX
+                };
+                let scores = innings
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, st)| {
+                        let mut overs = played.iter().filter(|o| o.innings == i && o.start < close);
+                        let balls = overs.clone().map(|o| o.balls as u16).sum();
+                        let last = overs.next_back()?;
+                        let later = played.iter().any(|o| o.innings == i && o.start >= close);
+                        Some(CloseScore {
+                            batting_team: self.team(st.batting_team).name.clone(),
+                            runs: last.total_runs,
+                            wickets: last.total_wickets,
+                            overs: self.form.overs(balls),
+                            complete: !later && i < self.previous_innings.len(),
+                        })
+                    })
+                    .collect();
+                CloseOfPlay {
+                    day,
+                    sessions: sessions.iter().filter(|s| s.day == day).cloned().collect(),
+                    scores,
+                    complete: self.complete() || self.conditions.clock.elapsed >= close,
+                }
+            })
+            .collect()
+    }
+
+    /// The seconds of play scheduled for each session, if the match is scheduled over days:
+    /// a third of the time allowed for a day's overs
+    fn session_seconds(&self) -> Option<u32> {
+        self.form.days?;
+        let seconds_per_over = match &self.form.over_rate {
+            Some(rules) => rules.seconds_per_over,
+            None => SCHEDULED_SECONDS_PER_BALL * self.form.balls_per_over as u32,
+        };
+        let day = self.form.overs_per_day as u32 * seconds_per_over;
+        Some((day / SESSIONS_PER_DAY as u32).max(1))
+    }
+
+    /// The day and session of play at the given seconds of play into the match, each
+    /// starting at 1. Any time after the last scheduled day is part of its last session.
+    fn session_at(&self, seconds: u32) -> (u8, u8) {
+        let sessions = SESSIONS_PER_DAY as u32;
+        let last = self.form.days.map_or(u32::MAX, |d| d as u32 * sessions - 1);
+        let index = self
+            .session_seconds()
+            .map_or(0, |length| (seconds / length).min(last));
+        ((index / sessions + 1) as u8, (index % sessions + 1) as u8)
+    }
+
+    /// Every over bowled in the match, including any in progress
+    fn overs_played(&self) -> Vec<OverPlayed> {
+        let balls_per_over = self.form.balls_per_over;
+        let mut played = Vec::new();
+        let mut clock = 0;
+        let all_innings = self
+            .previous_innings
+            .iter()
+            .chain(self.current_innings_stats.iter());
+        for (innings, st) in all_innings.enumerate() {
+            // The time each over of the innings began, kept the same way as the match clock
+            let mut starts = Vec::new();
+            for d in &st.deliveries {
+                if starts.len() <= d.overs.completed as usize {
+                    starts.push(clock);
+                }
+                let over_complete = d.outcome.legal() && d.overs.balls + 1 == balls_per_over;
+                clock += Clock::delivery_seconds(&d.outcome, over_complete);
+            }
+            let start = |over: u16| starts.get(over as usize).copied().unwrap_or(clock);
+            for over in st.over_summaries() {
+                played.push(OverPlayed {
+                    innings,
+                    start: start(over.overs.completed),
+                    runs: over.runs,
+                    wickets: over.wickets,
+                    balls: if over.overs.completed < st.overs.completed {
+                        balls_per_over
+                    } else {
+                        st.overs.balls
+                    },
+                    total_runs: over.total_runs,
+                    total_wickets: over.total_wickets,
+                });
+            }
+            // The over in progress has yet to be summarized
+            let last = st.over_summaries().last();
+            let (runs, wickets) = last.map_or((0, 0), |o| (o.total_runs, o.total_wickets));
+            let summarized = last.is_some_and(|o| o.overs.completed == st.overs.completed);
+            if !summarized && (st.overs.balls > 0 || st.runs() > runs || st.wickets() > wickets) {
+                played.push(OverPlayed {
+                    innings,
+                    start: start(st.overs.completed),
+                    runs: st.runs() - runs,
+                    wickets: st.wickets() - wickets,
+                    balls: st.overs.balls,
+                    total_runs: st.runs(),
+                    total_wickets: st.wickets(),
+                });
+            }
+        }
+        played
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Result,
+        form::Form,
+        game::{DeliveryOutcome, Runs},
+        player::PlayerDb,
+        tests::test_team,
+    };
+
+    fn wicket(state: &GameState) -> DeliveryOutcome {
+        DeliveryOutcome::bowled(state.striker().unwrap(), "bowler")
+    }
+
+    #[test]
+    fn sessions_follow_the_clock() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "ENG")?;
+        let team_b = test_team(&mut db, 2, "AUS")?;
+        let mut state = GameState::new(Form::test(), team_a, team_b)?;
+        let four = DeliveryOutcome {
+            runs: Runs::Four,
+            ..Default::default()
+        };
+        // A wicket with the first ball, then a four to start each over after
+        for over in 0..100 {
+            for ball in 0..6 {
+                let outcome = match (over, ball) {
+                    (0, 0) => wicket(&state),
+                    (_, 0) => four.clone(),
+                    _ => DeliveryOutcome::dot(),
+                };
+                state.update(&outcome)?;
+            }
+        }
+        // The wicket takes 2 minutes and each over 3.75, so overs start 120 + 225n seconds
+        // in after the first, and a two-hour session holds 32
+        assert_eq!(state.conditions.clock.elapsed, 120 + 225 * 100);
+        let sessions = state.session_summaries();
+        let summary = |s: &SessionSummary| (s.day, s.session, s.runs, s.wickets, s.overs);
+        assert_eq!(
+            sessions.iter().map(summary).collect::<Vec<_>>(),
+            vec![
+                (1, 1, 31 * 4, 1, Overs::new(32, 0)),
+                (1, 2, 32 * 4, 0, Overs::new(32, 0)),
+                (1, 3, 32 * 4, 0, Overs::new(32, 0)),
+                (2, 1, 4 * 4, 0, Overs::new(4, 0)),
+            ]
+        );
+        let reports = state.close_of_play_reports();
+        assert_eq!(reports.len(), 2);
+        assert!(reports[0].complete && !reports[1].complete);
+        assert_eq!(reports[0].sessions.len(), 3);
+        let close = |report: &CloseOfPlay| {
+            let score = &report.scores[0];
+            (score.runs, score.wickets, score.overs, score.complete)
+        };
+        assert_eq!(close(&reports[0]), (95 * 4, 1, Overs::new(96, 0), false));
+        assert_eq!(close(&reports[1]), (99 * 4, 1, Overs::new(100, 0), false));
+        Ok(())
+    }
+
+    #[test]
+    fn last_day_plays_on() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "ENG")?;
+        let team_b = test_team(&mut db, 2, "AUS")?;
+        let mut state = GameState::new(Form::builder().days(1, 4).build()?, team_a, team_b)?;
+        // Four overs with a wicket in each, 23 minutes in all, in a day of 16
+        for _ in 0..4 {
+            state.update(&wicket(&state))?;
+            for _ in 0..5 {
+                state.update(&DeliveryOutcome::dot())?;
+            }
+        }
+        assert!(state.complete());
+        let sessions = state.session_summaries();
+        let placed: Vec<_> = sessions
+            .iter()
+            .map(|s| (s.day, s.session, s.wickets))
+            .collect();
+        assert_eq!(placed, vec![(1, 1, 1), (1, 2, 1), (1, 3, 2)]);
+        let reports = state.close_of_play_reports();
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].complete);
+        assert_eq!(reports[0].scores[0].overs, Overs::new(4, 0));
+        Ok(())
+    }
+}
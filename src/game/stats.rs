@@ -1,15 +1,428 @@
 //! Player and team stats from a match
 
-use super::{DeliveryOutcome, Dismissal, Extra, Runs};
+use super::{
+    DeliveryOutcome, Dismissal, End, Extra, FieldingError, NonDeliveryEvent, Review, Runs,
+};
 use crate::{
     error::{Error, Result},
+    field::Zone,
+    form::{OverRatePenalty, OverRateRules, Overs},
+    model::pipeline::{Delivery, Length, Line},
     player::PlayerId,
     team::{BattingOrder, Bowlers, Team},
 };
+use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display};
 
+/// The runs and wickets in a single over, along with the score at its end. An over cut
+/// short by the end of the innings is also summarized.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverSummary {
+    /// The overs bowled in the innings before this one began, e.g. 12 for the 13th over
+    #[serde(flatten)]
+    pub overs: Overs,
+    pub bowler: PlayerId,
+    /// Runs scored in the over, including extras
+    pub runs: u16,
+    /// Wickets fallen in the over
+    pub wickets: u8,
+    /// The team's total at the end of the over
+    pub total_runs: u16,
+    /// The number of wickets fallen by the end of the over
+    pub total_wickets: u8,
+}
+
+impl OverSummary {
+    /// The over ball by ball, in the style of live coverage, e.g. ". 1 4 W 2 . (7 runs)".
+    /// `deliveries` is the innings' log, from which the symbols are found as it's shown.
+    pub fn describe<'a>(&'a self, deliveries: &'a [DeliveryRecord]) -> OverLine<'a> {
+        OverLine {
+            summary: self,
+            deliveries,
+        }
+    }
+}
+
+/// An over summary shown with the symbol of each delivery, as given by `ball_symbol`
+#[derive(Debug, Clone, Copy)]
+pub struct OverLine<'a> {
+    summary: &'a OverSummary,
+    deliveries: &'a [DeliveryRecord],
+}
+
+impl Display for OverLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let balls = self
+            .deliveries
+            .iter()
+            .filter(|d| d.overs.completed == self.summary.overs.completed);
+        for ball in balls {
+            write!(f, "{} ", ball_symbol(&ball.outcome))?;
+        }
+        let runs = self.summary.runs;
+        let plural = if runs == 1 { "" } else { "s" };
+        write!(f, "({} run{})", runs, plural)
+    }
+}
+
+/// The symbol for a delivery in an over summary, as shown in live coverage: "." for a dot
+/// ball or the runs off the bat, "wd" or "nb" for a wide or no-ball with any runs beyond
+/// the one for it in front, "b" or "lb" after byes or leg byes, and "W" after any runs
+/// for a wicket. Penalty runs are left out.
+pub fn ball_symbol(ball: &DeliveryOutcome) -> String {
+    let has = |wanted: fn(&Extra) -> bool| ball.extras.iter().any(wanted);
+    let extras: u8 = ball
+        .extras
+        .iter()
+        .filter(|ex| !matches!(ex, Extra::Penalty(_)))
+        .map(Extra::runs)
+        .sum();
+    let runs = ball.runs.runs() + extras;
+    let with_runs = |runs: u8, label: &str| match runs {
+        0 => label.to_string(),
+        n => format!("{}{}", n, label),
+    };
+    let mut symbol = if has(|ex| matches!(ex, Extra::Wide(_))) {
+        with_runs(runs - 1, "wd")
+    } else if has(|ex| matches!(ex, Extra::NoBall)) {
+        with_runs(runs - 1, "nb")
+    } else if has(|ex| matches!(ex, Extra::Bye(_))) {
+        with_runs(runs, "b")
+    } else if has(|ex| matches!(ex, Extra::LegBye(_))) {
+        with_runs(runs, "lb")
+    } else if runs == 0 {
+        ".".to_string()
+    } else {
+        runs.to_string()
+    };
+    if ball.wicket.is_some() {
+        if symbol == "." {
+            symbol.clear();
+        }
+        symbol.push('W');
+    }
+    symbol
+}
+
+/// A notable individual achievement within an innings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Milestone {
+    /// A batter passed a multiple of 50 runs (a fifty, century, 150, double century, ...)
+    BatterScore {
+        batter: PlayerId,
+        runs: u16,
+        balls: u16,
+    },
+    /// A bowler took their fifth wicket of the innings
+    FiveWickets { bowler: PlayerId, runs: u16 },
+}
+
+/// The team score at which a wicket fell
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallOfWicket {
+    /// The number of the wicket, starting at 1
+    pub wicket: u8,
+    pub runs: u16,
+    pub batter: PlayerId,
+    /// The overs bowled when the wicket fell, including the delivery on which it fell
+    #[serde(flatten)]
+    pub overs: Overs,
+}
+
+/// The work of the fielding side's wicket-keeper in an innings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeeperInningsStats {
+    pub keeper: PlayerId,
+    /// Catches taken, including those behind the stumps
+    pub catches: u8,
+    /// Catches taken behind the stumps off an edge
+    #[serde(default)]
+    pub caught_behind: u8,
+    pub stumpings: u8,
+    /// Byes conceded
+    pub byes: u16,
+}
+
+impl KeeperInningsStats {
+    fn new(team: &Team) -> Option<Self> {
+        let keeper = team.keeper?;
+        Some(Self {
+            keeper,
+            catches: 0,
+            caught_behind: 0,
+            stumpings: 0,
+            byes: 0,
+        })
+    }
+
+    fn update(&mut self, ball: &DeliveryOutcome) {
+        if ball.fielders.first() == Some(&self.keeper) {
+            match &ball.wicket {
+                Some((_, Dismissal::Caught { .. })) => self.catches += 1,
+                Some((_, Dismissal::CaughtBehind { .. })) => {
+                    self.catches += 1;
+                    self.caught_behind += 1;
+                }
+                Some((_, Dismissal::Stumped { .. })) => self.stumpings += 1,
+                _ => {}
+            }
+        }
+        for extra in &ball.extras {
+            if let Extra::Bye(runs) = extra {
+                self.byes += runs.runs() as u16;
+            }
+        }
+    }
+
+    /// The total number of dismissals the keeper took part in
+    pub fn dismissals(&self) -> u8 {
+        self.catches + self.stumpings
+    }
+}
+
+/// The dismissals a single fielder took part in during an innings, and the chances they
+/// missed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FielderInningsStats {
+    /// Catches taken, including those behind the stumps and off their own bowling
+    pub catches: u8,
+    /// Run outs the fielder had a hand in
+    pub run_outs: u8,
+    pub stumpings: u8,
+    /// Catches dropped
+    pub drops: u8,
+}
+
+impl FielderInningsStats {
+    /// The total number of dismissals the fielder took part in
+    pub fn dismissals(&self) -> u8 {
+        self.catches + self.run_outs + self.stumpings
+    }
+}
+
+/// The fielding of every member of the fielding side in an innings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TeamFieldingInningsStats {
+    /// Each fielder, in the order of the side. A substitute takes over the row of the
+    /// player they replace.
+    fielders: Vec<(PlayerId, FielderInningsStats)>,
+}
+
+impl TeamFieldingInningsStats {
+    fn new(team: &Team) -> Self {
+        Self {
+            fielders: team
+                .players
+                .iter()
+                .map(|(id, _)| (*id, FielderInningsStats::default()))
+                .collect(),
+        }
+    }
+
+    fn get_mut(&mut self, id: PlayerId) -> Option<&mut FielderInningsStats> {
+        self.fielders
+            .iter_mut()
+            .find(|(f, _)| *f == id)
+            .map(|(_, st)| st)
+    }
+
+    fn update(&mut self, ball: &DeliveryOutcome) {
+        for &fielder in &ball.fielders {
+            let st = match self.get_mut(fielder) {
+                Some(st) => st,
+                None => continue,
+            };
+            match &ball.wicket {
+                Some((_, Dismissal::Caught { .. }))
+                | Some((_, Dismissal::CaughtAndBowled { .. }))
+                | Some((_, Dismissal::CaughtBehind { .. })) => st.catches += 1,
+                Some((_, Dismissal::Stumped { .. })) => st.stumpings += 1,
+                Some((_, Dismissal::RunOutStriker(_)))
+                | Some((_, Dismissal::RunOutNonStriker(_))) => st.run_outs += 1,
+                _ => {}
+            }
+        }
+        if let Some(FieldingError::DroppedCatch { fielder }) = ball.fielding_error {
+            if let Some(st) = self.get_mut(fielder) {
+                st.drops += 1;
+            }
+        }
+    }
+
+    /// Credit a run out to the given fielder
+    fn run_out(&mut self, fielder: PlayerId) {
+        if let Some(st) = self.get_mut(fielder) {
+            st.run_outs += 1;
+        }
+    }
+
+    fn substitute(&mut self, id: PlayerId, replacement: PlayerId) {
+        if let Some(row) = self.fielders.iter_mut().find(|(f, _)| *f == id) {
+            row.0 = replacement;
+        }
+    }
+
+    /// The stats of the given fielder, if they are in the fielding side
+    pub fn get(&self, id: PlayerId) -> Option<&FielderInningsStats> {
+        self.fielders
+            .iter()
+            .find(|(f, _)| *f == id)
+            .map(|(_, st)| st)
+    }
+
+    /// Every member of the fielding side with their stats, in the order of the side
+    pub fn fielders(&self) -> impl Iterator<Item = (PlayerId, &FielderInningsStats)> {
+        self.fielders.iter().map(|(id, st)| (*id, st))
+    }
+
+    /// Write a table of the fielders who took part in a dismissal or dropped a catch, if
+    /// there are any
+    #[cfg(feature = "std-display")]
+    pub fn write_summary<W: std::io::Write + ?Sized>(&self, w: &mut W, team: &Team) -> Result<()> {
+        use prettytable::{format::consts::*, Table};
+        let mut table = Table::new();
+        table.set_format(*FORMAT_NO_LINESEP_WITH_TITLE);
+        table.set_titles(row!["Fielder", "Ct", "RO", "St", "Drop"]);
+        for (id, st) in &self.fielders {
+            if st.dismissals() + st.drops == 0 {
+                continue;
+            }
+            table.add_row(row![
+                team.scorecard_name(*id).ok_or(Error::PlayerNotFound(*id))?,
+                st.catches,
+                st.run_outs,
+                st.stumpings,
+                st.drops,
+            ]);
+        }
+        if !table.is_empty() {
+            table.print(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// The record of one batter against one bowler
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Matchup {
+    /// Balls faced by the batter from the bowler, not counting wides
+    pub balls: u32,
+    /// Runs the batter scored off the bowler
+    pub runs: u32,
+    /// Times the bowler dismissed the batter
+    pub dismissals: u32,
+}
+
+impl Matchup {
+    /// Add a delivery from the bowler to the batter on strike
+    fn record(&mut self, ball: &DeliveryOutcome, batter: PlayerId) {
+        let wide = ball.extras.iter().any(|ex| matches!(ex, Extra::Wide(_)));
+        if !wide && !ball.is_dead_ball() {
+            self.balls += 1;
+        }
+        self.runs += ball.batter_runs() as u32;
+        if let Some((out, dismissal)) = &ball.wicket {
+            if *out == batter && dismissal.credited_to_bowler() {
+                self.dismissals += 1;
+            }
+        }
+    }
+
+    /// Add another record of the same batter against the same bowler
+    pub fn combine(&mut self, other: &Matchup) {
+        self.balls += other.balls;
+        self.runs += other.runs;
+        self.dismissals += other.dismissals;
+    }
+
+    /// Runs per 100 balls, or 0 if the batter has yet to face the bowler
+    pub fn strike_rate(&self) -> f32 {
+        if self.balls == 0 {
+            return 0.;
+        }
+        self.runs as f32 * 100. / self.balls as f32
+    }
+}
+
+/// The scoring shots a batter played into one zone of the ground
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZoneScoring {
+    pub shots: u16,
+    /// Runs off the bat from the shots, not counting overthrows
+    pub runs: u16,
+    pub fours: u16,
+    pub sixes: u16,
+}
+
+/// A batter's scoring shots by the zone they went to, for drawing a wagon wheel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WagonWheel {
+    /// Indexed in the order of `Zone::ALL`
+    zones: [ZoneScoring; 8],
+}
+
+impl WagonWheel {
+    /// Add a scoring shot, if the delivery says where it went
+    fn record(&mut self, ball: &DeliveryOutcome) {
+        let zone = match ball.zone {
+            Some(zone) => &mut self.zones[zone.index()],
+            None => return,
+        };
+        zone.shots += 1;
+        zone.runs += ball.runs.runs() as u16;
+        match ball.runs {
+            Runs::Four => zone.fours += 1,
+            Runs::Six => zone.sixes += 1,
+            Runs::Running(_) => {}
+        }
+    }
+
+    /// The scoring in one zone
+    pub fn zone(&self, zone: Zone) -> &ZoneScoring {
+        &self.zones[zone.index()]
+    }
+
+    /// The scoring in every zone, round the ground from third man to fine leg
+    pub fn zones(&self) -> impl Iterator<Item = (Zone, &ZoneScoring)> {
+        Zone::ALL.iter().copied().zip(&self.zones)
+    }
+
+    /// The runs scored through the off side
+    pub fn off_side_runs(&self) -> u16 {
+        self.zones()
+            .filter(|(zone, _)| zone.off_side())
+            .map(|(_, scoring)| scoring.runs)
+            .sum()
+    }
+}
+
+/// A catch that went down, along with the striker's score at the time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroppedCatch {
+    pub fielder: PlayerId,
+    pub batter: PlayerId,
+    /// The batter's score when they were dropped
+    pub batter_runs: u16,
+    /// The overs bowled before the delivery
+    #[serde(flatten)]
+    pub overs: Overs,
+}
+
+/// A single delivery in the ball-by-ball log of an innings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryRecord {
+    /// The overs bowled before this delivery
+    #[serde(flatten)]
+    pub overs: Overs,
+    pub bowler: PlayerId,
+    pub striker: PlayerId,
+    pub non_striker: PlayerId,
+    pub outcome: DeliveryOutcome,
+}
+
 /// The stats of a batter for a single innings
-struct BatterInningsStats {
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatterInningsStats {
     /// Runs scored by this batter
     pub runs: u16,
     /// Legal deliveries made to this batter
@@ -20,24 +433,66 @@ struct BatterInningsStats {
     pub fours: u8,
     /// Number of sixes scored (the runs are also included in self.runs)
     pub sixes: u8,
+    /// Balls faced without scoring a run off the bat
+    pub dots: u16,
+    /// The batter's position in the order, from 1 for the first opener
+    #[serde(default)]
+    pub position: u8,
+    /// The score when the batter came to the crease
+    #[serde(default)]
+    pub came_in: Option<EntryPoint>,
+    /// Legal deliveries bowled while the batter was at the crease, at either end
+    #[serde(default)]
+    pub balls_at_crease: u16,
+    /// Seconds of play while the batter was at the crease
+    #[serde(default)]
+    pub seconds: u32,
+}
+
+/// The state of an innings when a batter came to the crease
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryPoint {
+    pub runs: u16,
+    pub wickets: u8,
+    pub overs: Overs,
+}
+
+impl Display for EntryPoint {
+    /// Formats in the usual style, e.g. "45/3 (12.4 ov)"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} ({} ov)", self.runs, self.wickets, self.overs)
+    }
 }
 
 impl BatterInningsStats {
-    /// Return the strike rate for the batter
+    /// Return the strike rate for the batter, or 0 if they have yet to face a ball
     pub fn strike_rate(&self) -> f32 {
+        if self.balls == 0 {
+            return 0.;
+        }
         (self.runs as f32) * 100. / (self.balls as f32)
     }
-}
 
-impl Default for BatterInningsStats {
-    fn default() -> Self {
-        Self {
-            runs: 0,
-            balls: 0,
-            out: None,
-            fours: 0,
-            sixes: 0,
+    /// Return the percentage of balls faced that were dots, or 0 if none were faced
+    pub fn dot_percentage(&self) -> f32 {
+        if self.balls == 0 {
+            return 0.;
         }
+        (self.dots as f32) * 100. / (self.balls as f32)
+    }
+
+    /// Return the percentage of runs scored in boundaries, or 0 if none were scored
+    pub fn boundary_percentage(&self) -> f32 {
+        if self.runs == 0 {
+            return 0.;
+        }
+        let boundary_runs = 4 * self.fours as u16 + 6 * self.sixes as u16;
+        (boundary_runs as f32) * 100. / (self.runs as f32)
+    }
+
+    /// Minutes at the crease, to the nearest minute
+    pub fn minutes(&self) -> u32 {
+        (self.seconds + 30) / 60
     }
 }
 
@@ -53,6 +508,70 @@ impl Display for BatterInningsStats {
     }
 }
 
+/// Extra runs conceded in an innings, broken down by type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtrasStats {
+    pub byes: u16,
+    pub leg_byes: u16,
+    pub wides: u16,
+    pub no_balls: u16,
+    pub penalties: u16,
+}
+
+impl ExtrasStats {
+    /// The total number of extra runs
+    pub fn total(&self) -> u16 {
+        self.byes + self.leg_byes + self.wides + self.no_balls + self.penalties
+    }
+
+    /// Add the runs from the extras of a delivery to the appropriate categories.
+    /// Overthrows are counted with the extra they were scored as, if any.
+    pub fn add(&mut self, ball: &DeliveryOutcome) {
+        for extra in &ball.extras {
+            let runs = extra.runs() as u16;
+            let category = match extra {
+                Extra::Overthrows(_) => match ball.overthrows_scored_as() {
+                    Some(scored_as) => scored_as,
+                    // Credited to the striker
+                    None => continue,
+                },
+                extra => extra,
+            };
+            match category {
+                Extra::Bye(_) => self.byes += runs,
+                Extra::LegBye(_) => self.leg_byes += runs,
+                Extra::Wide(_) => self.wides += runs,
+                Extra::NoBall => self.no_balls += runs,
+                Extra::Penalty(_) => self.penalties += runs,
+                Extra::Overthrows(_) => {}
+            }
+        }
+    }
+}
+
+impl Display for ExtrasStats {
+    /// Displayed in the standard scorecard style, e.g. "14 (b 4, lb 6, w 3, nb 1)"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            ("b", self.byes),
+            ("lb", self.leg_byes),
+            ("w", self.wides),
+            ("nb", self.no_balls),
+            ("pen", self.penalties),
+        ]
+        .iter()
+        .filter(|(_, runs)| *runs > 0)
+        .map(|(label, runs)| format!("{} {}", label, runs))
+        .collect();
+        if parts.is_empty() {
+            write!(f, "{}", self.total())
+        } else {
+            write!(f, "{} ({})", self.total(), parts.join(", "))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct TeamBattingInningsStats {
     /// Reference to the team's lineup
     batting_order: BattingOrder,
@@ -60,7 +579,7 @@ pub(crate) struct TeamBattingInningsStats {
     /// Individual batting stats
     batters: Vec<(PlayerId, BatterInningsStats)>,
     /// Extra runs awarded to the team this inning
-    extras: u16,
+    extras: ExtrasStats,
     /// Index of one of the current batters in self.batters
     batter_a: usize,
     /// The other of the current batters
@@ -74,24 +593,29 @@ impl TeamBattingInningsStats {
     /// Create a new team stats object for a fresh innings
     pub fn new(team: &Team) -> Result<Self> {
         let mut batting_order = team.batting_order();
+        let opener = |position| BatterInningsStats {
+            position,
+            came_in: Some(EntryPoint::default()),
+            ..Default::default()
+        };
         let batters = vec![
             (
                 batting_order
                     .next()
                     .ok_or_else(|| Error::MissingData("No first batter".into()))?,
-                BatterInningsStats::default(),
+                opener(1),
             ),
             (
                 batting_order
                     .next()
                     .ok_or_else(|| Error::MissingData("No second batter".into()))?,
-                BatterInningsStats::default(),
+                opener(2),
             ),
         ];
         Ok(Self {
             batting_order,
             batters,
-            extras: 0,
+            extras: ExtrasStats::default(),
             batter_a: 0,
             batter_b: 1,
             striker_a: true,
@@ -107,7 +631,7 @@ impl TeamBattingInningsStats {
     /// Return the total number of team runs
     pub fn team_runs(&self) -> u16 {
         let batter_runs = self.batters.iter().map(|(_, st)| st.runs).sum::<u16>();
-        batter_runs + self.extras
+        batter_runs + self.extras.total()
     }
 
     /// Return the total number of wickets
@@ -118,6 +642,112 @@ impl TeamBattingInningsStats {
             .count() as u8
     }
 
+    /// Set the batters at the crease, bringing in a different batter than the order
+    /// would suggest if necessary. Only a batter who has not yet faced a ball can be
+    /// replaced.
+    pub fn set_batters(&mut self, striker: PlayerId, non_striker: PlayerId) -> Result<()> {
+        if self.all_out() {
+            return Err(Error::CannotBat(striker));
+        }
+        for &wanted in &[striker, non_striker] {
+            let at_crease = [self.batter_a, self.batter_b]
+                .iter()
+                .any(|&i| self.batters[i].0 == wanted);
+            if at_crease {
+                continue;
+            }
+            let slot = [self.batter_a, self.batter_b]
+                .iter()
+                .copied()
+                .find(|&i| {
+                    let (id, st) = &self.batters[i];
+                    *id != striker && *id != non_striker && st.balls == 0 && st.out.is_none()
+                })
+                .ok_or(Error::CannotBat(wanted))?;
+            if !self.batting_order.remove(wanted) {
+                return Err(Error::CannotBat(wanted));
+            }
+            let displaced = std::mem::replace(&mut self.batters[slot].0, wanted);
+            self.batting_order.push_front(displaced);
+        }
+        self.striker_a = self.batters[self.batter_a].0 == striker;
+        Ok(())
+    }
+
+    /// Send in a batter who has still to bat next. If the batter who came in last has yet
+    /// to face a ball or score, e.g. just after a wicket, the promoted batter takes their
+    /// place at the crease and they come in next instead.
+    pub fn promote(&mut self, id: PlayerId) -> Result<()> {
+        if self.all_out() || !self.batting_order.promote(id) {
+            return Err(Error::CannotBat(id));
+        }
+        if let Some(displaced) = self.incoming() {
+            let newest = self.batter_a.max(self.batter_b);
+            self.batting_order.remove(id);
+            self.batters[newest].0 = id;
+            self.batting_order.push_front(displaced);
+        }
+        Ok(())
+    }
+
+    /// The batters still to come in, next first
+    pub fn remaining(&self) -> Vec<PlayerId> {
+        self.batting_order.remaining().collect()
+    }
+
+    /// The batter who came in last, if they have yet to face a ball or score
+    pub fn incoming(&self) -> Option<PlayerId> {
+        let (id, st) = self.batters.get(self.batter_a.max(self.batter_b))?;
+        (st.balls == 0 && st.runs == 0 && st.out.is_none()).then_some(*id)
+    }
+
+    /// Replace a batter with a substitute. A batter at the crease retires not out and the
+    /// substitute comes in in their place; otherwise the substitute takes their place in
+    /// the order.
+    pub fn substitute(&mut self, id: PlayerId, replacement: PlayerId) {
+        let at_crease = |i: usize| self.batters.get(i).is_some_and(|(b, _)| *b == id);
+        if at_crease(self.batter_a) {
+            self.bring_in(replacement);
+            self.batter_a = self.batters.len() - 1;
+        } else if at_crease(self.batter_b) {
+            self.bring_in(replacement);
+            self.batter_b = self.batters.len() - 1;
+        } else {
+            self.batting_order.replace(id, replacement);
+        }
+    }
+
+    /// Add a batter to the end of the list of those who have come to the crease
+    fn bring_in(&mut self, batter: PlayerId) {
+        let stats = BatterInningsStats {
+            position: self.batters.len() as u8 + 1,
+            ..Default::default()
+        };
+        self.batters.push((batter, stats));
+    }
+
+    /// Record the score at which any batter who has just come in arrived
+    fn mark_entries(&mut self, entry: EntryPoint) {
+        for (_, st) in self
+            .batters
+            .iter_mut()
+            .filter(|(_, st)| st.came_in.is_none())
+        {
+            st.came_in = Some(entry);
+        }
+    }
+
+    /// Add time spent at the crease to the given batters
+    fn add_time(&mut self, batters: &[PlayerId], seconds: u32) {
+        for (_, st) in self
+            .batters
+            .iter_mut()
+            .filter(|(id, _)| batters.contains(id))
+        {
+            st.seconds += seconds;
+        }
+    }
+
     /// Switch which batter is the striker. This must be done on a new over, and is done
     /// automatically when an odd number of runs are scored.
     pub fn switch_striker(&mut self) {
@@ -161,14 +791,24 @@ impl TeamBattingInningsStats {
             self.batter_b
         };
 
+        if ball.legal() {
+            for i in [self.batter_a, self.batter_b] {
+                self.batters[i].1.balls_at_crease += 1;
+            }
+        }
         let striker_stats: &mut BatterInningsStats = &mut self.batters[striker_idx].1;
         // No Balls are actually counted against the balls faced by a batter, since more often than
         // not it is possible for the batter to score additional runs form a no-ball.
-        if !ball.extras.iter().any(|ex| matches!(ex, Extra::Wide)) {
+        let wide = ball.extras.iter().any(|ex| matches!(ex, Extra::Wide(_)));
+        if !wide && !ball.is_dead_ball() {
             striker_stats.balls += 1;
+            if ball.batter_runs() == 0 {
+                striker_stats.dots += 1;
+            }
         }
 
-        let mut switch_striker: bool = false;
+        // Runs called short still take the batters to the other end
+        let mut switch_striker: bool = ball.short_runs() % 2 == 1;
 
         // Add runs and extras to the totals
         match ball.runs {
@@ -187,75 +827,124 @@ impl TeamBattingInningsStats {
                 striker_stats.sixes += 1;
             }
         }
+        // Overthrows of runs off the bat are the striker's
+        striker_stats.runs += (ball.batter_runs() - ball.runs.runs()) as u16;
         // Now done modifying striker_stats, but droping a reference does nothing.
-        self.extras += ball.extras.iter().map(|x| x.runs() as u16).sum::<u16>();
+        self.extras.add(ball);
 
-        // Switch if bye/leg byes result in an odd number of runs
-        for extra in ball
-            .extras
-            .iter()
-            .filter(|ex| matches!(ex, Extra::Bye(_) | Extra::LegBye(_)))
-        {
-            match extra {
-                Extra::Bye(Runs::Running(b)) | Extra::LegBye(Runs::Running(b)) => {
-                    if b % 2 == 1 {
-                        switch_striker = !switch_striker;
-                    }
+        // Switch if an odd number of byes, leg byes, wides or overthrows are run.
+        // Boundaries don't change the ends.
+        for extra in &ball.extras {
+            if let Extra::Bye(Runs::Running(n))
+            | Extra::LegBye(Runs::Running(n))
+            | Extra::Wide(Runs::Running(n))
+            | Extra::Overthrows(Runs::Running(n)) = extra
+            {
+                if n % 2 == 1 {
+                    switch_striker = !switch_striker;
                 }
-                _ => unreachable!(),
             }
         }
 
         // Check for wickets in the outcome
         if let Some((out_id, wicket)) = &ball.wicket {
-            let out_stats = self
-                .batters
-                .iter_mut()
-                .find(|(id, _)| id == out_id)
-                .ok_or_else(|| Error::PlayerNotFound(*out_id))?;
-            out_stats.1.out = Some(wicket.clone());
+            let striker_out = *out_id == self.striker();
+            self.dismiss(*out_id, wicket)?;
+            if matches!(
+                wicket,
+                Dismissal::RunOutStriker(_) | Dismissal::RunOutNonStriker(_)
+            ) {
+                // The runs completed leave the striker at the bowler's end if odd. If the
+                // end isn't known, the batters crossed on the run that failed, so the one
+                // out was running to the other end.
+                let end = ball
+                    .run_out_end
+                    .unwrap_or(if striker_out == switch_striker {
+                        End::Striker
+                    } else {
+                        End::Bowler
+                    });
+                // The incoming batter takes the dismissed one's place, and goes to the end
+                // the wicket was put down at, with the other batter at the far end
+                switch_striker = striker_out != (end == End::Striker);
+            }
+        }
 
-            //if matches!(wicket, Dismissal::RunOutNonStriker(_)) {
-            //self.batters[non_striker_idx].1.out = Some(wicket.clone());
-            //} else {
-            //striker_stats.out = Some(wicket.clone());
-            //}
+        if switch_striker {
+            self.switch_striker();
         }
+        Ok(())
+    }
+
+    /// The stats of each batter who has come to the crease, in batting order
+    pub fn batters(&self) -> &[(PlayerId, BatterInningsStats)] {
+        &self.batters
+    }
+
+    /// The breakdown of extras conceded
+    pub fn extras(&self) -> &ExtrasStats {
+        &self.extras
+    }
+
+    /// Returns the runs and balls faced of a batter, if they have batted
+    pub fn batter_score(&self, id: PlayerId) -> Option<(u16, u16)> {
+        self.batters
+            .iter()
+            .find(|(b, _)| *b == id)
+            .map(|(_, st)| (st.runs, st.balls))
+    }
+
+    /// Award penalty runs to the batting side
+    pub fn add_penalty_runs(&mut self, runs: u16) {
+        self.extras.penalties += runs;
+    }
+
+    /// Record the dismissal of the non-striker outside of a delivery
+    pub fn dismiss_non_striker(&mut self, dismissal: &Dismissal) -> Result<()> {
+        self.dismiss(self.non_striker(), dismissal)
+    }
+
+    /// Record the dismissal of one of the batters and bring in the next in the order
+    fn dismiss(&mut self, out_id: PlayerId, dismissal: &Dismissal) -> Result<()> {
+        let out_stats = self
+            .batters
+            .iter_mut()
+            .find(|(id, _)| *id == out_id)
+            .ok_or(Error::PlayerNotFound(out_id))?;
+        out_stats.1.out = Some(dismissal.clone());
 
         // Replace batters if they've been made out
         if self.batters[self.batter_a].1.out.is_some() {
             // This may not be a valid index if the lineup is over
             self.batter_a = self.batters.len();
             if let Some(batter) = self.batting_order.next() {
-                self.batters.push((batter, BatterInningsStats::default()));
+                self.bring_in(batter);
             }
         }
         if self.batters[self.batter_b].1.out.is_some() {
             // This may not be a valid index if the lineup is over
             self.batter_b = self.batters.len();
             if let Some(batter) = self.batting_order.next() {
-                self.batters.push((batter, BatterInningsStats::default()));
+                self.bring_in(batter);
             }
         }
-
-        if switch_striker {
-            self.switch_striker();
-        }
         Ok(())
     }
 
-    /// Print a summary table of the batting stats
-    // TODO: Consider returning the table to allow printing to e.g. a file
-    pub fn print_summary(&self, team: &Team) -> Result<()> {
+    /// Write a summary table of the batting stats
+    #[cfg(feature = "std-display")]
+    pub fn write_summary<W: std::io::Write + ?Sized>(&self, w: &mut W, team: &Team) -> Result<()> {
         use prettytable::{format::consts::*, Table};
         let mut table = Table::new();
         table.set_format(*FORMAT_NO_LINESEP_WITH_TITLE);
         // table.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
-        table.set_titles(row!["Batter", "Wicket", "R (B)", "4s", "6s", "SR"]);
+        table.set_titles(row![
+            "Batter", "Wicket", "R (B)", "4s", "6s", "SR", "Dot%", "Bdry%"
+        ]);
         for batter in &self.batters {
             let batter_stats = &batter.1;
             table.add_row(row![
-                team.get_name(batter.0)
+                team.scorecard_name(batter.0)
                     .ok_or(Error::PlayerNotFound(batter.0))?,
                 match &batter_stats.out {
                     Some(wicket) => format!("{}", wicket),
@@ -265,14 +954,18 @@ impl TeamBattingInningsStats {
                 batter_stats.fours,
                 batter_stats.sixes,
                 format!("{:.2}", batter_stats.strike_rate()),
+                format!("{:.1}", batter_stats.dot_percentage()),
+                format!("{:.1}", batter_stats.boundary_percentage()),
             ]);
         }
-        table.printstd();
+        table.print(w)?;
+        writeln!(w, "Extras {}", self.extras)?;
         Ok(())
     }
 }
 
 /// The bowling stats of a single bowler in a single innings
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BowlerInningsStats {
     /// Number of balls bowled
     pub balls: u16,
@@ -282,19 +975,51 @@ pub struct BowlerInningsStats {
     pub runs: u16,
     /// Wickets taken
     pub wickets: u8,
-    // TODO: consider tracking dots, 4s, and 6s
+    /// Legal balls from which no runs were conceded
+    pub dots: u16,
+    /// Singles conceded off the bat
+    pub singles: u16,
+    /// Fours conceded off the bat
+    pub fours: u16,
+    /// Sixes conceded off the bat
+    pub sixes: u16,
     /// Wides
     pub wides: u16,
     /// No-balls
     pub no_balls: u16,
+    /// Overs bowled in the current spell, from one end without a break. Zero once the
+    /// spell has ended.
+    pub spell_overs: u16,
+    /// Recent overs bowled, each counting for less the longer ago it was
+    pub workload: f32,
+    /// Where the bowler's deliveries pitched, for those the model described
+    #[serde(default, skip_serializing_if = "PitchMap::is_empty")]
+    pub pitch_map: PitchMap,
 }
 
 impl BowlerInningsStats {
-    /// Return the economy rate
+    /// The overs bowled
+    pub fn overs(&self, balls_per_over: u8) -> Overs {
+        Overs::from_balls(self.balls, balls_per_over)
+    }
+
+    /// Return the economy rate, or 0 if the bowler has yet to bowl a legal ball
     pub fn economy(&self, balls_per_over: u8) -> f32 {
+        if self.balls == 0 {
+            return 0.;
+        }
         (self.runs as f32) * (balls_per_over as f32) / (self.balls as f32)
     }
 
+    /// How tired the bowler is, from 0 (fresh) to 1 (exhausted). Long spells, a heavy
+    /// recent workload, and the overs bowled over the whole innings all contribute.
+    pub fn fatigue(&self, balls_per_over: u8) -> f32 {
+        let spell = self.spell_overs.saturating_sub(4) as f32;
+        let recent = (self.workload - 2.).max(0.);
+        let overs = self.balls as f32 / balls_per_over as f32;
+        (0.1 * spell + 0.1 * recent + 0.005 * overs).min(1.)
+    }
+
     // NOTE: bowler average and strike rate are not reasonable stats to evaluate at the
     // level of a single innings
 }
@@ -307,8 +1032,15 @@ impl Default for BowlerInningsStats {
             maiden_overs: 0,
             runs: 0,
             wickets: 0,
+            dots: 0,
+            singles: 0,
+            fours: 0,
+            sixes: 0,
             wides: 0,
             no_balls: 0,
+            spell_overs: 0,
+            workload: 0.,
+            pitch_map: PitchMap::default(),
         }
     }
 }
@@ -321,6 +1053,83 @@ impl Display for BowlerInningsStats {
     }
 }
 
+/// The deliveries a bowler sent down on one line and length, and what came of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PitchMapCell {
+    /// Deliveries bowled, including wides and no-balls
+    pub balls: u16,
+    /// Runs charged to the bowler
+    pub runs: u16,
+    /// Legal balls from which no runs were charged to the bowler
+    pub dots: u16,
+    /// Fours and sixes off the bat
+    pub boundaries: u16,
+    /// Wickets credited to the bowler
+    pub wickets: u8,
+}
+
+/// A bowler's deliveries by line and length, for plotting where they pitched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PitchMap {
+    /// Indexed by line and then length, in the order of `Line::ALL` and `Length::ALL`
+    cells: [[PitchMapCell; 6]; 4],
+}
+
+impl PitchMap {
+    fn record(&mut self, delivery: &Delivery, ball: &DeliveryOutcome) {
+        let cell = &mut self.cells[delivery.line as usize][delivery.length as usize];
+        let runs = ball.bowler_runs();
+        cell.balls += 1;
+        cell.runs += runs as u16;
+        if ball.legal() && runs == 0 {
+            cell.dots += 1;
+        }
+        if matches!(ball.runs, Runs::Four | Runs::Six) {
+            cell.boundaries += 1;
+        }
+        if let Some((_, dismissal)) = &ball.wicket {
+            if dismissal.credited_to_bowler() {
+                cell.wickets += 1;
+            }
+        }
+    }
+
+    /// The deliveries on the given line and length
+    pub fn cell(&self, line: Line, length: Length) -> &PitchMapCell {
+        &self.cells[line as usize][length as usize]
+    }
+
+    /// Every line and length with the deliveries bowled there
+    pub fn cells(&self) -> impl Iterator<Item = (Line, Length, &PitchMapCell)> {
+        Line::ALL.iter().zip(&self.cells).flat_map(|(line, row)| {
+            Length::ALL
+                .iter()
+                .zip(row)
+                .map(move |(length, cell)| (*line, *length, cell))
+        })
+    }
+
+    /// The total of the cells at the given length, across all lines
+    pub fn length(&self, length: Length) -> PitchMapCell {
+        self.cells.iter().map(|row| row[length as usize]).fold(
+            PitchMapCell::default(),
+            |total, cell| PitchMapCell {
+                balls: total.balls + cell.balls,
+                runs: total.runs + cell.runs,
+                dots: total.dots + cell.dots,
+                boundaries: total.boundaries + cell.boundaries,
+                wickets: total.wickets + cell.wickets,
+            },
+        )
+    }
+
+    /// Whether no described deliveries have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.cells().all(|(_, _, cell)| cell.balls == 0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct TeamBowlingInningsStats {
     /// Reference to team's bowling
     bowlers: Bowlers,
@@ -328,8 +1137,21 @@ pub(crate) struct TeamBowlingInningsStats {
     bowler_stats: Vec<(PlayerId, BowlerInningsStats)>,
     /// Index of bowler that is currently bowling
     current_bowler_index: usize,
-    /// Whether the current over is a maiden (so far)
+    /// Index of the bowler who bowled the previous over, from the other end
+    previous_bowler_index: Option<usize>,
+    /// Whether the current over is a maiden (so far). Only runs charged to the bowler
+    /// break a maiden, and an over shared by two bowlers is not one.
     current_over_maiden: bool,
+    /// Legal balls bowled in the current over. An over cut short is not a maiden.
+    #[serde(default)]
+    over_balls: u8,
+    /// The most overs a bowler may bowl in the innings, if limited
+    #[serde(default)]
+    max_overs: Option<u16>,
+    /// The index of the bowler from the other end and their spell as it stood before the
+    /// current over, so that the spell can continue if they are brought on for it
+    #[serde(default)]
+    other_end: Option<(usize, u16)>,
 }
 
 impl TeamBowlingInningsStats {
@@ -346,7 +1168,11 @@ impl TeamBowlingInningsStats {
             bowlers,
             bowler_stats,
             current_bowler_index: 0,
+            previous_bowler_index: None,
             current_over_maiden: true,
+            over_balls: 0,
+            max_overs: None,
+            other_end: None,
         })
     }
 
@@ -356,24 +1182,27 @@ impl TeamBowlingInningsStats {
 
         if ball.legal() {
             bowler_stats.balls += 1;
+            self.over_balls += 1;
         }
-        let bowler_runs: u8 = ball.runs.runs()
-            + ball
-                .extras
-                .iter()
-                .filter_map(|x| match x {
-                    Extra::NoBall | Extra::Wide => Some(x.runs()),
-                    _ => None,
-                })
-                .sum::<u8>();
+        // Byes, leg byes and penalty runs are not charged to the bowler
+        let bowler_runs = ball.bowler_runs();
         if bowler_runs > 0 {
             self.current_over_maiden = false;
         }
         bowler_stats.runs += bowler_runs as u16;
+        if ball.legal() && bowler_runs == 0 {
+            bowler_stats.dots += 1;
+        }
+        match ball.runs {
+            Runs::Running(1) => bowler_stats.singles += 1,
+            Runs::Four => bowler_stats.fours += 1,
+            Runs::Six => bowler_stats.sixes += 1,
+            _ => (),
+        }
         let wides = ball
             .extras
             .iter()
-            .filter(|x| matches!(x, Extra::Wide))
+            .filter(|x| matches!(x, Extra::Wide(_)))
             .count() as u16;
         bowler_stats.wides += wides;
         let no_balls = ball
@@ -382,37 +1211,150 @@ impl TeamBowlingInningsStats {
             .filter(|x| matches!(x, Extra::NoBall))
             .count() as u16;
         bowler_stats.no_balls += no_balls;
-        if ball.wicket.is_some() {
-            bowler_stats.wickets += 1;
+        if let Some((_, dismissal)) = &ball.wicket {
+            if dismissal.credited_to_bowler() {
+                bowler_stats.wickets += 1;
+            }
+        }
+        if let Some(delivery) = &ball.delivery {
+            bowler_stats.pitch_map.record(delivery, ball);
         }
     }
 
     /// Indicate that there is a new over and switch bowlers.
     /// A bowler must finish an over unless incapacitated or suspended (we will ignore
     /// these cases for now).
-    pub fn new_over(&mut self) -> Result<()> {
-        if self.current_over_maiden {
+    pub fn new_over(&mut self, balls_per_over: u8) -> Result<()> {
+        if self.current_over_maiden && self.over_balls >= balls_per_over {
             self.bowler_stats[self.current_bowler_index].1.maiden_overs += 1;
         }
         self.current_over_maiden = true;
+        self.over_balls = 0;
+        // Earlier overs weigh less on the bowlers as the innings goes on
+        for (_, stats) in &mut self.bowler_stats {
+            stats.workload *= WORKLOAD_DECAY;
+        }
+        let finished = &mut self.bowler_stats[self.current_bowler_index].1;
+        finished.workload += 1.;
+        finished.spell_overs += 1;
 
+        let bowler_stats = &self.bowler_stats;
+        let max_overs = self.max_overs;
         let next_bowler: PlayerId = self
             .bowlers
-            .next()
-            .ok_or_else(|| Error::MissingData("Could not get next bowler".into()))?;
-        self.current_bowler_index = match self
+            .next_rested(
+                |id| {
+                    bowler_stats
+                        .iter()
+                        .find(|(b, _)| *b == id)
+                        .map_or(0., |(_, st)| st.fatigue(balls_per_over))
+                },
+                |id| {
+                    max_overs.is_none_or(|max| {
+                        bowler_stats
+                            .iter()
+                            .find(|(b, _)| *b == id)
+                            .is_none_or(|(_, st)| st.balls < max * balls_per_over as u16)
+                    })
+                },
+            )
+            .ok_or_else(|| {
+                Error::MissingData("Nobody has overs left to bowl the next over".into())
+            })?;
+        self.other_end = self
+            .previous_bowler_index
+            .map(|i| (i, self.bowler_stats[i].1.spell_overs));
+        self.previous_bowler_index = Some(self.current_bowler_index);
+        self.bring_on(next_bowler);
+        Ok(())
+    }
+
+    /// Bring on a bowler for the over about to start. The bowler from the other end ends
+    /// their spell if they aren't continuing it.
+    pub fn bring_on(&mut self, bowler: PlayerId) {
+        if let Some((other_end, spell)) = self.other_end {
+            let (id, stats) = &mut self.bowler_stats[other_end];
+            stats.spell_overs = if *id == bowler { spell } else { 0 };
+        }
+        self.bowlers.brought_on(bowler);
+        self.set_current_bowler(bowler);
+    }
+
+    /// Limit the overs each bowler may bowl in the innings
+    pub fn set_max_overs(&mut self, max_overs: Option<u16>) {
+        self.max_overs = max_overs;
+    }
+
+    /// The overs a bowler has left in the innings, if limited
+    pub fn overs_left(&self, id: PlayerId, balls_per_over: u8) -> Option<u16> {
+        let balls = self
             .bowler_stats
             .iter()
-            .position(|(b, _)| b == &next_bowler)
-        {
+            .find(|(b, _)| *b == id)
+            .map_or(0, |(_, st)| st.balls);
+        self.max_overs
+            .map(|max| max.saturating_sub(balls.div_ceil(balls_per_over as u16)))
+    }
+
+    /// The bowlers the side intends to use, in the order of its rotation
+    pub fn rotation(&self) -> &[PlayerId] {
+        &self.bowlers.bowlers
+    }
+
+    /// The bowler of the previous over, who can't bowl the next, if any
+    pub fn previous_bowler(&self) -> Option<PlayerId> {
+        self.previous_bowler_index.map(|i| self.bowler_stats[i].0)
+    }
+
+    /// Replace the current bowler, adding them to the bowling figures if necessary
+    pub fn set_current_bowler(&mut self, bowler: PlayerId) {
+        // Neither bowler of an over split between two is credited with a maiden
+        if self.over_balls > 0 && bowler != self.current_bowler() {
+            self.current_over_maiden = false;
+        }
+        // A bowler replaced before bowling a ball, e.g. when following a recorded match,
+        // didn't bowl in the innings
+        if bowler != self.current_bowler() {
+            self.drop_unused_bowler();
+        }
+        self.current_bowler_index = match self.bowler_stats.iter().position(|(b, _)| *b == bowler) {
             Some(i) => i,
             None => {
                 self.bowler_stats
-                    .push((next_bowler, BowlerInningsStats::default()));
+                    .push((bowler, BowlerInningsStats::default()));
                 self.bowler_stats.len() - 1
             }
         };
-        Ok(())
+    }
+
+    /// Remove the current bowler if they were only just brought on and have yet to bowl.
+    /// Returns whether they were removed, in which case there is no current bowler.
+    fn drop_unused_bowler(&mut self) -> bool {
+        let (_, current) = &self.bowler_stats[self.current_bowler_index];
+        let unused = current.balls == 0 && current.wides == 0 && current.no_balls == 0;
+        let last = self.current_bowler_index + 1 == self.bowler_stats.len();
+        if unused && last {
+            self.bowler_stats.pop();
+        }
+        unused && last
+    }
+
+    /// Finalize the stats at the close of the innings, leaving out a bowler brought on for
+    /// an over that was never bowled
+    pub fn close(&mut self) {
+        if let Some(previous) = self.previous_bowler_index {
+            if self.drop_unused_bowler() {
+                self.current_bowler_index = previous;
+            }
+        }
+    }
+
+    /// Replace a bowler with a substitute, who finishes the over if the bowler was bowling
+    pub fn substitute(&mut self, id: PlayerId, replacement: PlayerId) {
+        self.bowlers.replace(id, replacement);
+        if self.current_bowler() == id {
+            self.set_current_bowler(replacement);
+        }
     }
 
     /// Returns a reference to the current bowler
@@ -420,68 +1362,142 @@ impl TeamBowlingInningsStats {
         self.bowler_stats[self.current_bowler_index].0
     }
 
-    /// Print a summary table of the bowling stats
-    // TODO: Consider returning the table to allow printing to e.g. a file
-    pub fn print_summary(&self, team: &Team, balls_per_over: u8) -> Result<()> {
+    /// The stats of each bowler who has bowled, in the order they came on
+    pub fn bowlers(&self) -> &[(PlayerId, BowlerInningsStats)] {
+        &self.bowler_stats
+    }
+
+    /// Returns the stats of the current bowler
+    pub fn current_bowler_stats(&self) -> &BowlerInningsStats {
+        &self.bowler_stats[self.current_bowler_index].1
+    }
+
+    /// Write a summary table of the bowling stats
+    #[cfg(feature = "std-display")]
+    pub fn write_summary<W: std::io::Write + ?Sized>(
+        &self,
+        w: &mut W,
+        team: &Team,
+        balls_per_over: u8,
+    ) -> Result<()> {
         use prettytable::{format::consts::*, Table};
         let mut table = Table::new();
         table.set_format(*FORMAT_NO_LINESEP_WITH_TITLE);
         // table.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
-        table.set_titles(row!["Bowler", "O", "M", "R", "W", "Econ"]);
+        table.set_titles(row![
+            "Bowler", "O", "M", "R", "W", "Econ", "0s", "1s", "4s", "6s"
+        ]);
         for bowler in &self.bowler_stats {
             let (bowler_id, bowler_stats) = bowler;
-            let overs_str = {
-                let n_overs = bowler_stats.balls / balls_per_over as u16;
-                let n_excess_balls = bowler_stats.balls % balls_per_over as u16;
-                if n_excess_balls == 0 {
-                    format!("{}", n_overs)
-                } else {
-                    format!("{}.{}", n_overs, n_excess_balls)
-                }
-            };
             table.add_row(row![
                 team.get_name(*bowler_id)
-                    .ok_or_else(|| Error::PlayerNotFound(*bowler_id))?,
-                overs_str,
+                    .ok_or(Error::PlayerNotFound(*bowler_id))?,
+                bowler_stats.overs(balls_per_over),
                 bowler_stats.maiden_overs,
                 bowler_stats.runs,
                 bowler_stats.wickets,
                 format!("{:.2}", bowler_stats.economy(balls_per_over)),
+                bowler_stats.dots,
+                bowler_stats.singles,
+                bowler_stats.fours,
+                bowler_stats.sixes,
             ]);
         }
-        table.printstd();
+        table.print(w)?;
         Ok(())
     }
 }
 
+/// The fraction of a bowler's recent workload that carries over to the next over
+const WORKLOAD_DECAY: f32 = 0.8;
+
 /// Collects and tracks stats in a given innings
-pub(crate) struct InningsStats<'a> {
-    pub batting_team: &'a Team,
-    pub bowling_team: &'a Team,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct InningsStats {
+    /// The ID of the batting team
+    pub batting_team: u16,
+    /// The ID of the bowling team
+    pub bowling_team: u16,
     pub batting_stats: TeamBattingInningsStats,
     pub bowling_stats: TeamBowlingInningsStats,
-    /// The number of overs that have been completed
-    pub overs: u16,
-    /// The number of legal balls delivered in the over
-    pub balls: u8,
+    /// The overs bowled in the innings
+    #[serde(flatten)]
+    pub overs: Overs,
+    /// The overs the innings is limited to, if any. This is less than the format's if
+    /// the innings was reduced.
+    #[serde(default)]
+    pub max_overs: Option<u16>,
+    /// Whether the batting side forfeited this innings without facing a ball
+    pub forfeited: bool,
+    /// Whether the batting side declared this innings closed
+    pub declared: bool,
+    /// Every delivery bowled in the innings, in order
+    pub deliveries: Vec<DeliveryRecord>,
+    /// A summary of each over bowled
+    over_summaries: Vec<OverSummary>,
+    /// Milestones reached by individuals in the innings, in order
+    pub milestones: Vec<Milestone>,
+    /// The score at each fall of wicket
+    pub fall_of_wickets: Vec<FallOfWicket>,
+    /// Every catch dropped in the innings, in order
+    pub dropped_catches: Vec<DroppedCatch>,
+    /// The fielding side's wicket-keeping, if they designated a keeper
+    pub keeper_stats: Option<KeeperInningsStats>,
+    /// The catches, run outs, stumpings and drops of each fielder
+    #[serde(default)]
+    pub fielding_stats: TeamFieldingInningsStats,
+    /// Every review of an umpire's decision in the innings, in order
+    #[serde(default)]
+    pub reviews: Vec<Review>,
+    /// The record of each batter against each bowler who bowled to them, by batter and
+    /// then bowler
+    #[serde(default)]
+    pub matchups: Vec<(PlayerId, PlayerId, Matchup)>,
+    /// Where each batter's scoring shots went, for those the model placed
+    #[serde(default)]
+    pub wagon_wheels: Vec<(PlayerId, WagonWheel)>,
+    /// Seconds of play elapsed in this innings
+    pub elapsed: u32,
+    /// Whether the fielding side must keep an extra fielder inside the ring for falling
+    /// behind the over rate
+    pub over_rate_penalty: bool,
     /// The number of balls per over
     // TODO: Consider reference to Form?
     balls_per_over: u8,
 }
 
-impl<'a> InningsStats<'a> {
-    pub fn new(batting_team: &'a Team, bowling_team: &'a Team, balls_per_over: u8) -> Result<Self> {
+impl InningsStats {
+    pub fn new(batting_team: &Team, bowling_team: &Team, balls_per_over: u8) -> Result<Self> {
         Ok(Self {
-            batting_team,
-            bowling_team,
+            batting_team: batting_team.id,
+            bowling_team: bowling_team.id,
             batting_stats: TeamBattingInningsStats::new(batting_team)?,
             bowling_stats: TeamBowlingInningsStats::new(bowling_team)?,
-            overs: 0,
-            balls: 0,
+            overs: Overs::default(),
+            max_overs: None,
+            forfeited: false,
+            declared: false,
+            deliveries: Vec::new(),
+            over_summaries: Vec::new(),
+            milestones: Vec::new(),
+            fall_of_wickets: Vec::new(),
+            dropped_catches: Vec::new(),
+            keeper_stats: KeeperInningsStats::new(bowling_team),
+            fielding_stats: TeamFieldingInningsStats::new(bowling_team),
+            reviews: Vec::new(),
+            matchups: Vec::new(),
+            wagon_wheels: Vec::new(),
+            elapsed: 0,
+            over_rate_penalty: false,
             balls_per_over,
         })
     }
 
+    /// Whether any play has taken place in this innings
+    pub fn started(&self) -> bool {
+        !self.deliveries.is_empty() || self.wickets() > 0
+    }
+
     /// Whether all (but one) batters have been made out. Indicates the innings must be
     /// complete.
     pub fn all_out(&self) -> bool {
@@ -500,17 +1516,1028 @@ impl<'a> InningsStats<'a> {
 
     /// Update the stats with a new delivery
     pub fn update(&mut self, ball: &DeliveryOutcome) -> Result<()> {
+        self.deliveries.push(DeliveryRecord {
+            overs: self.overs,
+            bowler: self.bowling_stats.current_bowler(),
+            striker: self.batting_stats.striker(),
+            non_striker: self.batting_stats.non_striker(),
+            outcome: ball.clone(),
+        });
+        let striker = self.batting_stats.striker();
+        let (striker_runs, _) = self
+            .batting_stats
+            .batter_score(striker)
+            .ok_or(Error::PlayerNotFound(striker))?;
+        let bowler_wickets = self.bowling_stats.current_bowler_stats().wickets;
+        let bowler = self.bowling_stats.current_bowler();
+        let index = match self
+            .matchups
+            .iter()
+            .position(|(b, w, _)| *b == striker && *w == bowler)
+        {
+            Some(index) => index,
+            None => {
+                self.matchups.push((striker, bowler, Matchup::default()));
+                self.matchups.len() - 1
+            }
+        };
+        self.matchups[index].2.record(ball, striker);
+        if ball.zone.is_some() {
+            match self.wagon_wheels.iter_mut().find(|(b, _)| *b == striker) {
+                Some((_, wheel)) => wheel.record(ball),
+                None => {
+                    let mut wheel = WagonWheel::default();
+                    wheel.record(ball);
+                    self.wagon_wheels.push((striker, wheel));
+                }
+            }
+        }
+        if let Some(FieldingError::DroppedCatch { fielder }) = ball.fielding_error {
+            self.dropped_catches.push(DroppedCatch {
+                fielder,
+                batter: striker,
+                batter_runs: striker_runs,
+                overs: self.overs,
+            });
+        }
         self.batting_stats.update(ball)?;
         self.bowling_stats.update(ball);
+        if let Some(keeper) = &mut self.keeper_stats {
+            keeper.update(ball);
+        }
+        self.fielding_stats.update(ball);
+        self.check_milestones(striker, striker_runs, bowler_wickets)?;
         if ball.legal() {
-            self.balls += 1;
+            self.overs.balls += 1;
         }
-        if self.balls >= self.balls_per_over {
-            self.balls = 0;
-            self.overs += 1;
+        if let Some((out_id, _)) = &ball.wicket {
+            self.record_fall_of_wicket(*out_id);
+        }
+        if self.overs.balls >= self.balls_per_over {
+            self.summarize_over();
+            self.overs = Overs::new(self.overs.completed + 1, 0);
             self.batting_stats.switch_striker();
-            self.bowling_stats.new_over()?;
+            self.bowling_stats.new_over(self.balls_per_over)?;
+        }
+        self.batting_stats.mark_entries(self.entry_point());
+        Ok(())
+    }
+
+    /// The state of the innings for a batter coming in now
+    fn entry_point(&self) -> EntryPoint {
+        EntryPoint {
+            runs: self.runs(),
+            wickets: self.wickets(),
+            overs: self.overs,
+        }
+    }
+
+    /// Record any milestones reached by the striker or bowler on the last delivery, given
+    /// their scores before it.
+    fn check_milestones(
+        &mut self,
+        striker: PlayerId,
+        striker_runs_before: u16,
+        bowler_wickets_before: u8,
+    ) -> Result<()> {
+        let (runs, balls) = self
+            .batting_stats
+            .batter_score(striker)
+            .ok_or(Error::PlayerNotFound(striker))?;
+        // A single shot can't pass more than one multiple of 50
+        if runs / 50 > striker_runs_before / 50 {
+            self.milestones.push(Milestone::BatterScore {
+                batter: striker,
+                runs: 50 * (runs / 50),
+                balls,
+            });
+        }
+        let bowler_stats = self.bowling_stats.current_bowler_stats();
+        if bowler_wickets_before < 5 && bowler_stats.wickets >= 5 {
+            self.milestones.push(Milestone::FiveWickets {
+                bowler: self.bowling_stats.current_bowler(),
+                runs: bowler_stats.runs,
+            });
+        }
+        Ok(())
+    }
+
+    /// Per-over runs and wickets, e.g. for Manhattan and worm charts
+    pub fn over_summaries(&self) -> &[OverSummary] {
+        &self.over_summaries
+    }
+
+    /// Record the summary of the over in progress
+    fn summarize_over(&mut self) {
+        let (prev_runs, prev_wickets) = self
+            .over_summaries
+            .last()
+            .map_or((0, 0), |o| (o.total_runs, o.total_wickets));
+        let total_runs = self.runs();
+        let total_wickets = self.wickets();
+        self.over_summaries.push(OverSummary {
+            overs: Overs::new(self.overs.completed, 0),
+            bowler: self.bowling_stats.current_bowler(),
+            runs: total_runs - prev_runs,
+            wickets: total_wickets - prev_wickets,
+            total_runs,
+            total_wickets,
+        });
+    }
+
+    /// Finalize the stats at the close of the innings, summarizing any incomplete over
+    pub fn close(&mut self) {
+        let over_in_progress = self.deliveries.last().is_some_and(|d| {
+            self.over_summaries
+                .last()
+                .is_none_or(|o| o.overs.completed < d.overs.completed)
+        });
+        if over_in_progress {
+            self.summarize_over();
+        }
+        self.bowling_stats.close();
+    }
+
+    /// The number of legal balls bowled in the innings
+    pub fn balls_bowled(&self) -> u16 {
+        self.overs.total_balls(self.balls_per_over)
+    }
+
+    /// The number of fours and sixes hit by the batters in the innings
+    pub fn boundaries(&self) -> u16 {
+        self.batting_stats
+            .batters()
+            .iter()
+            .map(|(_, st)| st.fours as u16 + st.sixes as u16)
+            .sum()
+    }
+
+    /// The batting side's run rate, in runs per over, or 0 before a legal ball is bowled
+    pub fn run_rate(&self) -> f32 {
+        let balls = self.balls_bowled();
+        if balls == 0 {
+            return 0.;
+        }
+        self.runs() as f32 * self.balls_per_over as f32 / balls as f32
+    }
+
+    /// The fielding side's over rate, in overs per hour, or 0 before any time has passed
+    pub fn over_rate(&self) -> f32 {
+        if self.elapsed == 0 {
+            return 0.;
+        }
+        let overs = self.balls_bowled() as f32 / self.balls_per_over as f32;
+        overs * 3600. / self.elapsed as f32
+    }
+
+    /// The number of whole overs by which the fielding side is behind the required rate.
+    /// The time allowed for a part over is in proportion to the balls bowled in it.
+    pub fn overs_behind_rate(&self, rules: &OverRateRules) -> u16 {
+        let allowed =
+            self.balls_bowled() as u32 * rules.seconds_per_over / self.balls_per_over as u32;
+        (self.elapsed.saturating_sub(allowed) / rules.seconds_per_over) as u16
+    }
+
+    /// Add time elapsed during the innings and apply any in-match over rate penalty. This
+    /// should be called after each delivery, and the time is credited to the batters who
+    /// were at the crease for it.
+    pub fn advance_clock(
+        &mut self,
+        seconds: u32,
+        rules: Option<&OverRateRules>,
+        overs_per_innings: Option<u16>,
+    ) {
+        self.elapsed += seconds;
+        if let Some(last) = self.deliveries.last() {
+            let batters = [last.striker, last.non_striker];
+            self.batting_stats.add_time(&batters, seconds);
+        }
+        if let (Some(rules), Some(opi)) = (rules, overs_per_innings) {
+            let scheduled_end = opi as u32 * rules.seconds_per_over;
+            if matches!(rules.penalty, OverRatePenalty::ExtraFielderInRing)
+                && self.overs.balls == 0
+                && self.elapsed > scheduled_end
+            {
+                self.over_rate_penalty = true;
+            }
+        }
+    }
+
+    /// Award penalty runs to the batting side if the fielding side is behind the required
+    /// over rate at the close of the innings.
+    pub fn apply_over_rate_penalty(&mut self, rules: &OverRateRules) {
+        if let OverRatePenalty::PenaltyRuns(runs) = rules.penalty {
+            let penalty = runs as u16 * self.overs_behind_rate(rules);
+            self.batting_stats.add_penalty_runs(penalty);
+        }
+    }
+
+    /// Send in a batter who has still to bat next, in place of the batter who has just
+    /// come in if they have yet to face a ball
+    pub fn promote(&mut self, id: PlayerId) -> Result<()> {
+        self.batting_stats.promote(id)
+    }
+
+    /// Replace a player of either side with a substitute for the rest of the innings
+    pub fn substitute(&mut self, team: u16, id: PlayerId, replacement: &(PlayerId, String)) {
+        if team == self.batting_team {
+            self.batting_stats.substitute(id, replacement.0);
+            self.batting_stats.mark_entries(self.entry_point());
+        } else {
+            self.bowling_stats.substitute(id, replacement.0);
+            self.fielding_stats.substitute(id, replacement.0);
+            if let Some(keeper) = self.keeper_stats.as_mut().filter(|k| k.keeper == id) {
+                keeper.keeper = replacement.0;
+            }
+        }
+    }
+
+    /// Update the stats with an event that does not count as a delivery
+    pub fn non_delivery_event(&mut self, event: &NonDeliveryEvent) -> Result<()> {
+        match event {
+            NonDeliveryEvent::RunOutNonStriker { bowler } => {
+                let non_striker = self.batting_stats.non_striker();
+                self.batting_stats
+                    .dismiss_non_striker(&Dismissal::RunOutNonStriker(bowler.clone()))?;
+                self.fielding_stats
+                    .run_out(self.bowling_stats.current_bowler());
+                self.record_fall_of_wicket(non_striker);
+                self.batting_stats.mark_entries(self.entry_point());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the next delivery is a free hit, i.e. the last ball was a no-ball or a wide
+    /// bowled while a free hit was pending. Only applies in limited-overs matches, so the
+    /// caller must check the form.
+    pub fn free_hit(&self) -> bool {
+        for record in self.deliveries.iter().rev() {
+            let extras = &record.outcome.extras;
+            if extras.iter().any(|ex| matches!(ex, Extra::NoBall)) {
+                return true;
+            }
+            // The free hit carries over a wide or a ball called dead
+            let wide = extras.iter().any(|ex| matches!(ex, Extra::Wide(_)));
+            if !wide && !record.outcome.is_dead_ball() {
+                return false;
+            }
+        }
+        false
+    }
+
+    /// The number of legal balls in a row, up to the last one, off which no runs were
+    /// scored
+    pub fn dots_in_a_row(&self) -> u16 {
+        self.deliveries
+            .iter()
+            .rev()
+            .filter(|record| record.outcome.legal())
+            .take_while(|record| {
+                record.outcome.runs.runs() == 0 && record.outcome.extras.is_empty()
+            })
+            .count() as u16
+    }
+
+    /// The runs and legal balls of the current partnership
+    pub fn partnership(&self) -> (u16, u16) {
+        let (runs, balls) = self.fall_of_wickets.last().map_or((0, 0), |fow| {
+            (fow.runs, fow.overs.total_balls(self.balls_per_over))
+        });
+        (self.runs() - runs, self.balls_bowled() - balls)
+    }
+
+    /// The record of the batter against the bowler in this innings
+    pub fn matchup(&self, batter: PlayerId, bowler: PlayerId) -> Matchup {
+        self.matchups
+            .iter()
+            .find(|(b, w, _)| *b == batter && *w == bowler)
+            .map_or_else(Matchup::default, |(_, _, matchup)| *matchup)
+    }
+
+    /// Where the batter's scoring shots went, if the model placed any of them
+    pub fn wagon_wheel(&self, batter: PlayerId) -> Option<&WagonWheel> {
+        self.wagon_wheels
+            .iter()
+            .find(|(b, _)| *b == batter)
+            .map(|(_, wheel)| wheel)
+    }
+
+    /// Record the team score as a wicket falls
+    fn record_fall_of_wicket(&mut self, batter: PlayerId) {
+        self.fall_of_wickets.push(FallOfWicket {
+            wicket: self.wickets(),
+            runs: self.runs(),
+            batter,
+            overs: self.overs,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use smallvec::smallvec;
+
+    fn test_team() -> Team {
+        Team {
+            id: 0,
+            name: "team".to_string(),
+            players: (0..11)
+                .map(|i| (PlayerId(i), format!("player_{}", i)))
+                .collect(),
+            keeper: Some(PlayerId(4)),
+            captain: None,
+            home: None,
+            roles: Default::default(),
+        }
+    }
+
+    #[test]
+    fn run_out_not_credited_to_bowler() -> Result<()> {
+        let team = test_team();
+        let mut bowling = TeamBowlingInningsStats::new(&team)?;
+        let run_out = DeliveryOutcome {
+            wicket: Some((PlayerId(0), Dismissal::RunOutStriker("fielder".to_string()))),
+            ..Default::default()
+        };
+        bowling.update(&run_out);
+        assert_eq!(bowling.bowler_stats[0].1.wickets, 0);
+        bowling.update(&DeliveryOutcome::bowled(PlayerId(1), "bowler"));
+        assert_eq!(bowling.bowler_stats[0].1.wickets, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn non_striker_run_out_is_not_a_ball() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let non_striker = innings.batting_stats.non_striker();
+        innings.non_delivery_event(&NonDeliveryEvent::RunOutNonStriker {
+            bowler: "bowler".to_string(),
+        })?;
+        assert_eq!(innings.wickets(), 1);
+        assert_eq!(innings.overs.balls, 0);
+        assert_ne!(innings.batting_stats.non_striker(), non_striker);
+        assert_eq!(innings.bowling_stats.bowler_stats[0].1.wickets, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn fielding_credits() {
+        let team = test_team();
+        let mut fielding = TeamFieldingInningsStats::new(&team);
+        let out = |dismissal, fielders: &[u32]| DeliveryOutcome {
+            wicket: Some((PlayerId(0), dismissal)),
+            fielders: fielders.iter().map(|&id| PlayerId(id)).collect(),
+            ..Default::default()
+        };
+        let name = |i: u32| format!("player_{}", i);
+        fielding.update(&out(
+            Dismissal::Caught {
+                caught: name(2),
+                bowler: name(10),
+            },
+            &[2],
+        ));
+        fielding.update(&out(
+            Dismissal::CaughtBehind {
+                keeper: name(4),
+                bowler: name(10),
+            },
+            &[4],
+        ));
+        fielding.update(&out(
+            Dismissal::Stumped {
+                keeper: name(4),
+                bowler: name(9),
+            },
+            &[4],
+        ));
+        fielding.update(&out(
+            Dismissal::RunOutNonStriker(format!("{}/{}", name(2), name(4))),
+            &[2, 4],
+        ));
+        // Fielders are credited by ID, whatever the dismissal calls them, so players who
+        // share a name aren't mixed up
+        fielding.update(&out(
+            Dismissal::Caught {
+                caught: name(5),
+                bowler: name(10),
+            },
+            &[6],
+        ));
+        // A dismissal that doesn't identify its fielders credits no one
+        fielding.update(&out(
+            Dismissal::Caught {
+                caught: name(7),
+                bowler: name(10),
+            },
+            &[],
+        ));
+        fielding.update(&DeliveryOutcome {
+            fielding_error: Some(FieldingError::DroppedCatch {
+                fielder: PlayerId(2),
+            }),
+            ..Default::default()
+        });
+        let stats = |id| *fielding.get(PlayerId(id)).unwrap();
+        assert_eq!(
+            stats(2),
+            FielderInningsStats {
+                catches: 1,
+                run_outs: 1,
+                stumpings: 0,
+                drops: 1,
+            }
+        );
+        assert_eq!(stats(4).dismissals(), 3);
+        assert_eq!((stats(5).catches, stats(6).catches), (0, 1));
+        assert_eq!(stats(7), FielderInningsStats::default());
+        assert_eq!(stats(10), FielderInningsStats::default());
+    }
+
+    #[test]
+    fn entry_points() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let four = DeliveryOutcome {
+            runs: Runs::Four,
+            ..Default::default()
+        };
+        for ball in [
+            four,
+            DeliveryOutcome::dot(),
+            DeliveryOutcome::bowled(PlayerId(0), "player_10"),
+        ] {
+            innings.update(&ball)?;
+            innings.advance_clock(30, None, None);
+        }
+        innings.update(&DeliveryOutcome::dot())?;
+        innings.advance_clock(30, None, None);
+        let batters = innings.batting_stats.batters();
+        let (opener, non_striker, incoming) = (&batters[0].1, &batters[1].1, &batters[2].1);
+        assert_eq!((opener.position, opener.balls_at_crease), (1, 3));
+        assert_eq!(opener.seconds, 90);
+        assert_eq!((non_striker.balls_at_crease, non_striker.minutes()), (4, 2));
+        assert_eq!(incoming.position, 3);
+        assert_eq!(
+            incoming.came_in.map(|entry| entry.to_string()).as_deref(),
+            Some("4/1 (0.3 ov)")
+        );
+        assert_eq!((incoming.balls_at_crease, incoming.seconds), (1, 30));
+        Ok(())
+    }
+
+    #[test]
+    fn over_rate_penalty_runs() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let rules = OverRateRules {
+            seconds_per_over: 240,
+            penalty: OverRatePenalty::PenaltyRuns(5),
+        };
+        assert_eq!(innings.over_rate(), 0.);
+        // Two and a half overs are allowed 600 seconds
+        innings.overs = Overs::new(2, 3);
+        innings.advance_clock(600 + 239, Some(&rules), None);
+        assert_eq!(innings.overs_behind_rate(&rules), 0);
+        innings.advance_clock(1, Some(&rules), None);
+        assert_eq!(innings.overs_behind_rate(&rules), 1);
+        innings.apply_over_rate_penalty(&rules);
+        assert_eq!(innings.runs(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn maidens() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let leg_bye = DeliveryOutcome {
+            extras: smallvec![Extra::LegBye(Runs::Running(1))],
+            ..Default::default()
+        };
+        let wide = DeliveryOutcome {
+            extras: smallvec![Extra::Wide(Runs::Running(0))],
+            ..Default::default()
+        };
+        // 0 0 1lb 4b W 0: a wicket maiden, as byes and leg byes aren't the bowler's
+        let over = [
+            DeliveryOutcome::dot(),
+            DeliveryOutcome::dot(),
+            leg_bye,
+            DeliveryOutcome {
+                extras: smallvec![Extra::Bye(Runs::Running(4))],
+                ..Default::default()
+            },
+            DeliveryOutcome::bowled(PlayerId(0), "player_10"),
+            DeliveryOutcome::dot(),
+        ];
+        for ball in &over {
+            innings.update(ball)?;
+        }
+        // 0 0 1w 0 0 0 0: the wide is charged to the bowler
+        innings.update(&wide)?;
+        for _ in 0..6 {
+            innings.update(&DeliveryOutcome::dot())?;
+        }
+        // Three dots before the innings closes are not a maiden
+        for _ in 0..3 {
+            innings.update(&DeliveryOutcome::dot())?;
+        }
+        innings.close();
+        let figures: Vec<_> = innings
+            .bowling_stats
+            .bowlers()
+            .iter()
+            .map(|(_, st)| (st.balls, st.maiden_overs, st.runs, st.wickets))
+            .collect();
+        assert_eq!(figures, [(9, 1, 0, 1), (6, 0, 1, 0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_rates() -> Result<()> {
+        let team = test_team();
+        let innings = InningsStats::new(&team, &team, 6)?;
+        assert_eq!((innings.run_rate(), innings.over_rate()), (0., 0.));
+        let batter = BatterInningsStats::default();
+        assert_eq!(batter.strike_rate(), 0.);
+        assert_eq!(batter.dot_percentage(), 0.);
+        assert_eq!(batter.boundary_percentage(), 0.);
+        assert_eq!(BowlerInningsStats::default().economy(6), 0.);
+        assert_eq!(Matchup::default().strike_rate(), 0.);
+        Ok(())
+    }
+
+    #[test]
+    fn over_symbols() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let with_extra = |extra, runs| DeliveryOutcome {
+            extras: smallvec![extra],
+            runs,
+            ..Default::default()
+        };
+        let over = [
+            DeliveryOutcome::dot(),
+            DeliveryOutcome::running(1),
+            DeliveryOutcome::four(),
+            DeliveryOutcome::bowled(PlayerId(0), "player_10"),
+            with_extra(Extra::Wide(Runs::Running(0)), Runs::Running(0)),
+            with_extra(Extra::NoBall, Runs::Four),
+            with_extra(Extra::LegBye(Runs::Running(1)), Runs::Running(0)),
+            DeliveryOutcome::dot(),
+        ];
+        for ball in &over {
+            innings.update(ball)?;
+        }
+        innings.update(&DeliveryOutcome::running(1))?;
+        innings.close();
+        let summaries = innings.over_summaries();
+        let line = |over: usize| summaries[over].describe(&innings.deliveries).to_string();
+        assert_eq!(line(0), ". 1 4 W wd 4nb 1lb . (12 runs)");
+        assert_eq!(line(1), "1 (1 run)");
+        Ok(())
+    }
+
+    #[test]
+    fn over_summaries_kept() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let overs = [
+            vec![
+                DeliveryOutcome::four(),
+                DeliveryOutcome::running(2),
+                DeliveryOutcome::bowled(PlayerId(0), "player_10"),
+                DeliveryOutcome::dot(),
+                DeliveryOutcome::six(),
+                DeliveryOutcome::running(1),
+            ],
+            vec![DeliveryOutcome::dot(); 6],
+            vec![DeliveryOutcome::running(3)],
+        ];
+        for ball in overs.iter().flatten() {
+            innings.update(ball)?;
+        }
+        // The last over is only summarized once the innings closes
+        assert_eq!(innings.over_summaries().len(), 2);
+        innings.close();
+        let summaries: Vec<_> = innings
+            .over_summaries()
+            .iter()
+            .map(|o| {
+                (
+                    o.overs.completed,
+                    o.runs,
+                    o.wickets,
+                    o.total_runs,
+                    o.total_wickets,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summaries,
+            [(0, 13, 1, 13, 1), (1, 0, 0, 13, 1), (2, 3, 0, 16, 1)]
+        );
+        // Closing again doesn't summarize the last over twice
+        innings.close();
+        assert_eq!(innings.over_summaries().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn scoring_breakdown() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let striker = innings.batting_stats.striker();
+        let wide = DeliveryOutcome {
+            extras: smallvec![Extra::Wide(Runs::Running(0))],
+            ..Default::default()
+        };
+        for ball in [
+            DeliveryOutcome::dot(),
+            wide,
+            DeliveryOutcome::four(),
+            DeliveryOutcome::dot(),
+            DeliveryOutcome::six(),
+            DeliveryOutcome::running(1),
+        ] {
+            innings.update(&ball)?;
+        }
+        let bowler = &innings.bowling_stats.bowlers()[0].1;
+        assert_eq!(
+            (bowler.dots, bowler.singles, bowler.fours, bowler.sixes),
+            (2, 1, 1, 1)
+        );
+        let (_, batter) = innings
+            .batting_stats
+            .batters()
+            .iter()
+            .find(|(id, _)| *id == striker)
+            .expect("the opener has batted");
+        // 2 dots off 5 balls, and 10 of the 11 runs in boundaries
+        assert_eq!(batter.dot_percentage(), 40.);
+        assert!((batter.boundary_percentage() - 1000. / 11.).abs() < 1e-4);
+        Ok(())
+    }
+
+    #[test]
+    fn positions_in_overs() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        for _ in 0..7 {
+            innings.update(&DeliveryOutcome::dot())?;
+        }
+        innings.update(&DeliveryOutcome::bowled(PlayerId(0), "player_10"))?;
+        assert_eq!(innings.deliveries[7].overs, Overs::new(1, 1));
+        assert_eq!(innings.fall_of_wickets[0].overs, Overs::new(1, 2));
+        assert_eq!(innings.over_summaries()[0].overs, Overs::new(0, 0));
+        // Records saved with separate over and ball fields still load
+        let fow: FallOfWicket = serde_json::from_str(
+            r#"{"wicket": 1, "runs": 12, "batter": 3, "over": 4, "ball": 2}"#,
+        )?;
+        assert_eq!(fow.overs, Overs::new(4, 2));
+        let json = serde_json::to_value(&innings.fall_of_wickets[0])?;
+        assert_eq!((&json["overs"], &json["balls"]), (&1.into(), &2.into()));
+        Ok(())
+    }
+
+    #[test]
+    fn extras_breakdown() {
+        let mut extras = ExtrasStats::default();
+        for extra in [
+            Extra::Bye(Runs::Four),
+            Extra::LegBye(Runs::Running(2)),
+            Extra::Wide(Runs::Running(0)),
+            Extra::Wide(Runs::Four),
+            Extra::NoBall,
+        ] {
+            extras.add(&DeliveryOutcome {
+                extras: smallvec![extra],
+                ..Default::default()
+            });
+        }
+        assert_eq!(extras.total(), 13);
+        assert_eq!(extras.to_string(), "13 (b 4, lb 2, w 6, nb 1)");
+        assert_eq!(ExtrasStats::default().to_string(), "0");
+    }
+
+    #[test]
+    fn no_ball_with_overthrows() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let striker = innings.batting_stats.striker();
+        // Two run off the bat from a no-ball, then four overthrows to the boundary
+        innings.update(&DeliveryOutcome {
+            runs: Runs::Running(2),
+            extras: smallvec![Extra::NoBall, Extra::Overthrows(Runs::Four)],
+            ..Default::default()
+        })?;
+        assert_eq!(innings.runs(), 7);
+        assert_eq!(innings.batting_stats.striker(), striker);
+        let batter = &innings.batting_stats.batters()[0].1;
+        assert_eq!((batter.runs, batter.balls, batter.fours), (6, 1, 0));
+        assert_eq!(innings.batting_stats.extras.to_string(), "1 (nb 1)");
+        let bowler = innings.bowling_stats.current_bowler_stats();
+        assert_eq!((bowler.runs, bowler.no_balls, bowler.balls), (7, 1, 0));
+
+        // Overthrows of a bye are byes, and aren't charged to the bowler
+        innings.update(&DeliveryOutcome {
+            extras: smallvec![
+                Extra::Bye(Runs::Running(1)),
+                Extra::Overthrows(Runs::Running(2))
+            ],
+            ..Default::default()
+        })?;
+        assert_eq!(innings.runs(), 10);
+        assert_ne!(innings.batting_stats.striker(), striker);
+        assert_eq!(innings.batting_stats.extras.byes, 3);
+        assert_eq!(innings.bowling_stats.current_bowler_stats().runs, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn short_runs_and_dead_balls() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let striker = innings.batting_stats.striker();
+        // Two run with one short: one is scored, but the batters end up back where they
+        // started
+        innings.update(&DeliveryOutcome::short_run(2, 1))?;
+        assert_eq!(innings.runs(), 1);
+        assert_eq!(innings.batting_stats.striker(), striker);
+        innings.update(&DeliveryOutcome::dead_ball())?;
+        assert_eq!(innings.overs.balls, 1);
+        assert_eq!(innings.batting_stats.batter_score(striker), Some((1, 1)));
+        let bowler = innings.bowling_stats.current_bowler_stats();
+        assert_eq!((bowler.balls, bowler.runs, bowler.dots), (1, 1, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn runs_off_a_wide() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let striker = innings.batting_stats.striker();
+        // A wide that the batters run one off, so they change ends
+        innings.update(&DeliveryOutcome {
+            extras: smallvec![Extra::Wide(Runs::Running(1))],
+            ..Default::default()
+        })?;
+        assert_eq!(innings.runs(), 2);
+        assert_eq!(innings.overs.balls, 0);
+        assert_eq!(innings.batting_stats.non_striker(), striker);
+        assert_eq!(innings.batting_stats.batter_score(striker), Some((0, 0)));
+        let bowler = innings.bowling_stats.current_bowler_stats();
+        assert_eq!((bowler.runs, bowler.wides, bowler.balls), (2, 1, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn fifty_milestone() -> Result<()> {
+        let team = test_team();
+        // Use long overs so the same batter stays on strike
+        let mut innings = InningsStats::new(&team, &team, 20)?;
+        let striker = innings.batting_stats.striker();
+        for _ in 0..8 {
+            innings.update(&DeliveryOutcome::six())?;
+        }
+        assert!(innings.milestones.is_empty());
+        innings.update(&DeliveryOutcome::running(2))?;
+        assert!(matches!(
+            innings.milestones.as_slice(),
+            [Milestone::BatterScore { batter, runs: 50, balls: 9 }] if *batter == striker
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn run_out_end() -> Result<()> {
+        let team = test_team();
+        let mut batting = TeamBattingInningsStats::new(&team)?;
+        // The non-striker is run out coming back for a second
+        let run_out = DeliveryOutcome {
+            wicket: Some((
+                PlayerId(1),
+                Dismissal::RunOutNonStriker("fielder".to_string()),
+            )),
+            ..DeliveryOutcome::running(1)
+        };
+        batting.update(&run_out)?;
+        assert_eq!(batting.batter_score(PlayerId(0)), Some((1, 1)));
+        assert_eq!(
+            (batting.striker(), batting.non_striker()),
+            (PlayerId(0), PlayerId(2))
+        );
+
+        // Sent back by the non-striker before they crossed, the striker is out at the end
+        // they left, and the new batter takes strike
+        let mut batting = TeamBattingInningsStats::new(&team)?;
+        let run_out = DeliveryOutcome {
+            wicket: Some((PlayerId(0), Dismissal::RunOutStriker("fielder".to_string()))),
+            run_out_end: Some(End::Striker),
+            ..DeliveryOutcome::running(0)
+        };
+        batting.update(&run_out)?;
+        assert_eq!(
+            (batting.striker(), batting.non_striker()),
+            (PlayerId(2), PlayerId(1))
+        );
+
+        // After one run, the non-striker turns for a second and is sent back, out at the
+        // striker's end, so the new batter faces with the striker at the bowler's end
+        let mut batting = TeamBattingInningsStats::new(&team)?;
+        let run_out = DeliveryOutcome {
+            wicket: Some((
+                PlayerId(1),
+                Dismissal::RunOutNonStriker("fielder".to_string()),
+            )),
+            run_out_end: Some(End::Striker),
+            ..DeliveryOutcome::running(1)
+        };
+        batting.update(&run_out)?;
+        assert_eq!(batting.batter_score(PlayerId(0)), Some((1, 1)));
+        assert_eq!(
+            (batting.striker(), batting.non_striker()),
+            (PlayerId(2), PlayerId(0))
+        );
+
+        // Out at the bowler's end having crossed on the first run, the striker is
+        // replaced at that end and the non-striker takes strike
+        let mut batting = TeamBattingInningsStats::new(&team)?;
+        let run_out = DeliveryOutcome {
+            wicket: Some((PlayerId(0), Dismissal::RunOutStriker("fielder".to_string()))),
+            run_out_end: Some(End::Bowler),
+            ..DeliveryOutcome::running(0)
+        };
+        batting.update(&run_out)?;
+        assert_eq!(
+            (batting.striker(), batting.non_striker()),
+            (PlayerId(1), PlayerId(2))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn keeper_stats() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        innings.update(&DeliveryOutcome::byes(2))?;
+        innings.update(&DeliveryOutcome::caught(
+            PlayerId(0),
+            "player_9",
+            PlayerId(4),
+            "player_4",
+        ))?;
+        innings.update(&DeliveryOutcome::caught(
+            PlayerId(1),
+            "player_9",
+            PlayerId(3),
+            "player_3",
+        ))?;
+        innings.update(&DeliveryOutcome::stumped(
+            PlayerId(2),
+            "player_9",
+            PlayerId(4),
+            "player_4",
+        ))?;
+        innings.update(&DeliveryOutcome::caught_behind(
+            PlayerId(3),
+            "player_9",
+            PlayerId(4),
+            "player_4",
+        ))?;
+        // Only the designated keeper's stumpings are theirs
+        innings.update(&DeliveryOutcome::stumped(
+            PlayerId(5),
+            "player_9",
+            PlayerId(3),
+            "player_3",
+        ))?;
+        // Nor is a catch by another player who shares their name
+        innings.update(&DeliveryOutcome::caught(
+            PlayerId(6),
+            "player_9",
+            PlayerId(3),
+            "player_4",
+        ))?;
+        let keeper = innings.keeper_stats.as_ref().unwrap();
+        assert_eq!(keeper.keeper, PlayerId(4));
+        assert_eq!((keeper.catches, keeper.caught_behind), (2, 1));
+        assert_eq!((keeper.stumpings, keeper.byes), (1, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn tired_bowlers_rested() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        for _ in 0..(30 * 6) {
+            innings.update(&DeliveryOutcome::dot())?;
+        }
+        let bowlers = innings.bowling_stats.bowlers();
+        assert!(bowlers.len() > 2);
+        assert!(bowlers.iter().all(|(_, st)| st.fatigue(6) < 1.));
+        Ok(())
+    }
+
+    #[test]
+    fn over_limits_kept() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        innings.bowling_stats.set_max_overs(Some(2));
+        // Part-timers bowl once the bowlers have used up their overs
+        for _ in 0..(20 * 6) {
+            innings.update(&DeliveryOutcome::dot())?;
+        }
+        let bowlers = innings.bowling_stats.bowlers();
+        assert!(bowlers.len() >= 10);
+        assert!(bowlers.iter().all(|(_, st)| st.balls <= 12));
+        // Nobody may bowl another over once every player has used up their overs
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        innings.bowling_stats.set_max_overs(Some(1));
+        for _ in 0..(11 * 6 - 1) {
+            innings.update(&DeliveryOutcome::dot())?;
         }
+        assert!(innings.update(&DeliveryOutcome::dot()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn batter_promoted_at_wicket() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        assert!(innings.promote(PlayerId(0)).is_err());
+        innings.update(&DeliveryOutcome::dot())?;
+        innings.update(&DeliveryOutcome::bowled(PlayerId(0), "player_9"))?;
+        assert_eq!(innings.batting_stats.striker(), PlayerId(2));
+        innings.promote(PlayerId(8))?;
+        assert_eq!(innings.batting_stats.striker(), PlayerId(8));
+        assert_eq!(
+            innings.batting_stats.remaining()[..2],
+            [PlayerId(2), PlayerId(3)]
+        );
+        innings.update(&DeliveryOutcome::dot())?;
+        // Once the new batter has faced, a promotion only changes the order
+        innings.promote(PlayerId(6))?;
+        assert_eq!(innings.batting_stats.striker(), PlayerId(8));
+        assert_eq!(
+            innings.batting_stats.remaining()[..2],
+            [PlayerId(6), PlayerId(2)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn dropped_catch_recorded() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        innings.update(&DeliveryOutcome::four())?;
+        let drop = DeliveryOutcome {
+            fielding_error: Some(FieldingError::DroppedCatch {
+                fielder: PlayerId(7),
+            }),
+            ..DeliveryOutcome::running(1)
+        };
+        innings.update(&drop)?;
+        let dropped = &innings.dropped_catches[0];
+        assert_eq!(
+            (dropped.fielder, dropped.batter),
+            (PlayerId(7), PlayerId(0))
+        );
+        assert_eq!((dropped.batter_runs, dropped.overs), (4, Overs::new(0, 1)));
+        Ok(())
+    }
+
+    #[test]
+    fn free_hit_and_partnership() -> Result<()> {
+        let team = test_team();
+        let mut innings = InningsStats::new(&team, &team, 6)?;
+        let no_ball = DeliveryOutcome {
+            extras: smallvec![Extra::NoBall],
+            ..Default::default()
+        };
+        let wide = DeliveryOutcome {
+            extras: smallvec![Extra::Wide(Runs::Running(0))],
+            ..Default::default()
+        };
+        innings.update(&no_ball)?;
+        assert!(innings.free_hit());
+        // A wide keeps the free hit pending
+        innings.update(&wide)?;
+        assert!(innings.free_hit());
+        innings.update(&DeliveryOutcome::four())?;
+        assert!(!innings.free_hit());
+        assert_eq!(innings.partnership(), (6, 1));
+        innings.update(&DeliveryOutcome::dot())?;
+        innings.update(&DeliveryOutcome::dot())?;
+        assert_eq!(innings.dots_in_a_row(), 2);
+
+        let striker = innings.batting_stats.striker();
+        innings.update(&DeliveryOutcome::bowled(striker, "bowler"))?;
+        innings.update(&DeliveryOutcome::running(1))?;
+        assert_eq!(innings.partnership(), (1, 1));
         Ok(())
     }
 }
@@ -0,0 +1,274 @@
+//! The events of a match as a stream of JSON lines, e.g. to pipe into another process
+use super::{
+    observer::MatchObserver,
+    stats::{DeliveryRecord, DroppedCatch, OverSummary},
+    DeliveryOutcome, Dismissal, GameState, MatchResult, Review,
+};
+use crate::{error::Result, player::PlayerId};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// A line of the event stream. Each is written as a JSON object whose `event` field
+/// names the kind of event, followed by the fields of that kind. Fields may be added,
+/// but existing ones keep their names and meanings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StreamEvent {
+    /// A delivery was bowled. The score is the batting side's after it.
+    Delivery {
+        innings: usize,
+        delivery: DeliveryRecord,
+        runs: u16,
+        wickets: u8,
+    },
+    /// A batter was dismissed, after the delivery that dismissed them
+    Wicket {
+        innings: usize,
+        batter: PlayerId,
+        dismissal: Dismissal,
+        runs: u16,
+        wickets: u8,
+    },
+    /// A side reviewed the umpire's decision on the last delivery
+    Review { innings: usize, review: Review },
+    /// A catch went down
+    DroppedCatch { innings: usize, drop: DroppedCatch },
+    /// The last ball of an over was bowled
+    OverComplete { innings: usize, over: OverSummary },
+    /// An innings closed with the given total
+    InningsEnd {
+        innings: usize,
+        batting_team: u16,
+        runs: u16,
+        wickets: u8,
+    },
+    /// The match is complete
+    MatchEnd {
+        result: MatchResult,
+        /// The result in standard phrasing
+        summary: String,
+    },
+}
+
+/// Writes every event of a match as a line of JSON. Writing stops at the first error,
+/// which is returned by `finish`.
+pub struct JsonLinesObserver<W: Write> {
+    writer: W,
+    error: Option<crate::error::Error>,
+}
+
+impl<W: Write> JsonLinesObserver<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Flush the stream and return the writer, or the first error in writing it
+    pub fn finish(mut self) -> Result<W> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write(&mut self, event: &StreamEvent) {
+        if self.error.is_some() {
+            return;
+        }
+        let written = serde_json::to_writer(&mut self.writer, event)
+            .map_err(Into::into)
+            .and_then(|_| self.writer.write_all(b"\n").map_err(Into::into));
+        if let Err(err) = written {
+            self.error = Some(err);
+        }
+    }
+
+    /// The innings of the latest delivery, counted from 0. This is taken from the state
+    /// rather than counted, so the stream can be attached to a match already under way.
+    fn innings(state: &GameState) -> usize {
+        (0..state.innings_started())
+            .rev()
+            .find(|&i| state.innings_deliveries(i).is_some_and(|d| !d.is_empty()))
+            .unwrap_or_default()
+    }
+
+    /// The batting side's score in the innings of the latest delivery
+    fn total(state: &GameState) -> (u16, u8) {
+        state
+            .innings_total(Self::innings(state))
+            .unwrap_or_default()
+    }
+}
+
+impl<W: Write> MatchObserver for JsonLinesObserver<W> {
+    fn on_delivery(&mut self, state: &GameState, _ball: &DeliveryOutcome) {
+        let innings = Self::innings(state);
+        let record = state
+            .innings_deliveries(innings)
+            .and_then(|deliveries| deliveries.last());
+        if let Some(record) = record {
+            let (runs, wickets) = Self::total(state);
+            let event = StreamEvent::Delivery {
+                innings,
+                delivery: record.clone(),
+                runs,
+                wickets,
+            };
+            self.write(&event);
+        }
+    }
+
+    fn on_wicket(&mut self, state: &GameState, batter: PlayerId, dismissal: &Dismissal) {
+        let (runs, wickets) = Self::total(state);
+        let event = StreamEvent::Wicket {
+            innings: Self::innings(state),
+            batter,
+            dismissal: dismissal.clone(),
+            runs,
+            wickets,
+        };
+        self.write(&event);
+    }
+
+    fn on_review(&mut self, state: &GameState, review: &Review) {
+        let event = StreamEvent::Review {
+            innings: Self::innings(state),
+            review: review.clone(),
+        };
+        self.write(&event);
+    }
+
+    fn on_dropped_catch(&mut self, state: &GameState, drop: &DroppedCatch) {
+        let event = StreamEvent::DroppedCatch {
+            innings: Self::innings(state),
+            drop: drop.clone(),
+        };
+        self.write(&event);
+    }
+
+    fn on_over_complete(&mut self, state: &GameState, over: &OverSummary) {
+        let event = StreamEvent::OverComplete {
+            innings: Self::innings(state),
+            over: over.clone(),
+        };
+        self.write(&event);
+    }
+
+    fn on_innings_end(&mut self, state: &GameState, innings: usize) {
+        if let Some(st) = state.completed_innings().get(innings) {
+            let event = StreamEvent::InningsEnd {
+                innings,
+                batting_team: st.batting_team,
+                runs: st.runs(),
+                wickets: st.wickets(),
+            };
+            self.write(&event);
+        }
+    }
+
+    fn on_match_end(&mut self, state: &GameState, result: &MatchResult) {
+        let event = StreamEvent::MatchEnd {
+            result: *result,
+            summary: result.describe(state),
+        };
+        self.write(&event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Result, form, game::MatchEvent, model, player::PlayerDb, seed, tests::test_team,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn json_lines_stream() -> Result<()> {
+        use model::NullModel;
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "PAK")?;
+        let team_b = test_team(&mut db, 2, "BAN")?;
+        let mut state = GameState::new(form::Form::t20(), team_a, team_b)?;
+        let mut stream = JsonLinesObserver::new(Vec::new());
+        state.simulate_with(&NullModel {}, &db, &mut thread_rng(), &mut stream)?;
+        let lines = String::from_utf8(stream.finish()?).unwrap();
+        let events = lines
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<Vec<StreamEvent>, _>>()?;
+        let deliveries = events
+            .iter()
+            .filter(|e| matches!(e, StreamEvent::Delivery { .. }))
+            .count();
+        let bowled = state
+            .events()
+            .iter()
+            .filter(|e| matches!(e, MatchEvent::Delivery(_)))
+            .count();
+        assert_eq!(deliveries, bowled);
+        let innings_ends: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::InningsEnd { innings, runs, .. } => Some((*innings, *runs)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(innings_ends.len(), 2);
+        assert_eq!(Some(innings_ends[1].1), state.innings_total(1).map(|t| t.0));
+        assert!(matches!(events.last(), Some(StreamEvent::MatchEnd { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn attached_mid_match() -> Result<()> {
+        use model::NullModel;
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "PAK")?;
+        let team_b = test_team(&mut db, 2, "BAN")?;
+        let mut state = GameState::new(form::Form::t20(), team_a, team_b)?;
+        let mut rng = seed::rng(3);
+        for delivery in state.deliveries(&NullModel {}, &db, &mut rng) {
+            let (number, _, _) = delivery?;
+            if number.innings == 1 && number.overs.completed == 2 {
+                break;
+            }
+        }
+        let bowled_before = state.innings_deliveries(1).map_or(0, |d| d.len());
+        assert!(bowled_before > 0 && !state.complete());
+        let mut stream = JsonLinesObserver::new(Vec::new());
+        state.simulate_with(&NullModel {}, &db, &mut rng, &mut stream)?;
+        let lines = String::from_utf8(stream.finish()?).unwrap();
+        let events = lines
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<Vec<StreamEvent>, _>>()?;
+        let mut deliveries = 0;
+        for event in &events {
+            match event {
+                StreamEvent::Delivery {
+                    innings,
+                    runs,
+                    wickets,
+                    ..
+                } => {
+                    assert_eq!(*innings, 1);
+                    assert!(Some((*runs, *wickets)) <= state.innings_total(1));
+                    deliveries += 1;
+                }
+                StreamEvent::Wicket { innings, .. }
+                | StreamEvent::Review { innings, .. }
+                | StreamEvent::DroppedCatch { innings, .. }
+                | StreamEvent::OverComplete { innings, .. }
+                | StreamEvent::InningsEnd { innings, .. } => assert_eq!(*innings, 1),
+                StreamEvent::MatchEnd { .. } => {}
+            }
+        }
+        let bowled = state.innings_deliveries(1).map_or(0, |d| d.len());
+        assert_eq!(deliveries, bowled - bowled_before);
+        Ok(())
+    }
+}
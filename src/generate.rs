@@ -0,0 +1,344 @@
+//! Procedural generation of fictional players and squads
+use crate::{
+    error::Result,
+    model::{
+        basic::try_choose,
+        naive_stats::{
+            default_no_ball_rate, default_wide_rate, BatRatingNaiveStats, BowlRatingNaiveStats,
+        },
+        FieldRating, PlayerRatingNaiveStats, RunningRating,
+    },
+    player::{BowlingStyle, Hand, PlayerDb, PlayerId, Role},
+    team::Team,
+};
+use rand::{seq::SliceRandom, Rng, RngCore};
+use rand_distr::{Distribution, Gamma};
+use serde::{Deserialize, Serialize};
+
+/// The kind of player to generate, which sets their role and their typical ratings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Archetype {
+    Opener,
+    /// A top-order batter who plays long innings
+    Anchor,
+    /// A middle-order hitter
+    Finisher,
+    WicketKeeper,
+    AllRounder,
+    /// A fast bowler who takes wickets at the cost of runs
+    StrikeBowler,
+    /// A seamer who keeps the runs down
+    ContainingBowler,
+    Spinner,
+}
+
+/// The ratings of an average player of an archetype, before their talent is applied
+struct Base {
+    /// Runs per wicket
+    avg: f32,
+    /// Runs per 100 balls
+    sr: f32,
+    /// The fraction of runs scored in boundaries
+    boundaries: f32,
+    /// The fraction of boundary runs that come from sixes
+    sixes: f32,
+    /// Balls per wicket
+    bowl_sr: f32,
+    /// Runs conceded per ball
+    bowl_rpb: f32,
+}
+
+impl Archetype {
+    pub fn role(&self) -> Role {
+        use Archetype::*;
+        match self {
+            Opener => Role::Opener,
+            Anchor => Role::TopOrder,
+            Finisher => Role::MiddleOrder,
+            WicketKeeper => Role::WicketKeeper,
+            AllRounder => Role::AllRounder,
+            StrikeBowler | ContainingBowler => Role::PaceBowler,
+            Spinner => Role::SpinBowler,
+        }
+    }
+
+    fn base(&self) -> Base {
+        use Archetype::*;
+        // Batters bowl as part-timers
+        let (bowl_sr, bowl_rpb) = match self {
+            AllRounder => (45., 0.85),
+            StrikeBowler => (30., 0.9),
+            ContainingBowler => (42., 0.75),
+            Spinner => (38., 0.8),
+            _ => (80., 1.1),
+        };
+        let (avg, sr, boundaries, sixes) = match self {
+            Opener => (38., 80., 0.5, 0.15),
+            Anchor => (42., 75., 0.45, 0.1),
+            Finisher => (30., 110., 0.6, 0.35),
+            WicketKeeper => (32., 85., 0.5, 0.2),
+            AllRounder => (28., 88., 0.5, 0.25),
+            StrikeBowler | ContainingBowler => (12., 65., 0.4, 0.15),
+            Spinner => (15., 70., 0.4, 0.15),
+        };
+        Base {
+            avg,
+            sr,
+            boundaries,
+            sixes,
+            bowl_sr,
+            bowl_rpb,
+        }
+    }
+
+    /// The bowling style of a generated player, if they bowl
+    fn style(&self, rng: &mut dyn RngCore) -> Option<BowlingStyle> {
+        use BowlingStyle::*;
+        let pace = [RightFast, RightFastMedium, LeftFast, LeftFastMedium];
+        let spin = [OffBreak, LegBreak, SlowLeftArm, LeftArmWrist];
+        match self {
+            Archetype::StrikeBowler => [RightFast, LeftFast].choose(rng).copied(),
+            Archetype::ContainingBowler => [RightFastMedium, RightMedium, LeftMedium]
+                .choose(rng)
+                .copied(),
+            Archetype::Spinner => spin.choose(rng).copied(),
+            Archetype::AllRounder => [pace, spin].concat().choose(rng).copied(),
+            _ => [OffBreak, RightMedium, LegBreak].choose(rng).copied(),
+        }
+    }
+}
+
+/// The archetypes of a generated XI, in batting order. Five of them bowl.
+pub const SQUAD: [Archetype; 11] = [
+    Archetype::Opener,
+    Archetype::Opener,
+    Archetype::Anchor,
+    Archetype::Anchor,
+    Archetype::Finisher,
+    Archetype::WicketKeeper,
+    Archetype::AllRounder,
+    Archetype::Spinner,
+    Archetype::StrikeBowler,
+    Archetype::StrikeBowler,
+    Archetype::ContainingBowler,
+];
+
+/// How players are generated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratorConfig {
+    /// The relative weights of the archetypes of players generated at random
+    pub archetypes: Vec<(f32, Archetype)>,
+    /// The shape of the gamma distribution of talent, whose mean is 1. Smaller shapes give
+    /// a wider spread with a longer tail of stars.
+    pub talent_shape: f32,
+    /// The fraction of players who bat left-handed
+    pub left_handed: f64,
+    pub first_names: Vec<String>,
+    pub surnames: Vec<String>,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        use Archetype::*;
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        Self {
+            archetypes: vec![
+                (2., Opener),
+                (2., Anchor),
+                (1., Finisher),
+                (1., WicketKeeper),
+                (1., AllRounder),
+                (2., StrikeBowler),
+                (1., ContainingBowler),
+                (1., Spinner),
+            ],
+            talent_shape: 4.,
+            left_handed: 0.25,
+            first_names: names(&[
+                "Alex", "Ben", "Chris", "Dan", "Eoin", "Faf", "Gus", "Hashim", "Imran", "Jos",
+                "Kane", "Lasith", "Mitch", "Nathan", "Ollie", "Pat", "Quinton", "Rashid", "Steve",
+                "Tim", "Usman", "Virat", "Wanindu", "Zak",
+            ]),
+            surnames: names(&[
+                "Abbott", "Brooks", "Carter", "Dhillon", "Edwards", "Fernando", "Green", "Hussain",
+                "Iqbal", "Jones", "Khan", "Latham", "Morris", "Naidu", "Owens", "Patel", "Rahman",
+                "Singh", "Taylor", "Umar", "Vaughan", "Walker", "Young", "Zaidi",
+            ]),
+        }
+    }
+}
+
+/// Generates fictional players with ratings drawn around those of their archetype
+#[derive(Debug, Clone)]
+pub struct PlayerGenerator {
+    config: GeneratorConfig,
+    talent: Gamma<f32>,
+}
+
+impl PlayerGenerator {
+    pub fn new(config: GeneratorConfig) -> Self {
+        // Scale the distribution so that an average player has a talent of 1
+        let shape = config.talent_shape.max(0.1);
+        let talent = Gamma::new(shape, 1. / shape).expect("valid gamma parameters");
+        Self { config, talent }
+    }
+
+    /// A random name from the configured lists
+    pub fn name(&self, rng: &mut dyn RngCore) -> String {
+        let first = self.config.first_names.choose(rng).map_or("", |s| s);
+        let last = self.config.surnames.choose(rng).map_or("", |s| s);
+        format!("{} {}", first, last).trim().to_string()
+    }
+
+    /// The ratings of a player of the archetype with the given talent, where 1 is average
+    pub fn rating(&self, archetype: Archetype, talent: f32) -> PlayerRatingNaiveStats {
+        let base = archetype.base();
+        let talent = talent.max(0.05);
+        let sr = base.sr * (0.85 + 0.15 * talent);
+        let runs_per_ball = 0.01 * sr;
+        let batting = BatRatingNaiveStats {
+            avg: (base.avg * talent).max(1.),
+            sr,
+            r4: runs_per_ball * base.boundaries * (1. - base.sixes) / 4.,
+            r6: runs_per_ball * base.boundaries * base.sixes / 6.,
+        };
+        let bowl_sr = (base.bowl_sr / talent.sqrt()).max(6.);
+        let bowling = BowlRatingNaiveStats {
+            sr: bowl_sr,
+            avg: bowl_sr * base.bowl_rpb * (1.15 - 0.15 * talent).max(0.5),
+            wide_rate: default_wide_rate(),
+            no_ball_rate: default_no_ball_rate(),
+        };
+        PlayerRatingNaiveStats {
+            batting,
+            bowling,
+            fielding: FieldRating::default(),
+            running: RunningRating::default(),
+        }
+    }
+
+    /// Add a generated player of the archetype to the database
+    pub fn player(
+        &self,
+        rng: &mut dyn RngCore,
+        db: &mut PlayerDb<PlayerRatingNaiveStats>,
+        archetype: Archetype,
+    ) -> Result<PlayerId> {
+        let talent = self.talent.sample(rng);
+        let name = self.name(rng);
+        let id = db.add(name, self.rating(archetype, talent))?.id;
+        let bats = if rng.gen_bool(self.config.left_handed) {
+            Hand::Left
+        } else {
+            Hand::Right
+        };
+        let bowls = archetype.style(rng);
+        if let Some(player) = db.get_mut(id) {
+            player.bats = bats;
+            player.bowls = bowls;
+            player.role = Some(archetype.role());
+        }
+        Ok(id)
+    }
+
+    /// Add a generated player of a random archetype to the database. It's an error if the
+    /// configured archetypes have no positive weight.
+    pub fn random_player(
+        &self,
+        rng: &mut dyn RngCore,
+        db: &mut PlayerDb<PlayerRatingNaiveStats>,
+    ) -> Result<(PlayerId, Archetype)> {
+        let archetype = try_choose(rng, &self.config.archetypes)?;
+        Ok((self.player(rng, db, archetype)?, archetype))
+    }
+
+    /// A balanced XI of generated players, with the roles of `SQUAD`
+    pub fn squad(
+        &self,
+        rng: &mut dyn RngCore,
+        db: &mut PlayerDb<PlayerRatingNaiveStats>,
+        id: u16,
+        name: &str,
+    ) -> Result<Team> {
+        let players = SQUAD
+            .iter()
+            .map(|&archetype| {
+                let id = self.player(rng, db, archetype)?;
+                let name = db.get(id).map(|p| p.name.clone()).unwrap_or_default();
+                Ok((id, name, archetype.role()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Team::with_roles(id, name.to_string(), players))
+    }
+}
+
+impl Default for PlayerGenerator {
+    fn default() -> Self {
+        Self::new(GeneratorConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, form, game, model, morale, seed};
+
+    #[test]
+    fn generated_squads() -> Result<()> {
+        use model::NaiveStatsModel;
+        let mut rng = seed::rng(7);
+        let generator = PlayerGenerator::default();
+        let mut db = PlayerDb::new();
+        let home = generator.squad(&mut rng, &mut db, 0, "Home")?;
+        let away = generator.squad(&mut rng, &mut db, 1, "Away")?;
+        home.validate(&db)?;
+        for (id, _) in home.players.iter().chain(&away.players) {
+            db.get(*id).unwrap().rating.validate()?;
+        }
+        let (id, _) = generator.random_player(&mut rng, &mut db)?;
+        assert!(db.get(id).is_some());
+        let mut state = game::GameState::new(form::Form::t20(), home, away)?;
+        state.simulate_to_completion(&NaiveStatsModel {}, &db, &mut rng)?;
+        assert!(state.result().is_some());
+        let config = morale::MoraleConfig::default();
+        morale::update_morale(&mut db, &state, &config);
+        let scorecard = state.scorecard()?;
+        // Judge the batters who didn't also bowl
+        let bowled = |id| scorecard.innings[1].bowling.iter().any(|b| b.player == id);
+        for bat in scorecard.innings[0]
+            .batting
+            .iter()
+            .filter(|b| !bowled(b.player))
+        {
+            let morale = db.get(bat.player).unwrap().morale;
+            assert_eq!(morale.value() > 0., bat.runs as f32 > config.expected_runs);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_archetype_weights() {
+        let mut rng = seed::rng(3);
+        let mut db = PlayerDb::new();
+        for archetypes in [
+            vec![],
+            vec![(0., Archetype::Opener)],
+            vec![(-1., Archetype::Spinner)],
+        ] {
+            let generator = PlayerGenerator::new(GeneratorConfig {
+                archetypes,
+                ..GeneratorConfig::default()
+            });
+            assert!(matches!(
+                generator.random_player(&mut rng, &mut db),
+                Err(Error::InvalidWeights(_))
+            ));
+        }
+        // The squad doesn't depend on the weights
+        let generator = PlayerGenerator::new(GeneratorConfig {
+            archetypes: Vec::new(),
+            ..GeneratorConfig::default()
+        });
+        assert!(generator.squad(&mut rng, &mut db, 0, "Home").is_ok());
+    }
+}
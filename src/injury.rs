@@ -0,0 +1,345 @@
+//! Injuries to players, which can end their part in a match and keep them out of the
+//! matches that follow
+use crate::{
+    error::{Error, Result},
+    game::{GameState, MatchResult},
+    model::{Model, PlayerRating},
+    player::{BowlingStyle, PlayerDb, PlayerId},
+    strategy::{SelectionContext, SelectionStrategy},
+    team::{Squad, Team},
+};
+use fnv::FnvHashMap;
+use rand::Rng;
+use rand_distr::{Distribution, Exp};
+use serde::{Deserialize, Serialize};
+
+/// How likely players are to be injured, and for how long
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InjuryConfig {
+    /// The chance of a fresh spinner being injured on a delivery they bowl
+    pub bowling_risk: f32,
+    /// How many times more likely a pace bowler is to be injured than a spinner
+    pub pace_factor: f32,
+    /// How many times more likely an exhausted bowler is to be injured than a fresh one
+    pub fatigue_factor: f32,
+    /// The overs a bowler can have bowled recently before their risk starts to rise
+    pub workload_threshold: f32,
+    /// The increase in risk, as a fraction, for each recent over above the threshold
+    pub workload_factor: f32,
+    /// The fraction of a bowler's recent workload that carries over to their next match
+    pub workload_decay: f32,
+    /// The chance of the striker being injured on a delivery
+    pub batting_risk: f32,
+    /// The mean number of further matches missed through an injury. Some injuries keep a
+    /// player out of no more than the match they are injured in.
+    pub mean_matches_out: f32,
+}
+
+impl Default for InjuryConfig {
+    fn default() -> Self {
+        Self {
+            bowling_risk: 5e-5,
+            pace_factor: 3.,
+            fatigue_factor: 3.,
+            workload_threshold: 40.,
+            workload_factor: 0.05,
+            workload_decay: 0.6,
+            batting_risk: 2e-5,
+            mean_matches_out: 2.,
+        }
+    }
+}
+
+impl InjuryConfig {
+    /// The chance of a bowler being injured on a delivery, given their style, how tired
+    /// they are from 0 to 1, and their recent workload in overs
+    pub fn bowler_risk(&self, style: Option<BowlingStyle>, fatigue: f32, workload: f32) -> f32 {
+        let pace = match style {
+            Some(style) if style.spin() => 1.,
+            _ => self.pace_factor,
+        };
+        let fatigue = 1. + (self.fatigue_factor - 1.) * fatigue.clamp(0., 1.);
+        let workload = 1. + self.workload_factor * (workload - self.workload_threshold).max(0.);
+        (self.bowling_risk * pace * fatigue * workload).min(1.)
+    }
+
+    /// The number of further matches an injury keeps a player out of
+    pub fn matches_out(&self, rng: &mut impl Rng) -> u16 {
+        match Exp::new(1. / self.mean_matches_out.max(0.01)) {
+            Ok(exp) => exp.sample(rng).floor() as u16,
+            Err(_) => 0,
+        }
+    }
+}
+
+/// An injury suffered during a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Injury {
+    pub player: PlayerId,
+    /// The number of their side's following matches the player will miss
+    pub matches_out: u16,
+}
+
+/// Which players are fit to play, and how much bowling each has done recently
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Availability {
+    /// The number of their side's matches each injured player has still to miss
+    injured: FnvHashMap<PlayerId, u16>,
+    /// The recent overs bowled by each bowler, decayed with each match
+    workload: FnvHashMap<PlayerId, f32>,
+}
+
+impl Availability {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the player is fit to play
+    pub fn available(&self, id: PlayerId) -> bool {
+        !self.injured.contains_key(&id)
+    }
+
+    /// The number of further matches the player is out for, if injured
+    pub fn matches_out(&self, id: PlayerId) -> Option<u16> {
+        self.injured.get(&id).copied()
+    }
+
+    /// The recent workload of a bowler, in overs
+    pub fn workload(&self, id: PlayerId) -> f32 {
+        self.workload.get(&id).copied().unwrap_or(0.)
+    }
+
+    /// Record an injury. A player who is already injured is out for the longer of the
+    /// two injuries.
+    pub fn injure(&mut self, injury: &Injury) {
+        if injury.matches_out > 0 {
+            let out = self.injured.entry(injury.player).or_insert(0);
+            *out = (*out).max(injury.matches_out);
+        }
+    }
+
+    /// Pick the XI for a match from a squad, leaving out anyone injured. Each injured
+    /// member of the squad misses the match, bringing them a match closer to fitness.
+    pub fn select_xi(
+        &mut self,
+        squad: &Squad,
+        strategy: &dyn SelectionStrategy,
+        context: &SelectionContext,
+    ) -> Result<Team> {
+        let xi = strategy.select(squad, context, &|id| self.available(id))?;
+        for (id, _, _) in &squad.players {
+            if let Some(out) = self.injured.get_mut(id) {
+                *out -= 1;
+                if *out == 0 {
+                    self.injured.remove(id);
+                }
+            }
+        }
+        Ok(xi)
+    }
+
+    /// Add the overs bowled in a completed match to the workloads of its players, after
+    /// decaying their earlier workload
+    pub fn record_match(&mut self, game: &GameState, config: &InjuryConfig) {
+        let (team_a, team_b) = game.teams();
+        for (id, _) in team_a.players.iter().chain(&team_b.players) {
+            if let Some(workload) = self.workload.get_mut(id) {
+                *workload *= config.workload_decay;
+            }
+        }
+        let balls_per_over = game.form().balls_per_over as f32;
+        for innings in game.completed_innings() {
+            for (id, bowl) in innings.bowling_stats.bowlers() {
+                *self.workload.entry(*id).or_insert(0.) += bowl.balls as f32 / balls_per_over;
+            }
+        }
+    }
+}
+
+/// Simulate the rest of a match in which players can be injured. After each delivery the
+/// bowler and the striker may be injured, with the bowler's risk rising with their
+/// fatigue and recent workload. An injured player is replaced by a substitute from their
+/// side's squad if one is available, and plays on otherwise. Returns the result and the
+/// injuries suffered, which are also recorded in `availability`.
+pub fn simulate_with_injuries<R: PlayerRating>(
+    game: &mut GameState,
+    model: &(impl Model<R> + ?Sized),
+    db: &PlayerDb<R>,
+    rng: &mut impl Rng,
+    config: &InjuryConfig,
+    availability: &mut Availability,
+    squads: &[Squad],
+) -> Result<(MatchResult, Vec<Injury>)> {
+    let mut injuries = Vec::new();
+    while !game.complete() {
+        let (bowler, bowler_risk, striker) = {
+            let snapshot = game.snapshot(db)?;
+            let bowler = snapshot.bowler.id;
+            let risk = config.bowler_risk(
+                snapshot.bowler.bowls,
+                snapshot.bowler_fatigue,
+                availability.workload(bowler),
+            );
+            (bowler, risk, snapshot.striker.id)
+        };
+        game.simulate_delivery(model, db, rng)?;
+        let mut injured = Vec::new();
+        if rng.gen::<f32>() < bowler_risk {
+            injured.push(bowler);
+        }
+        if rng.gen::<f32>() < config.batting_risk {
+            injured.push(striker);
+        }
+        for player in injured {
+            let injury = Injury {
+                player,
+                matches_out: config.matches_out(rng),
+            };
+            availability.injure(&injury);
+            injuries.push(injury);
+            if !game.complete() {
+                bring_on_substitute(game, player, availability, squads)?;
+            }
+        }
+    }
+    let result = game.result().ok_or(Error::MatchInProgress)?;
+    availability.record_match(game, config);
+    Ok((result, injuries))
+}
+
+/// Replace an injured player with the best available reserve of their side, if any
+fn bring_on_substitute(
+    game: &mut GameState,
+    player: PlayerId,
+    availability: &Availability,
+    squads: &[Squad],
+) -> Result<()> {
+    let (team_a, team_b) = game.teams();
+    let side = if team_a.get_name(player).is_some() {
+        team_a
+    } else {
+        team_b
+    };
+    let reserve = squads
+        .iter()
+        .find(|squad| squad.id == side.id)
+        .and_then(|squad| {
+            squad.replacement(side, side.role(player), |id| availability.available(id))
+        });
+    match reserve {
+        Some(reserve) => game.substitute(side.id, player, reserve),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        form, game,
+        model::{self, PlayerRatingNull},
+        player::PlayerDb,
+        tests::test_team,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn pace_and_workload_raise_risk() {
+        let config = InjuryConfig::default();
+        let spin = config.bowler_risk(Some(BowlingStyle::OffBreak), 0., 0.);
+        let pace = config.bowler_risk(Some(BowlingStyle::RightFast), 0., 0.);
+        let tired = config.bowler_risk(Some(BowlingStyle::RightFast), 1., 0.);
+        let overworked = config.bowler_risk(Some(BowlingStyle::RightFast), 0., 80.);
+        assert!(spin < pace && pace < tired && pace < overworked);
+    }
+
+    #[test]
+    fn injured_players_miss_matches() -> Result<()> {
+        use crate::{player::Role, strategy::BalancedSelection};
+        let roles = [
+            Role::Opener,
+            Role::Opener,
+            Role::TopOrder,
+            Role::TopOrder,
+            Role::MiddleOrder,
+            Role::WicketKeeper,
+            Role::AllRounder,
+            Role::PaceBowler,
+            Role::PaceBowler,
+            Role::PaceBowler,
+            Role::SpinBowler,
+            Role::PaceBowler,
+        ];
+        let players = roles
+            .iter()
+            .enumerate()
+            .map(|(i, &role)| (PlayerId(i as u32), format!("player_{}", i), role))
+            .collect();
+        let squad = Squad::new(0, "squad".to_string(), players);
+        let (strategy, context) = (BalancedSelection::default(), SelectionContext::default());
+        let mut availability = Availability::new();
+        let picked = |xi: &Team, id| xi.get_name(PlayerId(id)).is_some();
+        availability.injure(&Injury {
+            player: PlayerId(7),
+            matches_out: 2,
+        });
+        let xi = availability.select_xi(&squad, &strategy, &context)?;
+        assert!(!picked(&xi, 7) && picked(&xi, 11));
+        assert_eq!(availability.matches_out(PlayerId(7)), Some(1));
+        let xi = availability.select_xi(&squad, &strategy, &context)?;
+        assert!(!picked(&xi, 7));
+        assert!(availability.available(PlayerId(7)));
+        let xi = availability.select_xi(&squad, &strategy, &context)?;
+        assert!(picked(&xi, 7) && !picked(&xi, 11));
+        Ok(())
+    }
+
+    #[test]
+    fn injured_players_substituted() -> Result<()> {
+        use model::{Model, NullModel};
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "AFG")?;
+        let team_b = test_team(&mut db, 2, "IRE")?;
+        let batter = db
+            .add("sub_bat".to_string(), PlayerRatingNull::default())?
+            .id;
+        let bowler = db
+            .add("sub_bowl".to_string(), PlayerRatingNull::default())?
+            .id;
+        let db = db;
+        let mut state = game::GameState::new(form::Form::t20(), team_a.clone(), team_b.clone())?;
+        let mut rng = thread_rng();
+        let model = NullModel {};
+        for _ in 0..3 {
+            let ball = model.generate_delivery(&mut rng, state.snapshot(&db)?);
+            state.update(&ball)?;
+        }
+        let (striker, current_bowler) = {
+            let snapshot = state.snapshot(&db)?;
+            (snapshot.striker.id, snapshot.bowler.id)
+        };
+        state.substitute(team_a.id, striker, (batter, "sub_bat".to_string()))?;
+        state.substitute(team_b.id, current_bowler, (bowler, "sub_bowl".to_string()))?;
+        assert!(state
+            .substitute(
+                team_b.id,
+                team_b.players[0].0,
+                (batter, "sub_bat".to_string())
+            )
+            .is_err());
+        {
+            let snapshot = state.snapshot(&db)?;
+            assert_eq!(snapshot.bowler.id, bowler);
+            assert!(snapshot.striker.id == batter || snapshot.non_striker.id == batter);
+        }
+        state.simulate_to_completion(&model, &db, &mut rng)?;
+        assert!(state.teams().0.get_name(striker).is_none());
+
+        let log = state.log();
+        assert!(log.team_a.get_name(striker).is_some());
+        let replayed = game::GameState::replay(&log, None)?;
+        assert_eq!(replayed.result(), state.result());
+        assert_eq!(replayed.innings_total(0), state.innings_total(0));
+        Ok(())
+    }
+}
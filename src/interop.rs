@@ -0,0 +1,3 @@
+//! Conversion to and from data formats used by other cricket tools
+pub mod cricsheet;
+pub mod statsguru;
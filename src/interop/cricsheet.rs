@@ -0,0 +1,368 @@
+//! Import and export of matches in the Cricsheet JSON format
+//! (<https://cricsheet.org/format/json/>), and import from the older YAML format
+//! (<https://cricsheet.org/format/yaml/>)
+use crate::{
+    error::{Error, Result},
+    form::Form,
+    game::{
+        stats::DeliveryRecord, DeliveryOutcome, Dismissal, Extra, GameState, Margin, MatchResult,
+        Runs,
+    },
+    player::PlayerId,
+    team::Team,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io::Write};
+
+mod ingest;
+mod yaml;
+
+/// The version of the Cricsheet format that is produced
+pub const DATA_VERSION: &str = "1.1.0";
+
+/// A full match in the Cricsheet format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CricsheetMatch {
+    pub meta: Meta,
+    pub info: Info,
+    pub innings: Vec<Innings>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Meta {
+    pub data_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default)]
+    pub revision: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Info {
+    pub balls_per_over: u8,
+    pub match_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overs: Option<u16>,
+    pub teams: Vec<String>,
+    /// The players of each team, by team name
+    pub players: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub registry: Registry,
+    #[serde(default)]
+    pub outcome: Outcome,
+}
+
+/// Maps player names to unique identifiers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Registry {
+    pub people: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Outcome {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub by: Option<OutcomeBy>,
+    /// Set for results without a winner, e.g. "draw" or "tie"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    /// How the result was decided, if not in the usual way, e.g. "Awarded"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OutcomeBy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runs: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wickets: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub innings: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Innings {
+    pub team: String,
+    #[serde(default)]
+    pub overs: Vec<Over>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub declared: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub forfeited: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Over {
+    pub over: u16,
+    pub deliveries: Vec<Delivery>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub batter: String,
+    pub bowler: String,
+    pub non_striker: String,
+    pub runs: DeliveryRuns,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extras: Option<DeliveryExtras>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wickets: Vec<Wicket>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryRuns {
+    pub batter: u8,
+    pub extras: u8,
+    pub total: u8,
+    /// Set when four or six runs were run rather than scored as a boundary
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub non_boundary: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeliveryExtras {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byes: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legbyes: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noballs: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wides: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub penalty: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Wicket {
+    pub player_out: String,
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fielders: Vec<Fielder>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fielder {
+    pub name: String,
+}
+
+impl CricsheetMatch {
+    /// Convert a completed match to the Cricsheet format
+    pub fn from_game(game: &GameState) -> Result<Self> {
+        let result = game.result().ok_or(Error::MatchInProgress)?;
+        let (team_a, team_b) = game.teams();
+        let form = game.form();
+
+        let mut players = BTreeMap::new();
+        let mut people = BTreeMap::new();
+        for team in [team_a, team_b] {
+            players.insert(
+                team.name.clone(),
+                team.players.iter().map(|(_, name)| name.clone()).collect(),
+            );
+            for (id, name) in &team.players {
+                people.insert(name.clone(), registry_id(*id));
+            }
+        }
+
+        let innings = game
+            .completed_innings()
+            .iter()
+            .map(|st| {
+                let batting_team = game.team(st.batting_team);
+                Ok(Innings {
+                    team: batting_team.name.clone(),
+                    overs: export_overs(&st.deliveries, batting_team, game.team(st.bowling_team))?,
+                    declared: st.declared,
+                    forfeited: st.forfeited,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            meta: Meta {
+                data_version: DATA_VERSION.to_string(),
+                created: None,
+                revision: 1,
+            },
+            info: Info {
+                balls_per_over: form.balls_per_over,
+                match_type: match_type(form).to_string(),
+                overs: form.overs_per_innings,
+                teams: vec![team_a.name.clone(), team_b.name.clone()],
+                players,
+                registry: Registry { people },
+                outcome: export_outcome(&result, team_a, team_b),
+            },
+            innings,
+        })
+    }
+
+    /// Write the match as Cricsheet JSON
+    pub fn write_json<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+}
+
+/// A stable registry identifier for a player, in the style of Cricsheet's hex IDs
+fn registry_id(id: PlayerId) -> String {
+    format!("{:08x}", id.0)
+}
+
+/// The Cricsheet match type that best describes the format
+fn match_type(form: &Form) -> &'static str {
+    match form.overs_per_innings {
+        None if form.innings > 1 => "Test",
+        None => "MDM",
+        Some(50) => "ODI",
+        Some(20) => "T20",
+        Some(_) => "ODM",
+    }
+}
+
+fn export_outcome(result: &MatchResult, team_a: &Team, team_b: &Team) -> Outcome {
+    match result {
+        MatchResult::Win { winner, margin } => {
+            let winner = if *winner == team_a.id {
+                &team_a.name
+            } else {
+                &team_b.name
+            };
+            let by = match margin {
+                Margin::Runs(runs) => Some(OutcomeBy {
+                    runs: Some(*runs),
+                    ..Default::default()
+                }),
+                Margin::Wickets(wickets) => Some(OutcomeBy {
+                    wickets: Some(*wickets),
+                    ..Default::default()
+                }),
+                Margin::InningsAndRuns(runs) => Some(OutcomeBy {
+                    runs: Some(*runs),
+                    innings: Some(1),
+                    ..Default::default()
+                }),
+                Margin::Forfeit => None,
+            };
+            Outcome {
+                winner: Some(winner.clone()),
+                by,
+                method: (*margin == Margin::Forfeit).then(|| "Awarded".to_string()),
+                ..Default::default()
+            }
+        }
+        MatchResult::Tie => Outcome {
+            result: Some("tie".to_string()),
+            ..Default::default()
+        },
+        MatchResult::Draw => Outcome {
+            result: Some("draw".to_string()),
+            ..Default::default()
+        },
+        MatchResult::NoResult => Outcome {
+            result: Some("no result".to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+/// Group the deliveries of an innings into overs
+fn export_overs(
+    deliveries: &[DeliveryRecord],
+    batting: &Team,
+    bowling: &Team,
+) -> Result<Vec<Over>> {
+    let name = |team: &Team, id: PlayerId| -> Result<String> {
+        team.get_name(id)
+            .map(str::to_string)
+            .ok_or(Error::PlayerNotFound(id))
+    };
+    let mut overs: Vec<Over> = Vec::new();
+    // Cricsheet only records balls that were played
+    for record in deliveries.iter().filter(|r| !r.outcome.is_dead_ball()) {
+        let delivery = Delivery {
+            batter: name(batting, record.striker)?,
+            bowler: name(bowling, record.bowler)?,
+            non_striker: name(batting, record.non_striker)?,
+            runs: export_runs(&record.outcome),
+            extras: export_extras(&record.outcome),
+            wickets: record
+                .outcome
+                .wicket
+                .iter()
+                .map(|(id, dismissal)| Ok(export_wicket(name(batting, *id)?, dismissal)))
+                .collect::<Result<_>>()?,
+        };
+        match overs.last_mut() {
+            Some(over) if over.over == record.overs.completed => over.deliveries.push(delivery),
+            _ => overs.push(Over {
+                over: record.overs.completed,
+                deliveries: vec![delivery],
+            }),
+        }
+    }
+    Ok(overs)
+}
+
+fn export_runs(outcome: &DeliveryOutcome) -> DeliveryRuns {
+    let batter = outcome.batter_runs();
+    let total = outcome.runs.runs() + outcome.extras.iter().map(Extra::runs).sum::<u8>();
+    DeliveryRuns {
+        batter,
+        extras: total - batter,
+        total,
+        non_boundary: matches!(outcome.runs, Runs::Running(4) | Runs::Running(6)),
+    }
+}
+
+fn export_extras(outcome: &DeliveryOutcome) -> Option<DeliveryExtras> {
+    let mut out = DeliveryExtras::default();
+    for extra in &outcome.extras {
+        // Overthrows are counted as what the batters were running for
+        let category = match extra {
+            Extra::Overthrows(_) => match outcome.overthrows_scored_as() {
+                Some(scored_as) => scored_as,
+                None => continue,
+            },
+            extra => extra,
+        };
+        let field = match category {
+            Extra::Bye(_) => &mut out.byes,
+            Extra::LegBye(_) => &mut out.legbyes,
+            Extra::NoBall => &mut out.noballs,
+            Extra::Wide(_) => &mut out.wides,
+            Extra::Penalty(_) => &mut out.penalty,
+            Extra::Overthrows(_) => continue,
+        };
+        *field = Some(field.unwrap_or(0) + extra.runs());
+    }
+    let fields = [out.byes, out.legbyes, out.noballs, out.wides, out.penalty];
+    fields.iter().any(Option::is_some).then_some(out)
+}
+
+fn export_wicket(player_out: String, dismissal: &Dismissal) -> Wicket {
+    use Dismissal::*;
+    let (kind, fielder) = match dismissal {
+        Bowled { .. } => ("bowled", None),
+        CaughtAndBowled { .. } => ("caught and bowled", None),
+        // Older logs recorded a catch by the bowler as an ordinary catch
+        Caught { caught, bowler } if caught == bowler => ("caught and bowled", None),
+        Caught { caught, .. } => ("caught", Some(caught)),
+        CaughtBehind { keeper, .. } => ("caught", Some(keeper)),
+        Lbw { .. } => ("lbw", None),
+        RunOutStriker(fielder) | RunOutNonStriker(fielder) => ("run out", Some(fielder)),
+        Stumped { keeper, .. } => ("stumped", Some(keeper)),
+        HitWicket { .. } => ("hit wicket", None),
+    };
+    Wicket {
+        player_out,
+        kind: kind.to_string(),
+        fielders: fielder
+            .map(|name| Fielder { name: name.clone() })
+            .into_iter()
+            .collect(),
+    }
+}
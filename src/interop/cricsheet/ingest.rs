@@ -0,0 +1,367 @@
+//! Reading Cricsheet matches and replaying them through the engine
+use super::{CricsheetMatch, Delivery, Wicket};
+use crate::{
+    conditions::BallType,
+    error::{Error, Result},
+    form::Form,
+    game::{DeliveryOutcome, Dismissal, Extra, Extras, GameState, Runs},
+    model::PlayerRating,
+    player::{PlayerDb, PlayerId},
+    team::Team,
+};
+use std::{io::Read, sync::Arc};
+
+impl CricsheetMatch {
+    /// Parse a match from Cricsheet JSON
+    pub fn from_json<R: Read>(reader: R) -> Result<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// The closest Form to the format of the match. Time limits and over rate rules are
+    /// not applied, since they can't be reconstructed from the data. Cricsheet doesn't
+    /// record the follow-on margin, so the follow-on is taken from the order of the
+    /// innings: enforced by any deficit if the side that batted second batted again, and
+    /// never enforced otherwise.
+    pub fn form(&self) -> Form {
+        let limited = self.info.overs.is_some();
+        let followed_on = match self.innings.as_slice() {
+            [_, second, third, ..] => second.team == third.team,
+            _ => false,
+        };
+        Form {
+            innings: if limited { 1 } else { 2 },
+            overs_per_innings: self.info.overs,
+            balls_per_over: self.info.balls_per_over,
+            days: None,
+            over_rate: None,
+            follow_on: if !limited && followed_on {
+                Some(0)
+            } else {
+                None
+            },
+            ball_type: if limited {
+                BallType::WhiteLeather
+            } else {
+                BallType::RedLeather
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Build the two teams of the match, adding their players to the database with the
+    /// default rating. The first team returned is the one that batted first.
+    pub fn build_teams<R>(&self, db: &mut PlayerDb<R>) -> Result<(Team, Team)>
+    where
+        R: PlayerRating + Default,
+    {
+        let first = self
+            .innings
+            .first()
+            .map(|inn| inn.team.as_str())
+            .ok_or_else(|| Error::MissingData("Match has no innings".into()))?;
+        let mut names: Vec<&String> = self.info.teams.iter().collect();
+        if names.len() != 2 {
+            return Err(Error::MissingData("Match must have two teams".into()));
+        }
+        if names[0] != first {
+            names.swap(0, 1);
+        }
+        let mut build = |id: u16, name: &String| -> Result<Team> {
+            let players = self
+                .info
+                .players
+                .get(name)
+                .ok_or_else(|| Error::MissingData(format!("No players for {}", name)))?
+                .iter()
+                .map(|player| {
+                    let added = db.add(player.clone(), R::default())?;
+                    Ok((added.id, added.name.clone()))
+                })
+                .collect::<Result<_>>()?;
+            // The keeper and captain aren't recorded by Cricsheet
+            Ok(Team {
+                id,
+                name: name.clone(),
+                players,
+                keeper: None,
+                captain: None,
+                home: None,
+                roles: Default::default(),
+            })
+        };
+        Ok((build(0, names[0])?, build(1, names[1])?))
+    }
+
+    /// Replay every delivery of the match through a new game state, checking that the
+    /// engine reproduces the total of each innings.
+    pub fn replay(&self, team_a: Team, team_b: Team) -> Result<GameState> {
+        let team_a = Arc::new(team_a);
+        let team_b = Arc::new(team_b);
+        let mut state = GameState::new(self.form(), team_a.clone(), team_b.clone())?;
+        for (i, innings) in self.innings.iter().enumerate() {
+            let (batting, bowling) = if innings.team == team_a.name {
+                (&team_a, &team_b)
+            } else {
+                (&team_b, &team_a)
+            };
+            if innings.forfeited {
+                state.forfeit_innings(batting)?;
+                continue;
+            }
+            let mut expected_runs: u16 = 0;
+            for over in &innings.overs {
+                for delivery in &over.deliveries {
+                    let striker = player_id(batting, &delivery.batter)?;
+                    let non_striker = player_id(batting, &delivery.non_striker)?;
+                    state.set_bowler(player_id(bowling, &delivery.bowler)?)?;
+                    state.set_batters(striker, non_striker)?;
+                    let outcome = import_delivery(delivery, batting, bowling)?;
+                    expected_runs += delivery.runs.total as u16;
+                    state.update(&outcome)?;
+                }
+            }
+            if innings.declared {
+                state.declare()?;
+            }
+            let (runs, _) = state
+                .innings_total(i)
+                .ok_or_else(|| mismatch(format!("innings {} was not played", i + 1)))?;
+            if runs != expected_runs {
+                return Err(mismatch(format!(
+                    "innings {} total of {} does not match the recorded {}",
+                    i + 1,
+                    runs,
+                    expected_runs
+                )));
+            }
+        }
+        Ok(state)
+    }
+}
+
+fn mismatch(message: String) -> Error {
+    Error::ReplayMismatch(message)
+}
+
+fn player_id(team: &Team, name: &str) -> Result<PlayerId> {
+    team.players
+        .iter()
+        .find(|(_, n)| n == name)
+        .map(|(id, _)| *id)
+        .ok_or_else(|| Error::MissingData(format!("{} is not in {}", name, team.name)))
+}
+
+/// Convert a recorded delivery to the engine's outcome
+fn import_delivery(delivery: &Delivery, batting: &Team, bowling: &Team) -> Result<DeliveryOutcome> {
+    let runs = match (delivery.runs.batter, delivery.runs.non_boundary) {
+        (4, false) => Runs::Four,
+        (6, false) => Runs::Six,
+        (n, _) => Runs::Running(n),
+    };
+    let mut extras = Extras::new();
+    if let Some(ex) = &delivery.extras {
+        if let Some(wides) = ex.wides {
+            extras.push(Extra::Wide(Runs::Running(wides.saturating_sub(1))));
+        }
+        if let Some(noballs) = ex.noballs {
+            extras.push(Extra::NoBall);
+            // Under older Laws byes and leg byes off a no-ball were scored as no-balls
+            if noballs > 1 {
+                extras.push(Extra::Bye(Runs::Running(noballs - 1)));
+            }
+        }
+        if let Some(byes) = ex.byes {
+            extras.push(Extra::Bye(Runs::Running(byes)));
+        }
+        if let Some(legbyes) = ex.legbyes {
+            extras.push(Extra::LegBye(Runs::Running(legbyes)));
+        }
+        if let Some(penalty) = ex.penalty {
+            extras.push(Extra::Penalty(penalty));
+        }
+    }
+    let wicket = match delivery.wickets.as_slice() {
+        [] => None,
+        [wicket] => Some((
+            player_id(batting, &wicket.player_out)?,
+            import_dismissal(wicket, delivery)?,
+        )),
+        _ => {
+            return Err(Error::Unsupported(
+                "multiple wickets on one delivery".into(),
+            ))
+        }
+    };
+    // Substitute fielders aren't in the side, so they go uncredited
+    let fielders = match delivery.wickets.first() {
+        Some(wicket) if wicket.kind == "caught and bowled" => {
+            vec![player_id(bowling, &delivery.bowler)?]
+        }
+        Some(wicket) => wicket
+            .fielders
+            .iter()
+            .filter_map(|fielder| player_id(bowling, &fielder.name).ok())
+            .collect(),
+        None => Vec::new(),
+    };
+    Ok(DeliveryOutcome {
+        wicket,
+        runs,
+        extras,
+        delivery: None,
+        fielding_error: None,
+        signal: None,
+        zone: None,
+        fielders,
+        run_out_end: None,
+    })
+}
+
+fn import_dismissal(wicket: &Wicket, delivery: &Delivery) -> Result<Dismissal> {
+    let bowler = delivery.bowler.clone();
+    let fielder = wicket
+        .fielders
+        .first()
+        .map(|f| f.name.clone())
+        .unwrap_or_default();
+    Ok(match wicket.kind.as_str() {
+        "bowled" => Dismissal::Bowled { bowler },
+        "caught" => Dismissal::Caught {
+            caught: fielder,
+            bowler,
+        },
+        "caught and bowled" => Dismissal::CaughtAndBowled { bowler },
+        "lbw" => Dismissal::Lbw { bowler },
+        "stumped" => Dismissal::Stumped {
+            keeper: fielder,
+            bowler,
+        },
+        "hit wicket" => Dismissal::HitWicket { bowler },
+        "run out" if wicket.player_out == delivery.non_striker => {
+            Dismissal::RunOutNonStriker(fielder)
+        }
+        "run out" => Dismissal::RunOutStriker(fielder),
+        kind => return Err(Error::Unsupported(format!("dismissal kind \"{}\"", kind))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::PlayerRatingNull;
+
+    fn replay_fixture(json: &str) -> Result<GameState> {
+        let imported = CricsheetMatch::from_json(json.as_bytes())?;
+        let mut db = PlayerDb::<PlayerRatingNull>::new();
+        let (team_a, team_b) = imported.build_teams(&mut db)?;
+        imported.replay(team_a, team_b)
+    }
+
+    /// The side batting in each innings started
+    fn batting_order(game: &GameState) -> Vec<u16> {
+        let completed = game.completed_innings().iter().map(|st| st.batting_team);
+        completed.chain(game.batting_team().map(|t| t.id)).collect()
+    }
+
+    #[test]
+    fn follow_on_from_innings_order() -> Result<()> {
+        // India trail by 204 after the first innings each time
+        let enforced = replay_fixture(include_str!("fixtures/follow_on_enforced.json"))?;
+        assert_eq!(batting_order(&enforced), vec![0, 1, 1]);
+        let declined = replay_fixture(include_str!("fixtures/follow_on_declined.json"))?;
+        assert_eq!(batting_order(&declined), vec![0, 1, 0, 1]);
+        assert_eq!(declined.innings_total(0), Some((204, 0)));
+        assert_eq!(declined.innings_total(1), Some((0, 10)));
+        Ok(())
+    }
+
+    #[test]
+    fn maidens_from_scorecard() -> Result<()> {
+        let game = replay_fixture(include_str!("fixtures/maidens.json"))?;
+        let scorecard = game.scorecard()?;
+        assert_eq!(
+            scorecard.summary.as_deref(),
+            Some("Sussex won by 10 wickets (with 15 balls remaining)")
+        );
+        let figures = |innings: usize| -> Vec<_> {
+            scorecard.innings[innings]
+                .bowling
+                .iter()
+                .map(|b| (b.name.clone(), b.overs.clone(), b.maidens, b.runs))
+                .collect()
+        };
+        let line = |name: &str, overs: &str, maidens, runs| {
+            (name.to_string(), overs.to_string(), maidens, runs)
+        };
+        // Byes and leg byes don't spoil Stone's first over, but the no-ball in his second
+        // and the wide in Tate's first do
+        assert_eq!(
+            figures(0),
+            [line("S Stone", "2", 1, 1), line("T Tate", "2", 1, 1)]
+        );
+        // Lowe concedes nothing off the bat in the unfinished last over
+        assert_eq!(
+            figures(1),
+            [line("K Keane", "1", 0, 5), line("L Lowe", "0.3", 0, 0)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn scorecard_from_fixture() -> Result<()> {
+        let game = replay_fixture(include_str!("fixtures/two_overs.json"))?;
+        let scorecard = game.scorecard()?;
+        assert_eq!(
+            scorecard.summary.as_deref(),
+            Some("Sussex won by 9 wickets (with 6 balls remaining)")
+        );
+        let kent = &scorecard.innings[0];
+        assert_eq!((kent.runs, kent.wickets, kent.overs.as_str()), (19, 3, "2"));
+        let batting: Vec<_> = kent
+            .batting
+            .iter()
+            .map(|b| (b.name.as_str(), b.runs, b.balls, b.dismissal.clone()))
+            .collect();
+        let out = |how: &str| Some(how.to_string());
+        assert_eq!(
+            batting[..5],
+            [
+                ("A Able", 1, 2, out("b T Tate")),
+                ("B Baker", 4, 3, out("c R Reed b S Stone")),
+                ("C Carter", 2, 4, out("runout (M Moore)")),
+                ("D Dixon", 6, 3, None),
+                ("E Evans", 1, 1, None),
+            ]
+        );
+        // The no-ball run off the bat is the batter's, and those run as byes under the
+        // older Laws are byes
+        let extras = &kent.extras;
+        let extras = (extras.byes, extras.leg_byes, extras.wides, extras.no_balls);
+        assert_eq!(extras, (2, 1, 1, 1));
+        let bowling: Vec<_> = kent
+            .bowling
+            .iter()
+            .map(|b| (b.name.as_str(), b.overs.as_str(), b.runs, b.wickets))
+            .collect();
+        assert_eq!(bowling, [("S Stone", "1", 9, 1), ("T Tate", "1", 7, 1)]);
+        let name = |id| game.team(0).get_name(id).unwrap();
+        let fall: Vec<_> = kent
+            .fall_of_wickets
+            .iter()
+            .map(|fow| (fow.wicket, fow.runs, name(fow.batter)))
+            .collect();
+        assert_eq!(
+            fall,
+            [(1, 9, "B Baker"), (2, 11, "A Able"), (3, 18, "C Carter")]
+        );
+
+        let sussex = &scorecard.innings[1];
+        assert_eq!(
+            (sussex.runs, sussex.wickets, sussex.overs.as_str()),
+            (20, 1, "1")
+        );
+        let reed = sussex.batting.iter().find(|b| b.name == "R Reed").unwrap();
+        assert_eq!((reed.runs, reed.balls), (5, 2));
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+//! Cricsheet's YAML files, which use an older layout than the JSON ones. Each delivery is
+//! keyed by its over and ball, e.g. `0.1`, and older files don't list the players.
+use super::{
+    CricsheetMatch, Delivery, DeliveryExtras, DeliveryRuns, Fielder, Info, Innings, Meta, Outcome,
+    Over, Registry, Wicket,
+};
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use std::{collections::BTreeMap, io::Read};
+
+/// The size of a side made up from the players who appear in the deliveries
+const PLAYERS_PER_SIDE: usize = 11;
+
+#[derive(Debug, Deserialize)]
+struct YamlMatch {
+    meta: YamlMeta,
+    info: YamlInfo,
+    /// Each innings keyed by its name, e.g. "1st innings"
+    innings: Vec<BTreeMap<String, YamlInnings>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YamlMeta {
+    /// A number such as 0.9 in older files
+    data_version: Value,
+    #[serde(default)]
+    created: Option<Value>,
+    #[serde(default)]
+    revision: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct YamlInfo {
+    #[serde(default = "default_balls_per_over")]
+    balls_per_over: u8,
+    match_type: String,
+    #[serde(default)]
+    overs: Option<u16>,
+    teams: Vec<String>,
+    #[serde(default)]
+    players: Option<BTreeMap<String, Vec<String>>>,
+    #[serde(default)]
+    registry: Registry,
+    #[serde(default)]
+    outcome: Outcome,
+}
+
+fn default_balls_per_over() -> u8 {
+    6
+}
+
+#[derive(Debug, Deserialize)]
+struct YamlInnings {
+    team: String,
+    /// Each delivery keyed by its over and ball
+    #[serde(default)]
+    deliveries: Vec<Mapping>,
+    #[serde(default)]
+    declared: bool,
+    #[serde(default)]
+    forfeited: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct YamlDelivery {
+    batsman: String,
+    bowler: String,
+    non_striker: String,
+    runs: YamlRuns,
+    #[serde(default)]
+    extras: Option<DeliveryExtras>,
+    #[serde(default)]
+    wicket: Option<YamlWicket>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YamlRuns {
+    batsman: u8,
+    extras: u8,
+    total: u8,
+    #[serde(default)]
+    non_boundary: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct YamlWicket {
+    player_out: String,
+    kind: String,
+    #[serde(default)]
+    fielders: Vec<String>,
+}
+
+impl CricsheetMatch {
+    /// Parse a match from Cricsheet YAML. If the file doesn't list the players, each
+    /// side is taken to be those who batted or bowled for it, in order of appearance,
+    /// made up to eleven with placeholders such as "England 11".
+    pub fn from_yaml<R: Read>(reader: R) -> Result<Self> {
+        let yaml: YamlMatch = serde_yaml::from_reader(reader)?;
+        let innings = yaml
+            .innings
+            .into_iter()
+            .flat_map(BTreeMap::into_values)
+            .map(import_innings)
+            .collect::<Result<Vec<_>>>()?;
+        let players = match yaml.info.players {
+            Some(players) => players,
+            None => appearances(&yaml.info.teams, &innings),
+        };
+        Ok(Self {
+            meta: Meta {
+                data_version: scalar(&yaml.meta.data_version),
+                created: yaml.meta.created.as_ref().map(scalar),
+                revision: yaml.meta.revision,
+            },
+            info: Info {
+                balls_per_over: yaml.info.balls_per_over,
+                match_type: yaml.info.match_type,
+                overs: yaml.info.overs,
+                teams: yaml.info.teams,
+                players,
+                registry: yaml.info.registry,
+                outcome: yaml.info.outcome,
+            },
+            innings,
+        })
+    }
+}
+
+/// A scalar such as a version number or date as a string
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+/// Group an innings' deliveries into overs by the whole part of their keys
+fn import_innings(yaml: YamlInnings) -> Result<Innings> {
+    let mut overs: Vec<Over> = Vec::new();
+    for entry in yaml.deliveries {
+        let (key, delivery) = entry
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::MissingData("Empty delivery".into()))?;
+        let over = match &key {
+            Value::Number(n) => n.as_f64().map(|ball| ball.trunc() as u16),
+            Value::String(s) => s.split('.').next().and_then(|over| over.parse().ok()),
+            _ => None,
+        }
+        .ok_or_else(|| Error::MissingData(format!("Invalid delivery key {}", scalar(&key))))?;
+        let delivery = import_delivery(serde_yaml::from_value(delivery)?);
+        match overs.last_mut() {
+            Some(last) if last.over == over => last.deliveries.push(delivery),
+            _ => overs.push(Over {
+                over,
+                deliveries: vec![delivery],
+            }),
+        }
+    }
+    Ok(Innings {
+        team: yaml.team,
+        overs,
+        declared: yaml.declared,
+        forfeited: yaml.forfeited,
+    })
+}
+
+fn import_delivery(yaml: YamlDelivery) -> Delivery {
+    Delivery {
+        batter: yaml.batsman,
+        bowler: yaml.bowler,
+        non_striker: yaml.non_striker,
+        runs: DeliveryRuns {
+            batter: yaml.runs.batsman,
+            extras: yaml.runs.extras,
+            total: yaml.runs.total,
+            non_boundary: yaml.runs.non_boundary,
+        },
+        extras: yaml.extras,
+        wickets: yaml
+            .wicket
+            .into_iter()
+            .map(|wicket| Wicket {
+                player_out: wicket.player_out,
+                kind: wicket.kind,
+                fielders: wicket
+                    .fielders
+                    .into_iter()
+                    .map(|name| Fielder { name })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+/// The players of each team who batted or bowled, in order of appearance, and then
+/// placeholders up to eleven. Fielders are left out, since they may be substitutes.
+fn appearances(teams: &[String], innings: &[Innings]) -> BTreeMap<String, Vec<String>> {
+    let mut players: BTreeMap<String, Vec<String>> = teams
+        .iter()
+        .map(|team| (team.clone(), Vec::new()))
+        .collect();
+    let mut add = |team: &str, name: &String| {
+        if let Some(names) = players.get_mut(team) {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+    };
+    for inn in innings {
+        let bowling = teams.iter().find(|team| **team != inn.team);
+        for delivery in inn.overs.iter().flat_map(|over| &over.deliveries) {
+            add(&inn.team, &delivery.batter);
+            add(&inn.team, &delivery.non_striker);
+            if let Some(bowling) = bowling {
+                add(bowling, &delivery.bowler);
+            }
+        }
+    }
+    for (team, names) in &mut players {
+        for n in names.len() + 1..=PLAYERS_PER_SIDE {
+            names.push(format!("{} {}", team, n));
+        }
+    }
+    players
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{model::PlayerRatingNull, player::PlayerDb};
+
+    #[test]
+    fn same_match_as_json() -> Result<()> {
+        let yaml = CricsheetMatch::from_yaml(include_str!("fixtures/two_overs.yaml").as_bytes())?;
+        let json = CricsheetMatch::from_json(include_str!("fixtures/two_overs.json").as_bytes())?;
+        assert_eq!(yaml.meta.data_version, "0.9");
+        assert_eq!(yaml.meta.created.as_deref(), Some("2019-06-02"));
+        // The players are made up from those who batted and bowled
+        assert_eq!(yaml.info.players, json.info.players);
+        assert_eq!(yaml.innings[1].overs[1].deliveries.len(), 1);
+        let scorecard = |imported: &CricsheetMatch| -> Result<String> {
+            let mut db = PlayerDb::<PlayerRatingNull>::new();
+            let (team_a, team_b) = imported.build_teams(&mut db)?;
+            let game = imported.replay(team_a, team_b)?;
+            Ok(serde_json::to_string(&game.scorecard()?)?)
+        };
+        assert_eq!(scorecard(&yaml)?, scorecard(&json)?);
+        Ok(())
+    }
+}
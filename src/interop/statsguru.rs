@@ -0,0 +1,219 @@
+//! Import of career records exported as CSV from ESPNcricinfo's Statsguru
+//!
+//! Batting exports need the columns `Player`, `Inns`, `NO`, `Runs`, `BF`, `4s` and `6s`;
+//! bowling exports need `Player`, `Runs` and `Wkts`, along with `Balls` or `Overs`. Any
+//! other columns are ignored, as are players with a `-` in a needed column. Statsguru
+//! appends the country to each name, e.g. "V Kohli (INDIA)", and this is removed.
+use crate::{
+    error::{Error, Result},
+    model::{
+        naive_stats::{default_no_ball_rate, default_wide_rate, BattingCounts, BowlingCounts},
+        NaiveStatsFit, PlayerRatingNaiveStats,
+    },
+    player::PlayerDb,
+    team::Team,
+};
+use csv::StringRecord;
+use std::io::Read;
+
+/// Career records read from Statsguru exports, from which ratings are fitted
+#[derive(Debug, Clone, Default)]
+pub struct StatsguruImport {
+    fit: NaiveStatsFit,
+    /// Every player read, in the order first seen
+    names: Vec<String>,
+}
+
+impl StatsguruImport {
+    /// An import whose ratings are shrunk towards the mean of all players read with the
+    /// given weight, in deliveries
+    pub fn new(prior_balls: f32) -> Self {
+        Self {
+            fit: NaiveStatsFit::new(prior_balls),
+            names: Vec::new(),
+        }
+    }
+
+    /// Read the career batting records of a CSV export
+    pub fn read_batting<R: Read>(&mut self, reader: R) -> Result<()> {
+        let mut csv = csv::Reader::from_reader(reader);
+        let columns = Columns::new(csv.headers()?);
+        for record in csv.records() {
+            let record = record?;
+            let name = columns.name(&record)?;
+            let counts = (|| {
+                let innings = columns.number(&record, "Inns")?;
+                let not_outs = columns.number(&record, "NO")?;
+                Some(BattingCounts {
+                    balls: columns.number(&record, "BF")?,
+                    outs: innings.saturating_sub(not_outs),
+                    runs: columns.number(&record, "Runs")?,
+                    fours: columns.number(&record, "4s")?,
+                    sixes: columns.number(&record, "6s")?,
+                })
+            })();
+            if let Some(counts) = counts {
+                self.fit.add_batting(&name, counts);
+                self.add_name(name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the career bowling records of a CSV export
+    pub fn read_bowling<R: Read>(&mut self, reader: R) -> Result<()> {
+        let mut csv = csv::Reader::from_reader(reader);
+        let columns = Columns::new(csv.headers()?);
+        for record in csv.records() {
+            let record = record?;
+            let name = columns.name(&record)?;
+            let counts = (|| {
+                Some(BowlingCounts {
+                    deliveries: columns.balls(&record)?,
+                    wickets: columns.number(&record, "Wkts")?,
+                    runs: columns.number(&record, "Runs")?,
+                    ..Default::default()
+                })
+            })();
+            if let Some(counts) = counts {
+                self.fit.add_bowling(&name, counts);
+                self.add_name(name);
+            }
+        }
+        Ok(())
+    }
+
+    fn add_name(&mut self, name: String) {
+        if !self.names.contains(&name) {
+            self.names.push(name);
+        }
+    }
+
+    /// The fitted rating of a player. Statsguru doesn't give wides and no-balls, so these
+    /// take their usual rates.
+    pub fn rating(&self, name: &str) -> PlayerRatingNaiveStats {
+        let mut rating = self.fit.rating(name);
+        rating.bowling.wide_rate = default_wide_rate();
+        rating.bowling.no_ball_rate = default_no_ball_rate();
+        rating
+    }
+
+    /// A database of every player read, with their fitted ratings
+    pub fn build_db(&self) -> Result<PlayerDb<PlayerRatingNaiveStats>> {
+        let mut db = PlayerDb::new();
+        for name in &self.names {
+            db.add(name.clone(), self.rating(name))?;
+        }
+        Ok(db)
+    }
+
+    /// A team of the named players from a database built by `build_db`, in batting order
+    pub fn squad(
+        db: &PlayerDb<PlayerRatingNaiveStats>,
+        id: u16,
+        name: &str,
+        players: &[&str],
+    ) -> Result<Team> {
+        let players = players
+            .iter()
+            .map(|&player| {
+                db.find(player)
+                    .map(|p| (p.id, p.name.clone()))
+                    .ok_or_else(|| Error::MissingData(format!("No record for {}", player)))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Team {
+            id,
+            name: name.to_string(),
+            players,
+            keeper: None,
+            captain: None,
+            home: None,
+            roles: Default::default(),
+        })
+    }
+}
+
+/// The positions of the columns in an export
+struct Columns {
+    headers: StringRecord,
+}
+
+impl Columns {
+    fn new(headers: &StringRecord) -> Self {
+        Self {
+            headers: headers.clone(),
+        }
+    }
+
+    fn get<'r>(&self, record: &'r StringRecord, column: &str) -> Option<&'r str> {
+        let index = self.headers.iter().position(|h| h.trim() == column)?;
+        record.get(index).map(str::trim)
+    }
+
+    /// The player's name, without the country
+    fn name(&self, record: &StringRecord) -> Result<String> {
+        let name = self
+            .get(record, "Player")
+            .ok_or_else(|| Error::MissingData("Export has no Player column".into()))?;
+        let name = match name.find(" (") {
+            Some(i) => &name[..i],
+            None => name,
+        };
+        Ok(name.to_string())
+    }
+
+    /// A whole number, or None if it is missing or given as "-"
+    fn number(&self, record: &StringRecord, column: &str) -> Option<u32> {
+        self.get(record, column)?.parse().ok()
+    }
+
+    /// The deliveries bowled, from the balls or else the overs, e.g. "95.3" for six-ball
+    /// overs
+    fn balls(&self, record: &StringRecord) -> Option<u32> {
+        if let Some(balls) = self.number(record, "Balls") {
+            return Some(balls);
+        }
+        let overs = self.get(record, "Overs")?;
+        let (overs, balls) = match overs.split_once('.') {
+            Some((overs, balls)) => (overs.parse::<u32>().ok()?, balls.parse::<u32>().ok()?),
+            None => (overs.parse().ok()?, 0),
+        };
+        Some(6 * overs + balls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BATTING: &str = "\
+Player,Span,Mat,Inns,NO,Runs,HS,Ave,BF,SR,100,50,0,4s,6s
+V Kohli (INDIA),2008-2023,292,280,44,13848,183,58.67,14797,93.58,50,72,16,1294,150
+JJ Bumrah (INDIA),2016-2023,89,29,17,91,16,7.58,180,50.55,0,0,5,6,2
+A Debutant (INDIA),2023-2023,1,0,0,-,-,-,-,-,0,0,0,-,-
+";
+    const BOWLING: &str = "\
+Player,Span,Mat,Inns,Overs,Mdns,Runs,Wkts,BBI,Ave,Econ,SR,4,5
+JJ Bumrah (INDIA),2016-2023,89,88,771.3,60,3507,149,6/19,23.53,4.54,31.0,8,2
+V Kohli (INDIA),2008-2023,292,48,108.2,1,665,5,1/13,133.00,6.13,130.0,0,0
+";
+
+    #[test]
+    fn squad_from_exports() -> Result<()> {
+        let mut import = StatsguruImport::new(100.);
+        import.read_batting(BATTING.as_bytes())?;
+        import.read_bowling(BOWLING.as_bytes())?;
+        let db = import.build_db()?;
+        let team = StatsguruImport::squad(&db, 0, "India", &["V Kohli", "JJ Bumrah"])?;
+        let kohli = db.get(team.players[0].0).unwrap();
+        let bumrah = db.get(team.players[1].0).unwrap();
+        assert!(kohli.rating.batting.avg > 50.);
+        assert!(kohli.rating.batting.avg > bumrah.rating.batting.avg);
+        assert!(bumrah.rating.bowling.sr < kohli.rating.bowling.sr);
+        assert!(kohli.rating.validate().is_ok() && bumrah.rating.validate().is_ok());
+        assert!(db.find("A Debutant").is_none());
+        assert!(StatsguruImport::squad(&db, 0, "India", &["A Debutant"]).is_err());
+        Ok(())
+    }
+}
@@ -1,14 +1,35 @@
 //! Cricket simulation engine
+#[cfg(feature = "std-display")]
 #[macro_use]
 extern crate prettytable;
 
+pub mod aging;
+pub mod career;
 pub mod conditions;
+pub mod config;
+pub mod elo;
 pub mod error;
+pub mod eval;
+pub mod field;
 pub mod form;
 pub mod game;
+pub mod generate;
+pub mod injury;
+pub mod interop;
 pub mod model;
+pub mod montecarlo;
+pub mod morale;
+pub mod odds;
 pub mod player;
+pub mod seed;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "sqlite")]
+pub mod store;
+pub mod strategy;
+pub mod strength;
 pub mod team;
+pub mod tournament;
 
 #[cfg(test)]
 mod tests {
@@ -16,41 +37,102 @@ mod tests {
     use crate::error::Result;
     use crate::team::Team;
     use model::PlayerRatingNull;
-    use player::PlayerDb;
+    use player::{PlayerDb, PlayerId};
     use rand::thread_rng;
 
-    fn test_team(db: &mut PlayerDb<PlayerRatingNull>, id: u16, label: &str) -> Result<Team> {
+    pub(crate) fn test_team(
+        db: &mut PlayerDb<PlayerRatingNull>,
+        id: u16,
+        label: &str,
+    ) -> Result<Team> {
         const N_PLAYERS: usize = 11;
         let name = format!("team_{}", label);
-        let player_names = (0..N_PLAYERS)
-            .into_iter()
-            .map(|i| format!("{}_{}", label, i));
-        let players = player_names
+        let player_names = (0..N_PLAYERS).map(|i| format!("{}_{}", label, i));
+        let players: Vec<_> = player_names
             .map(|n| {
                 let player = db.add(n, PlayerRatingNull::default())?;
                 Ok((player.id, player.name.clone()))
             })
             .collect::<Result<_>>()?;
-        Ok(Team { id, name, players })
+        let keeper = Some(players[4].0);
+        Ok(Team {
+            id,
+            name,
+            players,
+            keeper,
+            captain: None,
+            home: None,
+            roles: Default::default(),
+        })
     }
 
     #[test]
     fn sim() -> Result<()> {
-        use model::{Model, NullModel};
+        use model::NullModel;
         let rules = form::Form::test();
         let mut db = PlayerDb::new();
         let team_a = test_team(&mut db, 1, "AUS")?;
         let team_b = test_team(&mut db, 5, "NZ")?;
         let db = db;
-        let mut state = game::GameState::new(rules, &team_a, &team_b)?;
+        let mut state = game::GameState::new(rules, team_a.clone(), team_b.clone())?;
         let mut rng = thread_rng();
-        let model = NullModel {};
+        // Models can be chosen at runtime
+        let model: Box<dyn model::Model<PlayerRatingNull>> =
+            Box::new(model::PressureModel::new(NullModel {}));
 
-        while !state.complete() {
-            let ball = model.generate_delivery(&mut rng, state.snapshot(&db)?);
-            state.update(&ball)?;
+        #[derive(Default)]
+        struct Counter {
+            deliveries: usize,
+            wickets: u8,
+            overs: u16,
+            innings: usize,
+            ended: bool,
         }
+        impl game::MatchObserver for Counter {
+            fn on_delivery(&mut self, _: &game::GameState, _: &game::DeliveryOutcome) {
+                self.deliveries += 1;
+            }
+            fn on_wicket(&mut self, _: &game::GameState, _: PlayerId, _: &game::Dismissal) {
+                self.wickets += 1;
+            }
+            fn on_over_complete(&mut self, _: &game::GameState, _: &game::stats::OverSummary) {
+                self.overs += 1;
+            }
+            fn on_innings_end(&mut self, _: &game::GameState, _: usize) {
+                self.innings += 1;
+            }
+            fn on_match_end(&mut self, _: &game::GameState, _: &game::MatchResult) {
+                self.ended = true;
+            }
+        }
+        let mut counter = Counter::default();
+        let result = state.simulate_with(&*model, &db, &mut rng, &mut counter)?;
+        assert_eq!(state.result(), Some(result));
+        assert!(counter.ended);
+        assert_eq!(counter.innings, state.innings_started());
+        assert_eq!(counter.overs, state.match_overs_bowled());
+        let wickets: u8 = (0..state.innings_started())
+            .map(|i| state.innings_total(i).unwrap().1)
+            .sum();
+        assert_eq!(counter.wickets, wickets);
+        #[cfg(feature = "std-display")]
         state.print_innings_summary()?;
+        assert!(!state.innings_deliveries(0).unwrap().is_empty());
+        let bowled = state
+            .events()
+            .iter()
+            .filter(|e| matches!(e, game::MatchEvent::Delivery(_)))
+            .count();
+        assert_eq!(counter.deliveries, bowled);
+        let scorecard = state.scorecard()?;
+        assert!(scorecard.result.is_some());
+        let summary = scorecard.summary.as_deref().unwrap_or_default();
+        assert!(
+            summary.starts_with("team_") || summary.starts_with("Match"),
+            "{}",
+            summary
+        );
+        assert!(serde_json::to_string(&scorecard).is_ok());
         Ok(())
     }
 }
@@ -1,14 +1,46 @@
 //! The interface and implementations for the cricket model(s)
-use crate::game::{DeliveryOutcome, GameSnapshot};
-use rand::Rng;
-//use serde::{Deserialize, Serialize};
+use crate::{
+    error::{Error, Result},
+    game::{DeliveryOutcome, DeliveryOutcomeKind, Dismissal, Extra, GameSnapshot, Runs},
+    player::Player,
+};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng, RngCore,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use smallvec::smallvec;
+use std::io::{Read, Write};
 
 pub mod null;
 pub use null::{NullModel, PlayerRatingNull};
 pub mod naive_stats;
-pub use naive_stats::{NaiveStatsModel, PlayerRatingNaiveStats};
+pub use naive_stats::{NaiveStatsFit, NaiveStatsModel, PlayerRatingNaiveStats};
+pub mod pipeline;
+pub use pipeline::{BattingModel, BowlingModel, CompositeModel, Delivery, FieldingModel};
+pub mod basic;
+pub use basic::{AggressiveBatting, BasicBatting, BasicBowling, BasicFielding};
+pub mod fielding;
+pub use fielding::{FieldRating, RatedFielding};
+pub mod running;
+pub use running::RunningRating;
+pub mod matchup;
+pub use matchup::StyledBowling;
+pub mod pressure;
+pub use pressure::PressureModel;
+pub mod elo;
+pub use elo::EloModel;
+pub mod markov;
+pub use markov::MarkovModel;
+pub mod alpha;
+pub mod convert;
 
-pub trait PlayerRating {}
+pub trait PlayerRating {
+    /// The player's ability in the field
+    fn fielding(&self) -> &FieldRating;
+    /// The player's speed between the wickets
+    fn running(&self) -> &RunningRating;
+}
 
 pub trait Model<R>
 where
@@ -17,5 +49,261 @@ where
     /// Generate the outcome of a single delivery.
     /// TODO: Incoporate variable/dynamic strategies, field conditions, etc.
     /// TODO: Should return a Result
-    fn generate_delivery(&self, rng: &mut impl Rng, state: GameSnapshot<R>) -> DeliveryOutcome;
+    fn generate_delivery(&self, rng: &mut dyn RngCore, state: GameSnapshot<R>) -> DeliveryOutcome;
+
+    /// The probability of each kind of outcome of the next delivery, for analytical uses
+    /// such as win probabilities and expected scores. Empty if the model can only sample
+    /// outcomes.
+    fn outcome_distribution(&self, _state: &GameSnapshot<R>) -> Vec<(f32, DeliveryOutcomeKind)> {
+        Vec::new()
+    }
+
+    /// Identifies the model, so that results can record what produced them. Usually
+    /// `ModelInfo::of::<Self>()`.
+    fn info(&self) -> ModelInfo;
+}
+
+/// A model, or a stage of one, with a name that identifies it wherever its parameters
+/// or results are saved. Unlike the name of its type, it doesn't depend on the compiler
+/// or where the type is defined.
+pub trait Named {
+    const NAME: &'static str;
+
+    /// The name including those of any wrapped models or stages
+    fn full_name() -> String {
+        Self::NAME.to_string()
+    }
+}
+
+/// The expected runs from a delivery, given the distribution of its outcomes, or 0 if the
+/// distribution is empty
+pub fn expected_runs(distribution: &[(f32, DeliveryOutcomeKind)]) -> f32 {
+    let total: f32 = distribution.iter().map(|(p, _)| p).sum();
+    if total <= 0. {
+        return 0.;
+    }
+    let runs: f32 = distribution
+        .iter()
+        .map(|(p, kind)| p * kind.runs() as f32)
+        .sum();
+    runs / total
+}
+
+/// Sample an outcome from a distribution of kinds, such as one given by
+/// `Model::outcome_distribution`. The distribution's weights need not be normalized.
+pub fn sample_outcome<R: PlayerRating>(
+    rng: &mut dyn RngCore,
+    state: &GameSnapshot<R>,
+    distribution: &[(f32, DeliveryOutcomeKind)],
+) -> DeliveryOutcome {
+    let dist = WeightedIndex::new(distribution.iter().map(|o| o.0)).expect("valid weights");
+    let kind = distribution[dist.sample(rng)].1;
+    outcome_of_kind(rng, state, kind)
+}
+
+/// A random member of the fielding side, or the bowler if none are known
+pub(crate) fn fielder<'a, R: PlayerRating>(
+    rng: &mut dyn RngCore,
+    state: &GameSnapshot<'a, R>,
+) -> &'a Player<R> {
+    match state.fielders.len() {
+        0 => state.bowler,
+        n => state.fielders[rng.gen_range(0..n)],
+    }
+}
+
+/// Build an outcome of the given kind for the players in the snapshot. Catches and run
+/// outs are credited to a random fielder, and stumpings to the keeper. A catch that falls
+/// to the keeper is taken behind.
+pub fn outcome_of_kind<R: PlayerRating>(
+    rng: &mut dyn RngCore,
+    state: &GameSnapshot<R>,
+    kind: DeliveryOutcomeKind,
+) -> DeliveryOutcome {
+    use DeliveryOutcomeKind as Kind;
+    let striker = state.striker.id;
+    let bowler = &state.bowler.name;
+    let fielder = fielder(rng, state);
+    match kind {
+        Kind::Runs(runs) => DeliveryOutcome::running(runs),
+        Kind::Four => DeliveryOutcome::four(),
+        Kind::Six => DeliveryOutcome::six(),
+        Kind::Wide => DeliveryOutcome {
+            extras: smallvec![Extra::Wide(Runs::Running(0))],
+            ..Default::default()
+        },
+        Kind::NoBall => DeliveryOutcome {
+            extras: smallvec![Extra::NoBall],
+            ..Default::default()
+        },
+        Kind::Byes(runs) => DeliveryOutcome::byes(runs),
+        Kind::LegByes(runs) => DeliveryOutcome {
+            extras: smallvec![Extra::LegBye(Runs::Running(runs))],
+            ..Default::default()
+        },
+        Kind::Bowled => DeliveryOutcome::bowled(striker, bowler),
+        Kind::Caught => state.caught_by(fielder),
+        Kind::Lbw => DeliveryOutcome::lbw(striker, bowler),
+        Kind::RunOut => DeliveryOutcome {
+            wicket: Some((striker, Dismissal::RunOutStriker(fielder.name.clone()))),
+            fielders: vec![fielder.id],
+            ..Default::default()
+        },
+        Kind::Stumped => {
+            let keeper = state.keeper.unwrap_or(fielder);
+            DeliveryOutcome::stumped(striker, bowler, keeper.id, &keeper.name)
+        }
+        Kind::HitWicket => DeliveryOutcome {
+            wicket: Some((
+                striker,
+                Dismissal::HitWicket {
+                    bowler: bowler.clone(),
+                },
+            )),
+            ..Default::default()
+        },
+    }
+}
+
+/// The name of a model and the version of the crate that implemented it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// The full name of the model, including any wrapped models or stages
+    pub name: String,
+    pub version: String,
+}
+
+impl ModelInfo {
+    pub fn of<M: Named + ?Sized>() -> Self {
+        Self::named(M::full_name())
+    }
+
+    /// The info of a model with the given name, implemented by this version of the crate
+    pub fn named(name: String) -> Self {
+        Self {
+            name,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// A model's parameters, tagged with the model and version that wrote them
+#[derive(Serialize, Deserialize)]
+struct SavedModel<M> {
+    info: ModelInfo,
+    params: M,
+}
+
+/// Save a model's parameters as JSON
+pub fn save_model<M: Named + Serialize, W: Write>(model: &M, writer: W) -> Result<()> {
+    let saved = SavedModel {
+        info: ModelInfo::of::<M>(),
+        params: model,
+    };
+    Ok(serde_json::to_writer(writer, &saved)?)
+}
+
+/// Load a model's parameters saved by `save_model`, along with the info of the model
+/// that saved them. Parameters saved by a different type of model are rejected; those
+/// saved by another version of the crate are returned with that version.
+pub fn load_model<M: Named + DeserializeOwned, Rd: Read>(reader: Rd) -> Result<(M, ModelInfo)> {
+    // Check the model before the parameters, which another model wouldn't match
+    let saved: SavedModel<serde_json::Value> = serde_json::from_reader(reader)?;
+    let expected = ModelInfo::of::<M>();
+    if saved.info.name != expected.name {
+        return Err(Error::ModelMismatch {
+            expected: expected.name,
+            found: saved.info.name,
+        });
+    }
+    Ok((serde_json::from_value(saved.params)?, saved.info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{form::Form, game::GameState, player::PlayerDb, seed, tests::test_team};
+
+    #[test]
+    fn expected_runs_of_a_distribution() {
+        assert_eq!(expected_runs(&[]), 0.);
+        let weights = [
+            (2., DeliveryOutcomeKind::Runs(0)),
+            (1., DeliveryOutcomeKind::Four),
+            (1., DeliveryOutcomeKind::Runs(2)),
+        ];
+        assert_eq!(expected_runs(&weights), 1.5);
+    }
+
+    #[test]
+    fn saved_and_loaded() -> Result<()> {
+        let mut saved = Vec::new();
+        save_model(&MarkovModel::default(), &mut saved)?;
+        let (_, info) = load_model::<MarkovModel, _>(saved.as_slice())?;
+        assert_eq!(info, ModelInfo::of::<MarkovModel>());
+        // Parameters are only loaded by the type of model that saved them
+        match load_model::<EloModel, _>(saved.as_slice()) {
+            Err(Error::ModelMismatch { expected, found }) => {
+                assert_eq!(
+                    (expected.as_str(), found.as_str()),
+                    ("EloModel", "MarkovModel")
+                );
+            }
+            other => panic!("expected a mismatch, got {:?}", other.map(|(_, info)| info)),
+        }
+        // but are loaded from another version, which is returned with them
+        let json = String::from_utf8(saved).unwrap().replace(
+            &format!("\"version\":\"{}\"", info.version),
+            "\"version\":\"0.0.1\"",
+        );
+        let (_, info) = load_model::<MarkovModel, _>(json.as_bytes())?;
+        assert_eq!(info.version, "0.0.1");
+        Ok(())
+    }
+
+    #[test]
+    fn distributions_of_a_state() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "ENG")?;
+        let team_b = test_team(&mut db, 2, "IND")?;
+        let state = GameState::new(Form::t20(), team_a, team_b)?;
+        let snapshot = state.snapshot(&db)?;
+        let distribution = NullModel {}.outcome_distribution(&snapshot);
+        let total: f32 = distribution.iter().map(|(p, _)| p).sum();
+        assert!((total - 1.).abs() < 1e-6);
+        // Singles, fours and sixes
+        assert!((expected_runs(&distribution) - 0.484).abs() < 1e-6);
+        // A model that can only sample outcomes has no distribution
+        let sampled = CompositeModel {
+            bowling: StyledBowling {},
+            batting: BasicBatting {},
+            fielding: RatedFielding {},
+        };
+        assert!(sampled.outcome_distribution(&snapshot).is_empty());
+
+        // An outcome sampled from a distribution is of one of its kinds
+        use DeliveryOutcomeKind::*;
+        let mut rng = seed::rng(1);
+        let kinds = [Runs(2), Four, Six, Wide, NoBall, Byes(1), LegByes(3)];
+        let wickets = [Bowled, Caught, Lbw, RunOut, Stumped, HitWicket];
+        for &kind in kinds.iter().chain(wickets.iter()) {
+            let outcome = sample_outcome(&mut rng, &snapshot, &[(2., kind)]);
+            assert_eq!(outcome.kind(), kind);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn model_names() {
+        let model = PressureModel::new(CompositeModel {
+            bowling: StyledBowling {},
+            batting: BasicBatting {},
+            fielding: RatedFielding {},
+        });
+        assert_eq!(
+            Model::<PlayerRatingNull>::info(&model).name,
+            "PressureModel<CompositeModel<StyledBowling, BasicBatting, RatedFielding>>"
+        );
+        assert_eq!(ModelInfo::of::<NullModel>().name, "NullModel");
+    }
 }
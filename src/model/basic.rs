@@ -0,0 +1,342 @@
+//! Simple stages for the composite model that don't depend on player ratings
+use super::{
+    fielder,
+    pipeline::{BattingModel, BowlingModel, Delivery, FieldingModel, Length, Line, Pace, Shot},
+    Named, PlayerRating,
+};
+use crate::{
+    error::{Error, Result},
+    game::{DeliveryOutcome, GameSnapshot},
+    strategy::AggressionStrategy,
+};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng, RngCore,
+};
+use serde::{Deserialize, Serialize};
+
+/// Choose one of the options with the given relative weights, which must be fixed
+/// tables with a positive total. Use `try_choose` for weights that come from a config.
+pub(crate) fn choose<T: Copy>(rng: &mut dyn RngCore, options: &[(f32, T)]) -> T {
+    try_choose(rng, options).expect("valid weights")
+}
+
+/// Choose one of the options with the given relative weights, or an error if there are
+/// none or they don't have a positive, finite total
+pub(crate) fn try_choose<T: Copy>(rng: &mut dyn RngCore, options: &[(f32, T)]) -> Result<T> {
+    let dist = WeightedIndex::new(options.iter().map(|o| o.0))
+        .map_err(|e| Error::InvalidWeights(e.to_string()))?;
+    Ok(options[dist.sample(rng)].1)
+}
+
+/// Bowls mostly good-length deliveries around off stump
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BasicBowling {}
+
+impl<R: PlayerRating> BowlingModel<R> for BasicBowling {
+    fn bowl(&self, rng: &mut dyn RngCore, _state: &GameSnapshot<R>) -> Delivery {
+        use Length::*;
+        use Line::*;
+        let line = choose(
+            rng,
+            &[
+                (0.2, WideOutsideOff),
+                (0.45, OutsideOff),
+                (0.25, Stumps),
+                (0.1, Leg),
+            ],
+        );
+        let length = choose(
+            rng,
+            &[
+                (0.01, FullToss),
+                (0.04, Yorker),
+                (0.25, Full),
+                (0.45, Good),
+                (0.2, Short),
+                (0.05, Bouncer),
+            ],
+        );
+        let pace = choose(
+            rng,
+            &[(0.4, Pace::Fast), (0.3, Pace::Medium), (0.3, Pace::Spin)],
+        );
+        // Seamers move the ball a little either way, spinners turn it further
+        let (speed, movement) = match pace {
+            Pace::Fast => (rng.gen_range(135.0..150.0), rng.gen_range(-8.0..8.0)),
+            Pace::Medium => (rng.gen_range(120.0..135.0), rng.gen_range(-12.0..12.0)),
+            Pace::Spin => (rng.gen_range(75.0..95.0), rng.gen_range(-25.0..25.0)),
+        };
+        Delivery {
+            line,
+            length,
+            pace,
+            speed,
+            movement,
+        }
+    }
+}
+
+impl Named for BasicBowling {
+    const NAME: &'static str = "BasicBowling";
+}
+
+/// Leaves wide balls, defends good ones, and attacks loose ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BasicBatting {}
+
+impl<R: PlayerRating> BattingModel<R> for BasicBatting {
+    fn shot(&self, rng: &mut dyn RngCore, _state: &GameSnapshot<R>, delivery: &Delivery) -> Shot {
+        use Shot::*;
+        if delivery.line == Line::WideOutsideOff && rng.gen_bool(0.7) {
+            return Leave;
+        }
+        match delivery.length {
+            Length::FullToss | Length::Short => choose(rng, &[(0.3, Rotate), (0.7, Attack)]),
+            Length::Yorker | Length::Good => choose(rng, &[(0.7, Defend), (0.3, Rotate)]),
+            Length::Full => choose(rng, &[(0.4, Defend), (0.3, Rotate), (0.3, Attack)]),
+            Length::Bouncer => choose(rng, &[(0.6, Leave), (0.4, Attack)]),
+        }
+    }
+}
+
+impl Named for BasicBatting {
+    const NAME: &'static str = "BasicBatting";
+}
+
+/// Chooses shots according to the aggression set by a strategy, which shifts the choice
+/// between defence and attack. Aggression outside 0 to 1 is clamped, and an undefined
+/// aggression is taken as neutral.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggressiveBatting<A> {
+    pub strategy: A,
+}
+
+impl<R: PlayerRating, A: AggressionStrategy> BattingModel<R> for AggressiveBatting<A> {
+    fn shot(&self, rng: &mut dyn RngCore, state: &GameSnapshot<R>, delivery: &Delivery) -> Shot {
+        use Shot::*;
+        let aggression = match self.strategy.aggression(&state.context) {
+            aggression if aggression.is_nan() => 0.5,
+            aggression => aggression.clamp(0., 1.),
+        };
+        let attack = 2. * aggression;
+        let defend = 2. * (1. - aggression);
+        if delivery.line == Line::WideOutsideOff && rng.gen_bool(0.7 * (1. - aggression) as f64) {
+            return Leave;
+        }
+        // The same weights as BasicBatting at neutral aggression, plus a small floor so that
+        // every option stays possible
+        let (leave, defence, rotate, attacking) = match delivery.length {
+            Length::FullToss | Length::Short => (0., 0., 0.3, 0.7),
+            Length::Yorker | Length::Good => (0., 0.7, 0.3, 0.),
+            Length::Full => (0., 0.4, 0.3, 0.3),
+            Length::Bouncer => (0.6, 0., 0., 0.4),
+        };
+        choose(
+            rng,
+            &[
+                (leave * defend + 0.01, Leave),
+                (defence * defend + 0.01, Defend),
+                (rotate + 0.01, Rotate),
+                (attacking * attack + 0.05 * aggression, Attack),
+            ],
+        )
+    }
+}
+
+impl<A> Named for AggressiveBatting<A> {
+    const NAME: &'static str = "AggressiveBatting";
+}
+
+/// What becomes of a shot, before any fielder has a say in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShotResult {
+    Bowled,
+    Lbw,
+    /// A defensive shot finds the edge and carries to the keeper
+    Edge,
+    /// The ball goes to hand in the field
+    Catch,
+    /// The striker charges a spinner and misses
+    Stumping,
+    /// The striker leaves the ball for the keeper
+    Missed,
+    Dot,
+    /// The ball is hit into the field for the given runs
+    Runs(u8),
+    Four,
+    Six,
+}
+
+/// The probability table the fielding models share, so that they differ only in how the
+/// fielders handle the ball and in what scales the chances
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ShotOdds {
+    /// Scales the chance of each way of getting out
+    pub risk: f32,
+    /// Scales the chance of an attacking shot going to hand
+    pub catch: f32,
+    /// The fraction of attacking shots that reach the boundary
+    pub boundary: f32,
+    /// The fraction of shots worked into the field that are run for two
+    pub twos: f32,
+    /// Whether a defensive shot outside off stump can find the edge
+    pub edges: bool,
+    /// Whether a batter attacking a spinner can be stumped
+    pub stumpings: bool,
+}
+
+impl Default for ShotOdds {
+    fn default() -> Self {
+        Self {
+            risk: 1.,
+            catch: 1.,
+            boundary: 0.4,
+            twos: 0.1,
+            edges: false,
+            stumpings: false,
+        }
+    }
+}
+
+impl ShotOdds {
+    /// The result of the shot for a roll uniform on [0, 1)
+    pub(crate) fn result(&self, roll: f32, delivery: &Delivery, shot: Shot) -> ShotResult {
+        use ShotResult::*;
+        let at_stumps = delivery.line == Line::Stumps;
+        let risk = self.risk;
+        let catch = 0.04 * risk * self.catch;
+        let edge = if self.edges && delivery.line == Line::OutsideOff {
+            0.015 * risk
+        } else {
+            0.
+        };
+        let stumping = if self.stumpings && delivery.pace == Pace::Spin {
+            0.02 * risk
+        } else {
+            0.
+        };
+        match shot {
+            Shot::Leave if at_stumps && roll < 0.2 * risk => Bowled,
+            Shot::Leave => Missed,
+            Shot::Defend if at_stumps && roll < 0.01 * risk => Lbw,
+            Shot::Defend if roll < edge => Edge,
+            Shot::Defend if roll < 0.8 => Dot,
+            Shot::Defend => Runs(1),
+            Shot::Rotate if roll < 0.005 * risk => Catch,
+            Shot::Rotate if roll < 0.3 => Dot,
+            Shot::Rotate if roll < 1. - self.twos => Runs(1),
+            Shot::Rotate => Runs(2),
+            Shot::Attack if roll < catch => Catch,
+            Shot::Attack if roll < catch + stumping => Stumping,
+            Shot::Attack if roll < 0.3 => Dot,
+            Shot::Attack if roll < 1. - self.boundary => Runs(1),
+            Shot::Attack if roll < 1. - self.boundary / 4. => Four,
+            Shot::Attack => Six,
+        }
+    }
+}
+
+/// Resolves shots with fixed probabilities, attributing catches to a random fielder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BasicFielding {}
+
+impl<R: PlayerRating> FieldingModel<R> for BasicFielding {
+    fn resolve(
+        &self,
+        rng: &mut dyn RngCore,
+        state: &GameSnapshot<R>,
+        delivery: &Delivery,
+        shot: Shot,
+    ) -> DeliveryOutcome {
+        let striker = state.striker.id;
+        let bowler = &state.bowler.name;
+        match ShotOdds::default().result(rng.gen(), delivery, shot) {
+            ShotResult::Bowled => DeliveryOutcome::bowled(striker, bowler),
+            ShotResult::Lbw => DeliveryOutcome::lbw(striker, bowler),
+            ShotResult::Edge | ShotResult::Catch => state.caught_by(fielder(rng, state)),
+            ShotResult::Stumping | ShotResult::Missed | ShotResult::Dot => DeliveryOutcome::dot(),
+            ShotResult::Runs(runs) => DeliveryOutcome::running(runs),
+            ShotResult::Four => DeliveryOutcome::four(),
+            ShotResult::Six => DeliveryOutcome::six(),
+        }
+    }
+}
+
+impl Named for BasicFielding {
+    const NAME: &'static str = "BasicFielding";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Result,
+        form::Form,
+        game::{GameState, MatchContext},
+        model::CompositeModel,
+        player::PlayerDb,
+        seed,
+        tests::test_team,
+    };
+
+    /// Returns an aggression well outside the usual range
+    struct Extreme(f32);
+
+    impl AggressionStrategy for Extreme {
+        fn aggression(&self, _context: &MatchContext) -> f32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn shared_shot_odds() {
+        let spin_outside_off = Delivery {
+            line: Line::OutsideOff,
+            length: Length::Good,
+            pace: Pace::Spin,
+            speed: 85.,
+            movement: 0.,
+        };
+        let basic = ShotOdds::default();
+        let rated = ShotOdds {
+            edges: true,
+            stumpings: true,
+            ..basic
+        };
+        let result = |odds: &ShotOdds, roll, shot| odds.result(roll, &spin_outside_off, shot);
+        // Only the rated fielders have edges and stumpings in their table
+        assert_eq!(result(&basic, 0.01, Shot::Defend), ShotResult::Dot);
+        assert_eq!(result(&rated, 0.01, Shot::Defend), ShotResult::Edge);
+        assert_eq!(result(&basic, 0.05, Shot::Attack), ShotResult::Dot);
+        assert_eq!(result(&rated, 0.05, Shot::Attack), ShotResult::Stumping);
+        // Otherwise the two share the same chances
+        for shot in [Shot::Leave, Shot::Rotate, Shot::Attack].iter() {
+            for roll in [0.001, 0.03, 0.5, 0.7, 0.95].iter() {
+                assert_eq!(result(&basic, *roll, *shot), result(&rated, *roll, *shot));
+            }
+        }
+        assert_eq!(result(&basic, 0.03, Shot::Attack), ShotResult::Catch);
+        assert_eq!(result(&basic, 0.95, Shot::Attack), ShotResult::Six);
+    }
+
+    #[test]
+    fn extreme_aggression_clamped() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "AFG")?;
+        let team_b = test_team(&mut db, 2, "BAN")?;
+        for aggression in [-3., 5., f32::NAN, f32::INFINITY].iter() {
+            let model = CompositeModel {
+                bowling: BasicBowling {},
+                batting: AggressiveBatting {
+                    strategy: Extreme(*aggression),
+                },
+                fielding: BasicFielding {},
+            };
+            let mut state = GameState::new(Form::t20(), team_a.clone(), team_b.clone())?;
+            state.simulate_to_completion(&model, &db, &mut seed::rng(1))?;
+            assert!(state.result().is_some());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+//! Conversions between rating schemes, so that a database built for one model can be used
+//! with another. A whole database is converted with `PlayerDb::convert`.
+use super::{
+    naive_stats::{
+        default_no_ball_rate, default_wide_rate, BatRatingNaiveStats, BowlRatingNaiveStats,
+    },
+    null::{BatRatingNull, BowlRatingNull},
+    PlayerRatingNaiveStats, PlayerRatingNull,
+};
+
+/// Every player is the same to the null model, so only fielding and running carry over
+impl From<&PlayerRatingNaiveStats> for PlayerRatingNull {
+    fn from(rating: &PlayerRatingNaiveStats) -> Self {
+        Self {
+            batting: BatRatingNull {},
+            bowling: BowlRatingNull {},
+            fielding: rating.fielding,
+            running: rating.running,
+        }
+    }
+}
+
+/// The stats that reproduce the null model's outcomes: a wicket every 50 balls and 0.484
+/// runs per ball, of which 0.08 come from fours and 0.024 from sixes
+impl From<&PlayerRatingNull> for PlayerRatingNaiveStats {
+    fn from(rating: &PlayerRatingNull) -> Self {
+        let runs_per_ball = 0.484;
+        let balls_per_wicket = 50.;
+        Self {
+            batting: BatRatingNaiveStats {
+                avg: runs_per_ball * balls_per_wicket,
+                sr: 100. * runs_per_ball,
+                r4: 0.02,
+                r6: 0.004,
+            },
+            bowling: BowlRatingNaiveStats {
+                sr: balls_per_wicket,
+                avg: runs_per_ball * balls_per_wicket,
+                wide_rate: default_wide_rate(),
+                no_ball_rate: default_no_ball_rate(),
+            },
+            fielding: rating.fielding,
+            running: rating.running,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Result, form, game, model, player::PlayerDb, tests::test_team};
+    use rand::thread_rng;
+
+    #[test]
+    fn converted_ratings() -> Result<()> {
+        use model::{NaiveStatsModel, NullModel, PlayerRatingNaiveStats};
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "NED")?;
+        let team_b = test_team(&mut db, 2, "SCO")?;
+        let stats: PlayerDb<PlayerRatingNaiveStats> = db.convert();
+        for (id, name) in &team_a.players {
+            assert_eq!(&stats.get(*id).unwrap().name, name);
+            stats.get(*id).unwrap().rating.validate()?;
+        }
+        let mut rng = thread_rng();
+        let mut state = game::GameState::new(form::Form::t20(), team_a.clone(), team_b.clone())?;
+        state.simulate_to_completion(&NaiveStatsModel {}, &stats, &mut rng)?;
+        let null: PlayerDb<PlayerRatingNull> = stats.convert();
+        let mut state = game::GameState::new(form::Form::t20(), team_a, team_b)?;
+        state.simulate_to_completion(&NullModel {}, &null, &mut rng)?;
+        assert!(state.result().is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+//! A model driven only by Elo-style ratings of the players
+use super::{sample_outcome, Model, ModelInfo, Named, PlayerRating};
+use crate::{
+    elo::EloRatings,
+    game::{DeliveryOutcome, DeliveryOutcomeKind, GameSnapshot},
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// Converts the rating difference between the striker and the bowler into outcome
+/// probabilities. Evenly matched players give a typical limited-overs scoring rate; a
+/// stronger batter scores more boundaries and gets out less, and vice versa. This needs
+/// no ball-by-ball data, so it suits quick league simulations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EloModel {
+    pub ratings: EloRatings,
+}
+
+impl<R: PlayerRating> Model<R> for EloModel {
+    fn generate_delivery(&self, rng: &mut dyn RngCore, state: GameSnapshot<R>) -> DeliveryOutcome {
+        let distribution = self.outcome_distribution(&state);
+        sample_outcome(rng, &state, &distribution)
+    }
+
+    fn outcome_distribution(&self, state: &GameSnapshot<R>) -> Vec<(f32, DeliveryOutcomeKind)> {
+        use DeliveryOutcomeKind::*;
+        // Twice the expected score, so that 1 is an even contest
+        let edge = 2.
+            * EloRatings::expected(
+                self.ratings.player(state.striker.id),
+                self.ratings.player(state.bowler.id),
+            );
+        let wicket = 0.025 * (2. - edge);
+        vec![
+            (0.45, Runs(0)),
+            (0.33, Runs(1)),
+            (0.06, Runs(2)),
+            (0.09 * edge, Four),
+            (0.02 * edge, Six),
+            (0.4 * wicket, Bowled),
+            (0.2 * wicket, Lbw),
+            (0.4 * wicket, Caught),
+        ]
+    }
+
+    fn info(&self) -> ModelInfo {
+        ModelInfo::of::<Self>()
+    }
+}
+
+impl Named for EloModel {
+    const NAME: &'static str = "EloModel";
+}
@@ -0,0 +1,172 @@
+//! Fielding ratings, and a fielding stage in which chances can go down
+use super::{
+    basic::{choose, ShotOdds, ShotResult},
+    fielder,
+    matchup::bowler_advantage,
+    pipeline::{Delivery, FieldingModel, Shot},
+    running::push_for_run,
+    Named, PlayerRating,
+};
+use crate::{
+    game::{DeliveryOutcome, FieldingError, GameSnapshot},
+    player::Player,
+};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// How reliable a player is in the field
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FieldRating {
+    /// The probability of holding a catchable chance
+    pub catching: f32,
+    /// The probability of stopping a ball cleanly in the field
+    pub ground: f32,
+    /// The strength and accuracy of the throw, from 0 to 1
+    pub arm: f32,
+    /// Skill behind the stumps, from 0 to 1, which stops byes and completes stumpings.
+    /// Only used for the designated keeper.
+    pub keeping: f32,
+}
+
+impl Default for FieldRating {
+    /// A competent international fielder
+    fn default() -> Self {
+        Self {
+            catching: 0.8,
+            ground: 0.97,
+            arm: 0.5,
+            keeping: 0.7,
+        }
+    }
+}
+
+/// Resolves shots from the same table of probabilities as BasicFielding, but each chance goes to a
+/// random fielder who holds or drops it according to their rating. Balls worked into the
+/// field can also be misfielded for an extra run, or the batters can push for one against
+/// the fielder's throw and risk a run out. The keeper takes edges, stops byes, and can
+/// stump batters who charge the spinners. Wickets are more likely when the bowler's
+/// style suits the matchup with the striker. The field trades catches against
+/// boundaries: close catchers take more of the chances, boundary riders stop more of the
+/// attacking shots, and gaps in the ring give away twos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatedFielding {}
+
+impl<R: PlayerRating> FieldingModel<R> for RatedFielding {
+    fn resolve(
+        &self,
+        rng: &mut dyn RngCore,
+        state: &GameSnapshot<R>,
+        delivery: &Delivery,
+        shot: Shot,
+    ) -> DeliveryOutcome {
+        let striker = state.striker.id;
+        let bowler = &state.bowler.name;
+        let placement = &state.field;
+        let odds = ShotOdds {
+            // Wicket chances grow when the matchup or the venue favours the bowler
+            risk: (1. + bowler_advantage(state.bowler.bowls, state.striker.bats))
+                / (1. + state.home_advantage).max(0.5),
+            catch: placement.catch_factor(),
+            boundary: (0.4 * placement.boundary_factor()).min(0.7),
+            twos: (0.1 * placement.gap_factor()).min(0.6),
+            edges: true,
+            stumpings: true,
+        };
+        match odds.result(rng.gen(), delivery, shot) {
+            ShotResult::Bowled => DeliveryOutcome::bowled(striker, bowler),
+            ShotResult::Lbw => DeliveryOutcome::lbw(striker, bowler),
+            ShotResult::Edge => edge(rng, state),
+            ShotResult::Catch => chance(rng, state),
+            ShotResult::Stumping => stumping(rng, state),
+            ShotResult::Missed => gather(rng, state),
+            ShotResult::Dot => DeliveryOutcome::dot(),
+            ShotResult::Runs(runs) => field(rng, state, shot, runs),
+            ShotResult::Four => DeliveryOutcome::four(),
+            ShotResult::Six => DeliveryOutcome::six(),
+        }
+    }
+}
+
+impl Named for RatedFielding {
+    const NAME: &'static str = "RatedFielding";
+}
+
+/// The designated keeper, or a random fielder standing in
+fn keeper<'a, R: PlayerRating>(
+    rng: &mut dyn RngCore,
+    state: &GameSnapshot<'a, R>,
+) -> &'a Player<R> {
+    match state.keeper {
+        Some(keeper) => keeper,
+        None => fielder(rng, state),
+    }
+}
+
+/// A catch goes to a fielder, who either holds it or drops it
+fn chance<R: PlayerRating>(rng: &mut dyn RngCore, state: &GameSnapshot<R>) -> DeliveryOutcome {
+    let fielder = fielder(rng, state);
+    take_catch(rng, state, fielder)
+}
+
+/// The striker edges the ball through to the keeper
+fn edge<R: PlayerRating>(rng: &mut dyn RngCore, state: &GameSnapshot<R>) -> DeliveryOutcome {
+    let keeper = keeper(rng, state);
+    take_catch(rng, state, keeper)
+}
+
+fn take_catch<R: PlayerRating>(
+    rng: &mut dyn RngCore,
+    state: &GameSnapshot<R>,
+    fielder: &Player<R>,
+) -> DeliveryOutcome {
+    if rng.gen::<f32>() < fielder.rating.fielding().catching {
+        return state.caught_by(fielder);
+    }
+    // The batters often cross while the ball is in the air
+    let runs = choose(rng, &[(0.6, 0), (0.4, 1)]);
+    DeliveryOutcome {
+        fielding_error: Some(FieldingError::DroppedCatch {
+            fielder: fielder.id,
+        }),
+        ..DeliveryOutcome::running(runs)
+    }
+}
+
+/// The keeper takes a ball the batter didn't play, occasionally letting it through for byes
+fn gather<R: PlayerRating>(rng: &mut dyn RngCore, state: &GameSnapshot<R>) -> DeliveryOutcome {
+    let keeping = keeper(rng, state).rating.fielding().keeping;
+    if rng.gen::<f32>() < 0.04 * (1. - keeping) {
+        return DeliveryOutcome::byes(choose(rng, &[(0.7, 1), (0.3, 2)]));
+    }
+    DeliveryOutcome::dot()
+}
+
+/// The striker charges a spinner and misses, and the keeper has a chance to stump them
+fn stumping<R: PlayerRating>(rng: &mut dyn RngCore, state: &GameSnapshot<R>) -> DeliveryOutcome {
+    let keeper = keeper(rng, state);
+    if rng.gen::<f32>() < keeper.rating.fielding().keeping {
+        let (striker, bowler) = (state.striker.id, &state.bowler.name);
+        return DeliveryOutcome::stumped(striker, bowler, keeper.id, &keeper.name);
+    }
+    DeliveryOutcome::dot()
+}
+
+/// The ball is hit into the field for the given runs, plus one if it is misfielded
+fn field<R: PlayerRating>(
+    rng: &mut dyn RngCore,
+    state: &GameSnapshot<R>,
+    shot: Shot,
+    runs: u8,
+) -> DeliveryOutcome {
+    let fielder = fielder(rng, state);
+    if rng.gen::<f32>() < fielder.rating.fielding().ground {
+        return push_for_run(rng, state, fielder, shot, runs);
+    }
+    DeliveryOutcome {
+        fielding_error: Some(FieldingError::Misfield {
+            fielder: fielder.id,
+        }),
+        ..DeliveryOutcome::running(runs + 1)
+    }
+}
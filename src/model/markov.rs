@@ -0,0 +1,284 @@
+//! A Markov-chain model of each ball, fitted from ball-by-ball data
+use super::{sample_outcome, Model, ModelInfo, Named, PlayerRating, PlayerRatingNull};
+use crate::{
+    error::Result,
+    game::{DeliveryOutcome, DeliveryOutcomeKind, GameSnapshot, GameState},
+    interop::cricsheet::CricsheetMatch,
+    player::PlayerDb,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// The number of overs in each phase of an innings
+pub const OVERS_PER_PHASE: u16 = 5;
+/// The number of phases tracked. Overs beyond the last phase are counted in it.
+pub const PHASES: usize = 10;
+/// The number of wicket states, from none down to nine down
+const WICKET_STATES: usize = 10;
+/// The weight, in deliveries, of the pooled distribution used to smooth each state
+const PRIOR_WEIGHT: f32 = 20.;
+
+/// The kinds of delivery the model distinguishes
+const CATEGORIES: [DeliveryOutcomeKind; 11] = [
+    DeliveryOutcomeKind::Runs(0),
+    DeliveryOutcomeKind::Runs(1),
+    DeliveryOutcomeKind::Runs(2),
+    DeliveryOutcomeKind::Runs(3),
+    DeliveryOutcomeKind::Four,
+    DeliveryOutcomeKind::Six,
+    DeliveryOutcomeKind::Bowled,
+    DeliveryOutcomeKind::Caught,
+    DeliveryOutcomeKind::Lbw,
+    DeliveryOutcomeKind::Wide,
+    DeliveryOutcomeKind::NoBall,
+];
+
+/// The index of the category of a recorded delivery. Run outs and rarer dismissals are
+/// counted as catches, byes and leg byes as runs, and all-run fours and fives as threes.
+fn category(outcome: &DeliveryOutcome) -> usize {
+    use DeliveryOutcomeKind as Kind;
+    let kind = match outcome.kind() {
+        Kind::RunOut | Kind::Stumped | Kind::HitWicket => Kind::Caught,
+        Kind::Runs(runs) | Kind::Byes(runs) | Kind::LegByes(runs) => Kind::Runs(runs.min(3)),
+        kind => kind,
+    };
+    CATEGORIES
+        .iter()
+        .position(|c| *c == kind)
+        .expect("every kind has a category")
+}
+
+/// Chooses each ball from the distribution observed in the same phase of the innings with
+/// the same number of wickets down. It ignores the players entirely, which makes it a
+/// well-calibrated baseline for other models.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "MarkovCounts")]
+pub struct MarkovModel {
+    /// Counts of each category, by phase and then wickets down
+    counts: Vec<Vec<[u32; CATEGORIES.len()]>>,
+    /// Counts of each category over every state, kept up to date as deliveries are added
+    #[serde(skip)]
+    totals: [u32; CATEGORIES.len()],
+}
+
+/// The saved form of the model, from which the totals are rebuilt
+#[derive(Deserialize)]
+struct MarkovCounts {
+    counts: Vec<Vec<[u32; CATEGORIES.len()]>>,
+}
+
+impl From<MarkovCounts> for MarkovModel {
+    fn from(saved: MarkovCounts) -> Self {
+        let mut totals = [0; CATEGORIES.len()];
+        for counts in saved.counts.iter().flatten() {
+            for (total, c) in totals.iter_mut().zip(counts) {
+                *total += c;
+            }
+        }
+        Self {
+            counts: saved.counts,
+            totals,
+        }
+    }
+}
+
+impl MarkovModel {
+    /// Fit the model from the deliveries of completed or partial matches
+    pub fn fit<'a>(games: impl IntoIterator<Item = &'a GameState>) -> Self {
+        let mut model = Self::default();
+        for game in games {
+            model.add_game(game);
+        }
+        model
+    }
+
+    /// Fit the model from Cricsheet matches, replaying each through the engine
+    pub fn fit_cricsheet(matches: &[CricsheetMatch]) -> Result<Self> {
+        let mut db = PlayerDb::<PlayerRatingNull>::new();
+        let games = matches
+            .iter()
+            .map(|m| {
+                let (team_a, team_b) = m.build_teams(&mut db)?;
+                m.replay(team_a, team_b)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::fit(&games))
+    }
+
+    /// Add the deliveries of a match to the fitted counts
+    pub fn add_game(&mut self, game: &GameState) {
+        if self.counts.is_empty() {
+            self.counts = vec![vec![[0; CATEGORIES.len()]; WICKET_STATES]; PHASES];
+        }
+        for innings in 0..game.innings_started() {
+            let mut wickets = 0;
+            for record in game.innings_deliveries(innings).unwrap_or_default() {
+                let index = category(&record.outcome);
+                self.counts[phase(record.overs.completed)][wickets.min(WICKET_STATES - 1)]
+                    [index] += 1;
+                self.totals[index] += 1;
+                if record.outcome.wicket.is_some() {
+                    wickets += 1;
+                }
+            }
+        }
+    }
+
+    /// The number of deliveries the model was fitted from
+    pub fn deliveries(&self) -> u32 {
+        self.totals.iter().sum()
+    }
+
+    /// The probability of each category in a state, smoothed towards the distribution
+    /// over all states so that rare states are still sensible
+    fn weights(&self, over: u16, wickets: u8) -> [f32; CATEGORIES.len()] {
+        let pooled = self.totals.map(|total| total as f32 + 1.);
+        let pooled_total: f32 = pooled.iter().sum();
+        let mut weights = [0.; CATEGORIES.len()];
+        let state = self
+            .counts
+            .get(phase(over))
+            .and_then(|phase| phase.get((wickets as usize).min(WICKET_STATES - 1)));
+        for (i, w) in weights.iter_mut().enumerate() {
+            let count = state.map_or(0., |counts| counts[i] as f32);
+            *w = count + PRIOR_WEIGHT * pooled[i] / pooled_total;
+        }
+        weights
+    }
+}
+
+fn phase(over: u16) -> usize {
+    ((over / OVERS_PER_PHASE) as usize).min(PHASES - 1)
+}
+
+impl<R: PlayerRating> Model<R> for MarkovModel {
+    fn generate_delivery(&self, rng: &mut dyn RngCore, state: GameSnapshot<R>) -> DeliveryOutcome {
+        let distribution = self.outcome_distribution(&state);
+        sample_outcome(rng, &state, &distribution)
+    }
+
+    fn outcome_distribution(&self, state: &GameSnapshot<R>) -> Vec<(f32, DeliveryOutcomeKind)> {
+        let weights = self.weights(state.context.overs.completed, state.context.wickets);
+        let total: f32 = weights.iter().sum();
+        weights
+            .iter()
+            .zip(CATEGORIES)
+            .map(|(w, kind)| (w / total, kind))
+            .collect()
+    }
+
+    fn info(&self) -> ModelInfo {
+        ModelInfo::of::<Self>()
+    }
+}
+
+impl Named for MarkovModel {
+    const NAME: &'static str = "MarkovModel";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eval,
+        form::Form,
+        model::{load_model, save_model, NullModel},
+        seed,
+        tests::test_team,
+    };
+
+    #[test]
+    fn totals_kept_with_counts() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "BAN")?;
+        let team_b = test_team(&mut db, 2, "ZIM")?;
+        let mut games = Vec::new();
+        for i in 0..2 {
+            let mut game = GameState::new(Form::t20(), team_a.clone(), team_b.clone())?;
+            game.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(i))?;
+            games.push(game);
+        }
+        let model = MarkovModel::fit(&games);
+        let balls: usize = games
+            .iter()
+            .flat_map(|g| (0..g.innings_started()).map(move |i| g.innings_deliveries(i)))
+            .map(|d| d.unwrap().len())
+            .sum();
+        assert_eq!(model.deliveries() as usize, balls);
+        let counted: u32 = model.counts.iter().flatten().flatten().sum();
+        assert_eq!(counted, model.deliveries());
+        // The totals are rebuilt when a saved model is loaded
+        let loaded: MarkovModel = serde_json::from_str(&serde_json::to_string(&model)?)?;
+        assert_eq!(loaded.totals, model.totals);
+        assert_eq!(loaded.weights(7, 2), model.weights(7, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn fitted_from_cricsheet() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "WI")?;
+        let team_b = test_team(&mut db, 2, "SL")?;
+        let mut game = GameState::new(Form::t20(), team_a, team_b)?;
+        game.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(2))?;
+        let mut json = Vec::new();
+        CricsheetMatch::from_game(&game)?.write_json(&mut json)?;
+        let imported = CricsheetMatch::from_json(json.as_slice())?;
+        let model = MarkovModel::fit_cricsheet(&[imported])?;
+        // The replayed deliveries are counted just as the originals are
+        assert_eq!(model.counts, MarkovModel::fit([&game]).counts);
+        Ok(())
+    }
+
+    #[test]
+    fn distribution_of_a_state() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "BAN")?;
+        let team_b = test_team(&mut db, 2, "ZIM")?;
+        let mut counts = vec![vec![[0; CATEGORIES.len()]; WICKET_STATES]; PHASES];
+        // Opening overs with no wickets down: 60 dots, 30 singles and 10 fours
+        counts[0][0][0] = 60;
+        counts[0][0][1] = 30;
+        counts[0][0][4] = 10;
+        let totals = counts[0][0];
+        let model = MarkovModel { counts, totals };
+        let state = GameState::new(Form::t20(), team_a, team_b)?;
+        let distribution = model.outcome_distribution(&state.snapshot(&db)?);
+        assert_eq!(distribution.len(), CATEGORIES.len());
+        let total: f32 = distribution.iter().map(|(p, _)| p).sum();
+        assert!((total - 1.).abs() < 1e-6);
+        // Each count is smoothed by 20 deliveries spread as the pooled counts plus one
+        let prior = |count: f32| PRIOR_WEIGHT * (count + 1.) / 111.;
+        let dots = (60. + prior(60.)) / (100. + PRIOR_WEIGHT);
+        let sixes = prior(0.) / (100. + PRIOR_WEIGHT);
+        assert_eq!(distribution[0].1, DeliveryOutcomeKind::Runs(0));
+        assert!((distribution[0].0 - dots).abs() < 1e-6);
+        assert!((distribution[5].0 - sixes).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn saved_evaluated_and_simulated() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "SA")?;
+        let team_b = test_team(&mut db, 2, "ENG")?;
+        let mut game = GameState::new(Form::t20(), team_a.clone(), team_b.clone())?;
+        game.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(3))?;
+        let fitted = MarkovModel::fit([&game]);
+
+        let mut saved = Vec::new();
+        save_model(&fitted, &mut saved)?;
+        let (model, info) = load_model::<MarkovModel, _>(saved.as_slice())?;
+        assert_eq!(model.totals, fitted.totals);
+        assert_eq!(info, Model::<PlayerRatingNull>::info(&model));
+
+        let evaluation = eval::evaluate(&model, &db, [&game])?;
+        assert_eq!(evaluation.deliveries, model.deliveries());
+        assert!(evaluation.log_loss().is_finite());
+
+        let mut state = GameState::new(Form::t20(), team_a, team_b)?;
+        state.simulate_to_completion(&model, &db, &mut seed::rng(4))?;
+        assert_eq!(state.scorecard()?.model, Some(info));
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! The effect of the bowler's style against the batter's hand
+use super::{
+    basic::BasicBowling,
+    pipeline::{BowlingModel, Delivery, Length, Pace},
+    Named, PlayerRating,
+};
+use crate::{
+    game::GameSnapshot,
+    player::{BowlingStyle, Hand},
+};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// How much the matchup favours the bowler, as a relative change in the chance of taking a
+/// wicket. Spin turning away from the bat is harder to play than spin turning in, and pace
+/// from the other side of the wicket angles the ball across the batter.
+pub fn bowler_advantage(style: Option<BowlingStyle>, batter: Hand) -> f32 {
+    let style = match style {
+        Some(style) => style,
+        None => return 0.,
+    };
+    match style.turns_away_from(batter) {
+        Some(true) => 0.15,
+        Some(false) => -0.05,
+        None if style.arm() != batter => 0.05,
+        None => 0.,
+    }
+}
+
+/// Bowls like BasicBowling, but with the pace and movement of the bowler's style if it is
+/// known. Spinners turn the ball in their natural direction, and the movement depends on
+/// the state of the ball. Tired bowlers are slower and bowl more loose balls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StyledBowling {}
+
+impl<R: PlayerRating> BowlingModel<R> for StyledBowling {
+    fn bowl(&self, rng: &mut dyn RngCore, state: &GameSnapshot<R>) -> Delivery {
+        let mut delivery = BasicBowling {}.bowl(rng, state);
+        // A tired bowler loses pace and strays in length
+        let fatigue = state.bowler_fatigue;
+        if rng.gen::<f32>() < 0.2 * fatigue {
+            delivery.length = if rng.gen_bool(0.5) {
+                Length::Short
+            } else {
+                Length::FullToss
+            };
+        }
+        let style = match state.bowler.bowls {
+            Some(style) => style,
+            None => return delivery,
+        };
+        use BowlingStyle::*;
+        // Movement is positive away from a right-hander
+        let (pace, speed, movement) = match style {
+            RightFast | LeftFast => (
+                Pace::Fast,
+                rng.gen_range(140.0..155.0),
+                rng.gen_range(-8.0..8.0),
+            ),
+            RightFastMedium | LeftFastMedium => (
+                Pace::Medium,
+                rng.gen_range(128.0..140.0),
+                rng.gen_range(-12.0..12.0),
+            ),
+            RightMedium | LeftMedium => (
+                Pace::Medium,
+                rng.gen_range(115.0..128.0),
+                rng.gen_range(-12.0..12.0),
+            ),
+            OffBreak | LeftArmWrist => (
+                Pace::Spin,
+                rng.gen_range(80.0..95.0),
+                -rng.gen_range(5.0..25.0),
+            ),
+            LegBreak | SlowLeftArm => (
+                Pace::Spin,
+                rng.gen_range(75.0..90.0),
+                rng.gen_range(5.0..25.0),
+            ),
+        };
+        // Seamers get the most from the ball when it is new or reversing
+        let factors = state.conditions.ball.condition_factors();
+        let assistance = match pace {
+            Pace::Spin => factors.spin,
+            _ => factors.seam.max(factors.swing + factors.reverse_swing),
+        };
+        Delivery {
+            pace,
+            speed: speed * (1. - 0.1 * fatigue),
+            movement: movement * assistance,
+            ..delivery
+        }
+    }
+}
+
+impl Named for StyledBowling {
+    const NAME: &'static str = "StyledBowling";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spin_turning_away() {
+        let right = bowler_advantage(Some(BowlingStyle::LegBreak), Hand::Right);
+        let left = bowler_advantage(Some(BowlingStyle::LegBreak), Hand::Left);
+        assert!(right > left);
+        let left = bowler_advantage(Some(BowlingStyle::OffBreak), Hand::Left);
+        assert!(left > bowler_advantage(Some(BowlingStyle::OffBreak), Hand::Right));
+        assert_eq!(bowler_advantage(None, Hand::Left), 0.);
+    }
+}
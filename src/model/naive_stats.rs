@@ -1,23 +1,42 @@
 //! A model that just uses the batters' and bowlers' averages
 
-use super::{null::FieldRatingNull, Model, PlayerRating};
-use crate::game::{DeliveryOutcome, GameSnapshot};
-use rand::{
-    distributions::{Distribution, WeightedIndex},
-    Rng,
+use super::{sample_outcome, FieldRating, Model, ModelInfo, Named, PlayerRating, RunningRating};
+use crate::{
+    error::{Error, Result},
+    game::{DeliveryOutcome, DeliveryOutcomeKind, GameSnapshot},
 };
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 
+mod fit;
+pub use fit::{BattingCounts, BowlingCounts, NaiveStatsFit, DEFAULT_PRIOR_BALLS};
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PlayerRatingNaiveStats {
     pub batting: BatRatingNaiveStats,
     pub bowling: BowlRatingNaiveStats,
-    // No stats for fielding right now
-    pub fielding: FieldRatingNull,
+    #[serde(default)]
+    pub fielding: FieldRating,
+    #[serde(default)]
+    pub running: RunningRating,
+}
+impl PlayerRatingNaiveStats {
+    /// Check that both the batting and bowling ratings can be used by the model
+    pub fn validate(&self) -> Result<()> {
+        self.batting.validate()?;
+        self.bowling.validate()
+    }
+}
+impl PlayerRating for PlayerRatingNaiveStats {
+    fn fielding(&self) -> &FieldRating {
+        &self.fielding
+    }
+    fn running(&self) -> &RunningRating {
+        &self.running
+    }
 }
-impl PlayerRating for PlayerRatingNaiveStats {}
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BatRatingNaiveStats {
     // Runs per wicket
     pub avg: f32,
@@ -30,108 +49,355 @@ pub struct BatRatingNaiveStats {
 }
 
 impl BatRatingNaiveStats {
+    /// A batter who has never been dismissed is treated as if they were out once
     pub fn from_career_stats(
         balls_faced: u32,
         outs: u32,
         runs: u32,
         fours: u32,
         sixes: u32,
-    ) -> Self {
+    ) -> Result<Self> {
+        if balls_faced == 0 {
+            return Err(Error::InvalidRating("no balls faced".to_string()));
+        }
         let bf = balls_faced as f32;
         let runs = runs as f32;
-        let avg = runs / outs as f32;
-        let sr = runs / bf;
+        let avg = runs / outs.max(1) as f32;
+        let sr = 100. * runs / bf;
         let r4 = fours as f32 / bf;
         let r6 = sixes as f32 / bf;
-        Self { avg, sr, r4, r6 }
+        let rating = Self { avg, sr, r4, r6 };
+        rating.validate()?;
+        Ok(rating)
+    }
+
+    /// Check that the rating can be used by the model, e.g. after loading it from a file
+    pub fn validate(&self) -> Result<()> {
+        let valid = [self.avg, self.sr, self.r4, self.r6]
+            .iter()
+            .all(|x| x.is_finite() && *x >= 0.);
+        if !valid || self.r4 + self.r6 > 1. {
+            return Err(Error::InvalidRating(format!("{:?}", self)));
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BowlRatingNaiveStats {
     // Balls per wicket
     pub sr: f32,
     // Runs per wicket
     pub avg: f32,
-    // TODO: wide/noball rates
+    // Wides per delivery
+    #[serde(default = "default_wide_rate")]
+    pub wide_rate: f32,
+    // No-balls per delivery
+    #[serde(default = "default_no_ball_rate")]
+    pub no_ball_rate: f32,
+}
+
+pub(crate) fn default_wide_rate() -> f32 {
+    0.025
+}
+pub(crate) fn default_no_ball_rate() -> f32 {
+    0.005
 }
 
 impl BowlRatingNaiveStats {
-    pub fn from_career_stats(deliveries: u32, wickets: u32, runs_allowed: u32) -> Self {
-        let wickets = wickets as f32;
-        let sr = deliveries as f32 / wickets;
+    /// Deliveries include the wides and no-balls bowled. A bowler who has never taken a
+    /// wicket is treated as if they had taken one.
+    pub fn from_career_stats(
+        deliveries: u32,
+        wickets: u32,
+        runs_allowed: u32,
+        wides: u32,
+        no_balls: u32,
+    ) -> Result<Self> {
+        if deliveries == 0 {
+            return Err(Error::InvalidRating("no deliveries bowled".to_string()));
+        }
+        let balls = deliveries as f32;
+        let wickets = wickets.max(1) as f32;
+        let sr = balls / wickets;
         let avg = runs_allowed as f32 / wickets;
-        Self { sr, avg }
+        let wide_rate = wides as f32 / balls;
+        let no_ball_rate = no_balls as f32 / balls;
+        let rating = Self {
+            sr,
+            avg,
+            wide_rate,
+            no_ball_rate,
+        };
+        rating.validate()?;
+        Ok(rating)
+    }
+
+    /// Check that the rating can be used by the model, e.g. after loading it from a file
+    pub fn validate(&self) -> Result<()> {
+        let valid = [self.sr, self.avg, self.wide_rate, self.no_ball_rate]
+            .iter()
+            .all(|x| x.is_finite() && *x >= 0.);
+        if !valid || self.sr < 1. || self.wide_rate + self.no_ball_rate >= 1. {
+            return Err(Error::InvalidRating(format!("{:?}", self)));
+        }
+        Ok(())
     }
 }
 
+// The largest fractional change to a rating from a player's form
+const MORALE_EFFECT: f32 = 0.1;
+// The venue can't make a side worse than this fraction of its usual self
+const MIN_ADVANTAGE: f32 = 0.5;
+
+// Byes and leg byes per legal delivery, roughly the international average
+const BYE_RATE: f32 = 0.003;
+const LEG_BYE_RATE: f32 = 0.008;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NaiveStatsModel {}
 
 impl Model<PlayerRatingNaiveStats> for NaiveStatsModel {
     fn generate_delivery(
         &self,
-        rng: &mut impl Rng,
+        rng: &mut dyn RngCore,
         state: GameSnapshot<PlayerRatingNaiveStats>,
     ) -> DeliveryOutcome {
-        let striker = state.striker;
-        let bowler = state.bowler;
-        let batter_rating = &striker.rating.batting;
-        let bowler_rating = &bowler.rating.bowling;
-
-        let bat_wkt_prob = batter_rating.sr * 0.01 / batter_rating.avg;
-        let bowl_wkt_prob = 1. / bowler_rating.sr;
-        let wkt_prob = avg_probs(bat_wkt_prob, bowl_wkt_prob);
-
-        // run rates given that no wicket was taken
-        let bat_run_rate = batter_rating.sr * 0.01 / (1. - bat_wkt_prob);
-        let bowl_run_rate = bowler_rating.avg / (bowler_rating.sr - 1.);
-        let run_rate = (bat_run_rate * bowl_run_rate).sqrt();
-        // NOTE: boundary fraction is, on average, roughly 50% +/- 5% in all formats.
-        // It's strangely not monotonic: lowest in ODI.
-        // The fraction of boundaries that are sixes is much higher in more limited overs.
-        let four_rate = batter_rating.r4;
-        let six_rate = batter_rating.r6;
-        // Run rate without boundaries; ignore contribution from bowler
-        let run_rate_nb = run_rate - 4. * four_rate - 6. * six_rate;
-        assert!(run_rate_nb > 0.);
-        assert!(run_rate_nb < 1.);
-
-        // assume for no reason that 10% of non-boundary runs are twos. neglect threes.
-        let tr = 0.1;
-        let two_rate = 0.5 * run_rate_nb * tr;
-        let one_rate = run_rate_nb * (1. - tr);
-
-        // TODO: account for other types of wickets
-
-        let dot_prob = 1.0 - wkt_prob - one_rate - two_rate - four_rate - six_rate;
-        assert!(dot_prob > 0.);
-        assert!(dot_prob < 1.);
-
-        // mutability for efficient taking of selected element
-        let mut outcomes: Vec<(f32, DeliveryOutcome)> = vec![
-            (dot_prob, DeliveryOutcome::dot()),
-            (one_rate, DeliveryOutcome::running(1)),
-            (two_rate, DeliveryOutcome::running(2)),
-            (four_rate, DeliveryOutcome::four()),
-            (six_rate, DeliveryOutcome::six()),
-            (
-                0.5 * wkt_prob,
-                DeliveryOutcome::bowled(striker.id, &bowler.name),
-            ),
-            (
-                0.5 * wkt_prob,
-                DeliveryOutcome::lbw(striker.id, &bowler.name),
-            ),
-        ];
-        let d = WeightedIndex::new(outcomes.iter().map(|i| i.0)).unwrap();
-        let choice = d.sample(rng);
-        outcomes.swap_remove(choice).1
+        let distribution = self.outcome_distribution(&state);
+        sample_outcome(rng, &state, &distribution)
+    }
+
+    fn outcome_distribution(
+        &self,
+        state: &GameSnapshot<PlayerRatingNaiveStats>,
+    ) -> Vec<(f32, DeliveryOutcomeKind)> {
+        // Players in form stay in longer and take wickets more often
+        let mut batting = state.striker.rating.batting.clone();
+        batting.avg *= state.striker_morale.multiplier(MORALE_EFFECT);
+        let mut bowling = state.bowler.rating.bowling.clone();
+        bowling.sr /= state.bowler_morale.multiplier(MORALE_EFFECT);
+        // The venue favours one side, and batters play the kind of bowling they grew up on
+        // better
+        let advantage = (1. + state.home_advantage).max(MIN_ADVANTAGE);
+        batting.avg *= advantage;
+        bowling.sr *= advantage;
+        distribution(&batting, &bowling)
+    }
+
+    fn info(&self) -> ModelInfo {
+        ModelInfo::of::<Self>()
     }
 }
 
+impl Named for NaiveStatsModel {
+    const NAME: &'static str = "NaiveStatsModel";
+}
+
+/// The outcome distribution for a batter facing a bowler. Extreme ratings are clamped so that
+/// this always gives a valid distribution.
+fn distribution(
+    batter_rating: &BatRatingNaiveStats,
+    bowler_rating: &BowlRatingNaiveStats,
+) -> Vec<(f32, DeliveryOutcomeKind)> {
+    let bat_wkt_prob = clamp_prob(batter_rating.sr * 0.01 / batter_rating.avg);
+    let bowl_wkt_prob = clamp_prob(1. / bowler_rating.sr);
+    let wkt_prob = avg_probs(bat_wkt_prob, bowl_wkt_prob);
+
+    // run rates given that no wicket was taken
+    let bat_run_rate = batter_rating.sr * 0.01 / (1. - bat_wkt_prob);
+    let bowl_run_rate = bowler_rating.avg / (bowler_rating.sr - 1.).max(1.);
+    let run_rate = (bat_run_rate * bowl_run_rate).sqrt();
+    // NOTE: boundary fraction is, on average, roughly 50% +/- 5% in all formats.
+    // It's strangely not monotonic: lowest in ODI.
+    // The fraction of boundaries that are sixes is much higher in more limited overs.
+    let four_rate = batter_rating.r4.max(0.);
+    let six_rate = batter_rating.r6.max(0.);
+    // Run rate without boundaries; ignore contribution from bowler. A big hitter can score
+    // more than this estimate from boundaries alone.
+    let run_rate_nb = (run_rate - 4. * four_rate - 6. * six_rate).max(0.);
+
+    // assume for no reason that 10% of non-boundary runs come from twos and 3% from threes
+    let (tr2, tr3) = (0.1, 0.03);
+    let three_rate = run_rate_nb * tr3 / 3.;
+    let two_rate = run_rate_nb * tr2 / 2.;
+    let one_rate = run_rate_nb * (1. - tr2 - tr3);
+
+    // The legal deliveries share what is left after the bowler's wides and no-balls
+    let wide_prob = bowler_rating.wide_rate.clamp(0., MAX_EXTRAS_PROB);
+    let no_ball_prob = bowler_rating.no_ball_rate.clamp(0., MAX_EXTRAS_PROB);
+    let legal = 1. - wide_prob - no_ball_prob;
+
+    // Rough split of the modes of dismissal in international cricket
+    use DeliveryOutcomeKind::*;
+    let wickets = [
+        (0.57, Caught),
+        (0.2, Bowled),
+        (0.15, Lbw),
+        (0.05, RunOut),
+        (0.03, Stumped),
+    ];
+    let mut scoring = vec![
+        (one_rate, Runs(1)),
+        (two_rate, Runs(2)),
+        (three_rate, Runs(3)),
+        (four_rate, Four),
+        (six_rate, Six),
+        (BYE_RATE, Byes(1)),
+        (LEG_BYE_RATE, LegByes(1)),
+    ];
+    scoring.extend(wickets.iter().map(|&(frac, kind)| (wkt_prob * frac, kind)));
+    // If the ratings leave no room for dot balls, scale everything else down to fit
+    let total: f32 = scoring.iter().map(|(p, _)| p).sum();
+    let scale = if total > 1. { 1. / total } else { 1. };
+    let dot_prob = (1. - total).max(0.);
+
+    let mut distribution = vec![
+        (wide_prob, Wide),
+        (no_ball_prob, NoBall),
+        (legal * dot_prob, Runs(0)),
+    ];
+    distribution.extend(
+        scoring
+            .into_iter()
+            .map(|(p, kind)| (legal * scale * p, kind)),
+    );
+    distribution
+}
+
+// Neither a wide nor a no-ball can be more likely than this
+const MAX_EXTRAS_PROB: f32 = 0.25;
+// Keeps probabilities strictly between 0 and 1 so that their log-odds are finite
+const MIN_PROB: f32 = 1e-4;
+
+fn clamp_prob(p: f32) -> f32 {
+    if p.is_nan() {
+        return MIN_PROB;
+    }
+    p.clamp(MIN_PROB, 1. - MIN_PROB)
+}
+
 /// Return the average of two probabilities (on a logistic scale)
 fn avg_probs(p1: f32, p2: f32) -> f32 {
-    // TODO: fix this for p_[12] = 1 (two sqrt terms)
-    let avg_odds = f32::sqrt(p1 * p2 / ((1. - p1) * (1. - p2)));
-    avg_odds / (1. + avg_odds)
+    let logit = |p: f32| {
+        let p = clamp_prob(p);
+        (p / (1. - p)).ln()
+    };
+    let avg_logit = 0.5 * (logit(p1) + logit(p2));
+    1. / (1. + (-avg_logit).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        form::Form,
+        game::{Dismissal, GameState},
+        player::PlayerDb,
+        seed,
+        team::Team,
+    };
+
+    #[test]
+    fn extreme_ratings() {
+        assert!(BatRatingNaiveStats::from_career_stats(0, 0, 0, 0, 0).is_err());
+        assert!(BowlRatingNaiveStats::from_career_stats(0, 0, 0, 0, 0).is_err());
+        // A batter who scores only in boundaries and is never out, against a bowler who
+        // concedes little and never takes a wicket
+        let hitter = BatRatingNaiveStats::from_career_stats(50, 0, 250, 5, 30).unwrap();
+        let miser = BowlRatingNaiveStats::from_career_stats(600, 0, 60, 0, 0).unwrap();
+        let tailender = BatRatingNaiveStats::from_career_stats(40, 20, 2, 0, 0).unwrap();
+        let wrecker = BowlRatingNaiveStats::from_career_stats(60, 30, 150, 10, 10).unwrap();
+        for (bat, bowl) in [
+            (&hitter, &miser),
+            (&hitter, &wrecker),
+            (&tailender, &miser),
+            (&tailender, &wrecker),
+        ] {
+            let dist = distribution(bat, bowl);
+            assert!(dist.iter().all(|(p, _)| p.is_finite() && *p >= 0.));
+            let total: f32 = dist.iter().map(|(p, _)| p).sum();
+            assert!((total - 1.).abs() < 1e-4, "{}", total);
+        }
+        assert!((avg_probs(1., 0.) - 0.5).abs() < 1e-3);
+        assert!(avg_probs(1., 1.) < 1.);
+    }
+
+    fn naive_team(db: &mut PlayerDb<PlayerRatingNaiveStats>, id: u16) -> Result<Team> {
+        let players = (0..11)
+            .map(|i| {
+                let rating = PlayerRatingNaiveStats {
+                    batting: BatRatingNaiveStats::from_career_stats(1000, 30, 800, 90, 10)?,
+                    bowling: BowlRatingNaiveStats::from_career_stats(1200, 40, 1000, 60, 20)?,
+                    fielding: FieldRating::default(),
+                    running: RunningRating::default(),
+                };
+                let player = db.add(format!("{}_{}", id, i), rating)?;
+                Ok((player.id, player.name.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Team {
+            id,
+            name: format!("team_{}", id),
+            keeper: Some(players[4].0),
+            players,
+            captain: None,
+            home: None,
+            roles: Default::default(),
+        })
+    }
+
+    #[test]
+    fn extras_and_dismissals() -> Result<()> {
+        let bat = BatRatingNaiveStats::from_career_stats(1000, 30, 800, 90, 10)?;
+        let bowl = BowlRatingNaiveStats::from_career_stats(1200, 40, 1000, 60, 20)?;
+        assert_eq!((bowl.wide_rate, bowl.no_ball_rate), (0.05, 1. / 60.));
+        let dist = distribution(&bat, &bowl);
+        let prob = |kind| {
+            dist.iter()
+                .find(|(_, k)| *k == kind)
+                .map_or(0., |(p, _)| *p)
+        };
+        use DeliveryOutcomeKind::*;
+        assert_eq!(prob(Wide), 0.05);
+        for kind in [
+            NoBall,
+            Runs(3),
+            Byes(1),
+            LegByes(1),
+            Caught,
+            RunOut,
+            Stumped,
+        ] {
+            assert!(prob(kind) > 0., "{:?}", kind);
+        }
+
+        // Catches in a simulated match are credited to players of the fielding side
+        let mut db = PlayerDb::new();
+        let team_a = naive_team(&mut db, 1)?;
+        let team_b = naive_team(&mut db, 2)?;
+        let mut state = GameState::new(Form::t20(), team_a.clone(), team_b.clone())?;
+        state.simulate_to_completion(&NaiveStatsModel {}, &db, &mut seed::rng(10))?;
+        let mut catches = 0;
+        for innings in state.completed_innings() {
+            let fielding = if innings.bowling_team == team_a.id {
+                &team_a
+            } else {
+                &team_b
+            };
+            for delivery in &innings.deliveries {
+                let outcome = &delivery.outcome;
+                if let Some((_, Dismissal::Caught { caught, .. })) = &outcome.wicket {
+                    catches += 1;
+                    assert_eq!(outcome.fielders.len(), 1);
+                    assert_eq!(
+                        fielding.get_name(outcome.fielders[0]),
+                        Some(caught.as_str())
+                    );
+                    assert_ne!(outcome.fielders[0], delivery.bowler);
+                }
+            }
+        }
+        assert!(catches > 0);
+        Ok(())
+    }
 }
@@ -0,0 +1,410 @@
+//! Fitting naive stats ratings from ball-by-ball data
+use super::{BatRatingNaiveStats, BowlRatingNaiveStats, PlayerRatingNaiveStats};
+use crate::{
+    error::Result,
+    game::{Extra, GameState, Runs},
+    interop::cricsheet::CricsheetMatch,
+    model::{FieldRating, PlayerRatingNull, RunningRating},
+    player::{PlayerDb, PlayerId},
+};
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
+
+/// The default weight, in deliveries, given to the league average when fitting a player.
+/// A player who has faced this many balls gets a rating halfway between their own record
+/// and the league's.
+pub const DEFAULT_PRIOR_BALLS: f32 = 100.;
+
+/// A batter's record, ball by ball
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct BattingCounts {
+    pub balls: u32,
+    pub outs: u32,
+    pub runs: u32,
+    pub fours: u32,
+    pub sixes: u32,
+}
+
+impl BattingCounts {
+    fn add(&mut self, other: &Self) {
+        self.balls += other.balls;
+        self.outs += other.outs;
+        self.runs += other.runs;
+        self.fours += other.fours;
+        self.sixes += other.sixes;
+    }
+}
+
+/// A bowler's record, ball by ball. Deliveries include wides and no-balls, and runs
+/// exclude byes and leg byes.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct BowlingCounts {
+    pub deliveries: u32,
+    pub wickets: u32,
+    pub runs: u32,
+    pub wides: u32,
+    pub no_balls: u32,
+}
+
+impl BowlingCounts {
+    fn add(&mut self, other: &Self) {
+        self.deliveries += other.deliveries;
+        self.wickets += other.wickets;
+        self.runs += other.runs;
+        self.wides += other.wides;
+        self.no_balls += other.no_balls;
+    }
+}
+
+/// Accumulates the records of players, keyed by name, and fits ratings from them.
+///
+/// Each rate is the maximum-likelihood estimate with the league's rate added as
+/// `prior_balls` pseudo-deliveries, so that players with few deliveries are shrunk towards
+/// the league mean.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SavedFit")]
+pub struct NaiveStatsFit {
+    pub prior_balls: f32,
+    batting: FnvHashMap<String, BattingCounts>,
+    bowling: FnvHashMap<String, BowlingCounts>,
+    /// The records of all batters together, kept up to date as records are added
+    #[serde(skip)]
+    league_batting: BattingCounts,
+    /// The records of all bowlers together, kept up to date as records are added
+    #[serde(skip)]
+    league_bowling: BowlingCounts,
+}
+
+/// The saved form of a fit, from which the league records are rebuilt
+#[derive(Deserialize)]
+struct SavedFit {
+    prior_balls: f32,
+    batting: FnvHashMap<String, BattingCounts>,
+    bowling: FnvHashMap<String, BowlingCounts>,
+}
+
+impl From<SavedFit> for NaiveStatsFit {
+    fn from(saved: SavedFit) -> Self {
+        let mut fit = Self::new(saved.prior_balls);
+        for (name, counts) in &saved.batting {
+            fit.add_batting(name, *counts);
+        }
+        for (name, counts) in &saved.bowling {
+            fit.add_bowling(name, *counts);
+        }
+        fit
+    }
+}
+
+impl Default for NaiveStatsFit {
+    fn default() -> Self {
+        Self::new(DEFAULT_PRIOR_BALLS)
+    }
+}
+
+impl NaiveStatsFit {
+    pub fn new(prior_balls: f32) -> Self {
+        Self {
+            prior_balls,
+            batting: FnvHashMap::default(),
+            bowling: FnvHashMap::default(),
+            league_batting: BattingCounts::default(),
+            league_bowling: BowlingCounts::default(),
+        }
+    }
+
+    /// Add the deliveries of a match to the players' records
+    pub fn add_game(&mut self, game: &GameState) {
+        let (team_a, team_b) = game.teams();
+        let name = |id: PlayerId| {
+            team_a
+                .get_name(id)
+                .or_else(|| team_b.get_name(id))
+                .map(str::to_string)
+        };
+        for innings in 0..game.innings_started() {
+            for record in game.innings_deliveries(innings).unwrap_or_default() {
+                let outcome = &record.outcome;
+                if outcome.is_dead_ball() {
+                    continue;
+                }
+                let wide = outcome.extras.iter().any(|ex| matches!(ex, Extra::Wide(_)));
+                if let Some(striker) = name(record.striker) {
+                    let mut bat = BattingCounts {
+                        balls: !wide as u32,
+                        runs: outcome.batter_runs() as u32,
+                        ..Default::default()
+                    };
+                    match outcome.runs {
+                        Runs::Four => bat.fours += 1,
+                        Runs::Six => bat.sixes += 1,
+                        Runs::Running(_) => {}
+                    }
+                    self.add_batting(&striker, bat);
+                }
+                if let Some(out) = outcome.wicket.as_ref().and_then(|(id, _)| name(*id)) {
+                    let outs = BattingCounts {
+                        outs: 1,
+                        ..Default::default()
+                    };
+                    self.add_batting(&out, outs);
+                }
+                if let Some(bowler) = name(record.bowler) {
+                    let mut bowl = BowlingCounts {
+                        deliveries: 1,
+                        runs: outcome.bowler_runs() as u32,
+                        ..Default::default()
+                    };
+                    for extra in &outcome.extras {
+                        match extra {
+                            Extra::Wide(_) => bowl.wides += 1,
+                            Extra::NoBall => bowl.no_balls += 1,
+                            _ => {}
+                        }
+                    }
+                    if outcome
+                        .wicket
+                        .as_ref()
+                        .is_some_and(|(_, d)| d.credited_to_bowler())
+                    {
+                        bowl.wickets += 1;
+                    }
+                    self.add_bowling(&bowler, bowl);
+                }
+            }
+        }
+    }
+
+    /// Add a Cricsheet match to the players' records, replaying it through the engine
+    pub fn add_cricsheet(&mut self, m: &CricsheetMatch) -> Result<()> {
+        let mut db = PlayerDb::<PlayerRatingNull>::new();
+        let (team_a, team_b) = m.build_teams(&mut db)?;
+        self.add_game(&m.replay(team_a, team_b)?);
+        Ok(())
+    }
+
+    /// Add to a batter's record, e.g. from career totals
+    pub fn add_batting(&mut self, name: &str, counts: BattingCounts) {
+        self.batting
+            .entry(name.to_string())
+            .or_default()
+            .add(&counts);
+        self.league_batting.add(&counts);
+    }
+
+    /// Add to a bowler's record, e.g. from career totals
+    pub fn add_bowling(&mut self, name: &str, counts: BowlingCounts) {
+        self.bowling
+            .entry(name.to_string())
+            .or_default()
+            .add(&counts);
+        self.league_bowling.add(&counts);
+    }
+
+    /// The record of a batter, if they faced any deliveries
+    pub fn batting(&self, name: &str) -> Option<&BattingCounts> {
+        self.batting.get(name)
+    }
+
+    /// The record of a bowler, if they bowled any deliveries
+    pub fn bowling(&self, name: &str) -> Option<&BowlingCounts> {
+        self.bowling.get(name)
+    }
+
+    /// The fitted rating of a player. A player with no record gets the league average.
+    pub fn rating(&self, name: &str) -> PlayerRatingNaiveStats {
+        let bat = self.batting.get(name).copied().unwrap_or_default();
+        let bowl = self.bowling.get(name).copied().unwrap_or_default();
+        PlayerRatingNaiveStats {
+            batting: self.fit_batting(&bat),
+            bowling: self.fit_bowling(&bowl),
+            fielding: FieldRating::default(),
+            running: RunningRating::default(),
+        }
+    }
+
+    /// The fitted ratings of every player with a record
+    pub fn ratings(&self) -> FnvHashMap<String, PlayerRatingNaiveStats> {
+        self.batting
+            .keys()
+            .chain(self.bowling.keys())
+            .map(|name| (name.clone(), self.rating(name)))
+            .collect()
+    }
+
+    fn fit_batting(&self, bat: &BattingCounts) -> BatRatingNaiveStats {
+        let league = &self.league_batting;
+        let rate = |count: u32, league_count: u32| {
+            shrunk_rate(
+                count,
+                bat.balls,
+                league_count,
+                league.balls,
+                self.prior_balls,
+            )
+        };
+        let out_rate = rate(bat.outs, league.outs).max(f32::EPSILON);
+        let run_rate = rate(bat.runs, league.runs);
+        BatRatingNaiveStats {
+            avg: run_rate / out_rate,
+            sr: 100. * run_rate,
+            r4: rate(bat.fours, league.fours),
+            r6: rate(bat.sixes, league.sixes),
+        }
+    }
+
+    fn fit_bowling(&self, bowl: &BowlingCounts) -> BowlRatingNaiveStats {
+        let league = &self.league_bowling;
+        let rate = |count: u32, league_count: u32| {
+            shrunk_rate(
+                count,
+                bowl.deliveries,
+                league_count,
+                league.deliveries,
+                self.prior_balls,
+            )
+        };
+        let wicket_rate = rate(bowl.wickets, league.wickets).max(f32::EPSILON);
+        BowlRatingNaiveStats {
+            sr: 1. / wicket_rate,
+            avg: rate(bowl.runs, league.runs) / wicket_rate,
+            wide_rate: rate(bowl.wides, league.wides),
+            no_ball_rate: rate(bowl.no_balls, league.no_balls),
+        }
+    }
+}
+
+/// The rate of an event per ball, with the league rate weighted as `prior` balls
+fn shrunk_rate(count: u32, balls: u32, league_count: u32, league_balls: u32, prior: f32) -> f32 {
+    let league_rate = league_count as f32 / league_balls.max(1) as f32;
+    let balls = balls as f32 + prior;
+    if balls <= 0. {
+        return league_rate;
+    }
+    (count as f32 + prior * league_rate) / balls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{form::Form, model::NullModel, seed, tests::test_team};
+
+    #[test]
+    fn league_kept_with_records() -> Result<()> {
+        let mut fit = NaiveStatsFit::new(50.);
+        let batting = |balls, outs, runs| BattingCounts {
+            balls,
+            outs,
+            runs,
+            ..Default::default()
+        };
+        fit.add_batting("Root", batting(300, 3, 150));
+        fit.add_batting("Pope", batting(100, 2, 50));
+        fit.add_batting("Root", batting(100, 1, 50));
+        assert_eq!(fit.batting("Root").unwrap().balls, 400);
+        // A player with no record is rated at the rate of all batters together
+        let league = fit.rating("nobody").batting;
+        assert!((league.sr - 50.).abs() < 1e-4);
+        assert!((league.avg - 250. / 6.).abs() < 1e-3);
+        fit.add_bowling(
+            "Wood",
+            BowlingCounts {
+                deliveries: 120,
+                wickets: 4,
+                runs: 90,
+                ..Default::default()
+            },
+        );
+        assert!((fit.rating("nobody").bowling.sr - 30.).abs() < 1e-4);
+        // The league records are rebuilt when a saved fit is loaded
+        let loaded: NaiveStatsFit = serde_json::from_str(&serde_json::to_string(&fit)?)?;
+        let ratings = |fit: &NaiveStatsFit| serde_json::to_string(&fit.rating("Pope"));
+        assert_eq!(ratings(&loaded)?, ratings(&fit)?);
+        assert_eq!(
+            serde_json::to_string(&loaded.rating("nobody"))?,
+            serde_json::to_string(&fit.rating("nobody"))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shrunk_towards_league() {
+        let mut fit = NaiveStatsFit::new(100.);
+        let batting = |balls, outs, runs, fours| BattingCounts {
+            balls,
+            outs,
+            runs,
+            fours,
+            ..Default::default()
+        };
+        fit.add_batting("Root", batting(300, 3, 210, 24));
+        fit.add_batting("Pope", batting(100, 2, 30, 0));
+        // The league scores 0.6 a ball, loses a wicket every 80 and hits a four every 16.7
+        let root = fit.rating("Root").batting;
+        assert!((root.sr - 100. * (210. + 60.) / 400.).abs() < 1e-4);
+        assert!((root.avg - (270. / 400.) / (4.25 / 400.)).abs() < 1e-3);
+        assert!((root.r4 - (24. + 6.) / 400.).abs() < 1e-6);
+        // Pope has faced as many balls as the prior, so is halfway to the league
+        let pope = fit.rating("Pope").batting;
+        assert!((pope.sr - 45.).abs() < 1e-4);
+        assert!((pope.avg - 90. / 3.25).abs() < 1e-3);
+        assert!((pope.r4 - 0.03).abs() < 1e-6);
+
+        fit.add_bowling(
+            "Wood",
+            BowlingCounts {
+                deliveries: 100,
+                wickets: 5,
+                runs: 80,
+                wides: 4,
+                no_balls: 0,
+            },
+        );
+        fit.add_bowling(
+            "Leach",
+            BowlingCounts {
+                deliveries: 300,
+                wickets: 3,
+                runs: 240,
+                wides: 0,
+                no_balls: 2,
+            },
+        );
+        // The league takes a wicket every 50 balls and concedes 0.8 a ball
+        let wood = fit.rating("Wood").bowling;
+        assert!((wood.sr - 200. / 7.).abs() < 1e-3);
+        assert!((wood.avg - 160. / 7.).abs() < 1e-3);
+        assert!((wood.wide_rate - 5. / 200.).abs() < 1e-6);
+        assert!((wood.no_ball_rate - 0.5 / 200.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn records_from_a_game() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "WI")?;
+        let team_b = test_team(&mut db, 2, "SL")?;
+        let mut game = GameState::new(Form::t20(), team_a.clone(), team_b)?;
+        game.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(5))?;
+        let mut fit = NaiveStatsFit::default();
+        fit.add_game(&game);
+        let faced: u32 = team_a
+            .players
+            .iter()
+            .filter_map(|(_, name)| fit.batting(name))
+            .map(|bat| bat.balls)
+            .sum();
+        // Wides aren't faced, but no-balls are
+        let balls = game
+            .innings_deliveries(0)
+            .unwrap()
+            .iter()
+            .map(|record| &record.outcome)
+            .filter(|o| {
+                !o.is_dead_ball() && !o.extras.iter().any(|ex| matches!(ex, Extra::Wide(_)))
+            })
+            .count();
+        assert_eq!(faced as usize, balls);
+        assert!(fit.ratings().values().all(|r| r.validate().is_ok()));
+        Ok(())
+    }
+}
@@ -1,62 +1,77 @@
 //! A model that doesn't depend on any data
-use super::{Model, PlayerRating};
-use crate::game::{DeliveryOutcome, GameSnapshot};
-use rand::{distributions::Uniform, Rng};
+use super::{sample_outcome, FieldRating, Model, ModelInfo, Named, PlayerRating, RunningRating};
+use crate::game::{DeliveryOutcome, DeliveryOutcomeKind, GameSnapshot};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PlayerRatingNull {
     pub batting: BatRatingNull,
     pub bowling: BowlRatingNull,
-    pub fielding: FieldRatingNull,
+    #[serde(default)]
+    pub fielding: FieldRating,
+    #[serde(default)]
+    pub running: RunningRating,
 }
 impl Default for PlayerRatingNull {
     fn default() -> Self {
         Self {
             batting: BatRatingNull {},
             bowling: BowlRatingNull {},
-            fielding: FieldRatingNull {},
+            fielding: FieldRating::default(),
+            running: RunningRating::default(),
         }
     }
 }
-impl PlayerRating for PlayerRatingNull {}
+impl PlayerRating for PlayerRatingNull {
+    fn fielding(&self) -> &FieldRating {
+        &self.fielding
+    }
+    fn running(&self) -> &RunningRating {
+        &self.running
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct BatRatingNull {}
 #[derive(Debug, Deserialize, Serialize)]
 pub struct BowlRatingNull {}
-#[derive(Debug, Deserialize, Serialize)]
-pub struct FieldRatingNull {}
 
 /// A very simple model that doesn't use player stats
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NullModel {}
 
 impl Model<PlayerRatingNull> for NullModel {
     fn generate_delivery(
         &self,
-        rng: &mut impl Rng,
+        rng: &mut dyn RngCore,
         state: GameSnapshot<PlayerRatingNull>,
     ) -> DeliveryOutcome {
-        let striker_id = state.striker.id;
-        let bowler = state.bowler;
-        // NOTE: Consider WeightedIndex distribution instead of manually cutting on a standard
-        // uniform value
-        let dist = Uniform::new(0., 1.);
-        let rand: f64 = rng.sample(dist);
-        if rand < 0.01 {
-            DeliveryOutcome::caught(striker_id, &bowler.name, "?fielder")
-        } else if rand <= 0.015 {
-            DeliveryOutcome::bowled(striker_id, &bowler.name)
-        } else if rand <= 0.02 {
-            DeliveryOutcome::lbw(striker_id, &bowler.name)
-        } else if rand <= 0.4 {
-            DeliveryOutcome::running(1)
-        } else if rand <= 0.42 {
-            DeliveryOutcome::four()
-        } else if rand <= 0.424 {
-            DeliveryOutcome::six()
-        } else {
-            DeliveryOutcome::dot()
-        }
+        let distribution = self.outcome_distribution(&state);
+        sample_outcome(rng, &state, &distribution)
+    }
+
+    fn outcome_distribution(
+        &self,
+        _state: &GameSnapshot<PlayerRatingNull>,
+    ) -> Vec<(f32, DeliveryOutcomeKind)> {
+        use DeliveryOutcomeKind::*;
+        vec![
+            (0.01, Caught),
+            (0.005, Bowled),
+            (0.005, Lbw),
+            (0.38, Runs(1)),
+            (0.02, Four),
+            (0.004, Six),
+            (0.576, Runs(0)),
+        ]
+    }
+
+    fn info(&self) -> ModelInfo {
+        ModelInfo::of::<Self>()
     }
 }
+
+impl Named for NullModel {
+    const NAME: &'static str = "NullModel";
+}
@@ -0,0 +1,270 @@
+//! A model built from separate bowling, batting, and fielding stages
+use super::{basic::choose, Model, ModelInfo, Named, PlayerRating};
+use crate::{
+    field::Zone,
+    game::{DeliveryOutcome, GameSnapshot},
+};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// Where the ball passes the batter, relative to the stumps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Line {
+    /// Wide of off stump, which can be left alone
+    WideOutsideOff,
+    /// In the channel just outside off stump
+    OutsideOff,
+    /// At the stumps
+    Stumps,
+    /// Down the leg side
+    Leg,
+}
+
+impl Line {
+    /// Every line, from wide of off stump to down the leg side
+    pub const ALL: [Line; 4] = [
+        Line::WideOutsideOff,
+        Line::OutsideOff,
+        Line::Stumps,
+        Line::Leg,
+    ];
+}
+
+/// Where the ball pitches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Length {
+    /// Reaches the batter without bouncing
+    FullToss,
+    /// At the batter's feet
+    Yorker,
+    Full,
+    Good,
+    Short,
+    Bouncer,
+}
+
+impl Length {
+    /// Every length, from the fullest to the shortest
+    pub const ALL: [Length; 6] = [
+        Length::FullToss,
+        Length::Yorker,
+        Length::Full,
+        Length::Good,
+        Length::Short,
+        Length::Bouncer,
+    ];
+}
+
+/// The style of the bowler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pace {
+    Fast,
+    Medium,
+    Spin,
+}
+
+impl Pace {
+    /// Every style, from the quickest to the slowest
+    pub const ALL: [Pace; 3] = [Pace::Fast, Pace::Medium, Pace::Spin];
+}
+
+/// The ball as bowled, before the batter plays at it. The composite model attaches it to
+/// the outcome for pitch maps. Only the bowling models set the speed and movement, e.g.
+/// StyledBowling from the bowler's style, fatigue and the state of the ball; the batting
+/// and fielding models go by the line, length and pace.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Delivery {
+    pub line: Line,
+    pub length: Length,
+    pub pace: Pace,
+    /// Speed out of the hand, in km/h
+    pub speed: f32,
+    /// Sideways movement in the air and off the pitch, in cm. Positive values move away
+    /// from a right-handed batter.
+    pub movement: f32,
+}
+
+/// The batter's decision of how to play a delivery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Shot {
+    /// Play no shot
+    Leave,
+    /// Block the ball
+    Defend,
+    /// Work the ball into a gap for runs
+    Rotate,
+    /// Try to hit a boundary
+    Attack,
+}
+
+/// The first stage: the bowler chooses and bowls a delivery
+pub trait BowlingModel<R: PlayerRating> {
+    fn bowl(&self, rng: &mut dyn RngCore, state: &GameSnapshot<R>) -> Delivery;
+}
+
+/// The second stage: the batter decides how to play the delivery
+pub trait BattingModel<R: PlayerRating> {
+    fn shot(&self, rng: &mut dyn RngCore, state: &GameSnapshot<R>, delivery: &Delivery) -> Shot;
+}
+
+/// The last stage: the outcome of the shot, including any chance for the fielders. The
+/// outcome can say where a scoring shot went; otherwise a zone is chosen to suit the
+/// delivery and the shot.
+pub trait FieldingModel<R: PlayerRating> {
+    fn resolve(
+        &self,
+        rng: &mut dyn RngCore,
+        state: &GameSnapshot<R>,
+        delivery: &Delivery,
+        shot: Shot,
+    ) -> DeliveryOutcome;
+}
+
+/// Combines one model for each stage into a complete Model, so that stages can be mixed
+/// and matched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeModel<Bo, Ba, F> {
+    pub bowling: Bo,
+    pub batting: Ba,
+    pub fielding: F,
+}
+
+impl<R, Bo, Ba, F> Model<R> for CompositeModel<Bo, Ba, F>
+where
+    R: PlayerRating,
+    Bo: BowlingModel<R> + Named,
+    Ba: BattingModel<R> + Named,
+    F: FieldingModel<R> + Named,
+{
+    fn generate_delivery(&self, rng: &mut dyn RngCore, state: GameSnapshot<R>) -> DeliveryOutcome {
+        let delivery = self.bowling.bowl(rng, &state);
+        let shot = self.batting.shot(rng, &state, &delivery);
+        let mut outcome = self.fielding.resolve(rng, &state, &delivery, shot);
+        outcome.delivery = Some(delivery);
+        if outcome.zone.is_none() && outcome.runs.runs() > 0 {
+            outcome.zone = Some(scoring_zone(rng, &delivery, shot));
+        }
+        outcome
+    }
+
+    fn info(&self) -> ModelInfo {
+        ModelInfo::of::<Self>()
+    }
+}
+
+impl<Bo: Named, Ba: Named, F: Named> Named for CompositeModel<Bo, Ba, F> {
+    const NAME: &'static str = "CompositeModel";
+
+    fn full_name() -> String {
+        format!(
+            "{}<{}, {}, {}>",
+            Self::NAME,
+            Bo::full_name(),
+            Ba::full_name(),
+            F::full_name()
+        )
+    }
+}
+
+/// A zone for a scoring shot. Balls outside off tend to go through the off side, and
+/// those at the body to leg; full balls are driven in front of square, while short
+/// balls are cut, pulled, or edged behind.
+fn scoring_zone(rng: &mut dyn RngCore, delivery: &Delivery, shot: Shot) -> Zone {
+    let off_side = match delivery.line {
+        Line::WideOutsideOff => 0.8,
+        Line::OutsideOff => 0.65,
+        Line::Stumps => 0.4,
+        Line::Leg => 0.15,
+    };
+    // Weights for behind square, square, in front of square, and straight
+    let depth = match (shot, delivery.length) {
+        (Shot::Attack, Length::FullToss | Length::Yorker | Length::Full) => [0.05, 0.15, 0.3, 0.5],
+        (Shot::Attack, Length::Good) => [0.15, 0.25, 0.35, 0.25],
+        (_, Length::Short | Length::Bouncer) => [0.3, 0.4, 0.2, 0.1],
+        _ => [0.15, 0.35, 0.35, 0.15],
+    };
+    let sector = choose(
+        rng,
+        &[(depth[0], 0), (depth[1], 1), (depth[2], 2), (depth[3], 3)],
+    );
+    if rng.gen::<f32>() < off_side {
+        Zone::ALL[sector]
+    } else {
+        Zone::ALL[7 - sector]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Result,
+        form, game, model,
+        player::{self, PlayerDb},
+        strategy,
+        tests::test_team,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn composite_model() -> Result<()> {
+        use model::{BasicBatting, BasicBowling, BasicFielding, CompositeModel};
+        use player::{BowlingStyle, Hand};
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "SA")?;
+        let team_b = test_team(&mut db, 2, "USA")?;
+        let model = CompositeModel {
+            bowling: BasicBowling {},
+            batting: BasicBatting {},
+            fielding: BasicFielding {},
+        };
+        let mut state = game::GameState::new(form::Form::odi(), team_a.clone(), team_b.clone())?;
+        state.simulate_to_completion(&model, &db, &mut thread_rng())?;
+        assert!(state.innings_total(0).unwrap().0 > 0);
+        let first_ball = &state.innings_deliveries(0).unwrap()[0].outcome;
+        assert!(first_ball.delivery.is_some_and(|d| d.speed > 0.));
+        for line in &state.scorecard()?.innings[0].batting {
+            let wheel = state.innings_wagon_wheel(0, line.player);
+            let runs: u16 = wheel.map_or(0, |w| w.zones().map(|(_, z)| z.runs).sum());
+            assert!(runs <= line.runs);
+            assert_eq!(wheel.is_some(), line.runs > 0);
+        }
+        for line in &state.scorecard()?.innings[0].bowling {
+            let map = state.innings_pitch_map(0, line.player).unwrap();
+            let cells = map.cells().map(|(_, _, cell)| cell);
+            let (runs, wickets) = cells.fold((0, 0), |(r, w), c| (r + c.runs, w + c.wickets));
+            assert_eq!((runs, wickets), (line.runs, line.wickets));
+        }
+
+        let model = CompositeModel {
+            bowling: model::StyledBowling {},
+            batting: model::AggressiveBatting {
+                strategy: strategy::SituationalAggression::default(),
+            },
+            fielding: model::RatedFielding {},
+        };
+        for (i, (id, _)) in team_b.players.iter().enumerate() {
+            let player = db.get_mut(*id).unwrap();
+            player.bats = if i % 2 == 0 { Hand::Left } else { Hand::Right };
+            player.bowls = Some(if i < 6 {
+                BowlingStyle::RightFastMedium
+            } else {
+                BowlingStyle::SlowLeftArm
+            });
+        }
+        let mut state = game::GameState::new(form::Form::t20(), team_a, team_b)?;
+        state.simulate_to_completion(&model, &db, &mut thread_rng())?;
+        let drops = state
+            .innings_deliveries(0)
+            .unwrap()
+            .iter()
+            .filter(|d| {
+                matches!(
+                    d.outcome.fielding_error,
+                    Some(game::FieldingError::DroppedCatch { .. })
+                )
+            })
+            .count();
+        assert_eq!(state.innings_dropped_catches(0).unwrap().len(), drops);
+        Ok(())
+    }
+}
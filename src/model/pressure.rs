@@ -0,0 +1,186 @@
+//! A wrapper that makes any model sensitive to the pressure of the match situation
+use super::{Model, ModelInfo, Named, PlayerRating};
+use crate::game::{DeliveryOutcome, GameSnapshot, MatchContext, Runs};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// How much pressure the batting side is under, from 0 to 1. A steep required rate, a
+/// run of dot balls, the tail being exposed, and a new batter at the crease all add to it.
+pub fn pressure(context: &MatchContext) -> f32 {
+    let chase = context
+        .required_run_rate
+        .map_or(0., |rate| ((rate - 6.) / 6.).clamp(0., 1.));
+    let dots = (context.dots_in_a_row as f32 / 6.).min(1.);
+    let tail = (3 - context.wickets_remaining.min(3)) as f32 / 3.;
+    let new_batter = (10 - context.striker_balls.min(10)) as f32 / 10.;
+    (0.4 * chase + 0.3 * dots + 0.2 * tail + 0.1 * new_batter).min(1.)
+}
+
+/// Wraps a model so that batters under pressure take more risks: some deliveries the
+/// base model would have kept quiet become boundaries, and some become wickets, except on
+/// a free hit. How the ball was bowled and any umpire's signal are kept from the base
+/// model's outcome.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PressureModel<M> {
+    pub model: M,
+    /// Scales the effect of pressure. At 1, a delivery under full pressure has an extra
+    /// 4% chance of a wicket and 6% chance of a boundary.
+    pub sensitivity: f32,
+}
+
+impl<M> PressureModel<M> {
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            sensitivity: 1.,
+        }
+    }
+}
+
+impl<R: PlayerRating, M: Model<R> + Named> Model<R> for PressureModel<M> {
+    fn generate_delivery(&self, rng: &mut dyn RngCore, state: GameSnapshot<R>) -> DeliveryOutcome {
+        let pressure = self.sensitivity * pressure(&state.context);
+        let (striker, bowler, keeper) = (state.striker.id, state.bowler, state.keeper);
+        let free_hit = state.context.free_hit;
+        let catcher = match state.fielders.len() {
+            0 => bowler,
+            n => state.fielders[rng.gen_range(0..n)],
+        };
+        let outcome = self.model.generate_delivery(rng, state);
+        // Only a legal ball that the base model didn't already decide can be changed
+        if outcome.wicket.is_some()
+            || !outcome.extras.is_empty()
+            || outcome.runs.runs() > 1
+            || outcome.fielding_error.is_some()
+        {
+            return outcome;
+        }
+        let roll: f32 = rng.gen();
+        if roll < 0.04 * pressure {
+            if free_hit {
+                return outcome;
+            }
+            let caught = if catcher.id == bowler.id {
+                DeliveryOutcome::caught_and_bowled(striker, bowler.id, &bowler.name)
+            } else if keeper.is_some_and(|keeper| keeper.id == catcher.id) {
+                DeliveryOutcome::caught_behind(striker, &bowler.name, catcher.id, &catcher.name)
+            } else {
+                DeliveryOutcome::caught(striker, &bowler.name, catcher.id, &catcher.name)
+            };
+            DeliveryOutcome {
+                wicket: caught.wicket,
+                fielders: caught.fielders,
+                runs: Runs::Running(0),
+                zone: None,
+                ..outcome
+            }
+        } else if roll < 0.1 * pressure {
+            DeliveryOutcome {
+                runs: Runs::Four,
+                ..outcome
+            }
+        } else {
+            outcome
+        }
+    }
+
+    fn info(&self) -> ModelInfo {
+        ModelInfo::of::<Self>()
+    }
+}
+
+impl<M: Named> Named for PressureModel<M> {
+    const NAME: &'static str = "PressureModel";
+
+    fn full_name() -> String {
+        format!("{}<{}>", Self::NAME, M::full_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Result,
+        form::Form,
+        game::{Extra, GameState},
+        model::{
+            pipeline::{Delivery, Length, Line, Pace},
+            PlayerRatingNull,
+        },
+        player::PlayerDb,
+        seed,
+        tests::test_team,
+    };
+
+    /// Bowls a dot ball of a known delivery, or a no-ball unless it is a free hit if
+    /// `no_balls` is set
+    struct Dots {
+        no_balls: bool,
+    }
+
+    impl Model<PlayerRatingNull> for Dots {
+        fn generate_delivery(
+            &self,
+            _rng: &mut dyn RngCore,
+            state: GameSnapshot<PlayerRatingNull>,
+        ) -> DeliveryOutcome {
+            let mut outcome = DeliveryOutcome::dot();
+            outcome.delivery = Some(Delivery {
+                line: Line::Stumps,
+                length: Length::Good,
+                pace: Pace::Fast,
+                speed: 140.,
+                movement: 0.,
+            });
+            if self.no_balls && !state.context.free_hit {
+                outcome.extras.push(Extra::NoBall);
+            }
+            outcome
+        }
+
+        fn info(&self) -> ModelInfo {
+            ModelInfo::of::<Self>()
+        }
+    }
+
+    impl Named for Dots {
+        const NAME: &'static str = "Dots";
+    }
+
+    fn pressed(no_balls: bool) -> PressureModel<Dots> {
+        PressureModel {
+            model: Dots { no_balls },
+            // Enough that any pressure at all turns the ball into a catch
+            sensitivity: 1000.,
+        }
+    }
+
+    #[test]
+    fn catches_keep_the_delivery() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "SL")?;
+        let team_b = test_team(&mut db, 2, "PAK")?;
+        let mut state = GameState::new(Form::t20(), team_a, team_b)?;
+        let (_, outcome, _) = state.simulate_delivery(&pressed(false), &db, &mut seed::rng(1))?;
+        assert!(outcome.wicket.is_some());
+        assert!(outcome.delivery.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn no_catches_on_a_free_hit() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "SL")?;
+        let team_b = test_team(&mut db, 2, "PAK")?;
+        let mut state = GameState::new(Form::t20(), team_a, team_b)?;
+        let mut rng = seed::rng(1);
+        for _ in 0..20 {
+            let (_, outcome, _) = state.simulate_delivery(&pressed(true), &db, &mut rng)?;
+            assert!(outcome.wicket.is_none());
+            assert!(outcome.delivery.is_some());
+        }
+        assert_eq!(state.overs_bowled(), Some(crate::form::Overs::new(1, 4)));
+        Ok(())
+    }
+}
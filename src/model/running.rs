@@ -0,0 +1,77 @@
+//! Running between the wickets, and the run outs that come from pushing for extra runs
+use super::{pipeline::Shot, PlayerRating};
+use crate::{
+    game::{DeliveryOutcome, Dismissal, End, GameSnapshot},
+    player::Player,
+};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// How quickly a player runs between the wickets
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RunningRating {
+    /// From 0 (very slow) to 1 (very fast)
+    pub speed: f32,
+}
+
+impl Default for RunningRating {
+    fn default() -> Self {
+        Self { speed: 0.5 }
+    }
+}
+
+/// The batters have completed `runs` off a ball worked into the field, and may push for
+/// one more against the fielder's throw. The throw goes to one end, and the batter nearest
+/// it can be run out: the one running to it if they have crossed, or otherwise the one
+/// who left it. Only the completed runs count.
+pub(crate) fn push_for_run<R: PlayerRating>(
+    rng: &mut dyn RngCore,
+    state: &GameSnapshot<R>,
+    fielder: &Player<R>,
+    shot: Shot,
+    runs: u8,
+) -> DeliveryOutcome {
+    let attempt = match shot {
+        Shot::Attack => 0.2,
+        Shot::Rotate => 0.15,
+        Shot::Leave | Shot::Defend => 0.05,
+    };
+    if !rng.gen_bool(attempt) {
+        return DeliveryOutcome::running(runs);
+    }
+    let end = if rng.gen_bool(0.5) {
+        End::Bowler
+    } else {
+        End::Striker
+    };
+    // Most run outs come after the batters cross, but one sent back by their partner is
+    // short of the end they left
+    let crossed = rng.gen_bool(0.75);
+    // On the first, third and so on, the striker runs to the bowler's end
+    let striker_running_to_end = (end == End::Bowler) != (runs % 2 == 1);
+    let striker_in_danger = striker_running_to_end == crossed;
+    let batter = if striker_in_danger {
+        state.striker
+    } else {
+        state.non_striker
+    };
+    let arm = fielder.rating.fielding().arm;
+    let speed = batter.rating.running().speed;
+    let out_prob = (0.25 + 0.5 * (arm - speed)).clamp(0.02, 0.9);
+    if !rng.gen_bool(out_prob as f64) {
+        return DeliveryOutcome::running(runs + 1);
+    }
+    let name = fielder.name.clone();
+    let dismissal = if striker_in_danger {
+        Dismissal::RunOutStriker(name)
+    } else {
+        Dismissal::RunOutNonStriker(name)
+    };
+    DeliveryOutcome {
+        wicket: Some((batter.id, dismissal)),
+        fielders: vec![fielder.id],
+        run_out_end: Some(end),
+        ..DeliveryOutcome::running(runs)
+    }
+}
@@ -0,0 +1,396 @@
+//! Simulating many independent matches in parallel to find the distribution of outcomes
+use crate::{
+    error::Result,
+    form::Form,
+    game::{CompactLog, GameState, Margin, MatchResult},
+    model::{Model, PlayerRating},
+    player::{PlayerDb, PlayerId},
+    seed,
+    team::Team,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, thread};
+
+/// The outcomes of one side across a batch of simulated matches
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TeamDistribution {
+    pub team: u16,
+    pub wins: usize,
+    /// The total of the side's match scores across all simulations
+    pub total_runs: u64,
+    /// The margin of each win
+    pub margins: Vec<Margin>,
+    /// The number of matches in which each player was the side's top scorer, counting
+    /// only matches in which the side batted. A tie goes to the batter higher in the order.
+    #[serde(default)]
+    pub top_batters: Vec<(PlayerId, usize)>,
+}
+
+impl TeamDistribution {
+    fn new(team: u16) -> Self {
+        Self {
+            team,
+            ..Default::default()
+        }
+    }
+
+    fn record_top_batter(&mut self, batter: PlayerId) {
+        match self.top_batters.iter_mut().find(|(id, _)| *id == batter) {
+            Some((_, count)) => *count += 1,
+            None => self.top_batters.push((batter, 1)),
+        }
+    }
+}
+
+/// The distribution of results from a batch of simulated matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Distribution {
+    pub matches: usize,
+    /// The side batting first
+    pub team_a: TeamDistribution,
+    pub team_b: TeamDistribution,
+    pub ties: usize,
+    pub draws: usize,
+    #[serde(default)]
+    pub no_results: usize,
+    /// The totals of each innings of the match, by its index, in the simulations in which
+    /// it was played
+    #[serde(default)]
+    pub innings_totals: Vec<Vec<u16>>,
+}
+
+impl Distribution {
+    fn new(team_a: &Team, team_b: &Team) -> Self {
+        Self {
+            matches: 0,
+            team_a: TeamDistribution::new(team_a.id),
+            team_b: TeamDistribution::new(team_b.id),
+            ties: 0,
+            draws: 0,
+            no_results: 0,
+            innings_totals: Vec::new(),
+        }
+    }
+
+    /// Add the outcome of one simulated match
+    fn record(&mut self, outcome: Outcome) {
+        self.matches += 1;
+        self.team_a.total_runs += outcome.scores.0 as u64;
+        self.team_b.total_runs += outcome.scores.1 as u64;
+        for (innings, total) in outcome.innings_totals.iter().enumerate() {
+            if self.innings_totals.len() <= innings {
+                self.innings_totals.push(Vec::new());
+            }
+            self.innings_totals[innings].push(*total);
+        }
+        if let Some(batter) = outcome.top_batters.0 {
+            self.team_a.record_top_batter(batter);
+        }
+        if let Some(batter) = outcome.top_batters.1 {
+            self.team_b.record_top_batter(batter);
+        }
+        match outcome.result {
+            MatchResult::Win { winner, margin } => {
+                let team = if winner == self.team_a.team {
+                    &mut self.team_a
+                } else {
+                    &mut self.team_b
+                };
+                team.wins += 1;
+                team.margins.push(margin);
+            }
+            MatchResult::Tie => self.ties += 1,
+            MatchResult::Draw => self.draws += 1,
+            MatchResult::NoResult => self.no_results += 1,
+        }
+    }
+
+    /// The fraction of matches won by the team with the given ID, or 0 if no matches
+    /// were simulated
+    pub fn win_fraction(&self, team: u16) -> f32 {
+        self.team(team)
+            .map_or(0., |t| t.wins as f32 / self.matches.max(1) as f32)
+    }
+
+    /// The average match score of the team with the given ID, or 0 if no matches were
+    /// simulated
+    pub fn average_score(&self, team: u16) -> f32 {
+        self.team(team)
+            .map_or(0., |t| t.total_runs as f32 / self.matches.max(1) as f32)
+    }
+
+    /// The total of the given innings, counted from 0, that a fraction `q` of the
+    /// simulations in which it was played fell at or below. None if it was never played.
+    pub fn innings_total_quantile(&self, innings: usize, q: f32) -> Option<u16> {
+        let mut totals = self.innings_totals.get(innings)?.clone();
+        if totals.is_empty() {
+            return None;
+        }
+        totals.sort_unstable();
+        let rank = (q.clamp(0., 1.) * totals.len() as f32).ceil() as usize;
+        Some(totals[rank.saturating_sub(1)])
+    }
+
+    /// The fraction of the simulations in which the given innings was played where its
+    /// total was more than `line`
+    pub fn innings_total_over(&self, innings: usize, line: f32) -> Option<f32> {
+        let totals = self.innings_totals.get(innings)?;
+        let over = totals.iter().filter(|&&total| total as f32 > line).count();
+        (!totals.is_empty()).then(|| over as f32 / totals.len() as f32)
+    }
+
+    /// The fraction of matches in which each player was the given team's top scorer,
+    /// out of those in which the side batted, most often first
+    pub fn top_batter_fractions(&self, team: u16) -> Vec<(PlayerId, f32)> {
+        let top_batters = self.team(team).map_or(&[][..], |t| &t.top_batters);
+        let batted: usize = top_batters.iter().map(|(_, count)| count).sum();
+        let mut fractions: Vec<_> = top_batters
+            .iter()
+            .map(|(id, count)| (*id, *count as f32 / batted as f32))
+            .collect();
+        fractions.sort_by(|a, b| b.1.total_cmp(&a.1));
+        fractions
+    }
+
+    pub fn team(&self, team: u16) -> Option<&TeamDistribution> {
+        [&self.team_a, &self.team_b]
+            .iter()
+            .copied()
+            .find(|t| t.team == team)
+    }
+}
+
+/// The outcome of a single simulated match
+struct Outcome {
+    result: MatchResult,
+    scores: (u16, u16),
+    /// The total of each innings played, in order
+    innings_totals: Vec<u16>,
+    /// The top scorer of each side, if it batted
+    top_batters: (Option<PlayerId>, Option<PlayerId>),
+    /// The record of the match, if it is being kept
+    log: Option<CompactLog>,
+}
+
+impl Outcome {
+    /// Simulate the rest of the match and take its outcome
+    fn simulate<M, R>(
+        mut game: GameState,
+        model: &M,
+        db: &PlayerDb<R>,
+        rng: &mut impl Rng,
+        keep_log: bool,
+    ) -> Result<Self>
+    where
+        M: Model<R> + ?Sized,
+        R: PlayerRating,
+    {
+        let result = game.simulate_to_completion(model, db, rng)?;
+        let (team_a, team_b) = game.teams();
+        let scores = (game.team_score(team_a), game.team_score(team_b));
+        let innings_totals = game
+            .completed_innings()
+            .iter()
+            .map(|st| st.runs())
+            .collect();
+        let top_batters = (top_scorer(&game, team_a.id), top_scorer(&game, team_b.id));
+        let log = keep_log.then(|| game.log().compact()).transpose()?;
+        Ok(Self {
+            result,
+            scores,
+            innings_totals,
+            top_batters,
+            log,
+        })
+    }
+}
+
+/// The player who scored the most runs for the given team over the match, if it batted.
+/// A tie goes to the batter who came in first.
+fn top_scorer(game: &GameState, team: u16) -> Option<PlayerId> {
+    let mut runs: Vec<(PlayerId, u16)> = Vec::new();
+    for st in game
+        .completed_innings()
+        .iter()
+        .filter(|st| st.batting_team == team)
+    {
+        for (id, batter) in st.batting_stats.batters() {
+            match runs.iter_mut().find(|(b, _)| b == id) {
+                Some((_, total)) => *total += batter.runs,
+                None => runs.push((*id, batter.runs)),
+            }
+        }
+    }
+    runs.into_iter()
+        .fold(None, |top: Option<(PlayerId, u16)>, (id, r)| match top {
+            Some((_, most)) if most >= r => top,
+            _ => Some((id, r)),
+        })
+        .map(|(id, _)| id)
+}
+
+/// Simulate `n` independent matches between two teams across all available threads.
+///
+/// Each match gets its own generator derived from `seed` and the index of the match (see
+/// the seed module), so the results do not depend on the number of threads.
+pub fn simulate_many<M, R>(
+    n: usize,
+    form: &Form,
+    teams: (Arc<Team>, Arc<Team>),
+    model: &M,
+    db: &PlayerDb<R>,
+    seed: u64,
+) -> Result<Distribution>
+where
+    M: Model<R> + Sync + ?Sized,
+    R: PlayerRating + Sync,
+{
+    simulate_batch(n, form, teams, model, db, seed, false).map(|(distribution, _)| distribution)
+}
+
+/// Simulate `n` matches as `simulate_many` does, also keeping the record of each match in
+/// the compact encoding, in match order
+pub fn simulate_many_logged<M, R>(
+    n: usize,
+    form: &Form,
+    teams: (Arc<Team>, Arc<Team>),
+    model: &M,
+    db: &PlayerDb<R>,
+    seed: u64,
+) -> Result<(Distribution, Vec<CompactLog>)>
+where
+    M: Model<R> + Sync + ?Sized,
+    R: PlayerRating + Sync,
+{
+    simulate_batch(n, form, teams, model, db, seed, true)
+}
+
+fn simulate_batch<M, R>(
+    n: usize,
+    form: &Form,
+    teams: (Arc<Team>, Arc<Team>),
+    model: &M,
+    db: &PlayerDb<R>,
+    seed: u64,
+    keep_logs: bool,
+) -> Result<(Distribution, Vec<CompactLog>)>
+where
+    M: Model<R> + Sync + ?Sized,
+    R: PlayerRating + Sync,
+{
+    let n_threads = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(n.max(1));
+    let (team_a, team_b) = teams;
+    let outcomes: Vec<Vec<Result<Outcome>>> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..n_threads)
+            .map(|worker| {
+                let (team_a, team_b) = (team_a.clone(), team_b.clone());
+                scope.spawn(move || {
+                    (worker..n)
+                        .step_by(n_threads)
+                        .map(|i| {
+                            let mut rng = seed::match_rng(seed, i as u64);
+                            let game =
+                                GameState::new(form.clone(), team_a.clone(), team_b.clone())?;
+                            Outcome::simulate(game, model, db, &mut rng, keep_logs)
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|w| w.join().expect("simulation thread panicked"))
+            .collect()
+    });
+
+    let mut distribution = Distribution::new(&team_a, &team_b);
+    let mut logs = Vec::new();
+    // Gather in match order so that the margins are reproducible
+    let mut outcomes: Vec<_> = outcomes.into_iter().map(|w| w.into_iter()).collect();
+    for i in 0..n {
+        let mut outcome = outcomes[i % n_threads]
+            .next()
+            .expect("every match is simulated")?;
+        logs.extend(outcome.log.take());
+        distribution.record(outcome);
+    }
+    Ok((distribution, logs))
+}
+
+impl GameState {
+    /// Estimate the chances of each result from the current position by simulating the
+    /// rest of the match `n_sims` times
+    pub fn win_probability<M, R>(
+        &self,
+        model: &M,
+        db: &PlayerDb<R>,
+        n_sims: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Distribution>
+    where
+        M: Model<R> + ?Sized,
+        R: PlayerRating,
+    {
+        let (team_a, team_b) = self.teams();
+        let mut distribution = Distribution::new(team_a, team_b);
+        for _ in 0..n_sims {
+            distribution.record(Outcome::simulate(self.clone(), model, db, rng, false)?);
+        }
+        Ok(distribution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{form, game, model, player::PlayerDb, tests::test_team};
+    use rand::thread_rng;
+
+    #[test]
+    fn monte_carlo_is_reproducible() -> Result<()> {
+        use model::NullModel;
+        use std::sync::Arc;
+        let mut db = PlayerDb::new();
+        let team_a = Arc::new(test_team(&mut db, 1, "ZIM")?);
+        let team_b = Arc::new(test_team(&mut db, 2, "KEN")?);
+        let teams = (team_a.clone(), team_b.clone());
+        let form = form::Form::t20();
+        let first = simulate_many(40, &form, teams.clone(), &NullModel {}, &db, 7)?;
+        let (second, logs) = simulate_many_logged(40, &form, teams, &NullModel {}, &db, 7)?;
+        assert_eq!(first.team_a.total_runs, second.team_a.total_runs);
+        assert_eq!(first.team_b.margins, second.team_b.margins);
+        // The kept records replay to the same results
+        assert_eq!(logs.len(), 40);
+        let replayed_runs = logs
+            .iter()
+            .map(|log| {
+                let replayed = game::GameState::replay(&log.expand()?, None)?;
+                Ok(replayed.team_score(&team_a) as u64)
+            })
+            .sum::<Result<u64>>()?;
+        assert_eq!(replayed_runs, first.team_a.total_runs);
+        let shared = (first.ties + first.draws) as f32 / 40.;
+        let total = first.win_fraction(team_a.id) + first.win_fraction(team_b.id) + shared;
+        assert!((total - 1.).abs() < 1e-4);
+        assert!(first.average_score(team_a.id) > 0.);
+
+        let mut state = game::GameState::new(form, team_a.clone(), team_b.clone())?;
+        let mut rng = thread_rng();
+        for delivery in state.deliveries(&NullModel {}, &db, &mut rng).take(60) {
+            delivery?;
+        }
+        let events = state.events().len();
+        let odds = state.win_probability(&NullModel {}, &db, 20, &mut rng)?;
+        assert_eq!(odds.matches, 20);
+        assert_eq!(state.events().len(), events);
+        assert!(!state.complete());
+        // No simulations give no wins or runs rather than NaN
+        let none = state.win_probability(&NullModel {}, &db, 0, &mut rng)?;
+        assert_eq!(none.win_fraction(team_a.id), 0.);
+        assert_eq!(none.average_score(team_b.id), 0.);
+        Ok(())
+    }
+}
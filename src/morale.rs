@@ -0,0 +1,102 @@
+//! Players' form and confidence, which carry over from one match to the next
+use crate::{
+    game::GameState,
+    model::PlayerRating,
+    player::{PlayerDb, PlayerId},
+};
+use serde::{Deserialize, Serialize};
+
+/// A player's current form, from -1 (badly out of form) through 0 (neutral) to 1 (in the
+/// form of their life)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Morale(f32);
+
+impl Morale {
+    pub fn new(value: f32) -> Self {
+        Self(value.clamp(-1., 1.))
+    }
+
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+
+    /// A factor for scaling a rating, which is `1 + effect` at the best form and
+    /// `1 - effect` at the worst
+    pub fn multiplier(&self, effect: f32) -> f32 {
+        1. + effect * self.0
+    }
+
+    /// Move the form towards a performance, measured relative to expectation so that 0 is
+    /// as expected
+    pub fn update(&mut self, performance: f32, config: &MoraleConfig) {
+        *self = Self::new(config.decay * self.0 + config.sensitivity * performance);
+    }
+}
+
+/// How form responds to performances
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoraleConfig {
+    /// The fraction of a player's form that remains after each match
+    pub decay: f32,
+    /// How much a single performance moves a player's form
+    pub sensitivity: f32,
+    /// The runs a batter is expected to score in an innings
+    pub expected_runs: f32,
+    /// The wickets a bowler is expected to take per ball bowled
+    pub expected_wickets_per_ball: f32,
+    /// The runs a bowler is expected to concede per ball bowled
+    pub expected_economy_per_ball: f32,
+}
+
+impl Default for MoraleConfig {
+    fn default() -> Self {
+        Self {
+            decay: 0.7,
+            sensitivity: 0.3,
+            expected_runs: 25.,
+            expected_wickets_per_ball: 1. / 40.,
+            expected_economy_per_ball: 0.8,
+        }
+    }
+}
+
+impl MoraleConfig {
+    /// How well a batter did against expectation, from -1 for a duck upwards
+    fn batting(&self, runs: u16) -> f32 {
+        ((runs as f32 - self.expected_runs) / self.expected_runs).min(2.)
+    }
+
+    /// How well a bowler did against expectation, from wickets taken and runs conceded
+    fn bowling(&self, balls: u16, runs: u16, wickets: u8) -> f32 {
+        let balls = balls as f32;
+        let expected_wickets = self.expected_wickets_per_ball * balls;
+        let expected_runs = self.expected_economy_per_ball * balls;
+        let wickets = (wickets as f32 - expected_wickets) / expected_wickets.max(1.);
+        let economy = (expected_runs - runs as f32) / expected_runs.max(1.);
+        (wickets + 0.5 * economy).clamp(-1., 2.)
+    }
+}
+
+/// Update the form of everyone who batted or bowled in a match. Each innings counts as a
+/// separate performance.
+pub fn update_morale<R>(db: &mut PlayerDb<R>, game: &GameState, config: &MoraleConfig)
+where
+    R: PlayerRating,
+{
+    let mut update = |id: PlayerId, performance: f32| {
+        if let Some(player) = db.get_mut(id) {
+            player.morale.update(performance, config);
+        }
+    };
+    for innings in game.completed_innings() {
+        for (id, bat) in innings.batting_stats.batters() {
+            update(*id, config.batting(bat.runs));
+        }
+        for (id, bowl) in innings.bowling_stats.bowlers() {
+            if bowl.balls > 0 {
+                update(*id, config.bowling(bowl.balls, bowl.runs, bowl.wickets));
+            }
+        }
+    }
+}
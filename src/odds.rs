@@ -0,0 +1,163 @@
+//! Betting markets priced from the distribution of simulated outcomes
+use crate::{montecarlo::Distribution, team::Team};
+use serde::{Deserialize, Serialize};
+
+/// The shortest decimal odds offered, so that backing a near-certainty still returns
+/// more than the stake
+pub const MIN_ODDS: f32 = 1.01;
+
+/// One outcome that can be backed in a market
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Selection {
+    pub name: String,
+    /// The chance of the outcome, from the simulations
+    pub probability: f32,
+    /// The decimal odds offered, including the bookmaker's margin
+    pub odds: f32,
+}
+
+/// A set of outcomes of which exactly one happens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Market {
+    pub name: String,
+    pub selections: Vec<Selection>,
+}
+
+impl Market {
+    /// Price outcomes from their chances, scaling them so that the implied probabilities
+    /// of the market sum to `1 + margin`. No odds are shorter than `MIN_ODDS`, so a
+    /// near-certain outcome carries less of the margin. Outcomes that never happened in
+    /// the simulations can't be priced, and are left out.
+    pub fn priced(name: impl Into<String>, outcomes: Vec<(String, f32)>, margin: f32) -> Self {
+        let total: f32 = outcomes.iter().map(|(_, p)| p).sum();
+        let selections = outcomes
+            .into_iter()
+            .filter(|(_, probability)| *probability > 0.)
+            .map(|(name, probability)| Selection {
+                name,
+                probability,
+                odds: (total / (probability * (1. + margin))).max(MIN_ODDS),
+            })
+            .collect();
+        Self {
+            name: name.into(),
+            selections,
+        }
+    }
+
+    /// The sum of the probabilities implied by the odds, which exceeds 1 by the margin
+    /// unless the odds of a near-certain outcome were raised to `MIN_ODDS`
+    pub fn overround(&self) -> f32 {
+        self.selections.iter().map(|s| 1. / s.odds).sum()
+    }
+}
+
+/// The match odds: either side to win, or a result without a winner. None if no matches
+/// were simulated.
+pub fn match_odds(
+    distribution: &Distribution,
+    team_a: &Team,
+    team_b: &Team,
+    margin: f32,
+) -> Option<Market> {
+    if distribution.matches == 0 {
+        return None;
+    }
+    let fraction = |n: usize| n as f32 / distribution.matches as f32;
+    let outcomes = vec![
+        (team_a.name.clone(), distribution.win_fraction(team_a.id)),
+        (team_b.name.clone(), distribution.win_fraction(team_b.id)),
+        ("Tie".to_string(), fraction(distribution.ties)),
+        ("Draw".to_string(), fraction(distribution.draws)),
+        ("No result".to_string(), fraction(distribution.no_results)),
+    ];
+    Some(Market::priced("Match odds", outcomes, margin))
+}
+
+/// Over or under a line on the total of the given innings, counted from 0. The line is
+/// set half a run above the median total so that the market is close to even. None if
+/// the innings was never played.
+pub fn innings_runs(distribution: &Distribution, innings: usize, margin: f32) -> Option<Market> {
+    let line = distribution.innings_total_quantile(innings, 0.5)? as f32 + 0.5;
+    let over = distribution.innings_total_over(innings, line)?;
+    let outcomes = vec![
+        (format!("Over {}", line), over),
+        (format!("Under {}", line), 1. - over),
+    ];
+    Some(Market::priced(
+        format!("Innings {} runs", innings + 1),
+        outcomes,
+        margin,
+    ))
+}
+
+/// The given team's top scorer over the match
+pub fn top_batter(distribution: &Distribution, team: &Team, margin: f32) -> Market {
+    let outcomes = distribution
+        .top_batter_fractions(team.id)
+        .into_iter()
+        .map(|(id, probability)| {
+            let name = team
+                .get_name(id)
+                .map_or_else(|| id.to_string(), str::to_string);
+            (name, probability)
+        })
+        .collect();
+    Market::priced(format!("Top {} batter", team.name), outcomes, margin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Result, form, model, montecarlo, player::PlayerDb, tests::test_team};
+
+    #[test]
+    fn near_certainties_priced_above_evens() {
+        let outcomes = vec![
+            ("Favourite".to_string(), 0.97),
+            ("Outsider".to_string(), 0.03),
+        ];
+        let market = Market::priced("Match odds", outcomes, 0.05);
+        assert_eq!(market.selections[0].odds, MIN_ODDS);
+        assert!((market.selections[1].odds - 1. / (0.03 * 1.05)).abs() < 1e-3);
+        let even = vec![("Over".to_string(), 0.5), ("Under".to_string(), 0.5)];
+        let market = Market::priced("Runs", even, 0.05);
+        assert!((market.overround() - 1.05).abs() < 1e-5);
+        assert!(market.selections.iter().all(|s| s.odds >= MIN_ODDS));
+    }
+
+    #[test]
+    fn betting_markets() -> Result<()> {
+        use model::NullModel;
+        use std::sync::Arc;
+        let mut db = PlayerDb::new();
+        let team_a = Arc::new(test_team(&mut db, 1, "NAM")?);
+        let team_b = Arc::new(test_team(&mut db, 2, "OMA")?);
+        let teams = (team_a.clone(), team_b.clone());
+        let form = form::Form::t20();
+        let distribution = montecarlo::simulate_many(60, &form, teams, &NullModel {}, &db, 3)?;
+        let low = distribution.innings_total_quantile(0, 0.1).unwrap();
+        let high = distribution.innings_total_quantile(0, 0.9).unwrap();
+        assert!(low <= high);
+
+        let margin = 0.05;
+        let odds = match_odds(&distribution, &team_a, &team_b, margin).unwrap();
+        assert!((odds.overround() - 1. - margin).abs() < 1e-3);
+        let fair: f32 = odds.selections.iter().map(|s| s.probability).sum();
+        assert!((fair - 1.).abs() < 1e-3);
+        let runs = innings_runs(&distribution, 0, margin).unwrap();
+        assert_eq!(runs.selections.len(), 2);
+        assert!(innings_runs(&distribution, 2, margin).is_none());
+        let top = top_batter(&distribution, &team_a, margin);
+        assert!((top.overround() - 1. - margin).abs() < 1e-3);
+        assert!(top
+            .selections
+            .iter()
+            .all(|s| team_a.players.iter().any(|(_, name)| *name == s.name)));
+
+        let teams = (team_a.clone(), team_b.clone());
+        let none = montecarlo::simulate_many(0, &form, teams, &NullModel {}, &db, 3)?;
+        assert!(match_odds(&none, &team_a, &team_b, margin).is_none());
+        Ok(())
+    }
+}
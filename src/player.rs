@@ -3,18 +3,26 @@
 use crate::{
     error::{Error, Result},
     model::PlayerRating,
+    morale::Morale,
 };
 use fnv::FnvHashMap;
-use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{Read, Write},
+};
 
-pub type PlayerId = usize;
-static PLAYER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+/// Identifies a player within a `PlayerDb`. IDs are given out by the database that a
+/// player is added to, and are saved with the player so that they stay the same when the
+/// database is loaded again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlayerId(pub u32);
 
-/// Retrieve a new unique player ID
-fn get_new_player_id() -> PlayerId {
-    // NOTE: This choice of ordering hasn't been considered.
-    PLAYER_COUNTER.fetch_add(1, Ordering::SeqCst)
+impl fmt::Display for PlayerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 pub struct PlayerDb<R>
@@ -22,6 +30,9 @@ where
     R: PlayerRating,
 {
     map: FnvHashMap<PlayerId, Player<R>>,
+    /// The ID to give the next player added. This is never lowered, so IDs of removed
+    /// players are not reused.
+    next_id: u32,
 }
 
 impl<R> PlayerDb<R>
@@ -31,20 +42,280 @@ where
     pub fn new() -> Self {
         Self {
             map: FnvHashMap::default(),
+            next_id: 0,
         }
     }
     pub fn get(&self, id: PlayerId) -> Option<&Player<R>> {
         self.map.get(&id)
     }
 
+    /// The first player found with the given name. Names need not be unique, so prefer IDs
+    /// where they are known.
+    pub fn find(&self, name: &str) -> Option<&Player<R>> {
+        self.players().into_iter().find(|p| p.name == name)
+    }
+
+    /// Every player with the given name, in order of ID
+    pub fn find_all(&self, name: &str) -> Vec<&Player<R>> {
+        self.query(|p| p.name == name)
+    }
+
+    /// The players satisfying a predicate, in order of ID
+    pub fn query(&self, predicate: impl Fn(&Player<R>) -> bool) -> Vec<&Player<R>> {
+        let mut players: Vec<_> = self.map.values().filter(|p| predicate(p)).collect();
+        players.sort_by_key(|p| p.id);
+        players
+    }
+
+    /// The players with the given role, in order of ID
+    pub fn with_role(&self, role: Role) -> Vec<&Player<R>> {
+        self.query(|p| p.role == Some(role))
+    }
+
+    /// The players whose rating satisfies a predicate, in order of ID
+    pub fn rated(&self, predicate: impl Fn(&R) -> bool) -> Vec<&Player<R>> {
+        self.query(|p| predicate(&p.rating))
+    }
+
+    /// Every player, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &Player<R>> {
+        self.map.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn contains(&self, id: PlayerId) -> bool {
+        self.map.contains_key(&id)
+    }
+
+    /// Remove a player from the database, returning them if they were present. Their ID is
+    /// not given to anyone else.
+    pub fn remove(&mut self, id: PlayerId) -> Option<Player<R>> {
+        self.map.remove(&id)
+    }
+
+    /// Mutable access to every player, in no particular order
+    pub fn players_mut(&mut self) -> impl Iterator<Item = &mut Player<R>> {
+        self.map.values_mut()
+    }
+
+    /// Mutable access to a player, e.g. to fill in their style
+    pub fn get_mut(&mut self, id: PlayerId) -> Option<&mut Player<R>> {
+        self.map.get_mut(&id)
+    }
+
     pub fn add(&mut self, name: String, rating: R) -> Result<&Player<R>> {
-        let id = get_new_player_id();
-        let player = Player { id, name, rating };
-        if let Some(p) = self.map.insert(player.id, player) {
-            return Err(Error::DuplicatePlayerId(p.id));
-        }
+        let id = PlayerId(self.next_id);
+        let player = Player {
+            id,
+            name,
+            bats: Hand::default(),
+            bowls: None,
+            born: None,
+            role: None,
+            morale: Morale::default(),
+            rating,
+        };
+        self.insert(player)?;
         Ok(self.map.get(&id).unwrap())
     }
+
+    /// Add several players at once, returning their IDs in the order given
+    pub fn add_all(
+        &mut self,
+        players: impl IntoIterator<Item = (String, R)>,
+    ) -> Result<Vec<PlayerId>> {
+        players
+            .into_iter()
+            .map(|(name, rating)| Ok(self.add(name, rating)?.id))
+            .collect()
+    }
+
+    /// Add a player who already has an ID, making sure it isn't given to anyone else. The
+    /// last possible ID is refused, since there would be none left for new players.
+    fn insert(&mut self, player: Player<R>) -> Result<()> {
+        let id = player.id;
+        if self.map.contains_key(&id) {
+            return Err(Error::DuplicatePlayerId(id));
+        }
+        let next = id.0.checked_add(1).ok_or(Error::PlayerIdsExhausted(id))?;
+        self.next_id = self.next_id.max(next);
+        self.map.insert(id, player);
+        Ok(())
+    }
+
+    /// A database of players who already have IDs, such as those loaded from storage
+    pub(crate) fn from_players(players: Vec<Player<R>>) -> Result<Self> {
+        let mut db = Self::new();
+        for player in players {
+            db.insert(player)?;
+        }
+        Ok(db)
+    }
+
+    /// The players in the database, in order of ID
+    pub fn players(&self) -> Vec<&Player<R>> {
+        let mut players: Vec<_> = self.map.values().collect();
+        players.sort_by_key(|p| p.id);
+        players
+    }
+
+    /// A copy of the database with every rating mapped to another scheme, keeping each
+    /// player's ID and details
+    pub fn map_ratings<T>(&self, f: impl Fn(&R) -> T) -> PlayerDb<T>
+    where
+        T: PlayerRating,
+    {
+        let map = self
+            .map
+            .iter()
+            .map(|(id, p)| {
+                let player = Player {
+                    id: p.id,
+                    name: p.name.clone(),
+                    bats: p.bats,
+                    bowls: p.bowls,
+                    born: p.born,
+                    role: p.role,
+                    morale: p.morale,
+                    rating: f(&p.rating),
+                };
+                (*id, player)
+            })
+            .collect();
+        PlayerDb {
+            map,
+            next_id: self.next_id,
+        }
+    }
+
+    /// A copy of the database for use with a model that takes another kind of rating
+    pub fn convert<T>(&self) -> PlayerDb<T>
+    where
+        T: PlayerRating + for<'a> From<&'a R>,
+    {
+        self.map_ratings(|r| T::from(r))
+    }
+}
+
+impl<R> PlayerDb<R>
+where
+    R: PlayerRating + Serialize,
+{
+    /// Save every player and their rating as a JSON list, keeping their IDs
+    pub fn save_json<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(writer, &self.players())?;
+        Ok(())
+    }
+
+    /// Save every player as a row of a CSV file, keeping their IDs. The rating is stored
+    /// as JSON in the last column, since its fields depend on the model.
+    pub fn save_csv<W: Write>(&self, writer: W) -> Result<()> {
+        let mut csv = csv::Writer::from_writer(writer);
+        for player in self.players() {
+            csv.serialize(PlayerRow {
+                id: player.id,
+                name: player.name.clone(),
+                bats: player.bats,
+                bowls: player.bowls,
+                born: player.born,
+                role: player.role,
+                morale: player.morale,
+                rating: serde_json::to_string(&player.rating)?,
+            })?;
+        }
+        csv.flush()?;
+        Ok(())
+    }
+}
+
+impl<R> PlayerDb<R>
+where
+    R: PlayerRating + DeserializeOwned,
+{
+    /// Load players saved by `save_json`. Players listed without an ID are given a new one.
+    pub fn load_json<Rd: Read>(reader: Rd) -> Result<Self> {
+        let players: Vec<SavedPlayer<R>> = serde_json::from_reader(reader)?;
+        let (saved, unsaved): (Vec<_>, Vec<_>) = players.into_iter().partition(|p| p.id.is_some());
+        // Take every saved ID before handing out new ones
+        let mut db = Self::new();
+        for p in saved.into_iter().chain(unsaved) {
+            let player = Player {
+                id: p.id.unwrap_or(PlayerId(db.next_id)),
+                name: p.name,
+                bats: p.bats,
+                bowls: p.bowls,
+                born: p.born,
+                role: p.role,
+                morale: p.morale,
+                rating: p.rating,
+            };
+            db.insert(player)?;
+        }
+        Ok(db)
+    }
+
+    /// Load players saved by `save_csv`
+    pub fn load_csv<Rd: Read>(reader: Rd) -> Result<Self> {
+        let mut csv = csv::Reader::from_reader(reader);
+        let players = csv
+            .deserialize()
+            .map(|row| {
+                let row: PlayerRow = row?;
+                Ok(Player {
+                    id: row.id,
+                    name: row.name,
+                    bats: row.bats,
+                    bowls: row.bowls,
+                    born: row.born,
+                    role: row.role,
+                    morale: row.morale,
+                    rating: serde_json::from_str(&row.rating)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Self::from_players(players)
+    }
+}
+
+/// A player as saved in a JSON file, which may not have been given an ID
+#[derive(Deserialize)]
+struct SavedPlayer<R> {
+    id: Option<PlayerId>,
+    name: String,
+    #[serde(default)]
+    bats: Hand,
+    #[serde(default)]
+    bowls: Option<BowlingStyle>,
+    #[serde(default)]
+    born: Option<Date>,
+    #[serde(default)]
+    role: Option<Role>,
+    #[serde(default)]
+    morale: Morale,
+    rating: R,
+}
+
+/// A player as a row of a CSV file
+#[derive(Serialize, Deserialize)]
+struct PlayerRow {
+    id: PlayerId,
+    name: String,
+    bats: Hand,
+    bowls: Option<BowlingStyle>,
+    #[serde(default)]
+    born: Option<Date>,
+    #[serde(default)]
+    role: Option<Role>,
+    #[serde(default)]
+    morale: Morale,
+    rating: String,
 }
 
 impl<R> Default for PlayerDb<R>
@@ -64,12 +335,200 @@ where
 {
     // TODO: consider using team + cap number to identify test players, although this
     // will not cover cricketers who have not made a test appearance.
-    #[serde(skip, default = "get_new_player_id")]
+    /// Saved with the player so that teams can refer to them across sessions
     pub id: PlayerId,
     pub name: String,
+    /// The hand the player bats with
+    #[serde(default)]
+    pub bats: Hand,
+    /// The player's style of bowling, if they bowl
+    #[serde(default)]
+    pub bowls: Option<BowlingStyle>,
+    /// The player's date of birth, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub born: Option<Date>,
+    /// The player's usual role in a side, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    /// The player's current form, which changes between matches
+    #[serde(default)]
+    pub morale: Morale,
     pub rating: R,
 }
 
+/// A calendar date, written as YYYY-MM-DD
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u8, day: u8) -> Result<Self> {
+        if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+            return Err(Error::InvalidDate(format!("{}-{}-{}", year, month, day)));
+        }
+        Ok(Self { year, month, day })
+    }
+
+    /// The number of years from an earlier date to this one, e.g. a player's age. Months
+    /// and days are counted as fractions of a year.
+    pub fn years_since(&self, earlier: &Date) -> f32 {
+        let fraction = |d: &Date| (d.month as f32 - 1.) / 12. + (d.day as f32 - 1.) / 365.;
+        (self.year - earlier.year) as f32 + fraction(self) - fraction(earlier)
+    }
+}
+
+/// The number of days in a month of the Gregorian calendar, from 1 for January
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl std::str::FromStr for Date {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidDate(s.to_string());
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next = || parts.next().ok_or_else(invalid);
+        let year = next()?.parse().map_err(|_| invalid())?;
+        let month = next()?.parse().map_err(|_| invalid())?;
+        let day = next()?.parse().map_err(|_| invalid())?;
+        Self::new(year, month, day)
+    }
+}
+
+impl std::convert::TryFrom<String> for Date {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Date> for String {
+    fn from(date: Date) -> Self {
+        date.to_string()
+    }
+}
+
+/// Which side a player bats or bowls from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Hand {
+    #[default]
+    Right,
+    Left,
+}
+
+/// The conventional classification of a bowler's style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BowlingStyle {
+    /// Right-arm fast (RF)
+    RightFast,
+    /// Right-arm fast-medium (RFM)
+    RightFastMedium,
+    /// Right-arm medium (RM)
+    RightMedium,
+    /// Left-arm fast (LF)
+    LeftFast,
+    /// Left-arm fast-medium (LFM)
+    LeftFastMedium,
+    /// Left-arm medium (LM)
+    LeftMedium,
+    /// Right-arm off break (OB)
+    OffBreak,
+    /// Right-arm leg break (LB)
+    LegBreak,
+    /// Slow left-arm orthodox (SLA)
+    SlowLeftArm,
+    /// Left-arm wrist spin (LWS)
+    LeftArmWrist,
+}
+
+impl BowlingStyle {
+    /// The arm the ball is bowled with
+    pub fn arm(&self) -> Hand {
+        use BowlingStyle::*;
+        match self {
+            RightFast | RightFastMedium | RightMedium | OffBreak | LegBreak => Hand::Right,
+            LeftFast | LeftFastMedium | LeftMedium | SlowLeftArm | LeftArmWrist => Hand::Left,
+        }
+    }
+
+    pub fn spin(&self) -> bool {
+        use BowlingStyle::*;
+        matches!(self, OffBreak | LegBreak | SlowLeftArm | LeftArmWrist)
+    }
+
+    /// For spinners, whether the stock ball turns away from a batter with the given hand.
+    /// Off breaks and left-arm wrist spin turn away from left-handers; leg breaks and
+    /// left-arm orthodox turn away from right-handers.
+    pub fn turns_away_from(&self, batter: Hand) -> Option<bool> {
+        use BowlingStyle::*;
+        let away_from = match self {
+            OffBreak | LeftArmWrist => Hand::Left,
+            LegBreak | SlowLeftArm => Hand::Right,
+            _ => return None,
+        };
+        Some(batter == away_from)
+    }
+}
+
+/// A player's role in the side, which decides their default place in the batting order
+/// and whether they bowl
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    Opener,
+    TopOrder,
+    MiddleOrder,
+    WicketKeeper,
+    AllRounder,
+    PaceBowler,
+    SpinBowler,
+}
+
+impl Role {
+    pub const ALL: [Role; 7] = [
+        Role::Opener,
+        Role::TopOrder,
+        Role::MiddleOrder,
+        Role::WicketKeeper,
+        Role::AllRounder,
+        Role::PaceBowler,
+        Role::SpinBowler,
+    ];
+
+    /// Where the role bats, from 0 at the top of the order
+    pub fn batting_rank(&self) -> u8 {
+        use Role::*;
+        match self {
+            Opener => 0,
+            TopOrder => 1,
+            MiddleOrder => 2,
+            WicketKeeper | AllRounder => 3,
+            PaceBowler | SpinBowler => 4,
+        }
+    }
+
+    /// Whether the role is expected to bowl
+    pub fn bowls(&self) -> bool {
+        matches!(self, Role::AllRounder | Role::PaceBowler | Role::SpinBowler)
+    }
+}
+
 impl<R> PartialEq for Player<R>
 where
     R: PlayerRating,
@@ -79,3 +538,111 @@ where
     }
 }
 impl<R> Eq for Player<R> where R: PlayerRating {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate, model::PlayerRatingNull, seed, tests::test_team};
+
+    #[test]
+    fn calendar_dates() {
+        assert!(Date::new(2023, 2, 28).is_ok());
+        assert!(Date::new(2023, 2, 29).is_err());
+        assert!(Date::new(2024, 2, 29).is_ok());
+        assert!(Date::new(1900, 2, 29).is_err());
+        assert!(Date::new(2000, 2, 29).is_ok());
+        assert!(Date::new(2024, 4, 31).is_err());
+        assert!(Date::new(2024, 12, 31).is_ok());
+        assert!("2024-02-31".parse::<Date>().is_err());
+        assert!(Date::new(2024, 0, 1).is_err());
+    }
+
+    #[test]
+    fn last_player_id() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let id = db
+            .add("A Player".to_string(), PlayerRatingNull::default())?
+            .id;
+        let mut player = db.remove(id).unwrap();
+        player.id = PlayerId(u32::MAX);
+        assert!(matches!(
+            PlayerDb::from_players(vec![player]),
+            Err(Error::PlayerIdsExhausted(PlayerId(u32::MAX)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn player_db_round_trip() -> Result<()> {
+        use Hand;
+        let mut db = PlayerDb::new();
+        let team = test_team(&mut db, 1, "BAN")?;
+        let opener = team.players[0].0;
+        db.get_mut(opener).unwrap().bats = Hand::Left;
+        let mut json = Vec::new();
+        db.save_json(&mut json)?;
+        let mut csv = Vec::new();
+        db.save_csv(&mut csv)?;
+        for loaded in [
+            PlayerDb::<PlayerRatingNull>::load_json(json.as_slice())?,
+            PlayerDb::<PlayerRatingNull>::load_csv(csv.as_slice())?,
+        ] {
+            // Teams saved alongside the database still refer to the same players
+            for (id, name) in &team.players {
+                assert_eq!(&loaded.get(*id).unwrap().name, name);
+            }
+            assert_eq!(loaded.get(opener).unwrap().bats, Hand::Left);
+        }
+        let mut loaded = PlayerDb::<PlayerRatingNull>::load_json(json.as_slice())?;
+        let new_id = loaded
+            .add("new".to_string(), PlayerRatingNull::default())?
+            .id;
+        assert!(team.players.iter().all(|(id, _)| *id != new_id));
+        // Each database hands out its own IDs, and never reuses them
+        assert_eq!(new_id, PlayerId(11));
+        loaded.remove(new_id);
+        let next = loaded.add("next".to_string(), PlayerRatingNull::default())?;
+        assert_eq!(next.id, PlayerId(12));
+        let mut other = PlayerDb::new();
+        assert_eq!(
+            other
+                .add("first".to_string(), PlayerRatingNull::default())?
+                .id,
+            PlayerId(0)
+        );
+        let unnamed = br#"[{"name": "anon", "rating": {"batting": {}, "bowling": {}}}]"#;
+        assert!(PlayerDb::<PlayerRatingNull>::load_json(&unnamed[..]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn player_db_queries() -> Result<()> {
+        use Role;
+        let mut rng = seed::rng(3);
+        let generator = generate::PlayerGenerator::default();
+        let mut db = PlayerDb::new();
+        generator.squad(&mut rng, &mut db, 0, "Side")?;
+        assert_eq!(db.len(), 11);
+        assert_eq!(db.with_role(Role::Opener).len(), 2);
+        assert_eq!(db.with_role(Role::PaceBowler).len(), 3);
+        let strike_bowlers = db.rated(|r| r.bowling.sr < 40.);
+        assert!(strike_bowlers.iter().all(|p| p.rating.bowling.sr < 40.));
+        let keeper = db.with_role(Role::WicketKeeper)[0].id;
+        assert!(db.remove(keeper).is_some());
+        assert!(!db.contains(keeper) && db.remove(keeper).is_none());
+        let ids = db.add_all(vec![
+            (
+                "Twin".to_string(),
+                generator.rating(generate::Archetype::Anchor, 1.),
+            ),
+            (
+                "Twin".to_string(),
+                generator.rating(generate::Archetype::Spinner, 1.),
+            ),
+        ])?;
+        assert_eq!(db.iter().count(), 12);
+        let twins: Vec<_> = db.find_all("Twin").iter().map(|p| p.id).collect();
+        assert_eq!(twins, ids);
+        Ok(())
+    }
+}
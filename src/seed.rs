@@ -0,0 +1,32 @@
+//! Seeding of random number generators for reproducible simulations.
+//!
+//! A simulation is reproducible bit-for-bit from its seed, the form, the teams, and the
+//! version of the model and of this crate. Competitions and batches give each match its
+//! own generator, seeded from the base seed and the index of the match, so that results
+//! do not depend on the order or the thread in which matches are played.
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The generator used for seeded simulations. Unlike rand's StdRng, its output is
+/// guaranteed not to change between versions.
+pub type SimRng = ChaCha8Rng;
+
+/// A generator seeded directly from the given seed
+pub fn rng(seed: u64) -> SimRng {
+    SimRng::seed_from_u64(seed)
+}
+
+/// The seed of the match with the given index, derived from a base seed. Consecutive
+/// indices and base seeds give unrelated seeds.
+pub fn match_seed(seed: u64, index: u64) -> u64 {
+    // SplitMix64 finalizer
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The generator for the match with the given index
+pub fn match_rng(seed: u64, index: u64) -> SimRng {
+    rng(match_seed(seed, index))
+}
@@ -0,0 +1,556 @@
+//! A small JSON-over-HTTP service that creates matches and simulates them on request
+//!
+//! - `POST /matches` with a format and two teams creates a match and returns its ID
+//! - `POST /matches/:id/deliveries?count=n` simulates the next `n` deliveries (default 1)
+//! - `POST /matches/:id/complete` simulates the rest of the match
+//! - `GET /matches/:id/scorecard` returns the scorecard so far
+//! - `GET /matches/:id/win-probability?simulations=n` estimates the chances of each result
+//! - `DELETE /matches/:id` discards a match
+//!
+//! Simulations run on the blocking thread pool, and a match left untouched for
+//! [`MATCH_TTL`] is discarded when another is created.
+use crate::{
+    config::FormConfig,
+    error::{Error, Result},
+    game::{BallNumber, DeliveryOutcome, GameState, MatchResult, ScoreSnapshot, Scorecard},
+    model::{Model, PlayerRating},
+    montecarlo::Distribution,
+    player::PlayerDb,
+    seed::{self, SimRng},
+    team::Team,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// The number of simulations behind a win probability, unless the request asks otherwise
+const DEFAULT_SIMULATIONS: usize = 1000;
+
+/// The most simulations a single request may ask for, to keep requests responsive
+pub const MAX_SIMULATIONS: usize = 100_000;
+
+/// The most deliveries a single request may simulate
+pub const MAX_DELIVERIES: usize = 1000;
+
+/// How long a match is kept without being requested
+pub const MATCH_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The most matches kept at once. Creating another discards the one least recently
+/// requested.
+pub const MAX_MATCHES: usize = 1000;
+
+/// The shared state of the service: the players, the model, and the matches in progress
+struct Service<M, R: PlayerRating> {
+    db: PlayerDb<R>,
+    model: M,
+    matches: Mutex<Matches>,
+}
+
+/// A match in progress. Each match keeps its own generator, so a match created with a seed
+/// is reproducible however requests for other matches are interleaved.
+type Game = Arc<Mutex<(GameState, SimRng)>>;
+
+#[derive(Default)]
+struct Matches {
+    next_id: u64,
+    /// Each match with when it was last requested
+    games: FnvHashMap<u64, (Game, Instant)>,
+}
+
+impl Matches {
+    /// Discard the matches not requested within `MATCH_TTL` of `now`
+    fn evict(&mut self, now: Instant) {
+        self.games
+            .retain(|_, (_, last_used)| now.saturating_duration_since(*last_used) <= MATCH_TTL);
+    }
+
+    /// Add a match created at `now` and return its ID, first discarding the stale matches
+    /// and then, if there are still too many, the least recently requested
+    fn insert(&mut self, game: Game, now: Instant) -> u64 {
+        self.evict(now);
+        while self.games.len() >= MAX_MATCHES {
+            let oldest = self
+                .games
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(id, _)| *id);
+            match oldest {
+                Some(id) => self.games.remove(&id),
+                None => break,
+            };
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.games.insert(id, (game, now));
+        id
+    }
+}
+
+impl<M, R: PlayerRating> Service<M, R> {
+    /// The matches in progress. The map is only held for lookups, which can't leave it
+    /// inconsistent, so it stays usable after a panic elsewhere.
+    fn matches(&self) -> MutexGuard<'_, Matches> {
+        self.matches.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The match with the given ID, marked as just requested
+    fn game(&self, id: u64) -> std::result::Result<Game, ApiError> {
+        let mut matches = self.matches();
+        let (game, last_used) = matches
+            .games
+            .get_mut(&id)
+            .ok_or_else(|| ApiError::not_found(id))?;
+        *last_used = Instant::now();
+        Ok(game.clone())
+    }
+}
+
+impl<M, R> Service<M, R>
+where
+    M: Model<R> + Send + Sync + 'static,
+    R: PlayerRating + Send + Sync + 'static,
+{
+    /// Run `f` on the match with the given ID on the blocking thread pool. Other matches
+    /// can be requested meanwhile; a panic in `f` only loses this match.
+    async fn with_match<T: Send + 'static>(
+        self: Arc<Self>,
+        id: u64,
+        f: impl FnOnce(&Self, &mut GameState, &mut SimRng) -> Result<T> + Send + 'static,
+    ) -> std::result::Result<T, ApiError> {
+        let game = self.game(id)?;
+        tokio::task::spawn_blocking(move || {
+            let mut game = game.lock().map_err(|_| ApiError::poisoned())?;
+            let (state, rng) = &mut *game;
+            Ok(f(&self, state, rng)?)
+        })
+        .await
+        .map_err(ApiError::from)?
+    }
+}
+
+/// An error returned to the client as JSON, e.g. `{"error": "Match is complete"}`
+#[derive(Debug)]
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn not_found(id: u64) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: format!("No match with ID {}", id),
+        }
+    }
+
+    fn poisoned() -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: "A previous request failed while simulating this match".into(),
+        }
+    }
+}
+
+impl From<tokio::task::JoinError> for ApiError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(err: Error) -> Self {
+        let status = match err {
+            Error::MatchComplete | Error::MatchInProgress => StatusCode::CONFLICT,
+            Error::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        Self {
+            status,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.message });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<Json<T>, ApiError>;
+
+/// The body of a request to create a match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewMatch {
+    /// The format, by preset name or in full. A test match if not given.
+    #[serde(default)]
+    pub form: FormConfig,
+    /// The side batting first
+    pub team_a: Team,
+    pub team_b: Team,
+    /// Seeds the match's generator, so that the match can be reproduced
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchCreated {
+    pub id: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryResponse {
+    pub ball: BallNumber,
+    pub outcome: DeliveryOutcome,
+    pub score: ScoreSnapshot,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompleteResponse {
+    pub result: MatchResult,
+    /// The result in standard phrasing
+    pub summary: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CountQuery {
+    count: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SimulationsQuery {
+    simulations: Option<usize>,
+    seed: Option<u64>,
+}
+
+/// The routes of the service, simulating with `model` and players from `db`
+pub fn router<M, R>(db: PlayerDb<R>, model: M) -> Router
+where
+    M: Model<R> + Send + Sync + 'static,
+    R: PlayerRating + Send + Sync + 'static,
+{
+    routes(Arc::new(Service {
+        db,
+        model,
+        matches: Mutex::new(Matches::default()),
+    }))
+}
+
+fn routes<M, R>(service: Arc<Service<M, R>>) -> Router
+where
+    M: Model<R> + Send + Sync + 'static,
+    R: PlayerRating + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/matches", post(create::<M, R>))
+        .route("/matches/:id", delete(discard::<M, R>))
+        .route("/matches/:id/deliveries", post(deliveries::<M, R>))
+        .route("/matches/:id/complete", post(complete::<M, R>))
+        .route("/matches/:id/scorecard", get(scorecard::<M, R>))
+        .route("/matches/:id/win-probability", get(win_probability::<M, R>))
+        .with_state(service)
+}
+
+/// Serve the routes on the given address until the process is stopped
+pub async fn serve<M, R>(addr: SocketAddr, db: PlayerDb<R>, model: M) -> Result<()>
+where
+    M: Model<R> + Send + Sync + 'static,
+    R: PlayerRating + Send + Sync + 'static,
+{
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router(db, model)).await?;
+    Ok(())
+}
+
+async fn create<M, R>(
+    State(service): State<Arc<Service<M, R>>>,
+    Json(request): Json<NewMatch>,
+) -> ApiResult<MatchCreated>
+where
+    R: PlayerRating,
+{
+    // Check the players here, so that a bad team is reported when it's given
+    let players = request.team_a.players.iter().chain(&request.team_b.players);
+    for (id, _) in players {
+        service.db.get(*id).ok_or(Error::PlayerNotFound(*id))?;
+    }
+    let state = GameState::new(request.form.resolve()?, request.team_a, request.team_b)?;
+    let rng = seed::rng(request.seed.unwrap_or_else(rand::random));
+    let game = Arc::new(Mutex::new((state, rng)));
+    let id = service.matches().insert(game, Instant::now());
+    Ok(Json(MatchCreated { id }))
+}
+
+async fn discard<M, R>(
+    State(service): State<Arc<Service<M, R>>>,
+    Path(id): Path<u64>,
+) -> std::result::Result<StatusCode, ApiError>
+where
+    R: PlayerRating,
+{
+    service
+        .matches()
+        .games
+        .remove(&id)
+        .ok_or_else(|| ApiError::not_found(id))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn deliveries<M, R>(
+    State(service): State<Arc<Service<M, R>>>,
+    Path(id): Path<u64>,
+    Query(query): Query<CountQuery>,
+) -> ApiResult<Vec<DeliveryResponse>>
+where
+    M: Model<R> + Send + Sync + 'static,
+    R: PlayerRating + Send + Sync + 'static,
+{
+    let count = query.count.unwrap_or(1).min(MAX_DELIVERIES);
+    let balls = service
+        .with_match(id, move |service, state, rng| {
+            if state.complete() {
+                return Err(Error::MatchComplete);
+            }
+            state
+                .deliveries(&service.model, &service.db, rng)
+                .take(count)
+                .map(|delivery| {
+                    delivery.map(|(ball, outcome, score)| DeliveryResponse {
+                        ball,
+                        outcome,
+                        score,
+                    })
+                })
+                .collect()
+        })
+        .await?;
+    Ok(Json(balls))
+}
+
+async fn complete<M, R>(
+    State(service): State<Arc<Service<M, R>>>,
+    Path(id): Path<u64>,
+) -> ApiResult<CompleteResponse>
+where
+    M: Model<R> + Send + Sync + 'static,
+    R: PlayerRating + Send + Sync + 'static,
+{
+    let response = service
+        .with_match(id, |service, state, rng| {
+            let result = state.simulate_to_completion(&service.model, &service.db, rng)?;
+            Ok(CompleteResponse {
+                result,
+                summary: result.describe(state),
+            })
+        })
+        .await?;
+    Ok(Json(response))
+}
+
+async fn scorecard<M, R>(
+    State(service): State<Arc<Service<M, R>>>,
+    Path(id): Path<u64>,
+) -> ApiResult<Scorecard>
+where
+    M: Model<R> + Send + Sync + 'static,
+    R: PlayerRating + Send + Sync + 'static,
+{
+    let scorecard = service
+        .with_match(id, |_, state, _| state.scorecard())
+        .await?;
+    Ok(Json(scorecard))
+}
+
+async fn win_probability<M, R>(
+    State(service): State<Arc<Service<M, R>>>,
+    Path(id): Path<u64>,
+    Query(query): Query<SimulationsQuery>,
+) -> ApiResult<Distribution>
+where
+    M: Model<R> + Send + Sync + 'static,
+    R: PlayerRating + Send + Sync + 'static,
+{
+    let simulations = query
+        .simulations
+        .unwrap_or(DEFAULT_SIMULATIONS)
+        .min(MAX_SIMULATIONS);
+    // Simulate from a copy, so that the match isn't held while the simulations run and
+    // its own generator isn't advanced
+    let state = service
+        .clone()
+        .with_match(id, |_, state, _| Ok(state.clone()))
+        .await?;
+    let mut rng = seed::rng(query.seed.unwrap_or_else(rand::random));
+    let distribution = tokio::task::spawn_blocking(move || {
+        state.win_probability(&service.model, &service.db, simulations, &mut rng)
+    })
+    .await??;
+    Ok(Json(distribution))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        model::{NullModel, PlayerRatingNull},
+        tests::test_team,
+    };
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    /// Make a request of `app`, returning the status and the JSON body, if any
+    async fn call(app: &Router, method: &str, uri: &str) -> (u16, serde_json::Value) {
+        call_with(app, method, uri, serde_json::Value::Null).await
+    }
+
+    async fn call_with(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (u16, serde_json::Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+        (status.as_u16(), body)
+    }
+
+    /// A service with two teams, and the body of a request for a T20 between them
+    fn service() -> Result<(Arc<Service<NullModel, PlayerRatingNull>>, serde_json::Value)> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "SA")?;
+        let team_b = test_team(&mut db, 2, "WI")?;
+        let service = Arc::new(Service {
+            db,
+            model: NullModel {},
+            matches: Mutex::new(Matches::default()),
+        });
+        let new_match = serde_json::json!({
+            "form": "t20", "team_a": team_a, "team_b": team_b, "seed": 3
+        });
+        Ok((service, new_match))
+    }
+
+    async fn create_match(app: &Router, new_match: &serde_json::Value) -> u64 {
+        let (status, created) = call_with(app, "POST", "/matches", new_match.clone()).await;
+        assert_eq!(status, 200);
+        created["id"].as_u64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn match_routes() -> Result<()> {
+        let (service, new_match) = service()?;
+        let app = routes(service);
+        let id = create_match(&app, &new_match).await;
+        let (status, balls) =
+            call(&app, "POST", &format!("/matches/{}/deliveries?count=6", id)).await;
+        assert_eq!(status, 200);
+        assert_eq!(balls.as_array().unwrap().len(), 6);
+        let uri = format!("/matches/{}/win-probability?simulations=10", id);
+        let (status, odds) = call(&app, "GET", &uri).await;
+        assert_eq!((status, odds["matches"].as_u64()), (200, Some(10)));
+        let uri = format!("/matches/{}/complete", id);
+        let (status, done) = call(&app, "POST", &uri).await;
+        assert_eq!(status, 200);
+        assert!(done["summary"].is_string());
+        let (status, _) = call(&app, "POST", &uri).await;
+        assert_eq!(status, 200);
+        let (status, _) = call(&app, "POST", &format!("/matches/{}/deliveries", id)).await;
+        assert_eq!(status, 409);
+        let (status, card) = call(&app, "GET", &format!("/matches/{}/scorecard", id)).await;
+        assert_eq!(
+            (status, card["innings"].as_array().map(Vec::len)),
+            (200, Some(2))
+        );
+        let (status, _) = call(&app, "GET", "/matches/99/scorecard").await;
+        assert_eq!(status, 404);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn deliveries_capped() -> Result<()> {
+        let (service, new_match) = service()?;
+        let app = routes(service);
+        let mut form = new_match.clone();
+        form["form"] = serde_json::json!("test");
+        let id = create_match(&app, &form).await;
+        let uri = format!("/matches/{}/deliveries?count={}", id, usize::MAX);
+        let (status, balls) = call(&app, "POST", &uri).await;
+        assert_eq!(status, 200);
+        assert_eq!(balls.as_array().unwrap().len(), MAX_DELIVERIES);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn matches_discarded() -> Result<()> {
+        let (service, new_match) = service()?;
+        let app = routes(service.clone());
+        let id = create_match(&app, &new_match).await;
+        let uri = format!("/matches/{}", id);
+        assert_eq!(call(&app, "DELETE", &uri).await.0, 204);
+        assert_eq!(call(&app, "DELETE", &uri).await.0, 404);
+        let scorecard = format!("/matches/{}/scorecard", id);
+        assert_eq!(call(&app, "GET", &scorecard).await.0, 404);
+
+        // A match left alone for longer than the TTL goes when the next is created
+        let stale = create_match(&app, &new_match).await;
+        let fresh = create_match(&app, &new_match).await;
+        let game = service.game(fresh).unwrap();
+        let later = Instant::now() + MATCH_TTL * 2;
+        let mut matches = service.matches();
+        matches.games.get_mut(&fresh).unwrap().1 = later;
+        let next = matches.insert(game.clone(), later);
+        assert!(!matches.games.contains_key(&stale));
+        assert!(matches.games.contains_key(&fresh) && matches.games.contains_key(&next));
+
+        // Beyond the most matches kept, the least recently requested goes
+        for i in 1..=MAX_MATCHES as u32 {
+            matches.insert(game.clone(), later + Duration::from_secs(i.into()));
+        }
+        assert_eq!(matches.games.len(), MAX_MATCHES);
+        assert!(!matches.games.contains_key(&fresh) && !matches.games.contains_key(&next));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn panics_confined_to_their_match() -> Result<()> {
+        let (service, new_match) = service()?;
+        let app = routes(service.clone());
+        let broken = create_match(&app, &new_match).await;
+        let healthy = create_match(&app, &new_match).await;
+        let game = service.game(broken).unwrap();
+        let _ = std::thread::spawn(move || {
+            let _guard = game.lock().unwrap();
+            panic!("simulation failed");
+        })
+        .join();
+        let uri = |id| format!("/matches/{}/deliveries", id);
+        assert_eq!(call(&app, "POST", &uri(broken)).await.0, 500);
+        assert_eq!(call(&app, "POST", &uri(healthy)).await.0, 200);
+        create_match(&app, &new_match).await;
+        Ok(())
+    }
+}
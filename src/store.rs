@@ -0,0 +1,427 @@
+//! Durable storage of players, teams and completed matches in a SQLite database
+use crate::{
+    error::{Error, Result},
+    game::{GameState, MatchResult, Scorecard},
+    model::PlayerRating,
+    morale::Morale,
+    player::{Player, PlayerDb, PlayerId},
+    team::Team,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+
+/// The changes to the schema, in order. A database records how many it has had applied
+/// as its `user_version`, so each is applied exactly once; add new ones to the end.
+const MIGRATIONS: [&str; 4] = [
+    // The original tables
+    "
+    CREATE TABLE players (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        bats TEXT NOT NULL,
+        bowls TEXT NOT NULL,
+        rating TEXT NOT NULL
+    );
+    CREATE TABLE teams (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        team TEXT NOT NULL
+    );
+    CREATE TABLE matches (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        team_a TEXT NOT NULL,
+        team_b TEXT NOT NULL,
+        winner TEXT,
+        scorecard TEXT NOT NULL
+    );
+    CREATE TABLE batting (
+        match_id INTEGER NOT NULL REFERENCES matches(id),
+        player INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        runs INTEGER NOT NULL,
+        balls INTEGER NOT NULL,
+        out INTEGER NOT NULL
+    );
+    CREATE TABLE bowling (
+        match_id INTEGER NOT NULL REFERENCES matches(id),
+        player INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        runs INTEGER NOT NULL,
+        wickets INTEGER NOT NULL
+    );
+    ",
+    // Players' dates of birth
+    "ALTER TABLE players ADD COLUMN born TEXT;",
+    // Players' form, starting out neutral
+    "ALTER TABLE players ADD COLUMN morale REAL NOT NULL DEFAULT 0;",
+    // Players' roles, starting out unknown
+    "ALTER TABLE players ADD COLUMN role TEXT NOT NULL DEFAULT 'null';",
+];
+
+/// The columns of the players table added by each migration after the first. A
+/// database created before versions were recorded has had the migrations up to the
+/// last of these it has.
+const PLAYER_COLUMNS: [&str; 3] = ["born", "morale", "role"];
+
+/// A player's total runs across the stored matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunScorer {
+    pub player: PlayerId,
+    pub name: String,
+    pub innings: u32,
+    pub runs: u32,
+}
+
+/// The record of matches between two sides, by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeadToHead {
+    pub played: u32,
+    /// Wins by the first side named
+    pub won: u32,
+    /// Wins by the second side named
+    pub lost: u32,
+    /// Ties and draws
+    pub other: u32,
+}
+
+/// A SQLite database of players, teams and completed matches. Ratings, teams and
+/// scorecards are stored as JSON, and the batting and bowling lines of each match are
+/// stored in their own tables so that they can be queried.
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Open the database at the path, creating it if necessary
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// A database that only lasts as long as the store
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        let version = match conn.pragma_query_value(None, "user_version", |row| row.get(0))? {
+            0 => unversioned(&conn)?,
+            version => version,
+        };
+        if version > MIGRATIONS.len() {
+            return Err(Error::SchemaVersion(version));
+        }
+        let tx = conn.transaction()?;
+        for migration in &MIGRATIONS[version..] {
+            tx.execute_batch(migration)?;
+        }
+        tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+        tx.commit()?;
+        Ok(Self { conn })
+    }
+
+    /// Save every player in the database, replacing any stored with the same ID
+    pub fn save_players<R>(&mut self, db: &PlayerDb<R>) -> Result<()>
+    where
+        R: PlayerRating + Serialize,
+    {
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR REPLACE INTO players (id, name, bats, bowls, born, role, morale, rating)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for player in db.players() {
+                insert.execute(params![
+                    player.id.0,
+                    player.name,
+                    serde_json::to_string(&player.bats)?,
+                    serde_json::to_string(&player.bowls)?,
+                    player.born.map(|d| d.to_string()),
+                    serde_json::to_string(&player.role)?,
+                    player.morale.value(),
+                    serde_json::to_string(&player.rating)?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Load every stored player, keeping their IDs
+    pub fn load_players<R>(&self) -> Result<PlayerDb<R>>
+    where
+        R: PlayerRating + DeserializeOwned,
+    {
+        let mut query = self
+            .conn
+            .prepare("SELECT id, name, bats, bowls, born, role, morale, rating FROM players")?;
+        let rows = query.query_map([], |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, f32>(6)?,
+                row.get::<_, String>(7)?,
+            ))
+        })?;
+        let players = rows
+            .map(|row| {
+                let (id, name, bats, bowls, born, role, morale, rating) = row?;
+                Ok(Player {
+                    id: PlayerId(id),
+                    name,
+                    bats: serde_json::from_str(&bats)?,
+                    bowls: serde_json::from_str(&bowls)?,
+                    born: born.map(|d| d.parse()).transpose()?,
+                    role: serde_json::from_str(&role)?,
+                    morale: Morale::new(morale),
+                    rating: serde_json::from_str(&rating)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        PlayerDb::from_players(players)
+    }
+
+    /// Save a team, replacing any stored with the same ID
+    pub fn save_team(&self, team: &Team) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO teams (id, name, team) VALUES (?1, ?2, ?3)",
+            params![team.id, team.name, serde_json::to_string(team)?],
+        )?;
+        Ok(())
+    }
+
+    /// Load the team with the given ID
+    pub fn load_team(&self, id: u16) -> Result<Team> {
+        let team: Option<String> = self
+            .conn
+            .query_row("SELECT team FROM teams WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        let team = team.ok_or_else(|| Error::MissingData(format!("No stored team {}", id)))?;
+        Ok(serde_json::from_str(&team)?)
+    }
+
+    /// Save the scorecard of a completed match, returning the ID it is stored under
+    pub fn save_match(&mut self, game: &GameState) -> Result<i64> {
+        let result = game.result().ok_or(Error::MatchInProgress)?;
+        let scorecard = game.scorecard()?;
+        let winner = match result {
+            MatchResult::Win { winner, .. } => {
+                let (team_a, team_b) = game.teams();
+                Some(if winner == team_a.id {
+                    team_a.name.clone()
+                } else {
+                    team_b.name.clone()
+                })
+            }
+            MatchResult::Tie | MatchResult::Draw | MatchResult::NoResult => None,
+        };
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO matches (team_a, team_b, winner, scorecard) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                scorecard.team_a,
+                scorecard.team_b,
+                winner,
+                serde_json::to_string(&scorecard)?
+            ],
+        )?;
+        let match_id = tx.last_insert_rowid();
+        {
+            let mut batting = tx.prepare(
+                "INSERT INTO batting (match_id, player, name, runs, balls, out)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            let mut bowling = tx.prepare(
+                "INSERT INTO bowling (match_id, player, name, runs, wickets)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for innings in &scorecard.innings {
+                for line in &innings.batting {
+                    batting.execute(params![
+                        match_id,
+                        line.player.0,
+                        line.name,
+                        line.runs,
+                        line.balls,
+                        line.dismissal.is_some(),
+                    ])?;
+                }
+                for line in &innings.bowling {
+                    bowling.execute(params![
+                        match_id,
+                        line.player.0,
+                        line.name,
+                        line.runs,
+                        line.wickets,
+                    ])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(match_id)
+    }
+
+    /// The scorecard of a stored match
+    pub fn scorecard(&self, match_id: i64) -> Result<Scorecard> {
+        let scorecard: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT scorecard FROM matches WHERE id = ?1",
+                [match_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let scorecard =
+            scorecard.ok_or_else(|| Error::MissingData(format!("No stored match {}", match_id)))?;
+        Ok(serde_json::from_str(&scorecard)?)
+    }
+
+    /// The number of stored matches
+    pub fn matches(&self) -> Result<u32> {
+        Ok(self
+            .conn
+            .query_row("SELECT COUNT(*) FROM matches", [], |row| row.get(0))?)
+    }
+
+    /// The players with the most runs across all stored matches, most first
+    pub fn top_run_scorers(&self, limit: u32) -> Result<Vec<RunScorer>> {
+        let mut query = self.conn.prepare(
+            "SELECT player, name, COUNT(*), SUM(runs) FROM batting
+             GROUP BY player ORDER BY SUM(runs) DESC, player LIMIT ?1",
+        )?;
+        let rows = query.query_map([limit], |row| {
+            Ok(RunScorer {
+                player: PlayerId(row.get(0)?),
+                name: row.get(1)?,
+                innings: row.get(2)?,
+                runs: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// The record of the first side against the second in stored matches
+    pub fn head_to_head(&self, team: &str, opponent: &str) -> Result<HeadToHead> {
+        let mut query = self.conn.prepare(
+            "SELECT winner FROM matches
+             WHERE (team_a = ?1 AND team_b = ?2) OR (team_a = ?2 AND team_b = ?1)",
+        )?;
+        let winners = query.query_map([team, opponent], |row| row.get::<_, Option<String>>(0))?;
+        let mut record = HeadToHead::default();
+        for winner in winners {
+            record.played += 1;
+            match winner? {
+                Some(w) if w == team => record.won += 1,
+                Some(_) => record.lost += 1,
+                None => record.other += 1,
+            }
+        }
+        Ok(record)
+    }
+}
+
+/// The migrations a database without a recorded version has had, judging by its tables
+fn unversioned(conn: &Connection) -> Result<usize> {
+    let mut query = conn.prepare("SELECT name FROM pragma_table_info('players')")?;
+    let columns = query
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    if columns.is_empty() {
+        return Ok(0);
+    }
+    let added = PLAYER_COLUMNS
+        .iter()
+        .rposition(|column| columns.iter().any(|c| c == column))
+        .map_or(0, |i| i + 1);
+    Ok(1 + added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        form, game,
+        model::{self, PlayerRatingNull},
+        tests::test_team,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn sqlite_store() -> Result<()> {
+        use model::NullModel;
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "ENG")?;
+        let team_b = test_team(&mut db, 2, "IND")?;
+        let mut store = Store::in_memory()?;
+        store.save_players(&db)?;
+        store.save_team(&team_a)?;
+        store.save_team(&team_b)?;
+        let mut rng = thread_rng();
+        for _ in 0..3 {
+            let mut state =
+                game::GameState::new(form::Form::t20(), team_a.clone(), team_b.clone())?;
+            assert!(store.save_match(&state).is_err());
+            state.simulate_to_completion(&NullModel {}, &db, &mut rng)?;
+            let id = store.save_match(&state)?;
+            assert_eq!(store.scorecard(id)?.result, state.result());
+        }
+        assert_eq!(store.matches()?, 3);
+        let loaded = store.load_players::<PlayerRatingNull>()?;
+        let reloaded = store.load_team(1)?;
+        assert!(reloaded
+            .players
+            .iter()
+            .all(|(id, name)| &loaded.get(*id).unwrap().name == name));
+        let top = store.top_run_scorers(5)?;
+        assert!(top.len() == 5 && top[0].runs >= top[4].runs);
+        let h2h = store.head_to_head("team_ENG", "team_IND")?;
+        assert_eq!(h2h.played, 3);
+        assert_eq!(h2h.won + h2h.lost + h2h.other, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn schema_migrated() -> Result<()> {
+        // A database written before birth dates, form and roles, or versions, were stored
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch(MIGRATIONS[0])?;
+        conn.execute(
+            "INSERT INTO players (id, name, bats, bowls, rating) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                7,
+                "A Player",
+                "\"Left\"",
+                "null",
+                serde_json::to_string(&PlayerRatingNull::default())?
+            ],
+        )?;
+        let store = Store::init(conn)?;
+        let version: usize = store
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        assert_eq!(version, MIGRATIONS.len());
+        let db = store.load_players::<PlayerRatingNull>()?;
+        let player = db.get(PlayerId(7)).unwrap();
+        assert_eq!((player.born, player.role), (None, None));
+        assert_eq!(player.morale, Morale::default());
+
+        // Opening it again applies nothing more
+        let store = Store::init(store.conn)?;
+        assert_eq!(store.load_players::<PlayerRatingNull>()?.len(), 1);
+
+        // A database from a later version of the crate is refused
+        store.conn.pragma_update(None, "user_version", 99)?;
+        assert!(matches!(
+            Store::init(store.conn),
+            Err(Error::SchemaVersion(99))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+//! Tactical decisions made by the captains during a match
+pub mod aggression;
+pub use aggression::{AggressionStrategy, ConstantAggression, SituationalAggression};
+pub mod batting_order;
+pub use batting_order::{BattingOrderContext, BattingOrderStrategy, PinchHitter};
+pub mod bowling;
+pub use bowling::{
+    BowlerOption, BowlingContext, BowlingStrategy, DeathSpecialists, PaceThenSpin, Phase,
+};
+pub mod declaration;
+pub use declaration::{DeclarationContext, DeclarationStrategy, LeadDeclaration, NeverDeclare};
+pub mod fielding;
+pub use fielding::{FieldContext, FieldingStrategy, FixedField, SituationalField};
+pub mod review;
+pub use review::{ConfidentReview, NeverReview, ReviewContext, ReviewStrategy};
+pub mod selection;
+pub use selection::{BalancedSelection, SelectionContext, SelectionStrategy};
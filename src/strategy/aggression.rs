@@ -0,0 +1,100 @@
+//! Deciding how aggressively the batters should play
+
+use crate::game::MatchContext;
+use serde::{Deserialize, Serialize};
+
+/// Decides how much risk the batters take. This is consulted before every delivery by
+/// batting models that support it.
+pub trait AggressionStrategy: Send + Sync {
+    /// The aggression for the next delivery, from 0 (pure defence) to 1 (attack every
+    /// ball). A neutral approach is 0.5.
+    fn aggression(&self, context: &MatchContext) -> f32;
+}
+
+/// Play with the same aggression regardless of the situation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstantAggression(pub f32);
+
+impl AggressionStrategy for ConstantAggression {
+    fn aggression(&self, _context: &MatchContext) -> f32 {
+        self.0.clamp(0., 1.)
+    }
+}
+
+/// Attack more as the balls left run out with wickets in hand, and as the required rate
+/// climbs above the par rate when chasing. Always attacks a free hit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SituationalAggression {
+    /// The aggression with no pressure from the situation
+    pub base: f32,
+    /// The run rate, in runs per six balls, above which a chasing side must take risks
+    pub par_rate: f32,
+}
+
+impl Default for SituationalAggression {
+    fn default() -> Self {
+        Self {
+            base: 0.4,
+            par_rate: 6.,
+        }
+    }
+}
+
+impl AggressionStrategy for SituationalAggression {
+    fn aggression(&self, context: &MatchContext) -> f32 {
+        if context.free_hit {
+            return 1.;
+        }
+        let mut aggression = self.base;
+        if let Some(balls) = context.balls_remaining {
+            // Wickets are worth less the fewer balls are left to use them
+            let overs_left = balls as f32 / 6.;
+            let wickets = context.wickets_remaining as f32;
+            aggression += 0.3 * (wickets / (overs_left + 1.)).min(1.);
+        }
+        if let Some(required) = context.required_run_rate {
+            aggression += 0.05 * (required - self.par_rate);
+        }
+        aggression.clamp(0., 1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::form::Overs;
+
+    fn chase(balls_remaining: u16, wickets_remaining: u8, required: f32) -> MatchContext {
+        MatchContext {
+            innings: 1,
+            runs: 100,
+            wickets: 10 - wickets_remaining,
+            overs: Overs::new(10, 0),
+            balls_remaining: Some(balls_remaining),
+            wickets_remaining,
+            target: Some(180),
+            required_run_rate: Some(required),
+            free_hit: false,
+            partnership_runs: 0,
+            partnership_balls: 0,
+            dots_in_a_row: 0,
+            striker_balls: 20,
+            striker_position: 6,
+            striker_came_in: Default::default(),
+        }
+    }
+
+    #[test]
+    fn situational_aggression() {
+        let strategy = SituationalAggression::default();
+        let calm = strategy.aggression(&chase(60, 5, 6.));
+        assert!(strategy.aggression(&chase(12, 5, 6.)) > calm);
+        assert!(strategy.aggression(&chase(60, 5, 10.)) > calm);
+        assert!(strategy.aggression(&chase(60, 1, 6.)) < calm);
+        let free_hit = MatchContext {
+            free_hit: true,
+            ..chase(60, 1, 3.)
+        };
+        assert_eq!(strategy.aggression(&free_hit), 1.);
+    }
+}
@@ -0,0 +1,77 @@
+//! Changing the batting order during an innings
+
+use crate::{game::MatchContext, player::PlayerId};
+use serde::{Deserialize, Serialize};
+
+/// The situation as seen by the batting side at the fall of a wicket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BattingOrderContext {
+    /// The state of the innings after the wicket
+    pub situation: MatchContext,
+    /// The batter who has just come in, if they have yet to face a ball
+    pub incoming: Option<PlayerId>,
+    /// The batters still to come in, next first
+    pub remaining: Vec<PlayerId>,
+    /// The batting side's captain, who makes the decision, if known
+    pub captain: Option<PlayerId>,
+}
+
+/// Decides who comes in to bat. This is consulted at the fall of every wicket, after the
+/// next batter in the order has come in.
+pub trait BattingOrderStrategy: Send + Sync {
+    /// A batter who has still to come in to send in instead of the incoming batter, who
+    /// then bats next. None keeps the order.
+    fn next_batter(&self, context: &BattingOrderContext) -> Option<PlayerId>;
+}
+
+/// Send in big hitters ahead of their place once few balls remain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinchHitter {
+    /// The batters to promote, first choice first
+    pub hitters: Vec<PlayerId>,
+    /// The balls left in the innings at or below which a hitter is sent in
+    pub balls_remaining: u16,
+}
+
+impl BattingOrderStrategy for PinchHitter {
+    fn next_batter(&self, context: &BattingOrderContext) -> Option<PlayerId> {
+        let balls = context.situation.balls_remaining?;
+        if balls > self.balls_remaining || context.incoming.is_none() {
+            return None;
+        }
+        self.hitters
+            .iter()
+            .copied()
+            .find(|hitter| context.remaining.contains(hitter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::Result, form, game, model, player::PlayerDb, strategy, tests::test_team};
+    use rand::thread_rng;
+
+    #[test]
+    fn pinch_hitter_promoted() -> Result<()> {
+        use model::NullModel;
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "NZ")?;
+        let team_b = test_team(&mut db, 2, "ENG")?;
+        let hitter = team_a.players[10].0;
+        let mut state = game::GameState::new(form::Form::t20(), team_a.clone(), team_b)?;
+        let strategy = strategy::PinchHitter {
+            hitters: vec![hitter],
+            balls_remaining: 120,
+        };
+        state.set_batting_order_strategy(&team_a, strategy);
+        state.simulate_to_completion(&NullModel {}, &db, &mut thread_rng())?;
+        let scorecard = state.scorecard()?;
+        let batting = &scorecard.innings[0].batting;
+        if batting.len() > 2 {
+            assert_eq!(batting[2].player, hitter);
+        }
+        let replayed = game::GameState::replay(&state.log(), None)?;
+        assert_eq!(replayed.result(), state.result());
+        Ok(())
+    }
+}
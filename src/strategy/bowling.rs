@@ -0,0 +1,272 @@
+//! Choosing who bowls each over
+use crate::{
+    game::stats::Matchup,
+    player::{PlayerId, Role},
+    team::FATIGUE_LIMIT,
+};
+use serde::{Deserialize, Serialize};
+
+/// The stage of an innings, which decides the kind of bowling a captain wants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    /// The fielding restrictions of a limited-overs innings, or the new ball otherwise
+    Opening,
+    Middle,
+    /// The last fifth of a limited-overs innings
+    Death,
+}
+
+impl Phase {
+    /// The phase of the given over, counted from 0. The opening phase lasts for a fifth of
+    /// a limited innings, but at least 6 overs, and for the first 10 overs otherwise.
+    pub fn of(over: u16, overs_per_innings: Option<u16>) -> Self {
+        match overs_per_innings {
+            Some(overs) => {
+                let fifth = (overs as f32 / 5.).round() as u16;
+                if over < fifth.max(6) {
+                    Phase::Opening
+                } else if over + fifth >= overs {
+                    Phase::Death
+                } else {
+                    Phase::Middle
+                }
+            }
+            None if over < 10 => Phase::Opening,
+            None => Phase::Middle,
+        }
+    }
+}
+
+/// A bowler who may bowl the next over, and how they have fared so far
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BowlerOption {
+    pub id: PlayerId,
+    pub role: Option<Role>,
+    /// Completed overs bowled in the innings
+    pub overs: u16,
+    pub runs: u16,
+    pub wickets: u8,
+    /// How tired the bowler is, from 0 (fresh) to 1 (exhausted)
+    pub fatigue: f32,
+    /// The overs the bowler may still bowl in the innings, if limited
+    pub overs_left: Option<u16>,
+    /// The striker's record against the bowler, in this match and before it if known
+    #[serde(default)]
+    pub against_striker: Matchup,
+}
+
+/// The state of the innings as seen by the fielding captain before an over
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BowlingContext {
+    /// The over about to be bowled, counted from 0
+    pub over: u16,
+    pub phase: Phase,
+    /// The overs left in the innings, including this one, if limited
+    pub overs_remaining: Option<u16>,
+    pub striker: PlayerId,
+    pub non_striker: PlayerId,
+    /// The bowler of the previous over, who can't bowl this one
+    pub previous: Option<PlayerId>,
+    /// The side's bowlers who may bowl this over, in the order of its rotation
+    pub options: Vec<BowlerOption>,
+    /// The fielding side's captain, who makes the decision, if known
+    pub captain: Option<PlayerId>,
+}
+
+impl BowlingContext {
+    /// The options who aren't too tired to bowl, in the order of the rotation
+    pub fn fresh(&self) -> impl Iterator<Item = &BowlerOption> {
+        self.options.iter().filter(|b| b.fatigue <= FATIGUE_LIMIT)
+    }
+}
+
+/// Decides who bowls each over. This is consulted at the start of every over of the
+/// innings in which the side is fielding.
+pub trait BowlingStrategy: Send + Sync {
+    /// The bowler of the next over, chosen from the context's options. If None, or a
+    /// player who isn't an option, is returned, the side's usual rotation decides.
+    fn choose(&self, context: &BowlingContext) -> Option<PlayerId>;
+}
+
+/// Open and close the innings with pace, and bowl spin through the middle overs. The
+/// least tired fresh bowler of the wanted kind is chosen, with all-rounders filling in
+/// for either.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaceThenSpin;
+
+impl BowlingStrategy for PaceThenSpin {
+    fn choose(&self, context: &BowlingContext) -> Option<PlayerId> {
+        let wanted = match context.phase {
+            Phase::Opening | Phase::Death => Role::PaceBowler,
+            Phase::Middle => Role::SpinBowler,
+        };
+        [wanted, Role::AllRounder].iter().find_map(|&role| {
+            context
+                .fresh()
+                .filter(|b| b.role == Some(role))
+                .min_by(|a, b| a.fatigue.total_cmp(&b.fatigue))
+                .map(|b| b.id)
+        })
+    }
+}
+
+/// Keep the given specialists' last overs for the death. Until then a specialist is only
+/// an option while they have more than `reserved` overs left, and the choice among the
+/// options is left to the `before` strategy. At the death, the freshest specialist who
+/// can bowl is chosen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeathSpecialists<S> {
+    pub specialists: Vec<PlayerId>,
+    /// The overs each specialist keeps for the death
+    pub reserved: u16,
+    /// The strategy for the overs before the death
+    pub before: S,
+}
+
+impl<S: BowlingStrategy> DeathSpecialists<S> {
+    pub fn new(specialists: Vec<PlayerId>, before: S) -> Self {
+        Self {
+            specialists,
+            reserved: 2,
+            before,
+        }
+    }
+}
+
+impl<S: BowlingStrategy> BowlingStrategy for DeathSpecialists<S> {
+    fn choose(&self, context: &BowlingContext) -> Option<PlayerId> {
+        let specialist = |b: &BowlerOption| self.specialists.contains(&b.id);
+        if context.phase == Phase::Death {
+            if let Some(b) = context
+                .options
+                .iter()
+                .filter(|b| specialist(b))
+                .min_by(|a, b| a.fatigue.total_cmp(&b.fatigue))
+            {
+                return Some(b.id);
+            }
+            return self.before.choose(context);
+        }
+        let mut before = context.clone();
+        before
+            .options
+            .retain(|b| !specialist(b) || b.overs_left.is_none_or(|left| left > self.reserved));
+        if before.options.is_empty() {
+            return self.before.choose(context);
+        }
+        // The rotation would still turn to the specialists, so pick someone if the
+        // strategy for the earlier overs doesn't
+        self.before.choose(&before).or_else(|| {
+            before
+                .fresh()
+                .next()
+                .or(before.options.first())
+                .map(|b| b.id)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Result,
+        form, game, generate, model,
+        player::{self, PlayerDb},
+        seed, strategy, team,
+    };
+
+    fn option(id: u32, role: Role, overs_left: u16) -> BowlerOption {
+        BowlerOption {
+            id: PlayerId(id),
+            role: Some(role),
+            overs: 4 - overs_left,
+            runs: 0,
+            wickets: 0,
+            fatigue: 0.,
+            overs_left: Some(overs_left),
+            against_striker: Matchup::default(),
+        }
+    }
+
+    fn context(over: u16) -> BowlingContext {
+        BowlingContext {
+            over,
+            phase: Phase::of(over, Some(20)),
+            overs_remaining: Some(20 - over),
+            striker: PlayerId(0),
+            non_striker: PlayerId(1),
+            previous: None,
+            captain: None,
+            options: vec![
+                option(6, Role::PaceBowler, 2),
+                option(7, Role::SpinBowler, 4),
+                option(8, Role::PaceBowler, 4),
+            ],
+        }
+    }
+
+    #[test]
+    fn phases() {
+        use Phase::*;
+        let t20: Vec<_> = [0, 5, 6, 15, 16, 19]
+            .iter()
+            .map(|&over| Phase::of(over, Some(20)))
+            .collect();
+        assert_eq!(t20, [Opening, Opening, Middle, Middle, Death, Death]);
+        assert_eq!(Phase::of(39, Some(50)), Middle);
+        assert_eq!(Phase::of(40, Some(50)), Death);
+        assert_eq!(Phase::of(100, None), Middle);
+    }
+
+    #[test]
+    fn specialists_saved_for_death() {
+        let strategy = DeathSpecialists::new(vec![PlayerId(6)], PaceThenSpin);
+        assert_eq!(PaceThenSpin.choose(&context(0)), Some(PlayerId(6)));
+        assert_eq!(strategy.choose(&context(0)), Some(PlayerId(8)));
+        assert_eq!(strategy.choose(&context(10)), Some(PlayerId(7)));
+        assert_eq!(strategy.choose(&context(18)), Some(PlayerId(6)));
+    }
+
+    #[test]
+    fn bowling_strategies() -> Result<()> {
+        use model::NaiveStatsModel;
+        use strategy::{DeathSpecialists, PaceThenSpin};
+        let mut rng = seed::rng(5);
+        let generator = generate::PlayerGenerator::default();
+        let mut db = PlayerDb::new();
+        let home = generator.squad(&mut rng, &mut db, 0, "Home")?;
+        let away = generator.squad(&mut rng, &mut db, 1, "Away")?;
+        let spinner = |team: &team::Team| {
+            team.players
+                .iter()
+                .map(|(id, _)| *id)
+                .find(|id| team.role(*id) == Some(player::Role::SpinBowler))
+                .unwrap()
+        };
+        let (home_spinner, away_spinner) = (spinner(&home), spinner(&away));
+        let death = home.bowlers().bowlers[0];
+        let mut state = game::GameState::new(form::Form::t20(), home.clone(), away.clone())?;
+        state.set_bowling_strategy(&home, DeathSpecialists::new(vec![death], PaceThenSpin))?;
+        state.set_bowling_strategy(&away, PaceThenSpin)?;
+        state.simulate_to_completion(&NaiveStatsModel {}, &db, &mut rng)?;
+        for innings in state.completed_innings() {
+            assert!(innings
+                .bowling_stats
+                .bowlers()
+                .iter()
+                .all(|(_, b)| b.balls <= 24));
+            let over = |n: u16| innings.deliveries.iter().find(|d| d.overs.completed == n);
+            if let Some(first) = over(0) {
+                assert_ne!(first.bowler, home_spinner);
+                assert_ne!(first.bowler, away_spinner);
+            }
+            if let Some(middle) = over(6) {
+                assert!(middle.bowler == home_spinner || middle.bowler == away_spinner);
+            }
+        }
+        let replayed = game::GameState::replay(&state.log(), None)?;
+        assert_eq!(replayed.result(), state.result());
+        Ok(())
+    }
+}
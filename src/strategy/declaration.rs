@@ -0,0 +1,66 @@
+//! Deciding when the batting side should declare their innings closed
+use crate::player::PlayerId;
+
+/// The match situation presented to a declaration strategy
+#[derive(Debug, Clone)]
+pub struct DeclarationContext {
+    /// The index of the current innings in the match, starting at 0
+    pub innings: usize,
+    /// Whether this is the last innings of the match
+    pub final_innings: bool,
+    /// The batting side's lead over the fielding side. Negative if trailing.
+    pub lead: i32,
+    /// Overs remaining in the match, if it is time-limited
+    pub overs_remaining: Option<u16>,
+    /// The number of wickets the batting side has in hand
+    pub wickets_in_hand: u8,
+    /// The batting side's captain, who makes the decision, if known
+    pub captain: Option<PlayerId>,
+}
+
+/// Decides whether the batting side should declare. This is consulted at the end of
+/// each over in formats which allow declarations.
+pub trait DeclarationStrategy: Send + Sync {
+    /// Whether the batting side should declare their innings closed
+    fn declare(&self, context: &DeclarationContext) -> bool;
+}
+
+/// A strategy that always bats on
+pub struct NeverDeclare;
+
+impl DeclarationStrategy for NeverDeclare {
+    fn declare(&self, _context: &DeclarationContext) -> bool {
+        false
+    }
+}
+
+/// Declare once the lead is large enough, or once the side is ahead and the time left
+/// is down to what is needed to bowl the opposition out.
+pub struct LeadDeclaration {
+    /// Declare once the lead reaches this many runs
+    pub target_lead: i32,
+    /// The number of overs the fielding side wants in which to bowl out the opposition
+    pub overs_to_bowl: u16,
+}
+
+impl Default for LeadDeclaration {
+    fn default() -> Self {
+        Self {
+            target_lead: 400,
+            overs_to_bowl: 100,
+        }
+    }
+}
+
+impl DeclarationStrategy for LeadDeclaration {
+    fn declare(&self, context: &DeclarationContext) -> bool {
+        // There is nothing to gain by declaring in the last innings
+        if context.final_innings {
+            return false;
+        }
+        let out_of_time = context
+            .overs_remaining
+            .is_some_and(|overs| overs <= self.overs_to_bowl);
+        context.lead >= self.target_lead || (context.lead > 0 && out_of_time)
+    }
+}
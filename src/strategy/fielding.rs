@@ -0,0 +1,73 @@
+//! Setting the field for each over
+use super::Phase;
+use crate::{field::Field, game::MatchContext, player::PlayerId};
+use serde::{Deserialize, Serialize};
+
+/// The situation as seen by the fielding captain when setting the field
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FieldContext {
+    /// The state of the innings before the over
+    pub situation: MatchContext,
+    pub phase: Phase,
+    /// The most fielders allowed outside the circle this over, if restricted
+    pub max_outside_ring: Option<u8>,
+    /// The bowler of the over
+    pub bowler: PlayerId,
+    /// The fielding side's captain, who sets the field, if known
+    pub captain: Option<PlayerId>,
+}
+
+/// Sets the field. This is consulted at the start of every over of the innings in which
+/// the side is fielding, and the field is brought in as far as the restrictions require.
+pub trait FieldingStrategy: Send + Sync {
+    fn field(&self, context: &FieldContext) -> Field;
+}
+
+/// The same field throughout
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FixedField(pub Field);
+
+impl FieldingStrategy for FixedField {
+    fn field(&self, _context: &FieldContext) -> Field {
+        self.0
+    }
+}
+
+/// Attack with the new ball and when the tail is in, defend the boundary at the death
+/// and when the chasing side needs runs quickly, and set a balanced field otherwise
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SituationalField {
+    /// The required run rate, in runs per six balls, above which the boundary is defended
+    pub defend_rate: f32,
+    /// The wickets in hand at or below which the batting side is attacked
+    pub tail: u8,
+}
+
+impl Default for SituationalField {
+    fn default() -> Self {
+        Self {
+            defend_rate: 9.,
+            tail: 2,
+        }
+    }
+}
+
+impl FieldingStrategy for SituationalField {
+    fn field(&self, context: &FieldContext) -> Field {
+        let situation = &context.situation;
+        if situation.wickets_remaining <= self.tail {
+            return Field::attacking();
+        }
+        if situation
+            .required_run_rate
+            .is_some_and(|rate| rate > self.defend_rate)
+        {
+            return Field::defensive();
+        }
+        match context.phase {
+            Phase::Opening => Field::attacking(),
+            Phase::Middle => Field::balanced(),
+            Phase::Death => Field::defensive(),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Deciding when to review an umpire's decision under the Decision Review System
+use crate::{game::Appeal, player::PlayerId};
+
+/// The situation presented to a review strategy after an appeal is decided
+#[derive(Debug, Clone)]
+pub struct ReviewContext {
+    /// The index of the current innings in the match, starting at 0
+    pub innings: usize,
+    /// The kind of dismissal appealed for
+    pub appeal: Appeal,
+    /// Whether the side considering a review is batting, i.e. the batter was given out.
+    /// Otherwise the fielding side is considering a review of a not out decision.
+    pub batting: bool,
+    /// How sure the side is that the decision is wrong, from 0 to 1
+    pub confidence: f32,
+    /// The reviews the side has left in the innings
+    pub reviews_remaining: u8,
+    /// The score of the batter the appeal was against
+    pub batter_runs: u16,
+    /// The number of wickets the batting side has in hand, before the decision
+    pub wickets_in_hand: u8,
+    /// The captain of the side considering a review, who makes the decision, if known
+    pub captain: Option<PlayerId>,
+}
+
+/// Decides whether a side should review an umpire's decision. This is consulted after
+/// each decision on an appeal that the side could review, as long as it has reviews left.
+pub trait ReviewStrategy: Send + Sync {
+    /// Whether the side should review the decision
+    fn review(&self, context: &ReviewContext) -> bool;
+}
+
+/// A strategy that always accepts the umpire's decision
+pub struct NeverReview;
+
+impl ReviewStrategy for NeverReview {
+    fn review(&self, _context: &ReviewContext) -> bool {
+        false
+    }
+}
+
+/// Review when sure enough the decision is wrong. The side is more careful with its last
+/// review, and more willing to spend one on a set batter.
+pub struct ConfidentReview {
+    /// The confidence needed to review
+    pub threshold: f32,
+}
+
+impl Default for ConfidentReview {
+    fn default() -> Self {
+        Self { threshold: 0.6 }
+    }
+}
+
+impl ReviewStrategy for ConfidentReview {
+    fn review(&self, context: &ReviewContext) -> bool {
+        let mut threshold = self.threshold;
+        if context.reviews_remaining == 1 {
+            threshold += 0.15;
+        }
+        if context.batter_runs >= 50 {
+            // Worth keeping a set batter in, or getting them out
+            threshold -= 0.1;
+        }
+        context.confidence >= threshold
+    }
+}
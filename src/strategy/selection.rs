@@ -0,0 +1,223 @@
+//! Picking the XI for a match from a squad
+use crate::{
+    conditions::{Pitch, Weather},
+    error::{Error, Result},
+    player::{PlayerId, Role},
+    team::{Squad, Team},
+};
+
+/// The conditions the selectors expect for a match
+#[derive(Debug, Clone, Default)]
+pub struct SelectionContext {
+    pub pitch: Pitch,
+    pub weather: Weather,
+}
+
+/// Picks a side's XI for a match from its squad
+pub trait SelectionStrategy: Send + Sync {
+    /// The XI to play, chosen from the squad members for whom `available` is true
+    fn select(
+        &self,
+        squad: &Squad,
+        context: &SelectionContext,
+        available: &dyn Fn(PlayerId) -> bool,
+    ) -> Result<Team>;
+}
+
+/// Picks a balanced side of two openers, three more batters, a wicket-keeper, an
+/// all-rounder, three seamers and a spinner. A second spinner replaces a seamer on a
+/// turning pitch, and a fourth seamer replaces the spinner when the ball should move
+/// around. Within each role the squad's order of preference is followed, and a role that
+/// can't be filled is made up from the nearest others. There is no stand-in for the
+/// wicket-keeper, so a squad without a fit keeper can't be picked from.
+pub struct BalancedSelection {
+    /// The turn in the pitch at which a second spinner is picked
+    pub turning: f32,
+    /// The help for the seamers, from the pitch or the cloud cover, at which a fourth
+    /// seamer is picked
+    pub seaming: f32,
+}
+
+impl Default for BalancedSelection {
+    fn default() -> Self {
+        Self {
+            turning: 0.6,
+            seaming: 0.6,
+        }
+    }
+}
+
+impl BalancedSelection {
+    /// The number of players of each role to pick
+    pub fn composition(&self, context: &SelectionContext) -> Vec<(Role, usize)> {
+        use Role::*;
+        let swing = context.pitch.seam.max(context.weather.cloud_cover);
+        let (pace, spin) = if context.pitch.turn >= self.turning {
+            (2, 2)
+        } else if swing >= self.seaming {
+            (4, 0)
+        } else {
+            (3, 1)
+        };
+        vec![
+            (Opener, 2),
+            (TopOrder, 2),
+            (MiddleOrder, 1),
+            (WicketKeeper, 1),
+            (AllRounder, 1),
+            (PaceBowler, pace),
+            (SpinBowler, spin),
+        ]
+    }
+}
+
+/// The roles that can best stand in for one that is short, most suitable first
+fn stand_ins(role: Role) -> &'static [Role] {
+    use Role::*;
+    match role {
+        Opener => &[TopOrder, MiddleOrder],
+        TopOrder => &[MiddleOrder, Opener],
+        MiddleOrder => &[TopOrder, AllRounder, Opener],
+        WicketKeeper => &[],
+        AllRounder => &[MiddleOrder, SpinBowler, PaceBowler],
+        PaceBowler => &[AllRounder, SpinBowler],
+        SpinBowler => &[AllRounder, PaceBowler],
+    }
+}
+
+impl SelectionStrategy for BalancedSelection {
+    fn select(
+        &self,
+        squad: &Squad,
+        context: &SelectionContext,
+        available: &dyn Fn(PlayerId) -> bool,
+    ) -> Result<Team> {
+        let mut left: Vec<_> = squad
+            .players
+            .iter()
+            .filter(|(id, _, _)| available(*id))
+            .collect();
+        let mut picked = Vec::new();
+        let mut take = |roles: &[Role], picked: &mut Vec<(PlayerId, String, Role)>| {
+            let i = roles
+                .iter()
+                .find_map(|role| left.iter().position(|(_, _, r)| r == role))?;
+            let (id, name, role) = left.remove(i);
+            picked.push((*id, name.clone(), *role));
+            Some(())
+        };
+        let mut short = Vec::new();
+        for (role, n) in self.composition(context) {
+            for _ in 0..n {
+                if take(&[role], &mut picked).is_none() {
+                    short.push(role);
+                }
+            }
+        }
+        for role in short {
+            if take(stand_ins(role), &mut picked).is_none() {
+                // Anyone fit will do
+                take(&Role::ALL, &mut picked);
+            }
+        }
+        if !picked
+            .iter()
+            .any(|(_, _, role)| *role == Role::WicketKeeper)
+        {
+            return Err(Error::NoKeeper(squad.name.clone()));
+        }
+        if picked.len() < 11 {
+            return Err(Error::InvalidTeam(
+                squad.name.clone(),
+                "fewer than 11 players available".into(),
+            ));
+        }
+        let mut team = Team::with_roles(squad.id, squad.name.clone(), picked);
+        team.captain = squad.captain.filter(|&id| team.get_name(id).is_some());
+        team.home = squad.home.clone();
+        Ok(team)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        form, generate, injury, model,
+        player::{self, PlayerDb},
+        seed, strategy, team, tournament,
+    };
+
+    #[test]
+    fn keeper_required() -> Result<()> {
+        use Role::*;
+        let roles = [
+            Opener,
+            Opener,
+            TopOrder,
+            TopOrder,
+            MiddleOrder,
+            WicketKeeper,
+            AllRounder,
+            PaceBowler,
+            PaceBowler,
+            PaceBowler,
+            SpinBowler,
+            MiddleOrder,
+        ];
+        let players = roles
+            .iter()
+            .enumerate()
+            .map(|(i, &role)| (PlayerId(i as u32), format!("player_{}", i), role))
+            .collect();
+        let squad = Squad::new(0, "squad".to_string(), players);
+        let (strategy, context) = (BalancedSelection::default(), SelectionContext::default());
+        let xi = strategy.select(&squad, &context, &|_| true)?;
+        assert_eq!(xi.keeper, Some(PlayerId(5)));
+        let keeper_injured = strategy.select(&squad, &context, &|id| id != PlayerId(5));
+        assert!(matches!(keeper_injured, Err(Error::NoKeeper(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn squads_picked_for_conditions() -> Result<()> {
+        use model::NaiveStatsModel;
+        use std::sync::Arc;
+        use strategy::{BalancedSelection, SelectionContext, SelectionStrategy};
+        let mut rng = seed::rng(11);
+        let generator = generate::PlayerGenerator::default();
+        let mut db = PlayerDb::new();
+        let mut teams = Vec::new();
+        let mut squads = Vec::new();
+        for (i, label) in ["Home", "Away"].iter().enumerate() {
+            let team = generator.squad(&mut rng, &mut db, i as u16, label)?;
+            let reserves = [generate::Archetype::Spinner, generate::Archetype::Opener]
+                .iter()
+                .map(|&archetype| {
+                    let id = generator.player(&mut rng, &mut db, archetype)?;
+                    Ok((id, db.get(id).unwrap().name.clone(), archetype.role()))
+                })
+                .collect::<Result<_>>()?;
+            squads.push(team::Squad::from_team(&team, reserves));
+            teams.push(Arc::new(team));
+        }
+        let mut turning = SelectionContext::default();
+        turning.pitch.turn = 0.8;
+        let xi = BalancedSelection::default().select(&squads[0], &turning, &|_| true)?;
+        xi.validate(&db)?;
+        let spinners = xi
+            .players
+            .iter()
+            .filter(|(id, _)| xi.role(*id) == Some(player::Role::SpinBowler))
+            .count();
+        assert_eq!(spinners, 2);
+
+        let mut tournament = tournament::Tournament::round_robin(form::Form::t20(), teams)
+            .with_squads(squads, BalancedSelection::default())
+            .with_conditions(turning)
+            .with_injuries(injury::InjuryConfig::default());
+        tournament.play(&NaiveStatsModel {}, &db, &mut rng)?;
+        assert_eq!(tournament.results().len(), 1);
+        Ok(())
+    }
+}
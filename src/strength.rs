@@ -0,0 +1,207 @@
+//! Summaries of a side's strength from its players' ratings, and quick pre-match
+//! predictions from them that don't need a simulation
+use crate::{
+    error::{Error, Result},
+    form::Form,
+    model::{PlayerRating, PlayerRatingNaiveStats, PlayerRatingNull},
+    player::PlayerDb,
+    team::Team,
+};
+use serde::{Deserialize, Serialize};
+
+/// The weight of each position in the batting order, from the top. Higher batters face
+/// more of the balls.
+const POSITION_WEIGHTS: [f32; 11] = [1., 1., 0.95, 0.9, 0.85, 0.75, 0.6, 0.45, 0.3, 0.2, 0.1];
+
+/// The number of bowlers in the rotation whose ratings make up the attack
+const ATTACK: usize = 5;
+
+/// The batting average at or above which a batter adds to the depth of the batting
+pub const DEPTH_AVERAGE: f32 = 20.;
+
+/// How much innings totals vary, as a fraction of the expected total. This sets how
+/// quickly the predicted chance of winning grows with the difference in expected totals.
+const SCORE_SPREAD: f32 = 0.15;
+
+/// Ratings that can be summarised by batting and bowling averages
+pub trait Averages {
+    /// Runs per dismissal and runs per 100 balls as a batter
+    fn batting_averages(&self) -> (f32, f32);
+    /// Runs conceded per wicket and balls per wicket as a bowler
+    fn bowling_averages(&self) -> (f32, f32);
+}
+
+impl Averages for PlayerRatingNaiveStats {
+    fn batting_averages(&self) -> (f32, f32) {
+        (self.batting.avg, self.batting.sr)
+    }
+    fn bowling_averages(&self) -> (f32, f32) {
+        (self.bowling.avg, self.bowling.sr)
+    }
+}
+
+/// The averages that reproduce the null model
+impl Averages for PlayerRatingNull {
+    fn batting_averages(&self) -> (f32, f32) {
+        PlayerRatingNaiveStats::from(self).batting_averages()
+    }
+    fn bowling_averages(&self) -> (f32, f32) {
+        PlayerRatingNaiveStats::from(self).bowling_averages()
+    }
+}
+
+/// A side's batting and bowling, summarised from the ratings of its players
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TeamStrength {
+    /// Runs per wicket of the batting, weighted towards the top of the order
+    pub batting_average: f32,
+    /// Runs per 100 balls of the batting, weighted towards the top of the order
+    pub batting_strike_rate: f32,
+    /// The number of batters averaging at least `DEPTH_AVERAGE`
+    pub batting_depth: u8,
+    /// Runs per wicket conceded by the main bowlers together
+    pub bowling_average: f32,
+    /// Balls per wicket of the main bowlers together
+    pub bowling_strike_rate: f32,
+}
+
+impl TeamStrength {
+    /// Summarise a side from its players' ratings
+    pub fn of<R>(team: &Team, db: &PlayerDb<R>) -> Result<Self>
+    where
+        R: PlayerRating + Averages,
+    {
+        let rating = |id| {
+            db.get(id)
+                .map(|p| &p.rating)
+                .ok_or(Error::PlayerNotFound(id))
+        };
+        let (mut runs, mut balls, mut weights) = (0., 0., 0.);
+        let mut batting_depth = 0;
+        for (id, weight) in team.batting_order().zip(POSITION_WEIGHTS.iter()) {
+            let (avg, sr) = rating(id)?.batting_averages();
+            // Weight the runs and balls of each dismissal, so the rates stay consistent
+            runs += weight * avg;
+            balls += weight * avg * 100. / sr.max(1.);
+            weights += weight;
+            if avg >= DEPTH_AVERAGE {
+                batting_depth += 1;
+            }
+        }
+        let bowlers = team.bowlers().bowlers;
+        if bowlers.is_empty() || weights == 0. {
+            return Err(Error::InvalidTeam(
+                team.name.clone(),
+                "no batters or bowlers".into(),
+            ));
+        }
+        // Combine the bowlers by the rates at which they concede runs and take wickets
+        let (mut runs_per_ball, mut wickets_per_ball) = (0., 0.);
+        let attack = bowlers.len().min(ATTACK);
+        for &id in bowlers.iter().take(attack) {
+            let (avg, sr) = rating(id)?.bowling_averages();
+            runs_per_ball += avg / sr.max(1.) / attack as f32;
+            wickets_per_ball += 1. / sr.max(1.) / attack as f32;
+        }
+        Ok(Self {
+            batting_average: runs / weights,
+            batting_strike_rate: 100. * runs / balls,
+            batting_depth,
+            bowling_average: runs_per_ball / wickets_per_ball,
+            bowling_strike_rate: 1. / wickets_per_ball,
+        })
+    }
+
+    /// The total this side is expected to make in an innings against the given bowling,
+    /// in the given format. Batting and bowling combine relative to the averages of the
+    /// null model, so an average batting side facing an average attack makes the average
+    /// score.
+    pub fn expected_score(&self, opponent: &TeamStrength, form: &Form) -> f32 {
+        let null = PlayerRatingNaiveStats::from(&PlayerRatingNull::default());
+        let (par_avg, par_sr) = null.batting_averages();
+        let runs_per_wicket = self.batting_average * opponent.bowling_average / par_avg;
+        let runs_per_ball = (self.batting_strike_rate / 100.)
+            * (opponent.bowling_average / opponent.bowling_strike_rate)
+            / (par_sr / 100.);
+        // This scales the batters' balls per dismissal by the bowlers' strike rate
+        let balls_per_wicket = (runs_per_wicket / runs_per_ball.max(0.01)).max(1.);
+        let wickets = (form.batsmen_per_side - 1) as f32;
+        let balls = balls_per_wicket * wickets;
+        let balls = match form.innings_balls() {
+            Some(quota) => balls.min(quota as f32),
+            None => balls,
+        };
+        balls * runs_per_ball
+    }
+}
+
+/// A quick estimate of the chance that `team` beats `opponent`, from the totals each is
+/// expected to make. Draws and ties are not considered, so the two sides' chances add up
+/// to 1. This is much cheaper than simulating the match, but only a rough guide.
+pub fn win_probability(team: &TeamStrength, opponent: &TeamStrength, form: &Form) -> f32 {
+    let score = team.expected_score(opponent, form);
+    let against = opponent.expected_score(team, form);
+    let spread = SCORE_SPREAD * (score + against) / 2.;
+    if spread <= 0. {
+        return 0.5;
+    }
+    1. / (1. + (-(score - against) / spread).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::naive_stats::{BatRatingNaiveStats, BowlRatingNaiveStats};
+
+    fn side(db: &mut PlayerDb<PlayerRatingNaiveStats>, id: u16, batting_avg: f32) -> Team {
+        let players = (0..11)
+            .map(|i| {
+                let rating = PlayerRatingNaiveStats {
+                    batting: BatRatingNaiveStats {
+                        avg: batting_avg,
+                        sr: 50.,
+                        r4: 0.02,
+                        r6: 0.004,
+                    },
+                    bowling: BowlRatingNaiveStats {
+                        sr: 50.,
+                        avg: 25.,
+                        wide_rate: 0.02,
+                        no_ball_rate: 0.005,
+                    },
+                    fielding: Default::default(),
+                    running: Default::default(),
+                };
+                let player = db.add(format!("player_{}_{}", id, i), rating).unwrap();
+                (player.id, player.name.clone())
+            })
+            .collect();
+        Team {
+            id,
+            name: format!("team_{}", id),
+            players,
+            keeper: None,
+            captain: None,
+            home: None,
+            roles: Default::default(),
+        }
+    }
+
+    #[test]
+    fn stronger_batting_favoured() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let strong = side(&mut db, 0, 40.);
+        let weak = side(&mut db, 1, 10.);
+        let strong = TeamStrength::of(&strong, &db)?;
+        let weak = TeamStrength::of(&weak, &db)?;
+        assert_eq!((strong.batting_depth, weak.batting_depth), (11, 0));
+        let form = Form::odi();
+        assert!(strong.expected_score(&weak, &form) > weak.expected_score(&strong, &form));
+        let p = win_probability(&strong, &weak, &form);
+        assert!(p > 0.5 && p < 1.);
+        let q = win_probability(&weak, &strong, &form);
+        assert!((p + q - 1.).abs() < 1e-5);
+        assert!((win_probability(&strong, &strong, &form) - 0.5).abs() < 1e-5);
+        Ok(())
+    }
+}
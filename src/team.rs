@@ -1,35 +1,242 @@
 //! Teams of players
 use crate::{
+    conditions::Venue,
+    error::{Error, Result},
     model::PlayerRating,
-    player::{Player, PlayerDb, PlayerId},
+    player::{Player, PlayerDb, PlayerId, Role},
+    strength::{Averages, TeamStrength},
 };
+use fnv::FnvHashMap;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
-#[derive(Debug, Deserialize, Serialize)]
+/// The number of players in a side
+pub const PLAYERS: usize = 11;
+
+/// The fewest bowlers a side can be picked with
+pub const MIN_BOWLERS: usize = 5;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Team {
     pub id: u16,
     pub name: String,
     /// The UIDs and names of the players
     pub players: Vec<(PlayerId, String)>,
+    /// The designated wicket-keeper, if known
+    #[serde(default)]
+    pub keeper: Option<PlayerId>,
+    /// The captain, if known
+    #[serde(default)]
+    pub captain: Option<PlayerId>,
+    /// The side's home ground, if it has one
+    #[serde(default)]
+    pub home: Option<Venue>,
+    /// The roles of the players, if known
+    #[serde(default)]
+    pub roles: FnvHashMap<PlayerId, Role>,
 }
 
 impl Team {
+    /// A team with the given roles. The first wicket-keeper listed takes the gloves.
+    pub fn with_roles(id: u16, name: String, players: Vec<(PlayerId, String, Role)>) -> Self {
+        let keeper = players
+            .iter()
+            .find(|(_, _, role)| *role == Role::WicketKeeper)
+            .map(|(id, _, _)| *id);
+        let roles = players.iter().map(|(id, _, role)| (*id, *role)).collect();
+        let players = players
+            .into_iter()
+            .map(|(id, name, _)| (id, name))
+            .collect();
+        Self {
+            id,
+            name,
+            players,
+            keeper,
+            captain: None,
+            home: None,
+            roles,
+        }
+    }
+
+    /// Make the given player captain
+    pub fn with_captain(mut self, captain: PlayerId) -> Self {
+        self.captain = Some(captain);
+        self
+    }
+
+    /// Give the side a home ground
+    pub fn with_home(mut self, venue: Venue) -> Self {
+        self.home = Some(venue);
+        self
+    }
+
+    /// The batting order, which is the order the players are listed in unless they have
+    /// roles. Players are then ordered by role, keeping the listed order within each role;
+    /// those without a role bat in the middle order.
     pub fn batting_order(&self) -> BattingOrder {
-        let n_batters = self.players.len();
-        let remaining: Vec<usize> = (0..n_batters).rev().collect();
-        let batters = self.players.iter().map(|(id, _)| id).cloned().collect();
-        BattingOrder { batters, remaining }
+        let mut batters: Vec<PlayerId> = self.players.iter().map(|(id, _)| *id).collect();
+        if !self.roles.is_empty() {
+            batters.sort_by_key(|id| {
+                self.roles
+                    .get(id)
+                    .map_or(Role::MiddleOrder.batting_rank(), Role::batting_rank)
+            });
+        }
+        BattingOrder {
+            remaining: batters.into(),
+        }
     }
 
+    /// The bowlers, in the order they are brought on. Without roles these are the players
+    /// listed from sixth to eleventh, starting from the bottom of the list. With roles,
+    /// pace bowlers open, followed by spinners and then all-rounders.
     pub fn bowlers(&self) -> Bowlers {
-        let bowlers: Vec<PlayerId> = self.players[5..11]
+        let bowlers: Vec<PlayerId> = if self.roles.is_empty() {
+            self.players
+                .iter()
+                .skip(5)
+                .take(6)
+                .map(|(id, _)| *id)
+                .rev()
+                .collect()
+        } else {
+            [Role::PaceBowler, Role::SpinBowler, Role::AllRounder]
+                .iter()
+                .flat_map(|role| {
+                    self.players
+                        .iter()
+                        .rev()
+                        .filter(move |(id, _)| self.roles.get(id) == Some(role))
+                        .map(|(id, _)| *id)
+                })
+                .collect()
+        };
+        // The rest of the side from the tail up, with the keeper last
+        let mut part_timers: Vec<PlayerId> = self
+            .players
             .iter()
-            .map(|(id, _)| id)
             .rev()
-            .cloned()
+            .map(|(id, _)| *id)
+            .filter(|id| !bowlers.contains(id) && Some(*id) != self.keeper)
             .collect();
-        let last: PlayerId = bowlers[1];
-        Bowlers { bowlers, last }
+        part_timers.extend(self.keeper);
+        // Nobody bowled the previous over, so the first bowler can start
+        Bowlers {
+            bowlers,
+            part_timers,
+            last: None,
+        }
+    }
+
+    /// Check the line-up itself: `players` different players, with roles, the
+    /// wicket-keeper and the captain given only for players in the side. This is checked
+    /// when a match is set up.
+    pub fn validate_lineup(&self, players: usize) -> Result<()> {
+        if self.players.len() != players {
+            return Err(Error::TeamSize {
+                team: self.name.clone(),
+                players: self.players.len(),
+                expected: players,
+            });
+        }
+        for (i, (id, _)) in self.players.iter().enumerate() {
+            if self.players[..i].iter().any(|(other, _)| other == id) {
+                return Err(Error::DuplicateInTeam(self.name.clone(), *id));
+            }
+        }
+        let not_in_team = |id: PlayerId, position: &str| Error::NotInTeam {
+            team: self.name.clone(),
+            player: id,
+            position: position.to_string(),
+        };
+        if let Some(id) = self.roles.keys().find(|id| self.get_name(**id).is_none()) {
+            return Err(not_in_team(*id, "role"));
+        }
+        if let Some(keeper) = self.keeper.filter(|id| self.get_name(*id).is_none()) {
+            return Err(not_in_team(keeper, "wicket-keeper"));
+        }
+        if let Some(captain) = self.captain.filter(|id| self.get_name(*id).is_none()) {
+            return Err(not_in_team(captain, "captain"));
+        }
+        Ok(())
+    }
+
+    /// Check that the side can take the field: it must have a valid line-up of 11
+    /// players who are all in the database, a wicket-keeper, and at least `MIN_BOWLERS`
+    /// bowlers.
+    pub fn validate<R: PlayerRating>(&self, db: &PlayerDb<R>) -> Result<()> {
+        self.validate_for(PLAYERS, db)
+    }
+
+    /// Check that the side can take the field in a format of `players` a side, as for
+    /// [`Team::validate`]. This is checked when simulation of a match starts.
+    pub fn validate_for<R: PlayerRating>(&self, players: usize, db: &PlayerDb<R>) -> Result<()> {
+        self.validate_lineup(players)?;
+        if let Some((id, _)) = self.players.iter().find(|(id, _)| !db.contains(*id)) {
+            return Err(Error::PlayerNotFound(*id));
+        }
+        if self.keeper.is_none() {
+            return Err(Error::NoKeeper(self.name.clone()));
+        }
+        let bowlers = self.bowlers().bowlers.len();
+        if bowlers < MIN_BOWLERS {
+            return Err(Error::TooFewBowlers(self.name.clone(), bowlers));
+        }
+        Ok(())
+    }
+
+    /// A summary of the side's batting and bowling from its players' ratings, e.g. for
+    /// seeding a tournament or estimating the result of a match without simulating it
+    pub fn strength<R>(&self, db: &PlayerDb<R>) -> Result<TeamStrength>
+    where
+        R: PlayerRating + Averages,
+    {
+        TeamStrength::of(self, db)
+    }
+
+    /// Put a substitute in the place of a player, taking over their role, the gloves if
+    /// they kept wicket and the captaincy if they led the side
+    pub fn replace(&mut self, player: PlayerId, replacement: (PlayerId, String)) -> Result<()> {
+        let id = replacement.0;
+        let slot = self
+            .players
+            .iter_mut()
+            .find(|(p, _)| *p == player)
+            .ok_or(Error::PlayerNotFound(player))?;
+        *slot = replacement;
+        if let Some(role) = self.roles.remove(&player) {
+            self.roles.insert(id, role);
+        }
+        if self.keeper == Some(player) {
+            self.keeper = Some(id);
+        }
+        if self.captain == Some(player) {
+            self.captain = Some(id);
+        }
+        Ok(())
+    }
+
+    /// The role of a player, if known
+    pub fn role(&self, id: PlayerId) -> Option<Role> {
+        self.roles.get(&id).copied()
+    }
+
+    /// The name of a player as shown on a scorecard, with "(c)" after the captain and
+    /// "†" after the wicket-keeper
+    pub fn scorecard_name(&self, id: PlayerId) -> Option<String> {
+        let mut name = self.get_name(id)?.to_string();
+        if self.captain == Some(id) {
+            name.push_str(" (c)");
+        }
+        if self.keeper == Some(id) {
+            name.push_str(if self.captain == Some(id) {
+                "†"
+            } else {
+                " †"
+            });
+        }
+        Some(name)
     }
 
     pub fn get_name(&self, id: PlayerId) -> Option<&str> {
@@ -40,6 +247,84 @@ impl Team {
     }
 }
 
+/// The players a side can pick its XI from, usually 15 to 20 of them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Squad {
+    pub id: u16,
+    pub name: String,
+    /// The players and their roles, in order of preference within each role
+    pub players: Vec<(PlayerId, String, Role)>,
+    /// The captain, who leads the side whenever they are picked
+    #[serde(default)]
+    pub captain: Option<PlayerId>,
+    /// The side's home ground, if it has one
+    #[serde(default)]
+    pub home: Option<Venue>,
+}
+
+impl Squad {
+    pub fn new(id: u16, name: String, players: Vec<(PlayerId, String, Role)>) -> Self {
+        Self {
+            id,
+            name,
+            players,
+            captain: None,
+            home: None,
+        }
+    }
+
+    /// A squad of a team's players followed by some reserves. Players of the team without
+    /// a role are taken to be middle-order batters, apart from the wicket-keeper. The
+    /// team's captain captains the squad.
+    pub fn from_team(team: &Team, reserves: Vec<(PlayerId, String, Role)>) -> Self {
+        let players = team
+            .players
+            .iter()
+            .map(|(id, name)| {
+                let role = match team.role(*id) {
+                    Some(role) => role,
+                    None if team.keeper == Some(*id) => Role::WicketKeeper,
+                    None => Role::MiddleOrder,
+                };
+                (*id, name.clone(), role)
+            })
+            .chain(reserves)
+            .collect();
+        Self {
+            captain: team.captain,
+            home: team.home.clone(),
+            ..Self::new(team.id, team.name.clone(), players)
+        }
+    }
+
+    /// The role of a player in the squad
+    pub fn role(&self, id: PlayerId) -> Option<Role> {
+        self.players
+            .iter()
+            .find(|(p, _, _)| *p == id)
+            .map(|(_, _, role)| *role)
+    }
+
+    /// The first fit player who is not in the side, preferring one with the given role,
+    /// e.g. to replace an injured player
+    pub fn replacement(
+        &self,
+        side: &Team,
+        role: Option<Role>,
+        available: impl Fn(PlayerId) -> bool,
+    ) -> Option<(PlayerId, String)> {
+        let free: Vec<_> = self
+            .players
+            .iter()
+            .filter(|(id, _, _)| side.get_name(*id).is_none() && available(*id))
+            .collect();
+        free.iter()
+            .find(|(_, _, r)| Some(*r) == role)
+            .or_else(|| free.first())
+            .map(|(id, name, _)| (*id, name.clone()))
+    }
+}
+
 impl PartialEq for Team {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -48,63 +333,168 @@ impl PartialEq for Team {
 
 impl Eq for Team {}
 
-/// Tracks the batting order. This must be able to change mid-game to adjust strategy
-/// (only for batters who have not yet batted, of course).
+/// Tracks the batting order. This can change mid-game to adjust strategy, but only for
+/// batters who have not yet batted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BattingOrder {
-    /// The reference list of players
-    batters: Vec<PlayerId>,
-    /// Indices of remaining batters in reverse order. (This allows for convenient
-    /// popping.)
-    remaining: Vec<usize>,
+    /// The batters still to come in, next first
+    remaining: VecDeque<PlayerId>,
 }
 
 impl BattingOrder {
+    /// The batters still to come in, next first
+    pub fn remaining(&self) -> impl Iterator<Item = PlayerId> + '_ {
+        self.remaining.iter().copied()
+    }
+
     /// Return a Vec of players remaining in the order that satisfy the given query
-    // TODO: Consider returning an impl Iterator instead of collecting into a Vec. This
-    // is complicated due to the lifetime constraints.
     pub fn query_remaining<R>(
         &self,
-        db: PlayerDb<R>,
+        db: &PlayerDb<R>,
         query: &dyn Fn(&Player<R>) -> bool,
     ) -> Vec<PlayerId>
     where
         R: PlayerRating,
     {
-        // TODO: Define whether this should be reversed
-        self.remaining
-            .iter()
-            .filter_map(|&i| {
-                let batter: PlayerId = self.batters[i];
-                if query(db.get(batter).expect("Not in db")) {
-                    Some(batter)
-                } else {
-                    None
-                }
-            })
+        self.remaining()
+            .filter(|&batter| db.get(batter).is_some_and(query))
             .collect()
     }
 
-    // TODO: Functions to modify the remaining order
+    /// Whether the batter has still to come in
+    pub fn is_remaining(&self, id: PlayerId) -> bool {
+        self.remaining.contains(&id)
+    }
+
+    /// Remove a batter from the remaining order. Returns false if they were not due to bat.
+    pub fn remove(&mut self, id: PlayerId) -> bool {
+        match self.remaining.iter().position(|&b| b == id) {
+            Some(pos) => {
+                self.remaining.remove(pos);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Put a substitute in the place of a batter in the lineup
+    pub fn replace(&mut self, id: PlayerId, replacement: PlayerId) {
+        if let Some(batter) = self.remaining.iter_mut().find(|b| **b == id) {
+            *batter = replacement;
+        }
+    }
+
+    /// Put a batter at the front of the remaining order, whether or not they were in it
+    pub fn push_front(&mut self, id: PlayerId) {
+        self.remove(id);
+        self.remaining.push_front(id);
+    }
+
+    /// Send in a batter who has still to bat next. Returns false if they were not due to
+    /// bat.
+    pub fn promote(&mut self, id: PlayerId) -> bool {
+        if !self.remove(id) {
+            return false;
+        }
+        self.remaining.push_front(id);
+        true
+    }
+
+    /// Change the order of the batters still to come in. The given batters come in
+    /// first, in the order given, followed by the rest in their current order. Fails if
+    /// any of them is not due to bat, leaving the order unchanged.
+    pub fn reorder(&mut self, order: &[PlayerId]) -> Result<()> {
+        for (i, &id) in order.iter().enumerate() {
+            if !self.is_remaining(id) || order[..i].contains(&id) {
+                return Err(Error::CannotBat(id));
+            }
+        }
+        self.remaining.retain(|id| !order.contains(id));
+        for &id in order.iter().rev() {
+            self.remaining.push_front(id);
+        }
+        Ok(())
+    }
 }
 
 impl Iterator for BattingOrder {
     type Item = PlayerId;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.remaining.pop().map(|i| self.batters[i])
+        self.remaining.pop_front()
     }
 }
 
-/// Iterates through available bowlers
-// TODO: Incorporate various strategies
+/// Iterates through available bowlers. This is the rotation used when the fielding side
+/// has no `BowlingStrategy`, or its strategy leaves the choice open.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bowlers {
     pub bowlers: Vec<PlayerId>,
-    /// The previous bowler so that we don't repeat
-    last: PlayerId,
+    /// The rest of the side, in the order they are turned to once no bowler may bowl
+    #[serde(default)]
+    pub part_timers: Vec<PlayerId>,
+    /// The previous bowler so that we don't repeat, if anyone has bowled
+    last: Option<PlayerId>,
 }
 
+/// The fatigue above which a bowler is taken off if anyone fresher is available
+pub const FATIGUE_LIMIT: f32 = 0.5;
+
 impl Bowlers {
-    // TODO: methods to adjust strategy (?)
+    /// The next bowler in the rotation who is `eligible`, unless they are too tired, in
+    /// which case the least tired of the other eligible bowlers is brought on. If no
+    /// bowler is eligible, e.g. because they have all used up their overs, the same goes
+    /// for the part-timers. Returns None if nobody at all is eligible, so an over limit is
+    /// never exceeded. The bowler of the last over is never chosen.
+    pub fn next_rested(
+        &mut self,
+        fatigue: impl Fn(PlayerId) -> f32,
+        eligible: impl Fn(PlayerId) -> bool,
+    ) -> Option<PlayerId> {
+        let last = self.last;
+        let available = |bowlers: &[PlayerId]| -> Vec<PlayerId> {
+            bowlers
+                .iter()
+                .copied()
+                .filter(|&b| Some(b) != last && eligible(b))
+                .collect()
+        };
+        let mut options = available(&self.bowlers);
+        if options.is_empty() {
+            options = available(&self.part_timers);
+        }
+        let planned = *options.first()?;
+        let bowler = if fatigue(planned) <= FATIGUE_LIMIT {
+            planned
+        } else {
+            options
+                .into_iter()
+                .min_by(|&a, &b| fatigue(a).total_cmp(&fatigue(b)))?
+        };
+        self.last = Some(bowler);
+        Some(bowler)
+    }
+
+    /// Record that a bowler was brought on other than by the rotation, so that they
+    /// aren't given the next over as well
+    pub fn brought_on(&mut self, bowler: PlayerId) {
+        self.last = Some(bowler);
+    }
+
+    /// Put a substitute in the place of a bowler in the rotation
+    pub fn replace(&mut self, id: PlayerId, replacement: PlayerId) {
+        for bowler in self
+            .bowlers
+            .iter_mut()
+            .chain(&mut self.part_timers)
+            .filter(|b| **b == id)
+        {
+            *bowler = replacement;
+        }
+        if self.last == Some(id) {
+            self.last = Some(replacement);
+        }
+    }
 }
 
 impl Iterator for Bowlers {
@@ -115,10 +505,124 @@ impl Iterator for Bowlers {
         let bowler: PlayerId = self
             .bowlers
             .iter()
-            .find(|&&b| self.last != b)
-            .cloned()
-            .unwrap();
-        self.last = bowler;
+            .find(|&&b| self.last != Some(b))
+            .cloned()?;
+        self.last = Some(bowler);
         Some(bowler)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::PlayerRatingNull;
+
+    #[test]
+    fn bowling_rotation() {
+        let ids = |ids: &[u32]| ids.iter().map(|&i| PlayerId(i)).collect::<Vec<_>>();
+        let mut bowlers = Bowlers {
+            bowlers: ids(&[5, 6, 7]),
+            part_timers: ids(&[2, 1]),
+            last: None,
+        };
+        let rotation: Vec<PlayerId> = bowlers.clone().take(3).collect();
+        assert_eq!(rotation, ids(&[5, 6, 5]));
+        // With nobody having bowled, the first bowler can open even if they're the only one
+        let mut alone = Bowlers {
+            bowlers: ids(&[5]),
+            part_timers: Vec::new(),
+            last: None,
+        };
+        assert_eq!(alone.next(), Some(PlayerId(5)));
+        assert_eq!(alone.next(), None);
+        // A tired opener gives way to the freshest other bowler
+        let tired = |id: PlayerId| if id == PlayerId(5) { 1. } else { 0. };
+        assert_eq!(bowlers.next_rested(tired, |_| true), Some(PlayerId(6)));
+        assert_eq!(bowlers.next_rested(tired, |_| true), Some(PlayerId(7)));
+        // Part-timers come on only once no bowler is eligible, and nobody goes over
+        let fresh = |_| 0.;
+        let part_timer = |id: PlayerId| id.0 < 5;
+        assert_eq!(bowlers.next_rested(fresh, part_timer), Some(PlayerId(2)));
+        assert_eq!(bowlers.next_rested(fresh, part_timer), Some(PlayerId(1)));
+        assert_eq!(bowlers.next_rested(fresh, |id| id == PlayerId(1)), None);
+    }
+
+    #[test]
+    fn roles() {
+        use Role::*;
+        let roles = [
+            PaceBowler,
+            Opener,
+            SpinBowler,
+            WicketKeeper,
+            Opener,
+            TopOrder,
+            AllRounder,
+            PaceBowler,
+            MiddleOrder,
+            PaceBowler,
+            TopOrder,
+        ];
+        let players = roles
+            .iter()
+            .enumerate()
+            .map(|(i, &role)| (PlayerId(i as u32), format!("player_{}", i), role))
+            .collect();
+        let team = Team::with_roles(0, "team".to_string(), players);
+        let mut db = PlayerDb::new();
+        for i in 0..11 {
+            db.add(format!("player_{}", i), PlayerRatingNull::default())
+                .unwrap();
+        }
+        assert_eq!(team.keeper, Some(PlayerId(3)));
+        let ids = |ids: &[u32]| ids.iter().map(|&i| PlayerId(i)).collect::<Vec<_>>();
+        let order: Vec<PlayerId> = team.batting_order().collect();
+        assert_eq!(order, ids(&[1, 4, 5, 10, 8, 3, 6, 0, 2, 7, 9]));
+        let mut batting_order = team.batting_order();
+        batting_order.next();
+        batting_order.reorder(&ids(&[9, 3])).unwrap();
+        assert_eq!(
+            batting_order.remaining().take(3).collect::<Vec<_>>(),
+            ids(&[9, 3, 4])
+        );
+        assert!(batting_order.reorder(&ids(&[1])).is_err());
+        assert!(batting_order.reorder(&ids(&[7, 7])).is_err());
+        let bowlers = team.bowlers().bowlers;
+        assert_eq!(bowlers, ids(&[9, 7, 0, 2, 6]));
+        assert!(team.validate(&db).is_ok());
+
+        let mut no_keeper = team.clone();
+        no_keeper.keeper = None;
+        assert!(no_keeper.validate(&db).is_err());
+        let captain = team.clone().with_captain(PlayerId(3));
+        assert!(captain.validate(&db).is_ok());
+        assert_eq!(
+            captain.scorecard_name(PlayerId(3)).unwrap(),
+            "player_3 (c)†"
+        );
+        assert_eq!(captain.scorecard_name(PlayerId(1)).unwrap(), "player_1");
+        assert!(team
+            .clone()
+            .with_captain(PlayerId(11))
+            .validate(&db)
+            .is_err());
+        let mut batting_heavy = team;
+        batting_heavy.roles.insert(PlayerId(6), MiddleOrder);
+        assert!(matches!(
+            batting_heavy.validate(&db),
+            Err(Error::TooFewBowlers(_, 4))
+        ));
+        let mut twelve = batting_heavy.clone();
+        twelve.players.push((PlayerId(11), "player_11".to_string()));
+        assert!(matches!(
+            twelve.validate_lineup(PLAYERS),
+            Err(Error::TeamSize { players: 12, .. })
+        ));
+        let mut duplicate = batting_heavy;
+        duplicate.players[10] = duplicate.players[0].clone();
+        assert!(matches!(
+            duplicate.validate_lineup(PLAYERS),
+            Err(Error::DuplicateInTeam(_, PlayerId(0)))
+        ));
+    }
+}
@@ -0,0 +1,708 @@
+//! Competitions between several teams
+use crate::{
+    career::Leaderboards,
+    conditions::{Ground, HomeAdvantage, Venue},
+    config::TournamentConfig,
+    error::{Error, Result},
+    form::Form,
+    game::{GameState, MatchResult},
+    injury::{simulate_with_injuries, Availability, InjuryConfig},
+    model::{Model, PlayerRating},
+    player::PlayerDb,
+    seed,
+    strategy::{SelectionContext, SelectionStrategy},
+    team::{Squad, Team},
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, sync::Arc};
+
+pub mod cup;
+pub mod nrr;
+pub use cup::Cup;
+
+/// Points awarded for a win
+pub const POINTS_WIN: u16 = 2;
+/// Points awarded to each side for a tie, draw, or no result
+pub const POINTS_SHARED: u16 = 1;
+
+/// The points awarded for each result in a tournament. Any left out when deserializing
+/// take their usual values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Points {
+    pub win: u16,
+    /// Points to each side for a tie, draw, or no result
+    pub shared: u16,
+}
+
+impl Default for Points {
+    fn default() -> Self {
+        Self {
+            win: POINTS_WIN,
+            shared: POINTS_SHARED,
+        }
+    }
+}
+
+/// A match between two teams, identified by their index in the tournament. The home side
+/// bats first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub home: usize,
+    pub away: usize,
+    /// Whether neither side is at home, e.g. at a tournament hosted elsewhere
+    #[serde(default)]
+    pub neutral: bool,
+}
+
+impl Fixture {
+    /// Where the team with the given index is playing in this fixture
+    pub fn ground(&self, team: usize) -> Ground {
+        if self.neutral {
+            Ground::Neutral
+        } else if team == self.home {
+            Ground::Home
+        } else {
+            Ground::Away
+        }
+    }
+}
+
+/// Every pairing of `n_teams` teams, each playing the others once. Home and away are
+/// alternated so that each team bats first in about half of its matches.
+pub fn round_robin(n_teams: usize) -> Vec<Fixture> {
+    let mut fixtures = Vec::new();
+    for i in 0..n_teams {
+        for j in i + 1..n_teams {
+            let (home, away) = if (i + j) % 2 == 0 { (i, j) } else { (j, i) };
+            fixtures.push(Fixture {
+                home,
+                away,
+                neutral: false,
+            });
+        }
+    }
+    fixtures
+}
+
+/// A team's record in a tournament
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Standing {
+    pub team: u16,
+    pub name: String,
+    pub played: u32,
+    pub won: u32,
+    pub lost: u32,
+    /// Ties, draws, and matches without a result
+    pub shared: u32,
+    pub points: u32,
+    /// Runs scored by this team
+    pub runs_for: u32,
+    /// Legal balls faced by this team, counting the full quota if bowled out
+    pub balls_faced: u32,
+    /// Runs conceded by this team
+    pub runs_against: u32,
+    /// Legal balls bowled by this team, counting the full quota if the opposition was
+    /// bowled out
+    pub balls_bowled: u32,
+}
+
+impl Standing {
+    fn new(team: &Team) -> Self {
+        Self {
+            team: team.id,
+            name: team.name.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Net run rate: runs scored per over faced, less runs conceded per over bowled
+    pub fn net_run_rate(&self, balls_per_over: u8) -> f32 {
+        nrr::run_rate(self.runs_for, self.balls_faced, balls_per_over)
+            - nrr::run_rate(self.runs_against, self.balls_bowled, balls_per_over)
+    }
+}
+
+/// The standings of each team in a tournament
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointsTable {
+    standings: Vec<Standing>,
+    balls_per_over: u8,
+    #[serde(default)]
+    points: Points,
+}
+
+impl PointsTable {
+    pub fn new(teams: &[Arc<Team>], balls_per_over: u8) -> Self {
+        Self {
+            standings: teams.iter().map(|t| Standing::new(t)).collect(),
+            balls_per_over,
+            points: Points::default(),
+        }
+    }
+
+    /// The points awarded for each result
+    pub fn points(&self) -> Points {
+        self.points
+    }
+
+    /// The standing of the team with the given ID
+    pub fn get(&self, team: u16) -> Option<&Standing> {
+        self.standings.iter().find(|st| st.team == team)
+    }
+
+    fn get_mut(&mut self, team: u16) -> Result<&mut Standing> {
+        self.standings
+            .iter_mut()
+            .find(|st| st.team == team)
+            .ok_or_else(|| Error::MissingData(format!("Team {} is not in the table", team)))
+    }
+
+    /// Add the result and run rates of a completed match. See [`nrr::innings_run_rates`]
+    /// for how each innings counts towards net run rate.
+    pub fn add_match(&mut self, game: &GameState) -> Result<()> {
+        let result = game.result().ok_or(Error::MatchInProgress)?;
+        for innings in nrr::innings_run_rates(game)? {
+            let batting = self.get_mut(innings.batting_team)?;
+            batting.runs_for += u32::from(innings.runs);
+            batting.balls_faced += u32::from(innings.balls);
+            let bowling = self.get_mut(innings.bowling_team)?;
+            bowling.runs_against += u32::from(innings.runs);
+            bowling.balls_bowled += u32::from(innings.balls);
+        }
+        let points = self.points;
+        let (team_a, team_b) = game.teams();
+        for id in [team_a.id, team_b.id] {
+            let standing = self.get_mut(id)?;
+            standing.played += 1;
+            match result {
+                MatchResult::Win { winner, .. } if winner == id => {
+                    standing.won += 1;
+                    standing.points += u32::from(points.win);
+                }
+                MatchResult::Win { .. } => standing.lost += 1,
+                MatchResult::Tie | MatchResult::Draw | MatchResult::NoResult => {
+                    standing.shared += 1;
+                    standing.points += u32::from(points.shared);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The standings ordered by points, then by net run rate
+    pub fn standings(&self) -> Vec<&Standing> {
+        let mut standings: Vec<&Standing> = self.standings.iter().collect();
+        let bpo = self.balls_per_over;
+        standings.sort_by(|a, b| {
+            b.points.cmp(&a.points).then(
+                b.net_run_rate(bpo)
+                    .partial_cmp(&a.net_run_rate(bpo))
+                    .unwrap_or(Ordering::Equal),
+            )
+        });
+        standings
+    }
+
+    /// Write the table of standings
+    #[cfg(feature = "std-display")]
+    pub fn write_table<W: std::io::Write + ?Sized>(&self, w: &mut W) -> Result<()> {
+        use prettytable::{format::consts::*, Table};
+        let mut table = Table::new();
+        table.set_format(*FORMAT_NO_LINESEP_WITH_TITLE);
+        table.set_titles(row!["Team", "P", "W", "L", "T/D", "Pts", "NRR"]);
+        for st in self.standings() {
+            table.add_row(row![
+                st.name,
+                st.played,
+                st.won,
+                st.lost,
+                st.shared,
+                st.points,
+                format!("{:+.3}", st.net_run_rate(self.balls_per_over)),
+            ]);
+        }
+        table.print(w)?;
+        Ok(())
+    }
+}
+
+/// An all-play-all tournament
+pub struct Tournament {
+    form: Form,
+    teams: Vec<Arc<Team>>,
+    fixtures: Vec<Fixture>,
+    /// The result of each fixture played so far, in order
+    results: Vec<MatchResult>,
+    table: PointsTable,
+    /// The best performers in the matches played so far
+    leaderboards: Leaderboards,
+    /// The sides' squads and how their XIs are picked, if they are picked before each
+    /// match rather than fixed
+    squads: Option<(Vec<Squad>, Arc<dyn SelectionStrategy>)>,
+    /// The conditions the matches are played in
+    conditions: SelectionContext,
+    /// The injury risk, if injuries are simulated
+    injuries: Option<InjuryConfig>,
+    /// The fitness of every player in the tournament
+    availability: Availability,
+    /// The ground every match is played at, if the tournament has a single host.
+    /// Otherwise each match is played at the home ground of the home side.
+    host: Option<Venue>,
+    /// How much playing at home helps a side
+    home_advantage: HomeAdvantage,
+}
+
+impl Tournament {
+    /// Schedule a round robin among the given teams, each match played with the given form.
+    /// Matches are played at the home side's ground, and are neutral if it has none.
+    pub fn round_robin(form: Form, teams: Vec<Arc<Team>>) -> Self {
+        let mut fixtures = round_robin(teams.len());
+        for fixture in &mut fixtures {
+            fixture.neutral = teams[fixture.home].home.is_none();
+        }
+        let table = PointsTable::new(&teams, form.balls_per_over);
+        let leaderboards = Leaderboards::new(form.balls_per_over);
+        Self {
+            form,
+            teams,
+            fixtures,
+            results: Vec::new(),
+            table,
+            leaderboards,
+            squads: None,
+            conditions: SelectionContext::default(),
+            injuries: None,
+            availability: Availability::new(),
+            host: None,
+            home_advantage: HomeAdvantage::default(),
+        }
+    }
+
+    /// Play every match at the given ground. A side from the host country is the home side
+    /// in each of its matches, and the other matches are neutral.
+    pub fn hosted_at(mut self, venue: Venue) -> Self {
+        for fixture in &mut self.fixtures {
+            let home = venue.is_home_of(&self.teams[fixture.home]);
+            let away = venue.is_home_of(&self.teams[fixture.away]);
+            if away && !home {
+                std::mem::swap(&mut fixture.home, &mut fixture.away);
+            }
+            fixture.neutral = home == away;
+        }
+        self.host = Some(venue);
+        self
+    }
+
+    /// Set how much playing at home helps a side
+    pub fn with_home_advantage(mut self, advantage: HomeAdvantage) -> Self {
+        self.home_advantage = advantage;
+        self
+    }
+
+    /// Award the given points for each result, rather than the usual
+    pub fn with_points(mut self, points: Points) -> Self {
+        self.table.points = points;
+        self
+    }
+
+    /// Schedule a round robin as described by a configuration, e.g. one loaded from a file
+    pub fn from_config(config: &TournamentConfig, teams: Vec<Arc<Team>>) -> Result<Self> {
+        let mut tournament = Self::round_robin(config.form.resolve()?, teams)
+            .with_points(config.points)
+            .with_home_advantage(config.home_advantage.clone());
+        if let Some(venue) = &config.host {
+            tournament = tournament.hosted_at(venue.clone());
+        }
+        Ok(tournament)
+    }
+
+    /// Pick each side's XI from its squad before every match, rather than always playing
+    /// the same team. Squads are matched to the teams by ID, and a team without a squad
+    /// keeps its XI.
+    pub fn with_squads(
+        mut self,
+        squads: Vec<Squad>,
+        strategy: impl SelectionStrategy + 'static,
+    ) -> Self {
+        self.squads = Some((squads, Arc::new(strategy)));
+        self
+    }
+
+    /// Play the matches in the given conditions, which the selectors take into account
+    pub fn with_conditions(mut self, conditions: SelectionContext) -> Self {
+        self.conditions = conditions;
+        self
+    }
+
+    /// Simulate injuries in the remaining fixtures. An injured player is replaced by a
+    /// substitute from their squad, and can't be picked until they are fit again. Every
+    /// side needs a squad to pick from, so playing a match between sides without squads
+    /// is an error.
+    pub fn with_injuries(mut self, config: InjuryConfig) -> Self {
+        self.injuries = Some(config);
+        self
+    }
+
+    /// The fitness of the players, which only changes if injuries are simulated
+    pub fn availability(&self) -> &Availability {
+        &self.availability
+    }
+
+    pub fn teams(&self) -> &[Arc<Team>] {
+        &self.teams
+    }
+
+    pub fn fixtures(&self) -> &[Fixture] {
+        &self.fixtures
+    }
+
+    /// The results of the fixtures played so far, in the order of the fixtures
+    pub fn results(&self) -> &[MatchResult] {
+        &self.results
+    }
+
+    pub fn table(&self) -> &PointsTable {
+        &self.table
+    }
+
+    pub fn leaderboards(&self) -> &Leaderboards {
+        &self.leaderboards
+    }
+
+    /// Write the table of standings followed by the leaderboards
+    #[cfg(feature = "std-display")]
+    pub fn write_summary<W: std::io::Write + ?Sized>(&self, w: &mut W) -> Result<()> {
+        self.table.write_table(w)?;
+        self.leaderboards.write_summary(w)
+    }
+
+    /// The winner of the match between the two teams with the given IDs, if they have met
+    /// and it was not shared
+    pub fn head_to_head(&self, team_a: u16, team_b: u16) -> Option<u16> {
+        self.fixtures
+            .iter()
+            .zip(&self.results)
+            .filter(|(fixture, _)| {
+                let ids = [self.teams[fixture.home].id, self.teams[fixture.away].id];
+                ids.contains(&team_a) && ids.contains(&team_b)
+            })
+            .find_map(|(_, result)| match result {
+                MatchResult::Win { winner, .. } => Some(*winner),
+                _ => None,
+            })
+    }
+
+    /// The standings ordered by points, then net run rate. Two teams tied on both are
+    /// ordered by the result of the match between them; three or more are left in table
+    /// order, since their results can go round in a circle.
+    pub fn standings(&self) -> Vec<&Standing> {
+        let mut standings = self.table.standings();
+        let bpo = self.form.balls_per_over;
+        let level = |a: &Standing, b: &Standing| {
+            a.points == b.points && a.net_run_rate(bpo) == b.net_run_rate(bpo)
+        };
+        let mut i = 0;
+        while i < standings.len() {
+            let tied = standings[i..]
+                .iter()
+                .take_while(|st| level(st, standings[i]))
+                .count()
+                .max(1);
+            if tied == 2 {
+                let (first, second) = (standings[i].team, standings[i + 1].team);
+                if self.head_to_head(first, second) == Some(second) {
+                    standings.swap(i, i + 1);
+                }
+            }
+            i += tied;
+        }
+        standings
+    }
+
+    /// Whether every fixture has been played
+    pub fn complete(&self) -> bool {
+        self.results.len() == self.fixtures.len()
+    }
+
+    /// Simulate every remaining fixture
+    pub fn play<R: PlayerRating>(
+        &mut self,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        while !self.complete() {
+            self.play_next(model, db, rng)?;
+        }
+        Ok(())
+    }
+
+    /// Simulate every remaining fixture reproducibly, each with a generator derived from
+    /// `seed` and the index of the fixture
+    pub fn play_seeded<R: PlayerRating>(
+        &mut self,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        seed: u64,
+    ) -> Result<()> {
+        while !self.complete() {
+            let index = self.results.len() as u64;
+            self.play_next(model, db, &mut seed::match_rng(seed, index))?;
+        }
+        Ok(())
+    }
+
+    fn play_next<R: PlayerRating>(
+        &mut self,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        let fixture = self.fixtures[self.results.len()];
+        let home = self.pick_side(fixture.home)?;
+        let away = self.pick_side(fixture.away)?;
+        let venue = self
+            .host
+            .clone()
+            .or_else(|| self.teams[fixture.home].home.clone());
+        let mut game = GameState::new(self.form.clone(), home, away)?;
+        if let Some(venue) = venue {
+            game.set_venue(venue, self.home_advantage.clone());
+        }
+        game.set_conditions(
+            self.conditions.pitch.clone(),
+            self.conditions.weather.clone(),
+        );
+        let result = match &self.injuries {
+            Some(config) => {
+                let squads = self.squads.as_ref().map_or(&[][..], |(s, _)| s.as_slice());
+                let availability = &mut self.availability;
+                simulate_with_injuries(&mut game, model, db, rng, config, availability, squads)?.0
+            }
+            None => game.simulate_to_completion(model, db, rng)?,
+        };
+        self.table.add_match(&game)?;
+        self.leaderboards.add_match(&game)?;
+        self.results.push(result);
+        Ok(())
+    }
+
+    /// The XI a team fields in its next match
+    fn pick_side(&mut self, index: usize) -> Result<Arc<Team>> {
+        let team = &self.teams[index];
+        let squad = self.squads.as_ref().and_then(|(squads, strategy)| {
+            Some((squads.iter().find(|s| s.id == team.id)?, strategy))
+        });
+        match squad {
+            Some((squad, strategy)) => {
+                let xi = self
+                    .availability
+                    .select_xi(squad, strategy.as_ref(), &self.conditions)?;
+                Ok(Arc::new(xi))
+            }
+            None if self.injuries.is_some() => Err(Error::InvalidTeam(
+                team.name.clone(),
+                "a squad is needed to leave out injured players".into(),
+            )),
+            None => Ok(team.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        form,
+        game::Margin,
+        model::{self, NullModel},
+        player::{PlayerDb, PlayerId},
+        seed,
+        tests::test_team,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn injuries_need_squads() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let teams = vec![
+            Arc::new(test_team(&mut db, 1, "AUS")?),
+            Arc::new(test_team(&mut db, 2, "IND")?),
+        ];
+        let mut tournament =
+            Tournament::round_robin(Form::t20(), teams).with_injuries(InjuryConfig::default());
+        let played = tournament.play(&NullModel {}, &db, &mut seed::rng(1));
+        assert!(matches!(played, Err(Error::InvalidTeam(..))));
+        assert!(tournament.results().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn head_to_head_between_two() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let teams = (1..=3)
+            .map(|id| Ok(Arc::new(test_team(&mut db, id, &format!("T{}", id))?)))
+            .collect::<Result<Vec<_>>>()?;
+        let mut tournament = Tournament::round_robin(Form::t20(), teams);
+        // Each side beat one of the others: 1 beat 2, 2 beat 3 and 3 beat 1
+        let beats = [(1, 2), (2, 3), (3, 1)];
+        tournament.results = tournament
+            .fixtures
+            .iter()
+            .map(|fixture| {
+                let ids = [fixture.home as u16 + 1, fixture.away as u16 + 1];
+                let (winner, _) = beats
+                    .iter()
+                    .find(|(w, l)| ids.contains(w) && ids.contains(l))
+                    .unwrap();
+                MatchResult::Win {
+                    winner: *winner,
+                    margin: Margin::Runs(1),
+                }
+            })
+            .collect();
+        let order = |tournament: &Tournament| -> Vec<u16> {
+            tournament.standings().iter().map(|st| st.team).collect()
+        };
+        for st in &mut tournament.table.standings {
+            st.points = 2;
+        }
+        // Three sides level are left in table order
+        assert_eq!(order(&tournament), [1, 2, 3]);
+        // Two level are split by the match between them
+        tournament.table.standings[1].points = 0;
+        assert_eq!(order(&tournament), [3, 1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn points_defaults() -> Result<()> {
+        let points: Points = serde_json::from_str(r#"{"win": 4}"#)?;
+        assert_eq!((points.win, points.shared), (4, POINTS_SHARED));
+        let points: Points = serde_json::from_str("{}")?;
+        assert_eq!(points, Points::default());
+        Ok(())
+    }
+
+    #[test]
+    fn long_running_totals() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let teams = vec![
+            Arc::new(test_team(&mut db, 1, "AUS")?),
+            Arc::new(test_team(&mut db, 2, "IND")?),
+        ];
+        let mut table = PointsTable::new(&teams, 6);
+        // Totals that a long league would build up, past what 16 bits can hold
+        for st in &mut table.standings {
+            st.points = u16::MAX as u32;
+            st.runs_for = u16::MAX as u32;
+            st.balls_faced = u16::MAX as u32;
+        }
+        let mut state = GameState::new(Form::t20(), (*teams[0]).clone(), (*teams[1]).clone())?;
+        state.simulate_to_completion(&NullModel {}, &db, &mut seed::rng(2))?;
+        table.add_match(&state)?;
+        let standing = table.get(1).unwrap();
+        assert!(standing.runs_for > u16::MAX as u32);
+        assert!(standing.balls_faced > u16::MAX as u32);
+        let points: u32 = table.standings.iter().map(|st| st.points).sum();
+        assert_eq!(points, 2 * u16::MAX as u32 + u32::from(POINTS_WIN));
+        Ok(())
+    }
+
+    #[test]
+    fn injured_players_replaced_from_squads() -> Result<()> {
+        use crate::{player::Role, strategy::BalancedSelection};
+        let mut db = PlayerDb::new();
+        let mut teams = Vec::new();
+        let mut squads = Vec::new();
+        for (id, label) in [(1, "AUS"), (2, "IND"), (3, "SA")] {
+            use Role::*;
+            let roles = [
+                Opener,
+                Opener,
+                TopOrder,
+                TopOrder,
+                WicketKeeper,
+                MiddleOrder,
+                AllRounder,
+                PaceBowler,
+                PaceBowler,
+                PaceBowler,
+                SpinBowler,
+            ];
+            let team = test_team(&mut db, id, label)?;
+            let mut players: Vec<_> = team
+                .players
+                .iter()
+                .zip(roles)
+                .map(|((id, name), role)| (*id, name.clone(), role))
+                .collect();
+            for (i, &role) in [PaceBowler, MiddleOrder, Opener].iter().enumerate() {
+                let player = db.add(format!("{}_reserve_{}", label, i), Default::default())?;
+                players.push((player.id, player.name.clone(), role));
+            }
+            squads.push(Squad::new(id, team.name.clone(), players.clone()));
+            teams.push(Arc::new(Team::with_roles(
+                id,
+                team.name,
+                players[..11].to_vec(),
+            )));
+        }
+        let config = InjuryConfig {
+            batting_risk: 0.01,
+            mean_matches_out: 5.,
+            ..Default::default()
+        };
+        let mut tournament = Tournament::round_robin(Form::t20(), teams)
+            .with_squads(squads.clone(), BalancedSelection::default())
+            .with_injuries(config);
+        tournament.play(&NullModel {}, &db, &mut seed::rng(4))?;
+        let stats = tournament.leaderboards().stats();
+        let played = |id| stats.get(id).map_or(0, |record| record.matches);
+        let reserves: Vec<PlayerId> = squads
+            .iter()
+            .flat_map(|squad| squad.players[11..].iter().map(|(id, _, _)| *id))
+            .collect();
+        // Reserves only play when someone is injured
+        assert!(reserves.iter().any(|&id| played(id) > 0));
+        for squad in &squads {
+            for (id, _, _) in &squad.players {
+                assert!(played(*id) <= 2);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn round_robin_tournament() -> Result<()> {
+        use model::NullModel;
+        use std::sync::Arc;
+        let mut db = PlayerDb::new();
+        let teams = ["AFG", "IRE", "SCO", "NED"]
+            .iter()
+            .enumerate()
+            .map(|(i, label)| Ok(Arc::new(test_team(&mut db, i as u16, label)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let mut tournament = Tournament::round_robin(form::Form::t20(), teams);
+        tournament.play(&NullModel {}, &db, &mut thread_rng())?;
+        assert_eq!(tournament.results().len(), 6);
+        let standings = tournament.table().standings();
+        assert!(standings.iter().all(|st| st.played == 3));
+        let points: u32 = standings.iter().map(|st| st.points).sum();
+        assert_eq!(points, 6 * u32::from(POINTS_WIN));
+        assert!(standings[0].points >= standings[3].points);
+        let leaderboards = tournament.leaderboards();
+        let runs = leaderboards.most_runs(3);
+        assert_eq!(runs.len(), 3);
+        assert!(runs[0].value >= runs[1].value && runs[1].value >= runs[2].value);
+        let top = leaderboards.stats().get(runs[0].player).unwrap();
+        assert_eq!(top.batting.runs, runs[0].value);
+        let economy = leaderboards.economy(2, 24);
+        assert!(economy.len() < 2 || economy[0].value <= economy[1].value);
+        #[cfg(feature = "std-display")]
+        tournament.write_summary(&mut std::io::sink())?;
+        Ok(())
+    }
+}
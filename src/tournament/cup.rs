@@ -0,0 +1,312 @@
+//! Group stages feeding a knockout bracket, in the style of a World Cup
+use super::Tournament;
+use crate::{
+    career::Leaderboards,
+    conditions::{HomeAdvantage, Venue},
+    error::{Error, Result},
+    form::Form,
+    game::{GameState, MatchResult},
+    model::{Model, PlayerRating},
+    player::PlayerDb,
+    seed,
+    team::Team,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// The number of teams from each group that qualify for the knockout stage
+pub const QUALIFIERS_PER_GROUP: usize = 2;
+
+/// A match in the knockout stage. The teams are filled in by team ID once they are known,
+/// so that the whole bracket can be shown before it is played.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KnockoutMatch {
+    /// The side that bats first: a group winner in the first round, and after that the
+    /// winner of the earlier of the two matches feeding this one
+    pub home: Option<u16>,
+    pub away: Option<u16>,
+    pub result: Option<MatchResult>,
+    /// The side that advanced. If the match is tied, the format's tie-breaker decides;
+    /// otherwise, or if drawn, the home side advances, since there is no super over.
+    pub winner: Option<u16>,
+}
+
+/// A competition of round-robin groups, the top sides of which play a knockout
+pub struct Cup {
+    form: Form,
+    groups: Vec<Tournament>,
+    /// The knockout stage by round, ending with the final. Each match in a round is fed by
+    /// two consecutive matches of the round before.
+    rounds: Vec<Vec<KnockoutMatch>>,
+    /// The ground every match is played at, if known
+    host: Option<Venue>,
+    /// How much playing at home helps a side
+    home_advantage: HomeAdvantage,
+    /// The best performers in the knockout matches played so far
+    knockout_leaderboards: Leaderboards,
+}
+
+impl Cup {
+    /// Set up the groups and an empty bracket. The number of qualifiers must be a power
+    /// of two, e.g. two groups feeding semifinals, and each group must have at least as
+    /// many sides as qualify from it.
+    pub fn new(form: Form, groups: Vec<Vec<Arc<Team>>>) -> Result<Self> {
+        if let Some(group) = groups.iter().find(|g| g.len() < QUALIFIERS_PER_GROUP) {
+            return Err(Error::Unsupported(format!(
+                "a group of {} teams, fewer than the {} that qualify",
+                group.len(),
+                QUALIFIERS_PER_GROUP
+            )));
+        }
+        let qualifiers = groups.len() * QUALIFIERS_PER_GROUP;
+        if !qualifiers.is_power_of_two() || qualifiers < 2 {
+            return Err(Error::Unsupported(format!(
+                "a knockout of {} qualifiers",
+                qualifiers
+            )));
+        }
+        let mut rounds = Vec::new();
+        let mut n_matches = qualifiers / 2;
+        while n_matches >= 1 {
+            rounds.push(vec![KnockoutMatch::default(); n_matches]);
+            n_matches /= 2;
+        }
+        let groups = groups
+            .into_iter()
+            .map(|teams| Tournament::round_robin(form.clone(), teams))
+            .collect();
+        let knockout_leaderboards = Leaderboards::new(form.balls_per_over);
+        Ok(Self {
+            form,
+            groups,
+            rounds,
+            host: None,
+            home_advantage: HomeAdvantage::default(),
+            knockout_leaderboards,
+        })
+    }
+
+    /// Play every match at the given ground. A side from the host country is the home side
+    /// in each of its group matches, and the other matches are neutral.
+    pub fn hosted_at(mut self, venue: Venue) -> Self {
+        self.groups = self
+            .groups
+            .into_iter()
+            .map(|group| group.hosted_at(venue.clone()))
+            .collect();
+        self.host = Some(venue);
+        self
+    }
+
+    /// Set how much playing at home helps a side
+    pub fn with_home_advantage(mut self, advantage: HomeAdvantage) -> Self {
+        self.groups = self
+            .groups
+            .into_iter()
+            .map(|group| group.with_home_advantage(advantage.clone()))
+            .collect();
+        self.home_advantage = advantage;
+        self
+    }
+
+    pub fn groups(&self) -> &[Tournament] {
+        &self.groups
+    }
+
+    /// The knockout bracket, ending with the final
+    pub fn rounds(&self) -> &[Vec<KnockoutMatch>] {
+        &self.rounds
+    }
+
+    /// The best performers in every match played so far, in the groups and the knockout
+    pub fn leaderboards(&self) -> Leaderboards {
+        let mut leaderboards = Leaderboards::new(self.form.balls_per_over);
+        for group in &self.groups {
+            leaderboards.merge(group.leaderboards());
+        }
+        leaderboards.merge(&self.knockout_leaderboards);
+        leaderboards
+    }
+
+    /// Write the table of each group followed by the leaderboards of the whole cup
+    #[cfg(feature = "std-display")]
+    pub fn write_summary<W: std::io::Write + ?Sized>(&self, w: &mut W) -> Result<()> {
+        for group in &self.groups {
+            group.table().write_table(w)?;
+        }
+        self.leaderboards().write_summary(w)
+    }
+
+    /// The team that won the final, once it has been played
+    pub fn champion(&self) -> Option<&Arc<Team>> {
+        let winner = self.rounds.last()?.first()?.winner?;
+        self.team(winner)
+    }
+
+    fn team(&self, id: u16) -> Option<&Arc<Team>> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.teams())
+            .find(|team| team.id == id)
+    }
+
+    /// Simulate the group stage and then the knockout
+    pub fn play<R: PlayerRating>(
+        &mut self,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        for group in &mut self.groups {
+            group.play(model, db, rng)?;
+        }
+        self.play_knockout(model, db, rng)
+    }
+
+    /// Simulate the competition reproducibly. Each group is seeded from `seed` and its
+    /// index, and the knockout stage from `seed` and the number of groups.
+    pub fn play_seeded<R: PlayerRating>(
+        &mut self,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        seed: u64,
+    ) -> Result<()> {
+        for (i, group) in self.groups.iter_mut().enumerate() {
+            group.play_seeded(model, db, seed::match_seed(seed, i as u64))?;
+        }
+        let knockout_index = self.groups.len() as u64;
+        self.play_knockout(model, db, &mut seed::match_rng(seed, knockout_index))
+    }
+
+    fn play_knockout<R: PlayerRating>(
+        &mut self,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        self.fill_first_round();
+        for round in 0..self.rounds.len() {
+            for i in 0..self.rounds[round].len() {
+                let winner = self.play_knockout_match(round, i, model, db, rng)?;
+                if let Some(next) = self.rounds.get_mut(round + 1) {
+                    let slot = &mut next[i / 2];
+                    if i % 2 == 0 {
+                        slot.home = Some(winner);
+                    } else {
+                        slot.away = Some(winner);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill the first knockout round from the group standings. The winner of each group
+    /// plays a runner-up from another group, so sides from the same group can only meet
+    /// again in the final.
+    fn fill_first_round(&mut self) {
+        let n_groups = self.groups.len();
+        let places: Vec<Vec<u16>> = self
+            .groups
+            .iter()
+            .map(|group| group.standings().iter().map(|st| st.team).collect())
+            .collect();
+        for (i, knockout_match) in self.rounds[0].iter_mut().enumerate() {
+            knockout_match.home = places[i].first().copied();
+            knockout_match.away = places[n_groups - 1 - i].get(1).copied();
+        }
+    }
+
+    fn play_knockout_match<R: PlayerRating>(
+        &mut self,
+        round: usize,
+        index: usize,
+        model: &(impl Model<R> + ?Sized),
+        db: &PlayerDb<R>,
+        rng: &mut impl Rng,
+    ) -> Result<u16> {
+        let knockout_match = &self.rounds[round][index];
+        let missing = || Error::MissingData("Knockout match has no opponent".into());
+        let home = knockout_match.home.ok_or_else(missing)?;
+        let away = knockout_match.away.ok_or_else(missing)?;
+        let mut game = GameState::new(
+            self.form.clone(),
+            self.team(home).ok_or_else(missing)?.clone(),
+            self.team(away).ok_or_else(missing)?.clone(),
+        )?;
+        if let Some(venue) = &self.host {
+            game.set_venue(venue.clone(), self.home_advantage.clone());
+        }
+        let result = game.simulate_to_completion(model, db, rng)?;
+        self.knockout_leaderboards.add_match(&game)?;
+        let winner = match result {
+            MatchResult::Win { winner, .. } => winner,
+            MatchResult::Tie | MatchResult::Draw | MatchResult::NoResult => {
+                game.tie_winner().unwrap_or(home)
+            }
+        };
+        let knockout_match = &mut self.rounds[round][index];
+        knockout_match.result = Some(result);
+        knockout_match.winner = Some(winner);
+        Ok(winner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{form, model, player::PlayerDb, tests::test_team, tournament};
+
+    #[test]
+    fn group_and_knockout_cup() -> Result<()> {
+        use model::NullModel;
+        use std::sync::Arc;
+        let mut db = PlayerDb::new();
+        let mut groups = vec![Vec::new(), Vec::new()];
+        for i in 0..8 {
+            let team = test_team(&mut db, i, &format!("T{}", i))?;
+            groups[i as usize % 2].push(Arc::new(team));
+        }
+        let mut cup = tournament::Cup::new(form::Form::odi(), groups.clone())?;
+        assert_eq!(cup.rounds().len(), 2);
+        let short = vec![groups[0].clone(), groups[1][..1].to_vec()];
+        assert!(matches!(
+            tournament::Cup::new(form::Form::odi(), short),
+            Err(Error::Unsupported(_))
+        ));
+        cup.play_seeded(&NullModel {}, &db, 2019)?;
+        let mut rerun = tournament::Cup::new(form::Form::odi(), groups)?;
+        rerun.play_seeded(&NullModel {}, &db, 2019)?;
+        assert_eq!(
+            serde_json::to_string(cup.rounds())?,
+            serde_json::to_string(rerun.rounds())?
+        );
+        let semifinals = &cup.rounds()[0];
+        let group_winner = cup.groups()[0].standings()[0].team;
+        assert_eq!(semifinals[0].home, Some(group_winner));
+        let finalists = [cup.rounds()[1][0].home, cup.rounds()[1][0].away];
+        assert!(finalists.contains(&semifinals[0].winner));
+        let champion = cup.champion().expect("final should be played");
+        assert!(finalists.contains(&Some(champion.id)));
+        // The cup's leaderboards cover the twelve group matches and three knockouts
+        let leaderboards = cup.leaderboards();
+        let matches: u32 = leaderboards.stats().iter().map(|(_, r)| r.matches).sum();
+        assert_eq!(matches, 15 * 22);
+        let group_runs: u32 = cup
+            .groups()
+            .iter()
+            .flat_map(|group| group.leaderboards().stats().iter())
+            .map(|(_, r)| r.batting.runs)
+            .sum();
+        let cup_runs: u32 = leaderboards
+            .stats()
+            .iter()
+            .map(|(_, r)| r.batting.runs)
+            .sum();
+        assert!(cup_runs > group_runs);
+        #[cfg(feature = "std-display")]
+        cup.write_summary(&mut std::io::sink())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! Net run rate, which separates teams level on points. Following the standard playing
+//! conditions, a side bowled out is taken to have faced its full quota of overs, and a
+//! match abandoned without a result doesn't count.
+use crate::{
+    error::{Error, Result},
+    form::Overs,
+    game::{GameState, MatchResult},
+};
+use serde::{Deserialize, Serialize};
+
+/// Runs per over from a number of runs and legal balls, or 0 if no balls were bowled, as
+/// for the other rates in the crate
+pub fn run_rate(runs: u32, balls: u32, balls_per_over: u8) -> f32 {
+    if balls == 0 {
+        0.
+    } else {
+        runs as f32 * balls_per_over as f32 / balls as f32
+    }
+}
+
+/// Net run rate: runs scored per over faced, less runs conceded per over bowled. The
+/// overs of a side bowled out should be given as its full quota.
+pub fn net_run_rate(
+    runs_for: u32,
+    overs_faced: Overs,
+    runs_against: u32,
+    overs_bowled: Overs,
+    balls_per_over: u8,
+) -> f32 {
+    let balls = |overs: Overs| overs.total_balls(balls_per_over) as u32;
+    run_rate(runs_for, balls(overs_faced), balls_per_over)
+        - run_rate(runs_against, balls(overs_bowled), balls_per_over)
+}
+
+/// The runs and legal balls an innings counts for towards net run rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InningsRunRate {
+    pub batting_team: u16,
+    pub bowling_team: u16,
+    pub runs: u16,
+    pub balls: u16,
+}
+
+/// The innings of a completed match as they count towards net run rate, in order. A side
+/// bowled out is credited with the full quota of overs its innings was allotted, and
+/// forfeited innings are left out. In a limited-overs match:
+/// - if the chase was cut short by rain, the side batting first is credited with the
+///   revised target less one off the overs the chase was allotted. The target is revised
+///   in proportion to the overs lost;
+/// - if the match was abandoned during the chase and decided on the scores so far, the
+///   side batting first is credited with the par score off the overs faced in the chase;
+/// - if there was no result, nothing counts.
+pub fn innings_run_rates(game: &GameState) -> Result<Vec<InningsRunRate>> {
+    let result = game.result().ok_or(Error::MatchInProgress)?;
+    if result == MatchResult::NoResult {
+        return Ok(Vec::new());
+    }
+    let form = game.form();
+    let quota = |max_overs: Option<u16>| {
+        max_overs
+            .map(|overs| Overs::new(overs, 0).total_balls(form.balls_per_over))
+            .or_else(|| form.innings_balls())
+    };
+    let played: Vec<_> = game
+        .completed_innings()
+        .iter()
+        .filter(|st| !st.forfeited)
+        .collect();
+    let mut innings: Vec<InningsRunRate> = played
+        .iter()
+        .map(|st| InningsRunRate {
+            batting_team: st.batting_team,
+            bowling_team: st.bowling_team,
+            runs: st.runs(),
+            balls: match quota(st.max_overs) {
+                Some(quota) if st.all_out() => quota,
+                _ => st.balls_bowled(),
+            },
+        })
+        .collect();
+    if let ([first, chase], 1) = (&played[..], form.innings) {
+        let (first_quota, chase_quota) = match (quota(first.max_overs), quota(chase.max_overs)) {
+            (Some(first_quota), Some(chase_quota)) => (first_quota, chase_quota),
+            _ => return Ok(innings),
+        };
+        if let Some(par) = game.par_score() {
+            innings[0].runs = par;
+            innings[0].balls = innings[1].balls;
+        } else if chase_quota < first_quota {
+            let revised = first.runs() as u32 * chase_quota as u32 / first_quota as u32;
+            innings[0].runs = revised as u16;
+            innings[0].balls = chase_quota;
+        }
+    }
+    Ok(innings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{form::Form, model::NullModel, player::PlayerDb, seed, tests::test_team};
+
+    #[test]
+    fn rates() {
+        assert_eq!(run_rate(0, 0, 6), 0.);
+        assert_eq!(run_rate(75, 60, 6), 7.5);
+        // 160 off 20 overs against 150 off a full quota of 20 after being bowled out
+        let nrr = net_run_rate(160, Overs::new(20, 0), 150, Overs::new(20, 0), 6);
+        assert!((nrr - 0.5).abs() < 1e-6);
+        // Part overs count by the ball: 100 off 16.4 overs is 6 an over
+        let nrr = net_run_rate(100, Overs::new(16, 4), 0, Overs::default(), 6);
+        assert!((nrr - 6.).abs() < 1e-6);
+        // 100 off 12.4 overs of 8 balls
+        let nrr = net_run_rate(0, Overs::default(), 100, Overs::new(12, 4), 8);
+        assert!((nrr + 8.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reduced_chase() -> Result<()> {
+        let mut db = PlayerDb::new();
+        let team_a = test_team(&mut db, 1, "PNG")?;
+        let team_b = test_team(&mut db, 2, "CAN")?;
+        let mut state = GameState::new(Form::t20(), team_a, team_b)?;
+        let mut rng = seed::rng(1);
+        for delivery in state.deliveries(&NullModel {}, &db, &mut rng) {
+            let (number, _, score) = delivery?;
+            if number.innings == 1 && score.balls >= 12 {
+                break;
+            }
+        }
+        assert!(innings_run_rates(&state).is_err());
+        state.reduce_overs(15)?;
+        state.simulate_to_completion(&NullModel {}, &db, &mut rng)?;
+        let innings = state.completed_innings();
+        assert!(innings[0].runs() > 0);
+        let rates = innings_run_rates(&state)?;
+        assert_eq!(rates.len(), 2);
+        // The side batting first is credited with the revised target less one off the
+        // 15 overs of the chase, at the rate it scored over its full 20 overs
+        assert_eq!(rates[0].runs as u32, innings[0].runs() as u32 * 90 / 120);
+        assert_eq!(rates[0].balls, 90);
+        let chase = if innings[1].all_out() {
+            90
+        } else {
+            innings[1].balls_bowled()
+        };
+        assert_eq!((rates[1].runs, rates[1].balls), (innings[1].runs(), chase));
+        Ok(())
+    }
+}